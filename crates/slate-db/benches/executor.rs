@@ -6,7 +6,7 @@ use criterion::{BatchSize, BenchmarkId, Criterion, criterion_group, criterion_ma
 use slate_db::bench::{Executor, Expression, IndexScanRange, LogicalOp, Node, Plan, ScanDirection};
 use slate_engine::{
     Catalog, CollectionHandle, CreateCollectionOptions, Engine, EngineError, EngineTransaction,
-    FunctionEntry, FunctionKind, IndexEntry, IndexRange, DEFAULT_CF,
+    FunctionEntry, FunctionKind, IndexConfig, IndexEntry, IndexRange, DEFAULT_CF,
 };
 
 // ── NoopTransaction ─────────────────────────────────────────
@@ -80,6 +80,18 @@ impl EngineTransaction for NoopTransaction {
         panic!("NoopTransaction::purge_before called");
     }
 
+    fn set_savepoint(&mut self) -> Result<(), EngineError> {
+        Ok(())
+    }
+
+    fn rollback_to_savepoint(&mut self) -> Result<(), EngineError> {
+        Ok(())
+    }
+
+    fn release_savepoint(&mut self) -> Result<(), EngineError> {
+        Ok(())
+    }
+
     fn commit(self) -> Result<(), EngineError> {
         Ok(())
     }
@@ -102,12 +114,21 @@ impl Catalog for NoopTransaction {
     fn drop_collection(&mut self, _: &str, _: &str) -> Result<(), EngineError> {
         panic!("NoopTransaction::drop_collection called");
     }
-    fn create_index(&mut self, _: &str, _: &str, _: &str) -> Result<(), EngineError> {
+    fn create_index(&mut self, _: &str, _: &str, _: impl Into<IndexConfig>) -> Result<(), EngineError> {
         panic!("NoopTransaction::create_index called");
     }
     fn drop_index(&mut self, _: &str, _: &str, _: &str) -> Result<(), EngineError> {
         panic!("NoopTransaction::drop_index called");
     }
+    fn reindex(&mut self, _: &str, _: &str, _: &str) -> Result<(), EngineError> {
+        panic!("NoopTransaction::reindex called");
+    }
+    fn start_index_build(&mut self, _: &str, _: &str, _: impl Into<IndexConfig>) -> Result<(), EngineError> {
+        panic!("NoopTransaction::start_index_build called");
+    }
+    fn continue_index_build(&mut self, _: &str, _: &str, _: &str, _: usize) -> Result<bool, EngineError> {
+        panic!("NoopTransaction::continue_index_build called");
+    }
     fn create_function(&mut self, _: &str, _: &str, _: FunctionKind, _: &str, _: u8, _: &[u8]) -> Result<(), EngineError> {
         panic!("NoopTransaction::create_function called");
     }
@@ -1,9 +1,14 @@
+use slate_engine::Compression;
+
 #[derive(Debug, Clone)]
 pub struct CollectionConfig {
     pub name: String,
     pub cf: String,
     pub pk_path: String,
     pub ttl_path: String,
+    /// Record payload compression (LZ4, or zstd with the `zstd` feature),
+    /// optionally with a shared dictionary.
+    pub compression: Compression,
 }
 
 impl Default for CollectionConfig {
@@ -13,6 +18,7 @@ impl Default for CollectionConfig {
             cf: slate_engine::DEFAULT_CF.to_string(),
             pk_path: "_id".to_string(),
             ttl_path: "ttl".to_string(),
+            compression: Compression::None,
         }
     }
 }
@@ -1,9 +1,15 @@
 use std::sync::Arc;
+use std::time::Duration;
 
 use bson::{RawBson, RawDocumentBuf};
-use slate_engine::{Catalog, Engine, EngineTransaction, FunctionKind, KvEngine};
+use slate_engine::{
+    Catalog, CollectionStorageStats, Engine, EngineTransaction, FunctionKind, IndexConfig, KvEngine,
+};
 use slate_query::{DistinctOptions, FindOptions};
-use slate_store::{BackupStore, Store};
+use slate_store::{
+    BackupInfo, BackupPolicy, BackupStore, ForkableStore, IncrementalBackupStore,
+    InstrumentedStore, MetricsSnapshot, SnapshotStore, Store,
+};
 use slate_vm::pool::VmPool;
 
 use crate::collection::CollectionConfig;
@@ -17,20 +23,82 @@ use crate::parser;
 use crate::planner::planner::Planner;
 use crate::statement::Statement;
 
+// ── RetryPolicy ────────────────────────────────────────────
+
+/// How [`Database::transact`] retries a transaction that hit a write conflict.
+///
+/// The delay starts at `initial_backoff` and doubles after every conflict,
+/// capped at `max_backoff`. Backoff sleeps require the `runtime` feature;
+/// without it, retries happen immediately.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Total attempts including the first one. `1` disables retries.
+    pub max_attempts: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            initial_backoff: Duration::from_millis(1),
+            max_backoff: Duration::from_millis(100),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Run `attempt` until it succeeds, fails with anything but a conflict,
+    /// or runs out of attempts.
+    pub(crate) fn run<T>(
+        &self,
+        mut attempt: impl FnMut() -> Result<T, DbError>,
+    ) -> Result<T, DbError> {
+        let mut backoff = self.initial_backoff;
+        let mut attempts = 1;
+        loop {
+            match attempt() {
+                Err(e) if e.is_conflict() && attempts < self.max_attempts => {
+                    #[cfg(feature = "runtime")]
+                    std::thread::sleep(backoff);
+                    backoff = backoff.saturating_mul(2).min(self.max_backoff);
+                    attempts += 1;
+                }
+                other => return other,
+            }
+        }
+    }
+}
+
 // ── DatabaseBuilder ────────────────────────────────────────
 
+/// Records backfilled per transaction by [`Database::build_index_blocking`], unless
+/// set with [`DatabaseBuilder::with_index_build_batch`].
+pub const DEFAULT_INDEX_BUILD_BATCH: usize = 1_000;
+
 pub struct DatabaseBuilder {
     pool: Option<VmPool>,
     clock: Option<Arc<dyn Fn() -> i64 + Send + Sync>>,
+    retry: RetryPolicy,
+    index_build_batch: usize,
     #[cfg(feature = "runtime")]
     sweep_interval: Option<std::time::Duration>,
 }
 
+impl Default for DatabaseBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl DatabaseBuilder {
     pub fn new() -> Self {
         Self {
             pool: None,
             clock: None,
+            retry: RetryPolicy::default(),
+            index_build_batch: DEFAULT_INDEX_BUILD_BATCH,
             #[cfg(feature = "runtime")]
             sweep_interval: None,
         }
@@ -53,6 +121,19 @@ impl DatabaseBuilder {
         self
     }
 
+    /// Configure how [`Database::transact`] retries on write conflicts.
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry = policy;
+        self
+    }
+
+    /// Set how many records [`Database::build_index_blocking`] backfills per
+    /// transaction. Smaller batches hold up concurrent writers for less time.
+    pub fn with_index_build_batch(mut self, batch: usize) -> Self {
+        self.index_build_batch = batch.max(1);
+        self
+    }
+
     /// Enable background TTL sweep at the given interval.
     #[cfg(feature = "runtime")]
     pub fn with_sweep(mut self, interval: std::time::Duration) -> Self {
@@ -64,11 +145,24 @@ impl DatabaseBuilder {
     ///
     /// When a script pool is configured, loads an initial hook snapshot
     /// from the engine so triggers and validators are available immediately.
+    /// A read-only store gets no `_sys_` column family and no TTL sweep.
+    /// Otherwise the entries of indexes written in an older key format are
+    /// deleted and the indexes restarted as builds, without backfilling
+    /// them: queries don't use them until
+    /// [`Database::resume_index_builds_blocking`] finishes the builds.
     pub fn open<S: Store + Send + Sync + 'static>(self, store: S) -> Result<Database<S>, DbError> {
+        let read_only = store.is_read_only();
         let engine = match self.clock {
-            Some(clock) => Arc::new(KvEngine::with_clock(store, move || clock())),
-            None => Arc::new(KvEngine::new(store)),
+            Some(clock) => KvEngine::with_clock(store, move || clock()),
+            None => KvEngine::new(store),
         };
+        let engine = Arc::new(engine.with_index_filter(parser::compile_index_filter));
+
+        // Indexes from an older key format are restarted as builds before
+        // first use. A read-only store keeps them, and queries don't use them.
+        if !read_only {
+            engine.migrate_indexes()?;
+        }
 
         // Load initial hook snapshot if scripting is enabled.
         let registry = if self.pool.is_some() {
@@ -82,14 +176,17 @@ impl DatabaseBuilder {
 
         #[cfg(feature = "runtime")]
         let ttl_handle = match self.sweep_interval {
+            Some(_) if read_only => None,
             Some(d) => crate::runtime::sweep::spawn(Arc::clone(&engine), d.as_secs()),
             None => None,
         };
 
         Ok(Database {
             engine,
-            pool: self.pool,
+            pool: self.pool.map(Arc::new),
             registry,
+            retry: self.retry,
+            index_build_batch: self.index_build_batch,
             #[cfg(feature = "runtime")]
             ttl_handle,
         })
@@ -100,8 +197,10 @@ impl DatabaseBuilder {
 
 pub struct Database<S: Store> {
     engine: Arc<KvEngine<S>>,
-    pool: Option<VmPool>,
+    pool: Option<Arc<VmPool>>,
     registry: Option<HookRegistry>,
+    retry: RetryPolicy,
+    index_build_batch: usize,
     #[cfg(feature = "runtime")]
     ttl_handle: Option<crate::runtime::sweep::TtlHandle>,
 }
@@ -117,19 +216,145 @@ impl<S: Store + BackupStore> Database<S> {
     }
 }
 
+impl<S: IncrementalBackupStore> Database<S> {
+    /// Add an incremental backup to `backup_dir`, applying `policy`'s
+    /// verification and retention.
+    pub fn create_backup(
+        &self,
+        backup_dir: impl AsRef<std::path::Path>,
+        policy: &BackupPolicy,
+    ) -> Result<BackupInfo, DbError> {
+        Ok(self.engine.create_backup(backup_dir.as_ref(), policy)?)
+    }
+
+    /// List the backups in `backup_dir`, oldest first.
+    pub fn list_backups(
+        backup_dir: impl AsRef<std::path::Path>,
+    ) -> Result<Vec<BackupInfo>, DbError> {
+        Ok(KvEngine::<S>::list_backups(backup_dir.as_ref())?)
+    }
+
+    /// Check that every file of backup `backup_id` exists with the expected size.
+    pub fn verify_backup(
+        backup_dir: impl AsRef<std::path::Path>,
+        backup_id: u32,
+    ) -> Result<(), DbError> {
+        KvEngine::<S>::verify_backup(backup_dir.as_ref(), backup_id)?;
+        Ok(())
+    }
+
+    /// Restore backup `backup_id` from `backup_dir` into `dest`. Open the
+    /// restored store at `dest` and pass it to [`DatabaseBuilder::open`].
+    pub fn restore(
+        backup_dir: impl AsRef<std::path::Path>,
+        backup_id: u32,
+        dest: impl AsRef<std::path::Path>,
+    ) -> Result<(), DbError> {
+        KvEngine::<S>::restore(backup_dir.as_ref(), backup_id, dest.as_ref())?;
+        Ok(())
+    }
+}
+
+impl<S: SnapshotStore> Database<S> {
+    /// Take a read-only, point-in-time snapshot of the database.
+    ///
+    /// Every transaction begun on the snapshot sees exactly the data that
+    /// was committed when it was taken, so several queries can run against
+    /// the same state. The snapshot owns its view and can be handed to
+    /// another thread.
+    pub fn snapshot(&self) -> Result<Snapshot<S::Snapshot>, DbError> {
+        Ok(Snapshot {
+            engine: self.engine.snapshot()?,
+        })
+    }
+}
+
+impl<S: ForkableStore + Send + Sync + 'static> Database<S> {
+    /// An isolated, writable copy of the database.
+    ///
+    /// The fork starts with every collection, index and function the
+    /// database has committed, and shares its script pool, clock and retry
+    /// policy. From then on the two diverge: writes to the fork never show up
+    /// here, and the other way round. How cheap this is depends on the
+    /// store; a `MemoryStore` fork costs one `Arc` clone per column family.
+    ///
+    /// The fork runs no background TTL sweep; call
+    /// [`Database::purge_expired`] if a test needs one.
+    pub fn fork(&self) -> Result<Database<S>, DbError> {
+        let engine = Arc::new(self.engine.fork()?);
+        let registry = if self.registry.is_some() {
+            let txn = engine.begin(true)?;
+            let snapshot = HookSnapshot::load_all(&txn)?;
+            txn.rollback()?;
+            Some(HookRegistry::new(snapshot))
+        } else {
+            None
+        };
+        Ok(Database {
+            engine,
+            pool: self.pool.clone(),
+            registry,
+            retry: self.retry.clone(),
+            index_build_batch: self.index_build_batch,
+            #[cfg(feature = "runtime")]
+            ttl_handle: None,
+        })
+    }
+}
+
+impl<S: InstrumentedStore> Database<S> {
+    /// Snapshot of the store's per-column-family I/O metrics — call counts,
+    /// bytes read and written, keys scanned, and latency histograms.
+    ///
+    /// Available when the database is opened over a `MetricsStore`. Counters
+    /// are cumulative since open (or the last [`Database::reset_metrics`]).
+    pub fn metrics(&self) -> MetricsSnapshot {
+        self.engine.store_metrics()
+    }
+
+    /// Zero every store metric counter.
+    pub fn reset_metrics(&self) {
+        self.engine.reset_store_metrics()
+    }
+}
+
 impl<S: Store> Database<S> {
     pub fn begin(&self, read_only: bool) -> Result<Transaction<'_, S>, DbError> {
         let txn = self.engine.begin(read_only)?;
         let snapshot = self.registry.as_ref().map(|r| r.snapshot());
         Ok(Transaction {
             txn,
-            pool: self.pool.as_ref(),
+            engine: &self.engine,
+            pool: self.pool.as_deref(),
             snapshot,
             registry: self.registry.as_ref(),
             hooks_dirty: false,
         })
     }
 
+    /// Run `f` in a write transaction and commit it, retrying on conflict.
+    ///
+    /// If `f` or the commit fails with [`DbError::Conflict`], the transaction
+    /// is rolled back and `f` runs again in a fresh one, following the
+    /// configured [`RetryPolicy`]. Any other error is returned immediately.
+    /// `f` may run more than once, so it should not have side effects
+    /// outside the transaction.
+    pub fn transact<T>(
+        &self,
+        mut f: impl FnMut(&mut Transaction<'_, S>) -> Result<T, DbError>,
+    ) -> Result<T, DbError> {
+        self.retry.run(|| {
+            let mut txn = self.begin(false)?;
+            match f(&mut txn) {
+                Ok(value) => txn.commit().map(|()| value),
+                Err(e) => {
+                    let _ = txn.rollback();
+                    Err(e)
+                }
+            }
+        })
+    }
+
     /// Purge expired documents from a collection.
     pub fn purge_expired(&self, cf: &str, collection: &str) -> Result<u64, DbError> {
         let txn = self.begin(false)?;
@@ -146,6 +371,99 @@ impl<S: Store> Database<S> {
         Ok(pairs)
     }
 
+    /// Approximate bytes and keys used by a collection's records and by each
+    /// of its indexes. How close the estimate is depends on the store.
+    pub fn collection_storage_stats(
+        &self,
+        cf: &str,
+        name: &str,
+    ) -> Result<CollectionStorageStats, DbError> {
+        Ok(self.engine.collection_storage_stats(cf, name)?)
+    }
+
+    /// Create an index without holding one write transaction over the
+    /// whole collection. Blocks until the index is ready.
+    ///
+    /// The index is registered as building, then the existing records are
+    /// backfilled a batch per transaction (see
+    /// [`DatabaseBuilder::with_index_build_batch`]), saving the resume
+    /// position with each batch. Writes made meanwhile maintain the index,
+    /// and queries don't use it until the last batch marks it ready.
+    /// [`build_index`](Self::build_index) runs the backfill on a background
+    /// thread instead.
+    ///
+    /// A build that fails, e.g. with [`DbError::DuplicateKey`] for a unique
+    /// index whose documents share a value, leaves the index building and
+    /// names it in the error. Fix the documents and call
+    /// [`resume_index_builds_blocking`](Self::resume_index_builds_blocking)
+    /// to finish it, or drop it.
+    pub fn build_index_blocking(
+        &self,
+        cf: &str,
+        collection: &str,
+        index: impl Into<IndexConfig>,
+    ) -> Result<(), DbError> {
+        let index = index.into();
+        self.transact(|txn| {
+            txn.txn.start_index_build(cf, collection, index.clone())?;
+            Ok(())
+        })?;
+        self.finish_index_build(cf, collection, &index.name())
+    }
+
+    /// Finish the index builds an earlier
+    /// [`build_index_blocking`](Self::build_index_blocking) left incomplete,
+    /// e.g. because the process stopped or a batch failed, from their saved
+    /// positions. Blocks until every build is done or one fails, and returns
+    /// how many were finished.
+    pub fn resume_index_builds_blocking(&self) -> Result<u64, DbError> {
+        let txn = self.begin(true)?;
+        let builds: Vec<(String, String, String)> = txn
+            .txn
+            .list_collections(None)?
+            .iter()
+            .flat_map(|handle| {
+                handle
+                    .indexes()
+                    .iter()
+                    .filter(|index| handle.is_building(index))
+                    .map(|index| {
+                        (
+                            handle.cf_name().to_string(),
+                            handle.name().to_string(),
+                            index.clone(),
+                        )
+                    })
+            })
+            .collect();
+        txn.rollback()?;
+        for (cf, collection, index) in &builds {
+            self.finish_index_build(cf, collection, index)?;
+        }
+        Ok(builds.len() as u64)
+    }
+
+    /// Backfill a building index batch by batch until it is ready. On
+    /// failure the index stays building, with the batches done so far kept.
+    fn finish_index_build(&self, cf: &str, collection: &str, name: &str) -> Result<(), DbError> {
+        while !index_build_batch(
+            &self.engine,
+            &self.retry,
+            self.index_build_batch,
+            cf,
+            collection,
+            name,
+        )? {}
+        Ok(())
+    }
+
+    /// Ask the store to reclaim space in a collection's record and index
+    /// ranges, e.g. after a large delete or purge.
+    pub fn compact_collection(&self, cf: &str, name: &str) -> Result<(), DbError> {
+        self.engine.compact_collection(cf, name)?;
+        Ok(())
+    }
+
     /// Gracefully stop background tasks.
     #[cfg(feature = "runtime")]
     pub fn shutdown(&mut self) {
@@ -160,10 +478,109 @@ impl<S: Store> Database<S> {
     }
 }
 
+#[cfg(feature = "runtime")]
+impl<S: Store + Send + Sync + 'static> Database<S> {
+    /// Create an index like [`build_index_blocking`](Self::build_index_blocking),
+    /// but backfill it on a background thread.
+    ///
+    /// Returns once the index is registered as building. Writes maintain it
+    /// from then on, and queries use it once the thread has backfilled the
+    /// existing records. Wait on the returned handle for the outcome.
+    pub fn build_index(
+        &self,
+        cf: &str,
+        collection: &str,
+        index: impl Into<IndexConfig>,
+    ) -> Result<crate::runtime::index_build::IndexBuildHandle, DbError> {
+        let index = index.into();
+        self.transact(|txn| {
+            txn.txn.start_index_build(cf, collection, index.clone())?;
+            Ok(())
+        })?;
+        Ok(crate::runtime::index_build::spawn(
+            Arc::clone(&self.engine),
+            self.retry.clone(),
+            self.index_build_batch,
+            cf.to_string(),
+            collection.to_string(),
+            index.name(),
+        ))
+    }
+}
+
+/// Backfill one batch of a building index in its own transaction, retrying
+/// on conflict. Returns `true` once the index is ready.
+pub(crate) fn index_build_batch<S: Store>(
+    engine: &KvEngine<S>,
+    retry: &RetryPolicy,
+    batch: usize,
+    cf: &str,
+    collection: &str,
+    name: &str,
+) -> Result<bool, DbError> {
+    let result = retry.run(|| {
+        let mut txn = engine.begin(false)?;
+        match txn.continue_index_build(cf, collection, name, batch) {
+            Ok(done) => {
+                txn.commit()?;
+                Ok(done)
+            }
+            Err(e) => {
+                let _ = txn.rollback();
+                Err(e.into())
+            }
+        }
+    });
+    result.map_err(|e| match e {
+        DbError::DuplicateKey(msg) => {
+            DbError::DuplicateKey(format!("building index {cf}:{collection}.{name}: {msg}"))
+        }
+        DbError::InvalidDocument(msg) => {
+            DbError::InvalidDocument(format!("building index {cf}:{collection}.{name}: {msg}"))
+        }
+        e => e,
+    })
+}
+
+// ── Snapshot ───────────────────────────────────────────────
+
+/// A read-only, point-in-time view of a [`Database`], returned by
+/// [`Database::snapshot`].
+///
+/// Run `find`, `count`, and `distinct` on the transactions it begins. Writes
+/// fail with [`StoreError::ReadOnly`](slate_store::StoreError::ReadOnly).
+/// TTL expiry is evaluated as of the moment the snapshot was taken.
+pub struct Snapshot<S: Store> {
+    engine: KvEngine<S>,
+}
+
+impl<S: Store> Snapshot<S> {
+    /// Begin a read-only transaction over the snapshot.
+    pub fn begin(&self) -> Result<Transaction<'_, S>, DbError> {
+        Ok(Transaction {
+            txn: self.engine.begin(true)?,
+            engine: &self.engine,
+            pool: None,
+            snapshot: None,
+            registry: None,
+            hooks_dirty: false,
+        })
+    }
+
+    /// List all collections in the snapshot as `(cf, name)` pairs.
+    pub fn list_collections(&self) -> Result<Vec<(String, String)>, DbError> {
+        let txn = self.begin()?;
+        let pairs = txn.list_collections()?;
+        let _ = txn.rollback();
+        Ok(pairs)
+    }
+}
+
 // ── Transaction ────────────────────────────────────────────
 
 pub struct Transaction<'db, S: Store + 'db> {
     txn: <KvEngine<S> as Engine>::Txn<'db>,
+    engine: &'db KvEngine<S>,
     pool: Option<&'db VmPool>,
     snapshot: Option<Arc<HookSnapshot>>,
     registry: Option<&'db HookRegistry>,
@@ -450,19 +867,41 @@ impl<'db, S: Store + 'db> Transaction<'db, S> {
 
     // ── Index operations ────────────────────────────────────────
 
-    /// Create an index on a field and backfill existing records.
-    pub fn create_index(&mut self, cf: &str, collection: &str, field: &str) -> Result<(), DbError> {
-        self.txn.create_index(cf, collection, field)?;
+    /// Create an index and backfill existing records.
+    ///
+    /// Pass a field name for a single-field index, or a list of fields
+    /// (`["status", "created_at"]`) for a compound index. For a partial
+    /// index, pass an [`IndexConfig`] with a filter document; only matching
+    /// documents are indexed, and queries only use the index when their
+    /// filter implies it. A unique index (`IndexConfig::unique`) fails with
+    /// [`DbError::DuplicateKey`] if existing documents share a value, and
+    /// rejects later writes that would.
+    pub fn create_index(
+        &mut self,
+        cf: &str,
+        collection: &str,
+        index: impl Into<IndexConfig>,
+    ) -> Result<(), DbError> {
+        self.txn.create_index(cf, collection, index)?;
         Ok(())
     }
 
-    /// Drop an index and remove all its entries.
-    pub fn drop_index(&mut self, cf: &str, collection: &str, field: &str) -> Result<(), DbError> {
-        self.txn.drop_index(cf, collection, field)?;
+    /// Drop an index by name and remove all its entries. A compound index is
+    /// named by its fields joined with `+`.
+    pub fn drop_index(&mut self, cf: &str, collection: &str, name: &str) -> Result<(), DbError> {
+        self.txn.drop_index(cf, collection, name)?;
         Ok(())
     }
 
-    /// List indexed fields for a collection.
+    /// Rebuild an index's entries from the stored documents, keeping its
+    /// options. [`DatabaseBuilder::open`] already rebuilds indexes written
+    /// in an older key format.
+    pub fn reindex(&mut self, cf: &str, collection: &str, name: &str) -> Result<(), DbError> {
+        self.txn.reindex(cf, collection, name)?;
+        Ok(())
+    }
+
+    /// List index names for a collection.
     pub fn list_indexes(&self, cf: &str, collection: &str) -> Result<Vec<String>, DbError> {
         let handle = self.txn.collection(cf, collection)?;
         Ok(handle.indexes().to_vec())
@@ -504,6 +943,10 @@ impl<'db, S: Store + 'db> Transaction<'db, S> {
             registry.swap(snapshot);
         }
 
+        // The commit already succeeded, so a failed eviction (e.g. a conflict
+        // with a concurrent writer) isn't reported; the next commit retries.
+        let _ = self.engine.evict_over_budget();
+
         Ok(())
     }
 
@@ -512,6 +955,31 @@ impl<'db, S: Store + 'db> Transaction<'db, S> {
         Ok(())
     }
 
+    /// Set a savepoint and return a guard over this transaction.
+    ///
+    /// Use the guard like the transaction itself. Call
+    /// [`Savepoint::release`] to keep what was written through it, or
+    /// [`Savepoint::rollback`] (or just drop it) to undo those writes while
+    /// keeping everything written before the savepoint. Savepoints nest.
+    ///
+    /// ```ignore
+    /// for batch in batches {
+    ///     let mut sp = txn.savepoint()?;
+    ///     match sp.insert_many(DEFAULT_CF, "items", batch) {
+    ///         Ok(_) => sp.release()?,
+    ///         Err(_) => sp.rollback()?,
+    ///     }
+    /// }
+    /// txn.commit()?;
+    /// ```
+    pub fn savepoint(&mut self) -> Result<Savepoint<'_, 'db, S>, DbError> {
+        self.txn.set_savepoint()?;
+        Ok(Savepoint {
+            txn: self,
+            done: false,
+        })
+    }
+
     // ── Collection management ───────────────────────────────────
 
     /// Create a collection with the given config.
@@ -519,15 +987,16 @@ impl<'db, S: Store + 'db> Transaction<'db, S> {
         let options = slate_engine::CreateCollectionOptions {
             pk_path: Some(config.pk_path.clone()),
             ttl_path: Some(config.ttl_path.clone()),
+            compression: Some(config.compression.clone()),
         };
         self.txn
             .create_collection(&config.cf, &config.name, &options)?;
 
         // Auto-create TTL index; ignore IndexExists for idempotent re-creation.
-        if let Err(e) = self.txn.create_index(&config.cf, &config.name, &config.ttl_path) {
-            if !matches!(e, slate_engine::EngineError::IndexExists(_)) {
-                return Err(e.into());
-            }
+        if let Err(e) = self.txn.create_index(&config.cf, &config.name, &config.ttl_path)
+            && !matches!(e, slate_engine::EngineError::IndexExists(_))
+        {
+            return Err(e.into());
         }
         Ok(())
     }
@@ -665,3 +1134,50 @@ impl<'db, S: Store + 'db> Transaction<'db, S> {
         }
     }
 }
+
+// ── Savepoint ──────────────────────────────────────────────
+
+/// Guard returned by [`Transaction::savepoint`]. Derefs to the transaction;
+/// rolls back to the savepoint when dropped without being released.
+pub struct Savepoint<'t, 'db, S: Store + 'db> {
+    txn: &'t mut Transaction<'db, S>,
+    done: bool,
+}
+
+impl<'t, 'db, S: Store + 'db> Savepoint<'t, 'db, S> {
+    /// Keep every write made since the savepoint.
+    pub fn release(mut self) -> Result<(), DbError> {
+        self.done = true;
+        self.txn.txn.release_savepoint()?;
+        Ok(())
+    }
+
+    /// Undo every write made since the savepoint. The transaction stays open.
+    pub fn rollback(mut self) -> Result<(), DbError> {
+        self.done = true;
+        self.txn.txn.rollback_to_savepoint()?;
+        Ok(())
+    }
+}
+
+impl<'db, S: Store + 'db> std::ops::Deref for Savepoint<'_, 'db, S> {
+    type Target = Transaction<'db, S>;
+
+    fn deref(&self) -> &Self::Target {
+        self.txn
+    }
+}
+
+impl<'db, S: Store + 'db> std::ops::DerefMut for Savepoint<'_, 'db, S> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.txn
+    }
+}
+
+impl<'db, S: Store + 'db> Drop for Savepoint<'_, 'db, S> {
+    fn drop(&mut self) {
+        if !self.done {
+            let _ = self.txn.txn.rollback_to_savepoint();
+        }
+    }
+}
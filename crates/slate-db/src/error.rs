@@ -5,6 +5,7 @@ use slate_store::StoreError;
 #[derive(Debug)]
 pub enum DbError {
     Store(StoreError),
+    Conflict(String),
     NotFound(String),
     CollectionNotFound(String),
     DuplicateKey(String),
@@ -21,6 +22,7 @@ impl fmt::Display for DbError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             DbError::Store(e) => write!(f, "store error: {e}"),
+            DbError::Conflict(msg) => write!(f, "write conflict: {msg}"),
             DbError::NotFound(id) => write!(f, "not found: {id}"),
             DbError::CollectionNotFound(name) => write!(f, "collection not found: {name}"),
             DbError::DuplicateKey(id) => write!(f, "duplicate key: {id}"),
@@ -37,9 +39,19 @@ impl fmt::Display for DbError {
 
 impl std::error::Error for DbError {}
 
+impl DbError {
+    /// Whether this error is a write conflict that a retry may resolve.
+    pub fn is_conflict(&self) -> bool {
+        matches!(self, DbError::Conflict(_))
+    }
+}
+
 impl From<StoreError> for DbError {
     fn from(e: StoreError) -> Self {
-        DbError::Store(e)
+        match e {
+            StoreError::Conflict(msg) => DbError::Conflict(msg),
+            other => DbError::Store(other),
+        }
     }
 }
 
@@ -71,7 +83,8 @@ impl From<slate_vm::VmError> for DbError {
 impl From<slate_engine::EngineError> for DbError {
     fn from(e: slate_engine::EngineError) -> Self {
         match e {
-            slate_engine::EngineError::Store(se) => DbError::Store(se),
+            slate_engine::EngineError::Store(se) => DbError::from(se),
+            slate_engine::EngineError::Conflict(msg) => DbError::Conflict(msg),
             slate_engine::EngineError::CollectionNotFound(name) => {
                 DbError::CollectionNotFound(name)
             }
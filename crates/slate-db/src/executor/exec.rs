@@ -30,22 +30,34 @@ fn raw_compare_two_values(a: &RawBsonRef, b: &RawBsonRef) -> Ordering {
             a.partial_cmp(b).unwrap_or(Ordering::Equal)
         }
         (RawBsonRef::Double(a), RawBsonRef::Int64(b)) => {
-            a.partial_cmp(&(*b as f64)).unwrap_or(Ordering::Equal)
+            cmp_int_double(*b, *a).map_or(Ordering::Equal, Ordering::reverse)
         }
         (RawBsonRef::Double(a), RawBsonRef::Int32(b)) => {
-            a.partial_cmp(&(*b as f64)).unwrap_or(Ordering::Equal)
+            cmp_int_double(*b as i64, *a).map_or(Ordering::Equal, Ordering::reverse)
         }
         (RawBsonRef::Int64(a), RawBsonRef::Double(b)) => {
-            (*a as f64).partial_cmp(b).unwrap_or(Ordering::Equal)
+            cmp_int_double(*a, *b).unwrap_or(Ordering::Equal)
         }
         (RawBsonRef::Int32(a), RawBsonRef::Double(b)) => {
-            (*a as f64).partial_cmp(b).unwrap_or(Ordering::Equal)
+            cmp_int_double(*a as i64, *b).unwrap_or(Ordering::Equal)
         }
         (RawBsonRef::Boolean(a), RawBsonRef::Boolean(b)) => a.cmp(b),
         (RawBsonRef::DateTime(a), RawBsonRef::DateTime(b)) => {
             a.timestamp_millis().cmp(&b.timestamp_millis())
         }
-        _ => Ordering::Equal,
+        // Other types sort in index order, within one type.
+        _ => slate_engine::compare_raw_values(*a, *b).unwrap_or(Ordering::Equal),
+    }
+}
+
+/// Compare an integer with a double exactly, as index keys order them:
+/// `(1 << 60) + 1` is greater than `2^60` as a double, which it would
+/// equal if converted. `None` for NaN.
+pub(crate) fn cmp_int_double(a: i64, b: f64) -> Option<Ordering> {
+    match (a as f64).partial_cmp(&b)? {
+        // Both are integers within 2^63 here, so i128 holds them exactly.
+        Ordering::Equal => Some((a as i128).cmp(&(b as i128))),
+        ord => Some(ord),
     }
 }
 
@@ -1,3 +1,5 @@
+use std::collections::HashSet;
+
 use bson::RawBson;
 use bson::raw::{CString, RawDocumentBuf};
 use slate_engine::{CollectionHandle, EngineTransaction, IndexRange};
@@ -8,24 +10,7 @@ use crate::planner::plan::{IndexScanRange, ScanDirection};
 
 /// Convert an owned `Bson` to `RawBson` for covered projection output.
 fn bson_to_raw_bson(val: &bson::Bson) -> Option<RawBson> {
-    Some(match val {
-        bson::Bson::String(s) => RawBson::String(s.clone()),
-        bson::Bson::Int32(i) => RawBson::Int32(*i),
-        bson::Bson::Int64(i) => RawBson::Int64(*i),
-        bson::Bson::Double(f) => RawBson::Double(*f),
-        bson::Bson::Boolean(b) => RawBson::Boolean(*b),
-        bson::Bson::DateTime(dt) => RawBson::DateTime(*dt),
-        _ => return None,
-    })
-}
-
-/// Extract a numeric value as i64 from a RawBson for cross-type comparison.
-fn raw_bson_as_i64(val: &RawBson) -> Option<i64> {
-    match val {
-        RawBson::Int32(n) => Some(*n as i64),
-        RawBson::Int64(n) => Some(*n),
-        _ => None,
-    }
+    RawBson::try_from(val.clone()).ok()
 }
 
 pub(crate) fn execute<'a, T: EngineTransaction>(
@@ -37,21 +22,24 @@ pub(crate) fn execute<'a, T: EngineTransaction>(
     limit: Option<usize>,
     covered: bool,
 ) -> Result<RawIter<'a>, DbError> {
-    // For Eq with numeric types (Int32/Int64), use a full field scan with post-filter
-    // to handle cross-type matching (e.g. query Int64(100) matching stored Int32(100)).
-    let is_numeric_eq = matches!(
-        range,
-        IndexScanRange::Eq(bson::Bson::Int32(_) | bson::Bson::Int64(_))
-    );
-
+    // Int32, Int64 and Double share index entries, so a numeric Eq finds
+    // every type holding that number.
     let engine_range = match range {
         IndexScanRange::Full => IndexRange::Full,
-        IndexScanRange::Eq(_) if is_numeric_eq => IndexRange::Full,
         IndexScanRange::Eq(v) => IndexRange::Eq(v),
         IndexScanRange::Range { lower, upper } => IndexRange::Range {
             lower: lower.as_ref().map(|(v, incl)| (v, *incl)),
             upper: upper.as_ref().map(|(v, incl)| (v, *incl)),
         },
+        IndexScanRange::Compound {
+            prefix,
+            lower,
+            upper,
+        } => IndexRange::Compound {
+            prefix,
+            lower: lower.as_ref().map(|(v, incl)| (v, *incl)),
+            upper: upper.as_ref().map(|(v, incl)| (v, *incl)),
+        },
     };
 
     let reverse = matches!(direction, ScanDirection::Reverse);
@@ -67,21 +55,12 @@ pub(crate) fn execute<'a, T: EngineTransaction>(
         None
     };
 
-    // For numeric Eq, extract the query value as i64 for cross-type comparison.
-    let numeric_eq_value: Option<i64> = if is_numeric_eq {
-        match range {
-            IndexScanRange::Eq(bson::Bson::Int32(n)) => Some(*n as i64),
-            IndexScanRange::Eq(bson::Bson::Int64(n)) => Some(*n),
-            _ => None,
-        }
-    } else {
-        None
-    };
-
     let pk_cstr = CString::try_from(handle.pk_path())
         .map_err(|e| DbError::Serialization(e.to_string()))?;
     let field_cstr = CString::try_from(field.as_str())
         .map_err(|e| DbError::InvalidQuery(format!("invalid field name: {e}")))?;
+    // A multikey index can hold several entries for one document.
+    let mut seen: Option<HashSet<Vec<u8>>> = handle.is_multikey(&field).then(HashSet::new);
     let mut iter = txn.scan_index(&handle, &field, engine_range, reverse)?;
     let mut count = 0usize;
     let mut done = false;
@@ -94,18 +73,10 @@ pub(crate) fn execute<'a, T: EngineTransaction>(
         for result in iter.by_ref() {
             match result {
                 Ok(entry) => {
-                    // Numeric Eq post-filter: compare as i64 to handle Int32/Int64 cross-matching
-                    if let Some(query_val) = numeric_eq_value {
-                        let value = match entry.value() {
-                            Ok(v) => v,
-                            Err(e) => {
-                                done = true;
-                                return Some(Err(DbError::from(e)));
-                            }
-                        };
-                        if raw_bson_as_i64(&value) != Some(query_val) {
-                            continue;
-                        }
+                    if let Some(seen) = seen.as_mut()
+                        && !seen.insert(entry.doc_id_bytes().to_vec())
+                    {
+                        continue;
                     }
 
                     // Limit logic
@@ -164,14 +135,12 @@ pub(crate) fn execute<'a, T: EngineTransaction>(
 
 /// Coerce a query value to match the stored type in the index.
 ///
-/// If the query value (e.g. Int64) differs from the stored value type (e.g. Int32),
+/// If the query value (e.g. Int64) differs from the stored value type (e.g. Double),
 /// we coerce to avoid type mismatches in the covered output.
 fn coerce_to_stored_type(query_val: &RawBson, stored_val: &RawBson) -> RawBson {
-    match (stored_val, query_val) {
-        // Stored Int32, query Int64 → coerce to Int32
-        (RawBson::Int32(_), RawBson::Int64(n)) => RawBson::Int32(*n as i32),
-        // Stored Int64, query Int32 → coerce to Int64
-        (RawBson::Int64(_), RawBson::Int32(n)) => RawBson::Int64(*n as i64),
+    match stored_val {
+        // An equal number of another type → the stored number
+        RawBson::Int32(_) | RawBson::Int64(_) | RawBson::Double(_) => stored_val.clone(),
         _ => query_val.clone(),
     }
 }
@@ -45,16 +45,14 @@ fn run_validators(
 
         // Check if validator returned { ok: false, reason: "..." }
         // The result is a RawDocumentBuf — parse it to check.
-        if let Ok(Some(ok_val)) = result.get("ok") {
-            if let bson::raw::RawBsonRef::Boolean(false) = ok_val {
-                let reason = result
-                    .get_str("reason")
-                    .unwrap_or("validation failed");
-                return Err(DbError::InvalidQuery(format!(
-                    "validation failed ({}): {}",
-                    validator.name, reason
-                )));
-            }
+        if let Ok(Some(bson::raw::RawBsonRef::Boolean(false))) = result.get("ok") {
+            let reason = result
+                .get_str("reason")
+                .unwrap_or("validation failed");
+            return Err(DbError::InvalidQuery(format!(
+                "validation failed ({}): {}",
+                validator.name, reason
+            )));
         }
     }
     Ok(())
@@ -120,6 +120,26 @@ impl<'a> RawField<'a> {
                 Some(RawBsonRef::DateTime(bson::DateTime::from_millis(ms)))
             }
             ElementType::Null => Some(RawBsonRef::Null),
+            ElementType::Binary => {
+                let len = i32::from_le_bytes(
+                    self.bytes[self.value_start..self.value_start + 4]
+                        .try_into()
+                        .ok()?,
+                ) as usize;
+                let subtype = bson::spec::BinarySubtype::from(self.bytes[self.value_start + 4]);
+                let bytes = self
+                    .bytes
+                    .get(self.value_start + 5..self.value_start + 5 + len)?;
+                Some(RawBsonRef::Binary(bson::RawBinaryRef { subtype, bytes }))
+            }
+            ElementType::Decimal128 => {
+                let d = bson::Decimal128::from_bytes(
+                    self.bytes[self.value_start..self.value_start + 16]
+                        .try_into()
+                        .ok()?,
+                );
+                Some(RawBsonRef::Decimal128(d))
+            }
             ElementType::Int32 => {
                 let v = i32::from_le_bytes(
                     self.bytes[self.value_start..self.value_start + 4]
@@ -518,6 +538,21 @@ mod tests {
         assert_eq!(field.element_end() - field.value_start(), 8);
     }
 
+    #[test]
+    fn value_binary_and_decimal128() {
+        let uuid = bson::Uuid::new();
+        let dec: bson::Decimal128 = "-1.25".parse().unwrap();
+        let doc = rawdoc! { "id": bson::Binary::from_uuid(uuid), "dec": dec };
+        let field = RawField::get(doc_bytes(&doc), "id").unwrap();
+        let Some(RawBsonRef::Binary(bin)) = field.value() else {
+            panic!("expected binary");
+        };
+        assert_eq!(bin.subtype, bson::spec::BinarySubtype::Uuid);
+        assert_eq!(bin.bytes, uuid.bytes());
+        let field = RawField::get(doc_bytes(&doc), "dec").unwrap();
+        assert_eq!(field.value(), Some(RawBsonRef::Decimal128(dec)));
+    }
+
     #[test]
     fn skip_decimal128() {
         let dec = bson::Decimal128::from_bytes([0u8; 16]);
@@ -5,7 +5,7 @@ use bson::raw::RawDocumentBuf;
 use bson::rawdoc;
 use slate_engine::{
     Catalog, CollectionHandle, CreateCollectionOptions, EngineError, EngineTransaction,
-    FunctionEntry, FunctionKind, IndexEntry, IndexRange,
+    FunctionEntry, FunctionKind, IndexConfig, IndexEntry, IndexRange,
 };
 use slate_query::{Sort, SortDirection};
 use std::cell::RefCell;
@@ -87,6 +87,18 @@ impl EngineTransaction for NoopTransaction {
         panic!("NoopTransaction::purge_before called");
     }
 
+    fn set_savepoint(&mut self) -> Result<(), EngineError> {
+        Ok(())
+    }
+
+    fn rollback_to_savepoint(&mut self) -> Result<(), EngineError> {
+        Ok(())
+    }
+
+    fn release_savepoint(&mut self) -> Result<(), EngineError> {
+        Ok(())
+    }
+
     fn commit(self) -> Result<(), EngineError> {
         Ok(())
     }
@@ -109,12 +121,21 @@ impl Catalog for NoopTransaction {
     fn drop_collection(&mut self, _: &str, _: &str) -> Result<(), EngineError> {
         panic!("NoopTransaction::drop_collection called");
     }
-    fn create_index(&mut self, _: &str, _: &str, _: &str) -> Result<(), EngineError> {
+    fn create_index(&mut self, _: &str, _: &str, _: impl Into<IndexConfig>) -> Result<(), EngineError> {
         panic!("NoopTransaction::create_index called");
     }
     fn drop_index(&mut self, _: &str, _: &str, _: &str) -> Result<(), EngineError> {
         panic!("NoopTransaction::drop_index called");
     }
+    fn reindex(&mut self, _: &str, _: &str, _: &str) -> Result<(), EngineError> {
+        panic!("NoopTransaction::reindex called");
+    }
+    fn start_index_build(&mut self, _: &str, _: &str, _: impl Into<IndexConfig>) -> Result<(), EngineError> {
+        panic!("NoopTransaction::start_index_build called");
+    }
+    fn continue_index_build(&mut self, _: &str, _: &str, _: &str, _: usize) -> Result<bool, EngineError> {
+        panic!("NoopTransaction::continue_index_build called");
+    }
     fn create_function(&mut self, _: &str, _: &str, _: FunctionKind, _: &str, _: u8, _: &[u8]) -> Result<(), EngineError> {
         panic!("NoopTransaction::create_function called");
     }
@@ -223,6 +244,18 @@ impl EngineTransaction for MockTransaction {
         Ok(0)
     }
 
+    fn set_savepoint(&mut self) -> Result<(), EngineError> {
+        Ok(())
+    }
+
+    fn rollback_to_savepoint(&mut self) -> Result<(), EngineError> {
+        Ok(())
+    }
+
+    fn release_savepoint(&mut self) -> Result<(), EngineError> {
+        Ok(())
+    }
+
     fn commit(self) -> Result<(), EngineError> {
         Ok(())
     }
@@ -245,12 +278,21 @@ impl Catalog for MockTransaction {
     fn drop_collection(&mut self, _: &str, _: &str) -> Result<(), EngineError> {
         panic!("MockTransaction::drop_collection called");
     }
-    fn create_index(&mut self, _: &str, _: &str, _: &str) -> Result<(), EngineError> {
+    fn create_index(&mut self, _: &str, _: &str, _: impl Into<IndexConfig>) -> Result<(), EngineError> {
         panic!("MockTransaction::create_index called");
     }
     fn drop_index(&mut self, _: &str, _: &str, _: &str) -> Result<(), EngineError> {
         panic!("MockTransaction::drop_index called");
     }
+    fn reindex(&mut self, _: &str, _: &str, _: &str) -> Result<(), EngineError> {
+        panic!("MockTransaction::reindex called");
+    }
+    fn start_index_build(&mut self, _: &str, _: &str, _: impl Into<IndexConfig>) -> Result<(), EngineError> {
+        panic!("MockTransaction::start_index_build called");
+    }
+    fn continue_index_build(&mut self, _: &str, _: &str, _: &str, _: usize) -> Result<bool, EngineError> {
+        panic!("MockTransaction::continue_index_build called");
+    }
     fn create_function(&mut self, _: &str, _: &str, _: FunctionKind, _: &str, _: u8, _: &[u8]) -> Result<(), EngineError> {
         panic!("MockTransaction::create_function called");
     }
@@ -5,6 +5,7 @@ use bson::raw::RawBsonRef;
 use bson::RawDocument;
 
 use crate::error::DbError;
+use crate::executor::exec::cmp_int_double;
 use crate::executor::raw_bson::RawField;
 use super::Expression;
 
@@ -91,9 +92,9 @@ fn value_eq(store_val: &RawBsonRef, query_val: &Bson) -> bool {
         (RawBsonRef::Int64(a), Bson::Int64(b)) => *a == *b,
         (RawBsonRef::Int64(a), Bson::Int32(b)) => *a == (*b as i64),
         (RawBsonRef::Double(a), Bson::Double(b)) => *a == *b,
-        (RawBsonRef::Double(a), Bson::Int64(b)) => *a == (*b as f64),
+        (RawBsonRef::Double(a), Bson::Int64(b)) => cmp_int_double(*b, *a) == Some(Ordering::Equal),
         (RawBsonRef::Double(a), Bson::Int32(b)) => *a == (*b as f64),
-        (RawBsonRef::Int64(a), Bson::Double(b)) => (*a as f64) == *b,
+        (RawBsonRef::Int64(a), Bson::Double(b)) => cmp_int_double(*a, *b) == Some(Ordering::Equal),
         (RawBsonRef::Int32(a), Bson::Double(b)) => (*a as f64) == *b,
         (RawBsonRef::Boolean(a), Bson::Boolean(b)) => *a == *b,
         (RawBsonRef::DateTime(a), Bson::DateTime(b)) => {
@@ -116,8 +117,8 @@ fn value_eq(store_val: &RawBsonRef, query_val: &Bson) -> bool {
         (RawBsonRef::DateTime(a), Bson::Int64(b)) => a.timestamp_millis() == (*b * 1000),
         (RawBsonRef::DateTime(a), Bson::Int32(b)) => a.timestamp_millis() == (*b as i64 * 1000),
 
-        // ── Incompatible types: silent exclusion ────────────────
-        _ => false,
+        // ── Other types: index order, within one type ───────────
+        _ => slate_engine::compare_values(*store_val, query_val) == Some(Ordering::Equal),
     }
 }
 
@@ -134,20 +135,19 @@ fn value_cmp(
             (RawBsonRef::Int32(a), Bson::Int64(b)) => predicate((*a as i64).cmp(b)),
             (RawBsonRef::Int64(a), Bson::Int64(b)) => predicate(a.cmp(b)),
             (RawBsonRef::Int64(a), Bson::Int32(b)) => predicate(a.cmp(&(*b as i64))),
-            (RawBsonRef::Double(a), Bson::Double(b)) => {
-                predicate(a.partial_cmp(b).unwrap_or(Ordering::Equal))
-            }
-            (RawBsonRef::Double(a), Bson::Int64(b)) => {
-                predicate(a.partial_cmp(&(*b as f64)).unwrap_or(Ordering::Equal))
-            }
-            (RawBsonRef::Double(a), Bson::Int32(b)) => {
-                predicate(a.partial_cmp(&(*b as f64)).unwrap_or(Ordering::Equal))
-            }
+            // NaN compares with nothing, as it has no place in index order.
+            (RawBsonRef::Double(a), Bson::Double(b)) => a.partial_cmp(b).is_some_and(predicate),
+            (RawBsonRef::Double(a), Bson::Int64(b)) => cmp_int_double(*b, *a)
+                .map(Ordering::reverse)
+                .is_some_and(predicate),
+            (RawBsonRef::Double(a), Bson::Int32(b)) => cmp_int_double(*b as i64, *a)
+                .map(Ordering::reverse)
+                .is_some_and(predicate),
             (RawBsonRef::Int64(a), Bson::Double(b)) => {
-                predicate((*a as f64).partial_cmp(b).unwrap_or(Ordering::Equal))
+                cmp_int_double(*a, *b).is_some_and(predicate)
             }
             (RawBsonRef::Int32(a), Bson::Double(b)) => {
-                predicate((*a as f64).partial_cmp(b).unwrap_or(Ordering::Equal))
+                cmp_int_double(*a as i64, *b).is_some_and(predicate)
             }
             (RawBsonRef::DateTime(a), Bson::DateTime(b)) => {
                 predicate(a.timestamp_millis().cmp(&b.timestamp_millis()))
@@ -175,8 +175,8 @@ fn value_cmp(
                 predicate(a.timestamp_millis().cmp(&(*b as i64 * 1000)))
             }
 
-            // ── Incompatible types: silent exclusion ────────────
-            _ => false,
+            // ── Other types: index order, within one type ───────
+            _ => slate_engine::compare_values(*store_val, query_val).is_some_and(predicate),
         },
         None => false,
     }
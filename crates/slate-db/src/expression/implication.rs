@@ -0,0 +1,169 @@
+use std::cmp::Ordering;
+
+use bson::Bson;
+
+use super::Expression;
+
+/// Whether every document matching `p` also matches `q`.
+///
+/// Conservative: `false` means "can't tell". Bounds are only compared
+/// when both are integers, both doubles or both dates, so they compare
+/// against any stored value the same way — a string bound coerces against
+/// numeric fields, where `"10" < "9"` no longer holds, and only integer
+/// bounds match dates (as epoch seconds).
+pub(super) fn implies(p: &Expression, q: &Expression) -> bool {
+    match (p, q) {
+        (_, Expression::And(qs)) => qs.iter().all(|q| implies(p, q)),
+        (Expression::Or(ps), _) => ps.iter().all(|p| implies(p, q)),
+        (Expression::And(ps), _) if ps.iter().any(|p| implies(p, q)) => true,
+        (_, Expression::Or(qs)) => qs.iter().any(|q| implies(p, q)),
+        (Expression::And(_), _) => false,
+        _ => leaf_implies(p, q),
+    }
+}
+
+fn leaf_implies(p: &Expression, q: &Expression) -> bool {
+    use Expression::*;
+
+    if field(p) != field(q) {
+        return false;
+    }
+    match (p, q) {
+        (Eq(_, a), Eq(_, b)) => a == b,
+        (Gt(_, a), Gt(_, b)) | (Gt(_, a), Gte(_, b)) | (Gte(_, a), Gte(_, b)) => {
+            ordered(a, b).is_some_and(|o| o != Ordering::Less)
+        }
+        (Gte(_, a), Gt(_, b)) => ordered(a, b) == Some(Ordering::Greater),
+        (Lt(_, a), Lt(_, b)) | (Lt(_, a), Lte(_, b)) | (Lte(_, a), Lte(_, b)) => {
+            ordered(a, b).is_some_and(|o| o != Ordering::Greater)
+        }
+        (Lte(_, a), Lt(_, b)) => ordered(a, b) == Some(Ordering::Less),
+        (Eq(_, a), Gt(_, b)) => ordered(a, b) == Some(Ordering::Greater),
+        (Eq(_, a), Gte(_, b)) => ordered(a, b).is_some_and(|o| o != Ordering::Less),
+        (Eq(_, a), Lt(_, b)) => ordered(a, b) == Some(Ordering::Less),
+        (Eq(_, a), Lte(_, b)) => ordered(a, b).is_some_and(|o| o != Ordering::Greater),
+        // Every comparison but `Eq(null)` requires the field to be present.
+        (Eq(_, Bson::Null), Exists(_, true)) => false,
+        (Eq(..) | Gt(..) | Gte(..) | Lt(..) | Lte(..) | Regex(..), Exists(_, true)) => true,
+        (Exists(_, a), Exists(_, b)) => a == b,
+        _ => false,
+    }
+}
+
+fn field(expr: &Expression) -> Option<&str> {
+    match expr {
+        Expression::Eq(f, _)
+        | Expression::Gt(f, _)
+        | Expression::Gte(f, _)
+        | Expression::Lt(f, _)
+        | Expression::Lte(f, _)
+        | Expression::Regex(f, _)
+        | Expression::Exists(f, _) => Some(f),
+        Expression::And(_) | Expression::Or(_) => None,
+    }
+}
+
+/// Order two bounds of the same kind.
+fn ordered(a: &Bson, b: &Bson) -> Option<Ordering> {
+    match (a, b) {
+        (Bson::Double(a), Bson::Double(b)) => a.partial_cmp(b),
+        (Bson::DateTime(a), Bson::DateTime(b)) => Some(a.cmp(b)),
+        _ => Some(integer(a)?.cmp(&integer(b)?)),
+    }
+}
+
+fn integer(value: &Bson) -> Option<i64> {
+    match value {
+        Bson::Int32(n) => Some(*n as i64),
+        Bson::Int64(n) => Some(*n),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bson::{RawDocumentBuf, doc};
+
+    use super::*;
+    use crate::parser::parse_filter;
+
+    fn filter(doc: bson::Document) -> Expression {
+        parse_filter(&RawDocumentBuf::try_from(&doc).unwrap()).unwrap()
+    }
+
+    fn implies_doc(p: bson::Document, q: bson::Document) -> bool {
+        implies(&filter(p), &filter(q))
+    }
+
+    #[test]
+    fn conjunct_implies_matching_filter() {
+        assert!(implies_doc(
+            doc! { "customer_id": "c1", "archived": false },
+            doc! { "archived": false },
+        ));
+        assert!(!implies_doc(
+            doc! { "customer_id": "c1" },
+            doc! { "archived": false }
+        ));
+        assert!(!implies_doc(
+            doc! { "archived": true },
+            doc! { "archived": false }
+        ));
+    }
+
+    #[test]
+    fn tighter_bounds_imply_looser_ones() {
+        assert!(implies_doc(
+            doc! { "n": { "$gt": 10 } },
+            doc! { "n": { "$gte": 5 } }
+        ));
+        assert!(implies_doc(
+            doc! { "n": 7 },
+            doc! { "n": { "$gt": 5, "$lte": 7 } }
+        ));
+        assert!(implies_doc(
+            doc! { "n": { "$gte": 5 } },
+            doc! { "n": { "$gte": 5 } }
+        ));
+        assert!(!implies_doc(
+            doc! { "n": { "$gte": 5 } },
+            doc! { "n": { "$gt": 5 } }
+        ));
+        assert!(!implies_doc(
+            doc! { "n": { "$lt": 10 } },
+            doc! { "n": { "$lt": 5 } }
+        ));
+    }
+
+    #[test]
+    fn bounds_of_different_kinds_are_not_compared() {
+        assert!(!implies_doc(
+            doc! { "n": { "$gt": 10 } },
+            doc! { "n": { "$gt": 5.0 } }
+        ));
+        assert!(!implies_doc(
+            doc! { "s": { "$gt": "9" } },
+            doc! { "s": { "$gt": "10" } }
+        ));
+    }
+
+    #[test]
+    fn or_and_exists() {
+        assert!(implies_doc(
+            doc! { "$or": [{ "tier": "gold" }, { "tier": "gold", "n": 1 }] },
+            doc! { "tier": "gold" },
+        ));
+        assert!(implies_doc(
+            doc! { "tier": "gold" },
+            doc! { "$or": [{ "tier": "gold" }, { "tier": "silver" }] },
+        ));
+        assert!(implies_doc(
+            doc! { "n": { "$gt": 1 } },
+            doc! { "n": { "$exists": true } }
+        ));
+        assert!(!implies_doc(
+            doc! { "n": null },
+            doc! { "n": { "$exists": true } }
+        ));
+    }
+}
@@ -1,4 +1,5 @@
 mod eval;
+mod implication;
 
 use bson::Bson;
 use bson::RawDocument;
@@ -41,4 +42,10 @@ impl Expression {
     pub(crate) fn matches(&self, raw: &RawDocument) -> Result<bool, DbError> {
         eval::matches(raw, self)
     }
+
+    /// Returns `true` if every document matching this expression also
+    /// matches `other`. May return `false` for implications it can't prove.
+    pub(crate) fn implies(&self, other: &Expression) -> bool {
+        implication::implies(self, other)
+    }
 }
@@ -15,14 +15,21 @@ mod statement;
 
 pub use bson::{Bson, Document, RawBson, RawDocumentBuf};
 pub use collection::CollectionConfig;
-pub use slate_engine::{FunctionKind, DEFAULT_CF};
+pub use slate_engine::{
+    CollectionStorageStats, Compression, FunctionKind, IndexConfig, DEFAULT_CF,
+};
 pub use slate_vm::VmError;
 pub use slate_vm::pool::{RuntimeRegistry, VmPool};
 pub use convert::IntoRawDocumentBuf;
 pub use cursor::{Cursor, CursorIter};
-pub use database::{Database, DatabaseBuilder, Transaction as DatabaseTransaction};
+pub use database::{
+    DEFAULT_INDEX_BUILD_BATCH, Database, DatabaseBuilder, RetryPolicy, Savepoint,
+    Snapshot as DatabaseSnapshot, Transaction as DatabaseTransaction,
+};
 pub use error::DbError;
 pub use hooks::{HookRegistry, HookSnapshot, ResolvedHook};
+#[cfg(feature = "runtime")]
+pub use runtime::index_build::IndexBuildHandle;
 
 #[cfg(feature = "bench-internals")]
 pub mod bench {
@@ -1,6 +1,9 @@
+use std::sync::Arc;
+
 use bson::raw::RawBsonRef;
-use bson::{Bson, RawDocumentBuf};
+use bson::{Bson, RawDocument, RawDocumentBuf};
 use regex::Regex;
+use slate_engine::{EngineError, IndexFilterFn};
 
 use crate::expression::Expression;
 
@@ -27,6 +30,11 @@ fn to_bson(raw: RawBsonRef<'_>) -> Result<Bson, FilterParseError> {
         RawBsonRef::Null => Ok(Bson::Null),
         RawBsonRef::DateTime(dt) => Ok(Bson::DateTime(dt)),
         RawBsonRef::ObjectId(oid) => Ok(Bson::ObjectId(oid)),
+        RawBsonRef::Decimal128(d) => Ok(Bson::Decimal128(d)),
+        RawBsonRef::Binary(b) => Ok(Bson::Binary(bson::Binary {
+            subtype: b.subtype,
+            bytes: b.bytes.to_vec(),
+        })),
         RawBsonRef::Document(d) => Ok(Bson::Document(
             bson::deserialize_from_slice(d.as_bytes())
                 .map_err(|e| FilterParseError(format!("invalid embedded document: {e}")))?,
@@ -48,6 +56,14 @@ fn to_bson(raw: RawBsonRef<'_>) -> Result<Bson, FilterParseError> {
     }
 }
 
+/// Compile a partial index filter into the engine's membership test.
+/// Installed on the engine by [`DatabaseBuilder::open`](crate::DatabaseBuilder::open).
+pub(crate) fn compile_index_filter(filter: &RawDocument) -> Result<IndexFilterFn, EngineError> {
+    let expr = parse_filter(&filter.to_owned())
+        .map_err(|e| EngineError::InvalidIndex(format!("invalid index filter: {}", e.0)))?;
+    Ok(Arc::new(move |doc| expr.matches(doc).unwrap_or(false)))
+}
+
 /// Parse a RawDocumentBuf filter into an owned Expression tree.
 ///
 /// Iterates the raw bytes directly — field keys are read as `&str` (zero-copy)
@@ -54,7 +54,8 @@ pub enum Node<Cf: Clone> {
     /// Full collection scan — yields (_id, doc) pairs.
     Scan { collection: CollectionHandle<Cf> },
 
-    /// Index scan — yields doc IDs from an index on `field`.
+    /// Index scan — yields doc IDs from the index named `field` (a single
+    /// field, or `a+b` for a compound index).
     IndexScan {
         collection: CollectionHandle<Cf>,
         field: String,
@@ -149,4 +150,11 @@ pub enum IndexScanRange {
         lower: Option<(bson::Bson, bool)>,
         upper: Option<(bson::Bson, bool)>,
     },
+    /// Compound index: exact values for the leading fields, then an
+    /// optional range on the next field.
+    Compound {
+        prefix: Vec<bson::Bson>,
+        lower: Option<(bson::Bson, bool)>,
+        upper: Option<(bson::Bson, bool)>,
+    },
 }
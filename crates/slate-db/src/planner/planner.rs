@@ -1,9 +1,10 @@
-use slate_engine::{Catalog, CollectionHandle};
+use slate_engine::{Catalog, CollectionHandle, IndexConfig, is_indexable};
 use slate_query::{Sort, SortDirection};
 
 use crate::error::DbError;
 use crate::expression::{Expression, LogicalOp};
 use crate::hooks::HookSnapshot;
+use crate::parser;
 use crate::statement::Statement;
 
 use super::plan::{IndexScanRange, Node, Plan, ScanDirection};
@@ -17,6 +18,7 @@ impl<'a, T: Catalog> Planner<'a, T>
 where
     T::Cf: Clone,
 {
+    #[allow(dead_code)] // used by the planner benches via bench-internals
     pub fn new(catalog: &'a T) -> Self {
         Self {
             catalog,
@@ -75,6 +77,7 @@ where
 
     // ── Find ────────────────────────────────────────────────────
 
+    #[allow(clippy::too_many_arguments)]
     fn plan_find(
         &self,
         cf: &str,
@@ -91,19 +94,50 @@ where
         let source_is_scan = matches!(source, Node::Scan { .. });
         let has_residual = residual.is_some();
 
+        // A compound index scan already yields rows ordered by the fields after
+        // its equality prefix — walk it in the sort direction and drop the Sort.
+        let compound_sorted = compound_sort_direction(&source, &sort);
+        let source = match (compound_sorted, source) {
+            (
+                Some(dir),
+                Node::IndexScan {
+                    collection: c,
+                    field: f,
+                    range: r,
+                    covered: cv,
+                    ..
+                },
+            ) => Node::IndexScan {
+                collection: c,
+                field: f,
+                range: r,
+                direction: dir,
+                limit: match take {
+                    Some(take) if !has_residual => Some(skip.unwrap_or(0) + take),
+                    _ => None,
+                },
+                covered: cv,
+            },
+            (_, source) => source,
+        };
+
         // Covered index optimization: Eq on indexed field, no residual,
-        // and projection only needs pk + the indexed field.
+        // and projection only needs pk + the indexed field. A multikey entry
+        // holds one element, not the stored array, and an Eq on null also
+        // matches documents without the field.
         let pk = handle.pk_path();
         let covered = !has_residual
             && matches!(
                 &source,
                 Node::IndexScan {
                     field,
-                    range: IndexScanRange::Eq(_),
+                    range: IndexScanRange::Eq(value),
                     ..
-                } if columns.as_ref().is_some_and(|cols|
-                    cols.iter().all(|c| c == pk || c == field)
-                )
+                } if !handle.is_multikey(field)
+                    && !matches!(value, bson::Bson::Null)
+                    && columns.as_ref().is_some_and(|cols|
+                        cols.iter().all(|c| c == pk || c == field)
+                    )
             );
 
         // Mark the IndexScan as covered if applicable.
@@ -150,13 +184,17 @@ where
             None => node,
         };
 
-        // Sort — try to use an index-ordered scan when possible.
+        // Sort — try to use an index-ordered scan when possible. Array
+        // values have no single position in a multikey index.
         let can_use_indexed_sort = !sort.is_empty()
             && take.is_some()
-            && handle.indexes().contains(&sort[0].field)
+            && usable_indexes(&handle, predicate).contains(&sort[0].field)
+            && !handle.is_multikey(&sort[0].field)
             && source_is_scan;
 
-        let node = if can_use_indexed_sort && sort.len() == 1 {
+        let node = if compound_sorted.is_some() {
+            node
+        } else if can_use_indexed_sort && sort.len() == 1 {
             let index_limit = if !has_residual {
                 Some(skip.unwrap_or(0) + take.unwrap_or(0))
             } else {
@@ -223,6 +261,7 @@ where
 
     // ── Distinct ──────────────────────────────────────────────────
 
+    #[allow(clippy::too_many_arguments)]
     fn plan_distinct(
         &self,
         cf: &str,
@@ -538,10 +577,11 @@ where
             return (Self::pk_lookup(handle, value), None);
         }
 
+        let indexes = usable_indexes(handle, predicate);
         match predicate {
-            Expression::And(children) => self.plan_and(handle, children, predicate),
-            Expression::Or(children) => self.plan_or(handle, children, predicate),
-            _ => self.plan_single(handle, predicate),
+            Expression::And(children) => self.plan_and(handle, &indexes, children, predicate),
+            Expression::Or(children) => self.plan_or(handle, &indexes, children, predicate),
+            _ => self.plan_single(handle, &indexes, predicate),
         }
     }
 
@@ -549,9 +589,10 @@ where
     fn plan_single(
         &self,
         handle: &CollectionHandle<T::Cf>,
+        indexes: &[String],
         predicate: &Expression,
     ) -> (Node<T::Cf>, Option<Expression>) {
-        if let Some(node) = self.try_index_scan(handle, predicate) {
+        if let Some(node) = self.try_index_scan(handle, indexes, predicate) {
             (node, None)
         } else {
             (
@@ -567,6 +608,7 @@ where
     fn plan_and(
         &self,
         handle: &CollectionHandle<T::Cf>,
+        indexes: &[String],
         children: &[Expression],
         original: &Expression,
     ) -> (Node<T::Cf>, Option<Expression>) {
@@ -581,21 +623,34 @@ where
             }
         }
 
+        // Priority 1: a compound index covering more than one field — one
+        // index walk instead of a scan plus residual filtering.
+        let compound = best_compound(handle, indexes, children);
+        if let Some(m) = compound.as_ref().filter(|m| m.fields > 1) {
+            return (m.node(handle), residual_from_and(children, &m.consumed));
+        }
+
         // Priority 1: Eq on an indexed field (most selective).
         for (i, child) in children.iter().enumerate() {
-            if let Expression::Eq(field, _) = child
-                && handle.indexes().contains(field)
+            if let Expression::Eq(field, value) = child
+                && indexes.contains(field)
+                && scannable(field, value, true)
             {
-                let node = self.try_index_scan(handle, child).unwrap();
+                let node = self.try_index_scan(handle, indexes, child).unwrap();
                 let residual = residual_from_and(children, &[i]);
                 return (node, residual);
             }
         }
 
+        // Priority 1b: Eq on the leading field of a compound index.
+        if let Some(m) = compound.as_ref().filter(|m| !m.prefix.is_empty()) {
+            return (m.node(handle), residual_from_and(children, &m.consumed));
+        }
+
         // Priority 2: Fully-indexable OR sub-groups.
         for (i, child) in children.iter().enumerate() {
             if let Expression::Or(or_children) = child
-                && let Some(node) = self.try_or_index_merge(handle, or_children)
+                && let Some(node) = self.try_or_index_merge(handle, indexes, or_children)
             {
                 let residual = residual_from_and(children, &[i]);
                 return (node, residual);
@@ -603,22 +658,33 @@ where
         }
 
         // Priority 3: Range conditions on an indexed field.
-        for field in handle.indexes() {
+        for field in indexes {
             let mut lower_idx = None;
             let mut upper_idx = None;
 
             for (i, child) in children.iter().enumerate() {
                 match child {
-                    Expression::Gt(f, _) | Expression::Gte(f, _) if f == field => {
+                    Expression::Gt(f, v) | Expression::Gte(f, v)
+                        if f == field && scannable(f, v, false) =>
+                    {
                         lower_idx = Some(i);
                     }
-                    Expression::Lt(f, _) | Expression::Lte(f, _) if f == field => {
+                    Expression::Lt(f, v) | Expression::Lte(f, v)
+                        if f == field && scannable(f, v, false) =>
+                    {
                         upper_idx = Some(i);
                     }
                     _ => {}
                 }
             }
 
+            // A multikey document can meet each bound through a different
+            // value: [1, 10] matches both $gt: 3 and $lt: 5 but has no entry
+            // between them. Scan one bound and filter on the other.
+            if handle.is_multikey(field) && lower_idx.is_some() {
+                upper_idx = None;
+            }
+
             if lower_idx.is_some() || upper_idx.is_some() {
                 let lower = lower_idx.map(|i| match &children[i] {
                     Expression::Gt(_, v) => (v.clone(), false),
@@ -645,6 +711,11 @@ where
             }
         }
 
+        // Priority 3b: Range on the leading field of a compound index.
+        if let Some(m) = compound {
+            return (m.node(handle), residual_from_and(children, &m.consumed));
+        }
+
         // No indexed condition found — full scan with whole predicate as residual.
         (
             Node::Scan {
@@ -660,10 +731,11 @@ where
     fn plan_or(
         &self,
         handle: &CollectionHandle<T::Cf>,
+        indexes: &[String],
         children: &[Expression],
         original: &Expression,
     ) -> (Node<T::Cf>, Option<Expression>) {
-        match self.try_or_index_merge(handle, children) {
+        match self.try_or_index_merge(handle, indexes, children) {
             Some(node) => (node, Some(original.clone())),
             None => (
                 Node::Scan {
@@ -679,24 +751,25 @@ where
     fn try_or_index_merge(
         &self,
         handle: &CollectionHandle<T::Cf>,
+        indexes: &[String],
         children: &[Expression],
     ) -> Option<Node<T::Cf>> {
         let mut nodes: Vec<Node<T::Cf>> = Vec::new();
 
         for child in children {
             match child {
-                Expression::Eq(field, _) if handle.indexes().contains(field) => {
-                    nodes.push(self.try_index_scan(handle, child)?);
+                Expression::Eq(..) => {
+                    nodes.push(self.try_index_scan(handle, indexes, child)?);
                 }
                 Expression::And(sub_children) => {
-                    let (node, _residual) = self.plan_and(handle, sub_children, child);
+                    let (node, _residual) = self.plan_and(handle, indexes, sub_children, child);
                     if matches!(node, Node::Scan { .. }) {
                         return None;
                     }
                     nodes.push(node);
                 }
                 Expression::Or(sub_children) => {
-                    nodes.push(self.try_or_index_merge(handle, sub_children)?);
+                    nodes.push(self.try_or_index_merge(handle, indexes, sub_children)?);
                 }
                 _ => return None,
             }
@@ -738,11 +811,17 @@ where
     }
 
     /// Try to convert a single expression into an IndexScan.
-    fn try_index_scan(&self, handle: &CollectionHandle<T::Cf>, expr: &Expression) -> Option<Node<T::Cf>> {
-        let (field, range) = match expr {
-            Expression::Eq(f, v) => (f, IndexScanRange::Eq(v.clone())),
+    fn try_index_scan(
+        &self,
+        handle: &CollectionHandle<T::Cf>,
+        indexes: &[String],
+        expr: &Expression,
+    ) -> Option<Node<T::Cf>> {
+        let (field, value, range) = match expr {
+            Expression::Eq(f, v) => (f, v, IndexScanRange::Eq(v.clone())),
             Expression::Gt(f, v) => (
                 f,
+                v,
                 IndexScanRange::Range {
                     lower: Some((v.clone(), false)),
                     upper: None,
@@ -750,6 +829,7 @@ where
             ),
             Expression::Gte(f, v) => (
                 f,
+                v,
                 IndexScanRange::Range {
                     lower: Some((v.clone(), true)),
                     upper: None,
@@ -757,6 +837,7 @@ where
             ),
             Expression::Lt(f, v) => (
                 f,
+                v,
                 IndexScanRange::Range {
                     lower: None,
                     upper: Some((v.clone(), false)),
@@ -764,6 +845,7 @@ where
             ),
             Expression::Lte(f, v) => (
                 f,
+                v,
                 IndexScanRange::Range {
                     lower: None,
                     upper: Some((v.clone(), true)),
@@ -772,7 +854,12 @@ where
             _ => return None,
         };
 
-        if !handle.indexes().contains(field) {
+        if !indexes.contains(field) {
+            // A compound index led by the field serves it just as well.
+            return best_compound(handle, indexes, std::slice::from_ref(expr))
+                .map(|m| m.node(handle));
+        }
+        if !scannable(field, value, matches!(expr, Expression::Eq(..))) {
             return None;
         }
 
@@ -789,6 +876,36 @@ where
 
 // ── Free functions ──────────────────────────────────────────────
 
+/// The indexes a plan for `predicate` may read: every full index, and each
+/// partial index whose filter the predicate implies. A partial index has
+/// no entries for documents outside its filter, an outdated one may order
+/// values differently from the filter, and one still building is missing
+/// the records not yet backfilled.
+fn usable_indexes<Cf: Clone>(handle: &CollectionHandle<Cf>, predicate: &Expression) -> Vec<String> {
+    handle
+        .indexes()
+        .iter()
+        .filter(|index| !handle.is_outdated(index) && !handle.is_building(index))
+        .filter(|index| match handle.index_filter(index) {
+            None => true,
+            Some(filter) => parser::parse_filter(&filter.document().to_owned())
+                .is_ok_and(|filter| predicate.implies(&filter)),
+        })
+        .cloned()
+        .collect()
+}
+
+/// Whether a single-field index scan can answer a comparison of `field`
+/// with `value`: it needs a value with a sortable encoding. Null only works
+/// for equality, which also matches documents without the field — and `[]`
+/// paths have no entries for those.
+fn scannable(field: &str, value: &bson::Bson, equality: bool) -> bool {
+    match value {
+        bson::Bson::Null => equality && !field.contains("[]"),
+        value => is_indexable(value),
+    }
+}
+
 /// Given AND children and a set of consumed indices, build the residual predicate.
 fn residual_from_and(children: &[Expression], consumed: &[usize]) -> Option<Expression> {
     let remaining: Vec<Expression> = children
@@ -805,6 +922,153 @@ fn residual_from_and(children: &[Expression], consumed: &[usize]) -> Option<Expr
     }
 }
 
+/// A compound index matched against the conjuncts of an AND, following the
+/// leftmost-prefix rule: equality on its leading fields, then at most one
+/// range on the next field, which ends the match.
+struct CompoundMatch {
+    index: String,
+    prefix: Vec<bson::Bson>,
+    lower: Option<(bson::Bson, bool)>,
+    upper: Option<(bson::Bson, bool)>,
+    /// Indices of the conjuncts the index scan makes redundant.
+    consumed: Vec<usize>,
+    /// Index fields constrained by the scan.
+    fields: usize,
+}
+
+impl CompoundMatch {
+    fn node<Cf: Clone>(&self, handle: &CollectionHandle<Cf>) -> Node<Cf> {
+        Node::IndexScan {
+            collection: handle.clone(),
+            field: self.index.clone(),
+            range: IndexScanRange::Compound {
+                prefix: self.prefix.clone(),
+                lower: self.lower.clone(),
+                upper: self.upper.clone(),
+            },
+            direction: ScanDirection::Forward,
+            limit: None,
+            covered: false,
+        }
+    }
+}
+
+/// The compound index covering the most fields of `children`. Ties go to
+/// the index created first.
+fn best_compound<Cf: Clone>(
+    handle: &CollectionHandle<Cf>,
+    indexes: &[String],
+    children: &[Expression],
+) -> Option<CompoundMatch> {
+    let mut best: Option<CompoundMatch> = None;
+    for index in indexes {
+        if !index.contains(IndexConfig::SEPARATOR) {
+            continue;
+        }
+        if let Some(m) = match_compound(index, handle.is_multikey(index), children)
+            && best.as_ref().is_none_or(|b| m.fields > b.fields)
+        {
+            best = Some(m);
+        }
+    }
+    best
+}
+
+fn match_compound(index: &str, multikey: bool, children: &[Expression]) -> Option<CompoundMatch> {
+    let mut m = CompoundMatch {
+        index: index.to_string(),
+        prefix: Vec::new(),
+        lower: None,
+        upper: None,
+        consumed: Vec::new(),
+        fields: 0,
+    };
+    // Null is left to the residual filter: a compound entry tells a missing
+    // field from an explicit null, where filters match both.
+    let usable = |v: &bson::Bson| !matches!(v, bson::Bson::Null) && is_indexable(v);
+
+    for field in index.split(IndexConfig::SEPARATOR) {
+        let eq = children
+            .iter()
+            .position(|c| matches!(c, Expression::Eq(f, v) if f == field && usable(v)));
+        if let Some(i) = eq {
+            let Expression::Eq(_, value) = &children[i] else {
+                unreachable!()
+            };
+            m.prefix.push(value.clone());
+            m.consumed.push(i);
+            m.fields += 1;
+            continue;
+        }
+
+        let mut upper_at = None;
+        for (i, child) in children.iter().enumerate() {
+            match child {
+                Expression::Gt(f, v) if f == field && usable(v) && m.lower.is_none() => {
+                    m.lower = Some((v.clone(), false));
+                    m.consumed.push(i);
+                }
+                Expression::Gte(f, v) if f == field && usable(v) && m.lower.is_none() => {
+                    m.lower = Some((v.clone(), true));
+                    m.consumed.push(i);
+                }
+                Expression::Lt(f, v) if f == field && usable(v) && m.upper.is_none() => {
+                    m.upper = Some((v.clone(), false));
+                    upper_at = Some(i);
+                }
+                Expression::Lte(f, v) if f == field && usable(v) && m.upper.is_none() => {
+                    m.upper = Some((v.clone(), true));
+                    upper_at = Some(i);
+                }
+                _ => {}
+            }
+        }
+        // As for a single-field index, a multikey document can meet each
+        // bound through a different value, so only one bounds the scan.
+        if multikey && m.lower.is_some() {
+            m.upper = None;
+        } else if let Some(i) = upper_at {
+            m.consumed.push(i);
+        }
+        if m.lower.is_some() || m.upper.is_some() {
+            m.fields += 1;
+        }
+        break;
+    }
+
+    (m.fields > 0).then_some(m)
+}
+
+/// Scan direction that makes a compound IndexScan yield rows in `sort`
+/// order: the sort fields must be the index fields right after the
+/// equality prefix, all in the same direction, and the index not multikey.
+fn compound_sort_direction<Cf: Clone>(source: &Node<Cf>, sort: &[Sort]) -> Option<ScanDirection> {
+    let Node::IndexScan {
+        collection,
+        field: index,
+        range: IndexScanRange::Compound { prefix, .. },
+        ..
+    } = source
+    else {
+        return None;
+    };
+    if collection.is_multikey(index) {
+        return None;
+    }
+    let direction = sort.first()?.direction;
+    let mut rest = index.split(IndexConfig::SEPARATOR).skip(prefix.len());
+    let aligned = sort
+        .iter()
+        .all(|s| s.direction == direction && rest.next() == Some(s.field.as_str()));
+    if !aligned {
+        return None;
+    }
+    Some(match direction {
+        SortDirection::Asc => ScanDirection::Forward,
+        SortDirection::Desc => ScanDirection::Reverse,
+    })
+}
+
 /// Replace the Scan node inside a plan subtree with an ordered IndexScan.
 fn replace_scan_with_index_order<Cf: Clone>(
     node: Node<Cf>,
@@ -1,5 +1,5 @@
 use bson::Bson;
-use slate_engine::{Catalog, Engine, EngineTransaction, KvEngine, DEFAULT_CF};
+use slate_engine::{Catalog, Engine, EngineTransaction, IndexConfig, KvEngine, DEFAULT_CF};
 use slate_query::{Sort, SortDirection};
 use slate_store::MemoryStore;
 
@@ -123,6 +123,79 @@ fn find_indexed_eq_uses_index_scan() {
     }
 }
 
+#[test]
+fn find_indexed_eq_null_uses_index_scan() {
+    let engine = setup();
+    let txn = engine.begin(true).unwrap();
+    let planner = Planner::new(&txn);
+
+    let plan = planner
+        .plan(find_stmt(Expression::Eq("status".into(), Bson::Null)))
+        .unwrap();
+    let node = unwrap_projection(unwrap_find(plan));
+
+    // Missing and explicit null both have entries: KeyLookup > IndexScan(status, Eq)
+    match node {
+        Node::KeyLookup { source, .. } => match *source {
+            Node::IndexScan {
+                range: IndexScanRange::Eq(v),
+                ..
+            } => assert_eq!(v, Bson::Null),
+            _ => panic!("expected IndexScan Eq"),
+        },
+        _ => panic!("expected KeyLookup"),
+    }
+}
+
+#[test]
+fn find_indexed_eq_unsortable_type_falls_back_to_scan() {
+    let engine = setup();
+    let txn = engine.begin(true).unwrap();
+    let planner = Planner::new(&txn);
+
+    let plan = planner
+        .plan(find_stmt(Expression::Eq(
+            "status".into(),
+            Bson::Timestamp(bson::Timestamp {
+                time: 1,
+                increment: 0,
+            }),
+        )))
+        .unwrap();
+    let node = unwrap_projection(unwrap_find(plan));
+
+    // Timestamps have no index encoding: Filter > Scan
+    match node {
+        Node::Filter { source, .. } => assert!(is_scan(&source)),
+        _ => panic!("expected Filter"),
+    }
+}
+
+#[test]
+fn find_on_building_index_falls_back_to_scan() {
+    let engine = engine();
+    let mut txn = engine.begin(false).unwrap();
+    txn.create_collection(DEFAULT_CF, "users", &Default::default())
+        .unwrap();
+    txn.start_index_build(DEFAULT_CF, "users", "status")
+        .unwrap();
+    let planner = Planner::new(&txn);
+
+    let plan = planner
+        .plan(find_stmt(Expression::Eq(
+            "status".into(),
+            Bson::String("active".into()),
+        )))
+        .unwrap();
+    let node = unwrap_projection(unwrap_find(plan));
+
+    // Not backfilled yet: Filter > Scan
+    match node {
+        Node::Filter { source, .. } => assert!(is_scan(&source)),
+        _ => panic!("expected Filter"),
+    }
+}
+
 #[test]
 fn find_indexed_range_uses_index_scan() {
     let engine = setup();
@@ -418,6 +491,190 @@ fn sort_without_take_does_not_use_index_order() {
     }
 }
 
+// ── Find: compound index ────────────────────────────────────
+
+/// `setup()` plus a compound index on ["status", "created_at"].
+fn setup_compound() -> KvEngine<MemoryStore> {
+    let engine = setup();
+    let mut txn = engine.begin(false).unwrap();
+    txn.create_index(DEFAULT_CF, "users", ["status", "created_at"]).unwrap();
+    txn.commit().unwrap();
+    engine
+}
+
+#[test]
+fn compound_index_serves_eq_and_range_in_one_scan() {
+    let engine = setup_compound();
+    let txn = engine.begin(true).unwrap();
+    let planner = Planner::new(&txn);
+
+    let plan = planner
+        .plan(find_stmt(Expression::And(vec![
+            Expression::Eq("status".into(), Bson::String("active".into())),
+            Expression::Gt("created_at".into(), Bson::Int64(100)),
+        ])))
+        .unwrap();
+    let node = unwrap_projection(unwrap_find(plan));
+
+    // KeyLookup > IndexScan(status+created_at, Compound) — no residual
+    match node {
+        Node::KeyLookup { source, .. } => match *source {
+            Node::IndexScan {
+                field,
+                range: IndexScanRange::Compound { prefix, lower, upper },
+                ..
+            } => {
+                assert_eq!(field, "status+created_at");
+                assert_eq!(prefix, vec![Bson::String("active".into())]);
+                assert_eq!(lower, Some((Bson::Int64(100), false)));
+                assert_eq!(upper, None);
+            }
+            _ => panic!("expected compound IndexScan"),
+        },
+        _ => panic!("expected KeyLookup"),
+    }
+}
+
+#[test]
+fn compound_index_requires_leading_field() {
+    let engine = setup_compound();
+    let txn = engine.begin(true).unwrap();
+    let planner = Planner::new(&txn);
+
+    let plan = planner
+        .plan(find_stmt(Expression::Gt(
+            "created_at".into(),
+            Bson::Int64(100),
+        )))
+        .unwrap();
+    let node = unwrap_projection(unwrap_find(plan));
+
+    // Filter > Scan — created_at alone is not a prefix of the index
+    match node {
+        Node::Filter { source, .. } => assert!(is_scan(&source)),
+        _ => panic!("expected Filter"),
+    }
+}
+
+#[test]
+fn compound_index_orders_sort_after_eq_prefix() {
+    let engine = engine();
+    let mut txn = engine.begin(false).unwrap();
+    txn.create_collection(DEFAULT_CF, "users", &Default::default()).unwrap();
+    txn.create_index(DEFAULT_CF, "users", ["status", "created_at"]).unwrap();
+    txn.commit().unwrap();
+
+    let txn = engine.begin(true).unwrap();
+    let planner = Planner::new(&txn);
+
+    let plan = planner
+        .plan(Statement::Find {
+            cf: DEFAULT_CF,
+            collection: "users",
+            predicate: Expression::Eq("status".into(), Bson::String("active".into())),
+            sort: vec![Sort {
+                field: "created_at".into(),
+                direction: SortDirection::Desc,
+            }],
+            skip: Some(2),
+            take: Some(10),
+            projection: None,
+        })
+        .unwrap();
+    let node = unwrap_projection(unwrap_find(plan));
+
+    // Limit > KeyLookup > IndexScan(Reverse, limit 12) — no Sort node
+    match node {
+        Node::Limit { source, .. } => match *source {
+            Node::KeyLookup { source, .. } => match *source {
+                Node::IndexScan {
+                    field,
+                    range: IndexScanRange::Compound { .. },
+                    direction: ScanDirection::Reverse,
+                    limit,
+                    ..
+                } => {
+                    assert_eq!(field, "status+created_at");
+                    assert_eq!(limit, Some(12));
+                }
+                _ => panic!("expected compound IndexScan Reverse"),
+            },
+            _ => panic!("expected KeyLookup"),
+        },
+        _ => panic!("expected Limit"),
+    }
+}
+
+// ── Find: partial index ─────────────────────────────────────
+
+/// "orders" with a partial index on `customer_id` over unarchived orders.
+fn setup_partial() -> KvEngine<MemoryStore> {
+    let engine = engine().with_index_filter(crate::parser::compile_index_filter);
+    let mut txn = engine.begin(false).unwrap();
+    txn.create_collection(DEFAULT_CF, "orders", &Default::default()).unwrap();
+    let index = IndexConfig::new(["customer_id"]).with_filter(bson::rawdoc! { "archived": false });
+    txn.create_index(DEFAULT_CF, "orders", index).unwrap();
+    txn.commit().unwrap();
+    engine
+}
+
+fn find_orders(predicate: Expression) -> Statement<'static> {
+    Statement::Find {
+        cf: DEFAULT_CF,
+        collection: "orders",
+        predicate,
+        sort: vec![],
+        skip: None,
+        take: None,
+        projection: None,
+    }
+}
+
+#[test]
+fn partial_index_used_when_query_implies_filter() {
+    let engine = setup_partial();
+    let txn = engine.begin(true).unwrap();
+    let planner = Planner::new(&txn);
+
+    let plan = planner
+        .plan(find_orders(Expression::And(vec![
+            Expression::Eq("customer_id".into(), Bson::String("c1".into())),
+            Expression::Eq("archived".into(), Bson::Boolean(false)),
+        ])))
+        .unwrap();
+    let node = unwrap_projection(unwrap_find(plan));
+
+    // Filter(archived = false) > KeyLookup > IndexScan(customer_id)
+    match node {
+        Node::Filter { source, .. } => match *source {
+            Node::KeyLookup { source, .. } => assert!(is_index_scan_on(&source, "customer_id")),
+            _ => panic!("expected KeyLookup"),
+        },
+        _ => panic!("expected Filter"),
+    }
+}
+
+#[test]
+fn partial_index_skipped_when_query_does_not_imply_filter() {
+    let engine = setup_partial();
+    let txn = engine.begin(true).unwrap();
+    let planner = Planner::new(&txn);
+
+    // Archived orders have no entries — the index would miss them.
+    let plan = planner
+        .plan(find_orders(Expression::Eq(
+            "customer_id".into(),
+            Bson::String("c1".into()),
+        )))
+        .unwrap();
+    let node = unwrap_projection(unwrap_find(plan));
+
+    match node {
+        Node::Filter { source, .. } => assert!(is_scan(&source)),
+        _ => panic!("expected Filter"),
+    }
+}
+
 // ── Find: covered index ─────────────────────────────────────
 
 #[test]
@@ -484,6 +741,68 @@ fn not_covered_when_projection_needs_extra_fields() {
     }
 }
 
+#[test]
+fn multikey_index_is_not_covered_or_sort_ordered() {
+    let engine = setup();
+    let txn = engine.begin(false).unwrap();
+    let handle = txn.collection(DEFAULT_CF, "users").unwrap();
+    let doc = bson::rawdoc! { "_id": "u1", "status": ["active", "trial"], "age": [30, 40] };
+    txn.put(&handle, &doc).unwrap();
+    txn.commit().unwrap();
+
+    let txn = engine.begin(true).unwrap();
+    let planner = Planner::new(&txn);
+
+    // The entry holds one element, not the array: fetch the record.
+    let plan = planner
+        .plan(Statement::Find {
+            cf: DEFAULT_CF,
+            collection: "users",
+            predicate: Expression::Eq("status".into(), Bson::String("active".into())),
+            sort: vec![],
+            skip: None,
+            take: None,
+            projection: Some(vec!["_id".into(), "status".into()]),
+        })
+        .unwrap();
+    match unwrap_find(plan) {
+        Node::Projection { source, .. } => match *source {
+            Node::KeyLookup { source, .. } => match *source {
+                Node::IndexScan { covered, .. } => assert!(!covered),
+                _ => panic!("expected IndexScan"),
+            },
+            _ => panic!("expected KeyLookup"),
+        },
+        _ => panic!("expected Projection"),
+    }
+
+    // Sort > Filter > Scan (no indexed sort on a multikey field)
+    let plan = planner
+        .plan(Statement::Find {
+            cf: DEFAULT_CF,
+            collection: "users",
+            predicate: Expression::Exists("_id".into(), true),
+            sort: vec![Sort {
+                field: "age".into(),
+                direction: SortDirection::Desc,
+            }],
+            skip: None,
+            take: Some(10),
+            projection: None,
+        })
+        .unwrap();
+    match unwrap_projection(unwrap_find(plan)) {
+        Node::Limit { source, .. } => match *source {
+            Node::Sort { source, .. } => match *source {
+                Node::Filter { source, .. } => assert!(is_scan(&source)),
+                _ => panic!("expected Filter"),
+            },
+            _ => panic!("expected Sort"),
+        },
+        _ => panic!("expected Limit"),
+    }
+}
+
 // ── Find: projection + limit ────────────────────────────────
 
 #[test]
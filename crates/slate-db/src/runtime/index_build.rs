@@ -0,0 +1,85 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+
+use slate_engine::KvEngine;
+use slate_store::Store;
+
+use crate::database::{RetryPolicy, index_build_batch};
+use crate::error::DbError;
+
+/// An index build running on a background thread, returned by
+/// [`Database::build_index`](crate::Database::build_index).
+///
+/// Dropping the handle stops the build after its current batch. The index
+/// stays building, and
+/// [`Database::resume_index_builds_blocking`](crate::Database::resume_index_builds_blocking)
+/// finishes it from where it stopped.
+pub struct IndexBuildHandle {
+    stop: Arc<AtomicBool>,
+    handle: Option<thread::JoinHandle<Result<(), DbError>>>,
+}
+
+impl IndexBuildHandle {
+    /// Whether the build has ended, successfully or not.
+    pub fn is_finished(&self) -> bool {
+        self.handle.as_ref().is_none_or(|h| h.is_finished())
+    }
+
+    /// Block until the build ends. A failed build leaves the index building
+    /// and returns the error, as
+    /// [`build_index_blocking`](crate::Database::build_index_blocking) does.
+    pub fn wait(mut self) -> Result<(), DbError> {
+        self.join()
+    }
+
+    /// Stop the build after its current batch and wait for the thread. The
+    /// index stays building unless the build had already finished.
+    pub fn cancel(mut self) -> Result<(), DbError> {
+        self.stop.store(true, Ordering::Relaxed);
+        self.join()
+    }
+
+    fn join(&mut self) -> Result<(), DbError> {
+        match self.handle.take() {
+            Some(h) => h
+                .join()
+                .unwrap_or_else(|panic| std::panic::resume_unwind(panic)),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Drop for IndexBuildHandle {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(h) = self.handle.take() {
+            let _ = h.join();
+        }
+    }
+}
+
+/// Spawn a thread that backfills the building index `name` batch by batch.
+pub(crate) fn spawn<S: Store + Send + Sync + 'static>(
+    engine: Arc<KvEngine<S>>,
+    retry: RetryPolicy,
+    batch: usize,
+    cf: String,
+    collection: String,
+    name: String,
+) -> IndexBuildHandle {
+    let stop = Arc::new(AtomicBool::new(false));
+    let build_stop = Arc::clone(&stop);
+    let handle = thread::spawn(move || {
+        while !build_stop.load(Ordering::Relaxed) {
+            if index_build_batch(&engine, &retry, batch, &cf, &collection, &name)? {
+                break;
+            }
+        }
+        Ok(())
+    });
+    IndexBuildHandle {
+        stop,
+        handle: Some(handle),
+    }
+}
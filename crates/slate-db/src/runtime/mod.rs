@@ -1 +1,2 @@
+pub(crate) mod index_build;
 pub(crate) mod sweep;
@@ -0,0 +1,44 @@
+mod common;
+use common::*;
+
+use bson::{doc, rawdoc};
+use slate_db::{DEFAULT_CF, DatabaseBuilder};
+use slate_store::MemoryStore;
+
+// ── Backup tests ────────────────────────────────────────────────
+
+#[test]
+fn memory_backup_restores_documents_and_indexes() {
+    let (db, dir) = temp_db();
+    create_collection(&db, COLLECTION);
+
+    let mut txn = db.begin(false).unwrap();
+    txn.create_index(DEFAULT_CF, COLLECTION, "status").unwrap();
+    txn.insert_one(
+        DEFAULT_CF,
+        COLLECTION,
+        doc! { "_id": "acct-1", "name": "Acme", "status": "active" },
+    )
+    .unwrap()
+    .drain()
+    .unwrap();
+    txn.commit().unwrap();
+
+    let path = dir.path().join("db.snapshot");
+    db.backup(&path).unwrap();
+
+    let restored = DatabaseBuilder::new()
+        .open(MemoryStore::load(&path).unwrap())
+        .unwrap();
+    let txn = restored.begin(true).unwrap();
+    let record = txn
+        .find_one(DEFAULT_CF, COLLECTION, rawdoc! { "status": "active" })
+        .unwrap()
+        .unwrap();
+    assert_eq!(record.get_str("name").unwrap(), "Acme");
+    assert!(
+        txn.list_indexes(DEFAULT_CF, COLLECTION)
+            .unwrap()
+            .contains(&"status".to_string())
+    );
+}
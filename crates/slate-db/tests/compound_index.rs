@@ -0,0 +1,221 @@
+mod common;
+use common::*;
+
+use bson::{Bson, RawDocumentBuf, doc, rawdoc};
+use slate_db::{DEFAULT_CF, Database, DbError};
+use slate_query::{FindOptions, Sort, SortDirection};
+use slate_store::MemoryStore;
+
+// ── Compound index tests ────────────────────────────────────────
+
+fn index_status_revenue(db: &Database<MemoryStore>) {
+    let mut txn = db.begin(false).unwrap();
+    txn.create_index(DEFAULT_CF, COLLECTION, ["status", "revenue"])
+        .unwrap();
+    txn.commit().unwrap();
+}
+
+fn find_ids(
+    db: &Database<MemoryStore>,
+    filter: RawDocumentBuf,
+    options: FindOptions,
+) -> Vec<String> {
+    let txn = db.begin(true).unwrap();
+    txn.find(DEFAULT_CF, COLLECTION, filter, options)
+        .unwrap()
+        .iter()
+        .unwrap()
+        .map(|r| r.unwrap().get_str("_id").unwrap().to_string())
+        .collect()
+}
+
+fn by_revenue(direction: SortDirection, take: Option<usize>) -> FindOptions {
+    FindOptions {
+        sort: vec![Sort {
+            field: "revenue".into(),
+            direction,
+        }],
+        take,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn compound_index_is_listed_by_name() {
+    let (db, _dir) = temp_db();
+    seed_records(&db);
+    index_status_revenue(&db);
+
+    let txn = db.begin(true).unwrap();
+    let indexes = txn.list_indexes(DEFAULT_CF, COLLECTION).unwrap();
+    assert!(indexes.contains(&"status+revenue".to_string()));
+}
+
+#[test]
+fn compound_index_matches_full_scan_results() {
+    let (plain, _d1) = temp_db();
+    seed_records(&plain);
+    let (indexed, _d2) = temp_db();
+    seed_records(&indexed);
+    index_status_revenue(&indexed);
+
+    let filters = [
+        rawdoc! { "status": "active", "revenue": { "$gt": 60000.0 } },
+        rawdoc! { "status": "active", "revenue": { "$gte": 50000.0, "$lt": 95000.0 } },
+        rawdoc! { "status": "active", "revenue": 95000.0 },
+        rawdoc! { "status": "active" },
+        rawdoc! { "status": "missing", "revenue": { "$gt": 0.0 } },
+        rawdoc! { "status": "active", "active": true },
+    ];
+    for filter in filters {
+        let mut expected = find_ids(&plain, filter.clone(), FindOptions::default());
+        let mut actual = find_ids(&indexed, filter.clone(), FindOptions::default());
+        expected.sort();
+        actual.sort();
+        assert_eq!(actual, expected, "filter {filter:?}");
+    }
+}
+
+#[test]
+fn compound_index_sorts_after_equality_prefix() {
+    let (db, _dir) = temp_db();
+    seed_records(&db);
+    index_status_revenue(&db);
+
+    let filter = eq_filter("status", Bson::String("active".into()));
+    let asc = find_ids(&db, filter.clone(), by_revenue(SortDirection::Asc, None));
+    assert_eq!(asc, vec!["acct-1", "acct-4", "acct-5"]);
+
+    let desc = find_ids(&db, filter, by_revenue(SortDirection::Desc, Some(2)));
+    assert_eq!(desc, vec!["acct-5", "acct-4"]);
+}
+
+#[test]
+fn compound_index_follows_writes() {
+    let (db, _dir) = temp_db();
+    seed_records(&db);
+    index_status_revenue(&db);
+
+    let txn = db.begin(false).unwrap();
+    txn.update_one(
+        DEFAULT_CF,
+        COLLECTION,
+        doc! { "_id": "acct-2" },
+        doc! { "$set": { "status": "active" } },
+    )
+    .unwrap()
+    .drain()
+    .unwrap();
+    txn.delete_one(DEFAULT_CF, COLLECTION, doc! { "_id": "acct-5" })
+        .unwrap()
+        .drain()
+        .unwrap();
+    txn.commit().unwrap();
+
+    let ids = find_ids(
+        &db,
+        rawdoc! { "status": "active", "revenue": { "$gt": 60000.0 } },
+        by_revenue(SortDirection::Asc, None),
+    );
+    assert_eq!(ids, vec!["acct-2", "acct-4"]);
+}
+
+#[test]
+fn compound_index_drops_by_name() {
+    let (db, _dir) = temp_db();
+    seed_records(&db);
+    index_status_revenue(&db);
+
+    let mut txn = db.begin(false).unwrap();
+    txn.drop_index(DEFAULT_CF, COLLECTION, "status+revenue")
+        .unwrap();
+    let indexes = txn.list_indexes(DEFAULT_CF, COLLECTION).unwrap();
+    assert!(!indexes.contains(&"status+revenue".to_string()));
+}
+
+#[test]
+fn compound_index_rejects_repeated_fields() {
+    let (db, _dir) = temp_db();
+    seed_records(&db);
+
+    let mut txn = db.begin(false).unwrap();
+    let result = txn.create_index(DEFAULT_CF, COLLECTION, ["status", "status"]);
+    assert!(
+        matches!(result, Err(DbError::InvalidQuery(_))),
+        "got {result:?}"
+    );
+}
+
+#[test]
+fn index_rejects_field_names_containing_separator() {
+    let (db, _dir) = temp_db();
+    seed_records(&db);
+
+    // "a+b" would read back from the catalog as the compound index [a, b].
+    let mut txn = db.begin(false).unwrap();
+    let result = txn.create_index(DEFAULT_CF, COLLECTION, ["a+b"]);
+    assert!(
+        matches!(result, Err(DbError::InvalidQuery(_))),
+        "got {result:?}"
+    );
+    let result = txn.create_index(DEFAULT_CF, COLLECTION, ["status", "a+b"]);
+    assert!(
+        matches!(result, Err(DbError::InvalidQuery(_))),
+        "got {result:?}"
+    );
+    let indexes = txn.list_indexes(DEFAULT_CF, COLLECTION).unwrap();
+    assert!(indexes.iter().all(|name| !name.contains("a+b")));
+}
+
+#[test]
+fn compound_index_rejects_parallel_arrays() {
+    let (db, _dir) = temp_db();
+    create_collection(&db, COLLECTION);
+    let mut txn = db.begin(false).unwrap();
+    txn.create_index(DEFAULT_CF, COLLECTION, ["tags", "sizes"])
+        .unwrap();
+    txn.insert_many(
+        DEFAULT_CF,
+        COLLECTION,
+        vec![doc! { "_id": "a", "tags": ["x", "y"], "sizes": 1 }],
+    )
+    .unwrap()
+    .drain()
+    .unwrap();
+    let result = txn
+        .insert_many(
+            DEFAULT_CF,
+            COLLECTION,
+            vec![doc! { "_id": "b", "tags": ["x", "y"], "sizes": [1, 2] }],
+        )
+        .and_then(|cursor| cursor.drain());
+    assert!(
+        matches!(result, Err(DbError::InvalidDocument(_))),
+        "got {result:?}"
+    );
+    txn.commit().unwrap();
+
+    // Backfill meets the same document already stored.
+    let (db, _dir) = temp_db();
+    create_collection(&db, COLLECTION);
+    let mut txn = db.begin(false).unwrap();
+    txn.insert_many(
+        DEFAULT_CF,
+        COLLECTION,
+        vec![doc! { "_id": "b", "tags": ["x", "y"], "sizes": [1, 2] }],
+    )
+    .unwrap()
+    .drain()
+    .unwrap();
+    let result = txn.create_index(DEFAULT_CF, COLLECTION, ["tags", "sizes"]);
+    assert!(
+        matches!(result, Err(DbError::InvalidDocument(_))),
+        "got {result:?}"
+    );
+    txn.commit().unwrap();
+    let result = db.build_index_blocking(DEFAULT_CF, COLLECTION, ["tags", "sizes"]);
+    assert!(
+        matches!(result, Err(DbError::InvalidDocument(_))),
+        "got {result:?}"
+    );
+}
@@ -0,0 +1,100 @@
+mod common;
+use common::*;
+
+use bson::{Bson, doc, rawdoc};
+use slate_db::{DEFAULT_CF, Database, DatabaseBuilder};
+use slate_store::MemoryStore;
+
+// ── eviction tests ──────────────────────────────────────────────
+
+/// A database whose default CF holds about 20 of the documents below.
+fn bounded_db() -> Database<MemoryStore> {
+    let store = MemoryStore::new().with_cf_budget(DEFAULT_CF, 4_000);
+    let db = DatabaseBuilder::new().open(store).unwrap();
+    create_collection(&db, COLLECTION);
+    let mut txn = db.begin(false).unwrap();
+    txn.create_index(DEFAULT_CF, COLLECTION, "status").unwrap();
+    txn.commit().unwrap();
+    db
+}
+
+fn insert(db: &Database<MemoryStore>, id: usize) {
+    let mut txn = db.begin(false).unwrap();
+    txn.insert_one(
+        DEFAULT_CF,
+        COLLECTION,
+        doc! { "_id": format!("acct-{id}"), "status": "active", "notes": "x".repeat(100) },
+    )
+    .unwrap()
+    .drain()
+    .unwrap();
+    txn.commit().unwrap();
+}
+
+fn exists(db: &Database<MemoryStore>, id: usize) -> bool {
+    let txn = db.begin(true).unwrap();
+    txn.find_one(DEFAULT_CF, COLLECTION, doc! { "_id": format!("acct-{id}") })
+        .unwrap()
+        .is_some()
+}
+
+#[test]
+fn inserts_past_the_budget_evict_old_records() {
+    let db = bounded_db();
+    for id in 0..100 {
+        insert(&db, id);
+    }
+
+    let stats = db.collection_storage_stats(DEFAULT_CF, COLLECTION).unwrap();
+    assert!(stats.evictions > 0);
+    assert_eq!(stats.records.keys + stats.evictions, 100);
+    assert!(!exists(&db, 0));
+    assert!(exists(&db, 99));
+}
+
+#[test]
+fn recently_read_records_survive_eviction() {
+    let db = bounded_db();
+    for id in 0..10 {
+        insert(&db, id);
+    }
+    for id in 10..100 {
+        // Keep acct-0 hot while newer records push the rest out.
+        assert!(exists(&db, 0));
+        insert(&db, id);
+    }
+    assert!(exists(&db, 0));
+    assert!(!exists(&db, 1));
+}
+
+#[test]
+fn eviction_removes_index_entries() {
+    let db = bounded_db();
+    for id in 0..100 {
+        insert(&db, id);
+    }
+
+    let stats = db.collection_storage_stats(DEFAULT_CF, COLLECTION).unwrap();
+    assert_eq!(stats.indexes[0].0, "status");
+    assert_eq!(stats.indexes[0].1.keys, stats.records.keys);
+
+    let txn = db.begin(true).unwrap();
+    let by_index = txn
+        .count(
+            DEFAULT_CF,
+            COLLECTION,
+            eq_filter("status", Bson::String("active".into())),
+        )
+        .unwrap();
+    let total = txn.count(DEFAULT_CF, COLLECTION, rawdoc! {}).unwrap();
+    assert_eq!(by_index, total);
+}
+
+#[test]
+fn unbounded_store_never_evicts() {
+    let (db, _dir) = temp_db();
+    seed_records(&db);
+    let stats = db.collection_storage_stats(DEFAULT_CF, COLLECTION).unwrap();
+    assert_eq!(stats.evictions, 0);
+    assert_eq!(stats.records.keys, 5);
+}
@@ -1142,3 +1142,55 @@ fn find_gte_lte_on_indexed_field() {
     assert!(names.contains(&"Alice"));
     assert!(names.contains(&"Charlie"));
 }
+
+// ── Compressed collections ──────────────────────────────────────
+
+#[test]
+fn find_filters_compressed_records() {
+    let (db, _dir) = temp_db();
+    let mut txn = db.begin(false).unwrap();
+    txn.create_collection(&CollectionConfig {
+        name: "notes".to_string(),
+        compression: slate_db::Compression::Lz4 { dictionary: None },
+        ..Default::default()
+    })
+    .unwrap();
+    txn.create_index(DEFAULT_CF, "notes", "status").unwrap();
+    let body = "lorem ipsum dolor sit amet ".repeat(40);
+    txn.insert_many(
+        DEFAULT_CF,
+        "notes",
+        vec![
+            doc! { "_id": "n1", "status": "open", "score": 10, "body": &body },
+            doc! { "_id": "n2", "status": "closed", "score": 20, "body": &body },
+            doc! { "_id": "n3", "status": "open", "score": 30, "body": &body },
+        ],
+    )
+    .unwrap()
+    .drain()
+    .unwrap();
+    txn.commit().unwrap();
+
+    let txn = db.begin(true).unwrap();
+    // Indexed eq + residual range filter over decompressed documents.
+    let results = txn
+        .find(
+            DEFAULT_CF,
+            "notes",
+            rawdoc! { "status": "open", "score": { "$gt": 15 } },
+            FindOptions::default(),
+        )
+        .unwrap()
+        .iter()
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(sorted_ids(&results), vec!["n3"]);
+    assert_eq!(results[0].get_str("body").unwrap(), body);
+
+    // Full scan path.
+    let count = txn
+        .count(DEFAULT_CF, "notes", rawdoc! { "score": { "$lt": 25 } })
+        .unwrap();
+    assert_eq!(count, 2);
+}
@@ -0,0 +1,109 @@
+mod common;
+use common::*;
+
+use std::sync::Arc;
+
+use bson::{doc, rawdoc};
+use slate_db::{DEFAULT_CF, DatabaseBuilder, RuntimeRegistry, VmPool};
+use slate_store::MemoryStore;
+use slate_vm::{LuaScriptRuntime, RuntimeKind};
+
+fn scripting_pool() -> VmPool {
+    let mut reg = RuntimeRegistry::new();
+    reg.register(RuntimeKind::Lua, Arc::new(LuaScriptRuntime::new()));
+    VmPool::new(reg)
+}
+
+// ── fork tests ──────────────────────────────────────────────────
+
+#[test]
+fn fork_starts_with_parent_data_and_catalog() {
+    let (db, _dir) = temp_db();
+    seed_records(&db);
+    let mut txn = db.begin(false).unwrap();
+    txn.create_index(DEFAULT_CF, COLLECTION, "status").unwrap();
+    txn.commit().unwrap();
+
+    let fork = db.fork().unwrap();
+    assert_eq!(
+        fork.list_collections().unwrap(),
+        db.list_collections().unwrap()
+    );
+    let txn = fork.begin(true).unwrap();
+    assert_eq!(txn.count(DEFAULT_CF, COLLECTION, rawdoc! {}).unwrap(), 5);
+    let stats = fork
+        .collection_storage_stats(DEFAULT_CF, COLLECTION)
+        .unwrap();
+    assert_eq!(stats.indexes[0].0, "status");
+    assert_eq!(stats.indexes[0].1.keys, 5);
+}
+
+#[test]
+fn fork_writes_stay_in_the_fork() {
+    let (db, _dir) = temp_db();
+    seed_records(&db);
+    let fork = db.fork().unwrap();
+
+    let mut txn = fork.begin(false).unwrap();
+    txn.insert_one(DEFAULT_CF, COLLECTION, doc! { "_id": "acct-6" })
+        .unwrap()
+        .drain()
+        .unwrap();
+    txn.delete_one(DEFAULT_CF, COLLECTION, doc! { "_id": "acct-1" })
+        .unwrap()
+        .drain()
+        .unwrap();
+    txn.commit().unwrap();
+    create_collection(&fork, "fork_only");
+
+    let txn = db.begin(true).unwrap();
+    assert_eq!(txn.count(DEFAULT_CF, COLLECTION, rawdoc! {}).unwrap(), 5);
+    assert!(
+        txn.find_one(DEFAULT_CF, COLLECTION, doc! { "_id": "acct-6" })
+            .unwrap()
+            .is_none()
+    );
+    assert_eq!(db.list_collections().unwrap().len(), 1);
+}
+
+#[test]
+fn parent_writes_after_fork_stay_in_the_parent() {
+    let (db, _dir) = temp_db();
+    seed_records(&db);
+    let fork = db.fork().unwrap();
+
+    let mut txn = db.begin(false).unwrap();
+    txn.insert_one(DEFAULT_CF, COLLECTION, doc! { "_id": "acct-6" })
+        .unwrap()
+        .drain()
+        .unwrap();
+    txn.commit().unwrap();
+
+    let txn = fork.begin(true).unwrap();
+    assert_eq!(txn.count(DEFAULT_CF, COLLECTION, rawdoc! {}).unwrap(), 5);
+}
+
+#[test]
+fn fork_carries_over_hooks() {
+    let db = DatabaseBuilder::new()
+        .with_scripting(scripting_pool())
+        .open(MemoryStore::new())
+        .unwrap();
+    create_collection(&db, COLLECTION);
+    let mut txn = db.begin(false).unwrap();
+    txn.register_trigger(
+        DEFAULT_CF,
+        COLLECTION,
+        "bad_trigger",
+        "return function(ctx, event) error('trigger failed!') end",
+    )
+    .unwrap();
+    txn.commit().unwrap();
+
+    let fork = db.fork().unwrap();
+    let mut txn = fork.begin(false).unwrap();
+    let result = txn
+        .insert_one(DEFAULT_CF, COLLECTION, doc! { "_id": "r1" })
+        .and_then(|cursor| cursor.drain());
+    assert!(result.is_err());
+}
@@ -14,7 +14,7 @@ fn create_collection_with_indexes(db: &slate_db::Database<slate_store::MemorySto
     })
     .unwrap();
     for field in indexes {
-        txn.create_index(DEFAULT_CF, name, field).unwrap();
+        txn.create_index(DEFAULT_CF, name, *field).unwrap();
     }
     txn.commit().unwrap();
 }
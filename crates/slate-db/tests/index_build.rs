@@ -0,0 +1,306 @@
+mod common;
+use common::*;
+
+use bson::{Bson, Document, doc};
+use slate_db::{
+    CollectionConfig, DEFAULT_CF, Database, DatabaseBuilder, DbError, IndexConfig, RetryPolicy,
+};
+use slate_query::FindOptions;
+use slate_store::{DurableMemoryStore, MemoryStore, Store, Transaction};
+
+// ── Online index build tests ────────────────────────────────────
+
+/// Database that backfills index builds two records per transaction.
+fn batched_db() -> Database<MemoryStore> {
+    DatabaseBuilder::new()
+        .with_index_build_batch(2)
+        .with_retry_policy(RetryPolicy {
+            max_attempts: 50,
+            ..Default::default()
+        })
+        .open(MemoryStore::new())
+        .unwrap()
+}
+
+fn find_ids(db: &Database<MemoryStore>, filter: bson::RawDocumentBuf) -> Vec<String> {
+    let txn = db.begin(true).unwrap();
+    let mut ids: Vec<String> = txn
+        .find(DEFAULT_CF, COLLECTION, filter, FindOptions::default())
+        .unwrap()
+        .iter()
+        .unwrap()
+        .map(|r| {
+            Document::try_from(r.unwrap())
+                .unwrap()
+                .get_str("_id")
+                .unwrap()
+                .to_string()
+        })
+        .collect();
+    ids.sort();
+    ids
+}
+
+#[test]
+fn build_index_backfills_existing_records() {
+    let db = batched_db();
+    seed_records(&db);
+    db.build_index_blocking(DEFAULT_CF, COLLECTION, "status")
+        .unwrap();
+
+    let txn = db.begin(true).unwrap();
+    let indexes = txn.list_indexes(DEFAULT_CF, COLLECTION).unwrap();
+    assert!(indexes.contains(&"status".to_string()));
+    drop(txn);
+    let stats = db.collection_storage_stats(DEFAULT_CF, COLLECTION).unwrap();
+    assert_eq!(stats.indexes[0].0, "status");
+    assert_eq!(stats.indexes[0].1.keys, 5);
+    assert_eq!(
+        find_ids(&db, eq_filter("status", Bson::String("active".into()))),
+        vec!["acct-1", "acct-4", "acct-5"]
+    );
+
+    assert!(matches!(
+        db.build_index_blocking(DEFAULT_CF, COLLECTION, "status"),
+        Err(DbError::IndexExists(_))
+    ));
+    assert_eq!(db.resume_index_builds_blocking().unwrap(), 0);
+}
+
+#[test]
+fn build_unique_index_with_duplicates_fails_and_can_resume() {
+    let db = batched_db();
+    seed_records(&db);
+    let result = db.build_index_blocking(
+        DEFAULT_CF,
+        COLLECTION,
+        IndexConfig::new(["status"]).unique(),
+    );
+    match result {
+        Err(DbError::DuplicateKey(msg)) => assert!(msg.contains("status"), "{msg}"),
+        other => panic!("expected DuplicateKey, got {other:?}"),
+    }
+
+    // The failed build is kept, unused by queries until it finishes.
+    let txn = db.begin(true).unwrap();
+    let indexes = txn.list_indexes(DEFAULT_CF, COLLECTION).unwrap();
+    assert!(indexes.contains(&"status".to_string()));
+    drop(txn);
+    assert_eq!(
+        find_ids(&db, eq_filter("status", Bson::String("active".into()))),
+        vec!["acct-1", "acct-4", "acct-5"]
+    );
+
+    db.transact(|txn| {
+        for id in ["acct-4", "acct-5"] {
+            txn.delete_one(
+                DEFAULT_CF,
+                COLLECTION,
+                eq_filter("_id", Bson::String(id.into())),
+            )?
+            .drain()?;
+        }
+        Ok(())
+    })
+    .unwrap();
+    assert_eq!(db.resume_index_builds_blocking().unwrap(), 1);
+
+    let stats = db.collection_storage_stats(DEFAULT_CF, COLLECTION).unwrap();
+    let (_, status) = stats
+        .indexes
+        .iter()
+        .find(|(name, _)| name == "status")
+        .unwrap();
+    assert_eq!(status.keys, 3);
+    let mut txn = db.begin(false).unwrap();
+    let dup = txn
+        .insert_one(
+            DEFAULT_CF,
+            COLLECTION,
+            doc! { "_id": "acct-6", "status": "active" },
+        )
+        .and_then(|cursor| cursor.drain());
+    assert!(matches!(dup, Err(DbError::DuplicateKey(_))));
+}
+
+#[test]
+fn build_index_keeps_up_with_concurrent_writes() {
+    let db = batched_db();
+    create_collection(&db, COLLECTION);
+    let mut txn = db.begin(false).unwrap();
+    txn.insert_many(
+        DEFAULT_CF,
+        COLLECTION,
+        (0..200).map(|i| doc! { "_id": format!("d{i:03}"), "bucket": i % 4 }),
+    )
+    .unwrap()
+    .drain()
+    .unwrap();
+    txn.commit().unwrap();
+
+    std::thread::scope(|s| {
+        let builder = s.spawn(|| db.build_index_blocking(DEFAULT_CF, COLLECTION, "bucket"));
+        for i in 0..50 {
+            db.transact(|txn| {
+                txn.update_one(
+                    DEFAULT_CF,
+                    COLLECTION,
+                    eq_filter("_id", Bson::String(format!("d{:03}", i * 4))),
+                    doc! { "$set": { "bucket": 9 } },
+                )?
+                .drain()?;
+                txn.insert_one(
+                    DEFAULT_CF,
+                    COLLECTION,
+                    doc! { "_id": format!("n{i:03}"), "bucket": 9 },
+                )?
+                .drain()?;
+                txn.delete_one(
+                    DEFAULT_CF,
+                    COLLECTION,
+                    eq_filter("_id", Bson::String(format!("d{:03}", i * 4 + 1))),
+                )?
+                .drain()?;
+                Ok(())
+            })
+            .unwrap();
+        }
+        builder.join().unwrap().unwrap();
+    });
+
+    let nines = find_ids(&db, eq_filter("bucket", Bson::Int32(9)));
+    assert_eq!(nines.len(), 100);
+    assert!(
+        nines
+            .iter()
+            .all(|id| id.starts_with('n') || id[1..].parse::<u32>().unwrap() % 4 == 0)
+    );
+    assert!(find_ids(&db, eq_filter("bucket", Bson::Int32(0))).is_empty());
+    assert!(find_ids(&db, eq_filter("bucket", Bson::Int32(1))).is_empty());
+    assert_eq!(find_ids(&db, eq_filter("bucket", Bson::Int32(2))).len(), 50);
+    let stats = db.collection_storage_stats(DEFAULT_CF, COLLECTION).unwrap();
+    assert_eq!(stats.indexes[0].1.keys, stats.records.keys);
+}
+
+#[cfg(feature = "runtime")]
+#[test]
+fn background_build_index_backfills_existing_records() {
+    let db = batched_db();
+    seed_records(&db);
+    let build = db.build_index(DEFAULT_CF, COLLECTION, "status").unwrap();
+    build.wait().unwrap();
+
+    assert_eq!(
+        find_ids(&db, eq_filter("status", Bson::String("active".into()))),
+        vec!["acct-1", "acct-4", "acct-5"]
+    );
+    let stats = db.collection_storage_stats(DEFAULT_CF, COLLECTION).unwrap();
+    assert_eq!(stats.indexes[0].1.keys, 5);
+    assert_eq!(db.resume_index_builds_blocking().unwrap(), 0);
+
+    let build = db
+        .build_index(
+            DEFAULT_CF,
+            COLLECTION,
+            IndexConfig::new(["active"]).unique(),
+        )
+        .unwrap();
+    match build.wait() {
+        Err(DbError::DuplicateKey(msg)) => assert!(msg.contains("active"), "{msg}"),
+        other => panic!("expected DuplicateKey, got {other:?}"),
+    }
+}
+
+#[cfg(feature = "runtime")]
+#[test]
+fn cancelled_background_build_resumes() {
+    let db = batched_db();
+    create_collection(&db, COLLECTION);
+    let mut txn = db.begin(false).unwrap();
+    txn.insert_many(
+        DEFAULT_CF,
+        COLLECTION,
+        (0..200).map(|i| doc! { "_id": format!("d{i:03}"), "bucket": i % 4 }),
+    )
+    .unwrap()
+    .drain()
+    .unwrap();
+    txn.commit().unwrap();
+
+    let build = db.build_index(DEFAULT_CF, COLLECTION, "bucket").unwrap();
+    build.cancel().unwrap();
+    assert!(db.resume_index_builds_blocking().unwrap() <= 1);
+
+    assert_eq!(find_ids(&db, eq_filter("bucket", Bson::Int32(2))).len(), 50);
+    let stats = db.collection_storage_stats(DEFAULT_CF, COLLECTION).unwrap();
+    assert_eq!(stats.indexes[0].1.keys, stats.records.keys);
+}
+
+#[test]
+fn open_restarts_outdated_indexes_as_builds() {
+    let dir = tempfile::tempdir().unwrap();
+    let open = || {
+        DatabaseBuilder::new()
+            .open(DurableMemoryStore::open(dir.path()).unwrap())
+            .unwrap()
+    };
+    let db = open();
+    let mut txn = db.begin(false).unwrap();
+    txn.create_collection(&CollectionConfig {
+        name: COLLECTION.to_string(),
+        ..Default::default()
+    })
+    .unwrap();
+    txn.create_index(DEFAULT_CF, COLLECTION, "n").unwrap();
+    txn.insert_many(
+        DEFAULT_CF,
+        COLLECTION,
+        vec![doc! { "_id": "a", "n": 5 }, doc! { "_id": "b", "n": 5.0 }],
+    )
+    .unwrap()
+    .drain()
+    .unwrap();
+    txn.commit().unwrap();
+    drop(db);
+
+    // Leave the index config as an older version wrote it: unique, with no
+    // key format, which kept 5 and 5.0 apart.
+    let store = DurableMemoryStore::open(dir.path()).unwrap();
+    let txn = store.begin(false).unwrap();
+    let sys = txn.cf("_sys_").unwrap();
+    let key = [
+        b"x\0",
+        DEFAULT_CF.as_bytes(),
+        b"\0",
+        COLLECTION.as_bytes(),
+        b"\0n",
+    ]
+    .concat();
+    let meta = bson::serialize_to_vec(&doc! { "unique": true }).unwrap();
+    txn.put(&sys, &key, &meta).unwrap();
+    txn.commit().unwrap();
+    drop(store);
+
+    // The open only restarts the index; the rebuild then fails on the
+    // values that now collide.
+    let db = open();
+    match db.resume_index_builds_blocking() {
+        Err(DbError::DuplicateKey(msg)) => assert!(msg.contains(".n"), "{msg}"),
+        other => panic!("expected DuplicateKey, got {other:?}"),
+    }
+
+    db.transact(|txn| {
+        txn.delete_one(
+            DEFAULT_CF,
+            COLLECTION,
+            eq_filter("_id", Bson::String("b".into())),
+        )?
+        .drain()?;
+        Ok(())
+    })
+    .unwrap();
+    assert_eq!(db.resume_index_builds_blocking().unwrap(), 1);
+    let stats = db.collection_storage_stats(DEFAULT_CF, COLLECTION).unwrap();
+    let (_, n) = stats.indexes.iter().find(|(name, _)| name == "n").unwrap();
+    assert_eq!(n.keys, 1);
+}
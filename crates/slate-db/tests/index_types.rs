@@ -0,0 +1,199 @@
+mod common;
+use common::*;
+
+use bson::spec::BinarySubtype;
+use bson::{Binary, Bson, Decimal128, Document, RawDocumentBuf, doc, rawdoc};
+use slate_db::{DEFAULT_CF, Database};
+use slate_query::{FindOptions, Sort, SortDirection};
+use slate_store::MemoryStore;
+
+// ── Index value type tests ──────────────────────────────────────
+
+fn decimal(s: &str) -> Decimal128 {
+    s.parse().unwrap()
+}
+
+fn uuid(n: u8) -> Bson {
+    let uuid = bson::Uuid::from_bytes([0, 0, 0, 0, 0, 0, 0x40, 0, 0x80, 0, 0, 0, 0, 0, 0, n]);
+    Bson::Binary(Binary::from(uuid))
+}
+
+fn bytes(b: &[u8]) -> Bson {
+    Bson::Binary(Binary {
+        subtype: BinarySubtype::Generic,
+        bytes: b.to_vec(),
+    })
+}
+
+fn seed_values(db: &Database<MemoryStore>) {
+    create_collection(db, COLLECTION);
+    let mut txn = db.begin(false).unwrap();
+    txn.insert_many(
+        DEFAULT_CF,
+        COLLECTION,
+        vec![
+            doc! { "_id": "v1", "v": "a", "price": decimal("10.50"), "ref": uuid(1), "addr": { "city": "Austin", "zip": 78701 } },
+            doc! { "_id": "v2", "v": "ab", "price": decimal("9.99"), "ref": uuid(2), "addr": { "city": "Boston" } },
+            doc! { "_id": "v3", "v": Bson::Null, "price": decimal("100"), "ref": bytes(b"\x01\x02"), "addr": { "city": "Austin" } },
+            doc! { "_id": "v4", "v": 3, "price": 12.5, "ref": bson::oid::ObjectId::from_bytes([7; 12]) },
+            doc! { "_id": "v5" },
+        ],
+    )
+    .unwrap()
+    .drain()
+    .unwrap();
+    txn.commit().unwrap();
+}
+
+fn indexed_values() -> (Database<MemoryStore>, tempfile::TempDir) {
+    let (db, dir) = temp_db();
+    seed_values(&db);
+    let mut txn = db.begin(false).unwrap();
+    for field in ["v", "price", "ref", "addr"] {
+        txn.create_index(DEFAULT_CF, COLLECTION, field).unwrap();
+    }
+    txn.commit().unwrap();
+    (db, dir)
+}
+
+fn find_ids(
+    db: &Database<MemoryStore>,
+    filter: RawDocumentBuf,
+    options: FindOptions,
+) -> Vec<String> {
+    let txn = db.begin(true).unwrap();
+    txn.find(DEFAULT_CF, COLLECTION, filter, options)
+        .unwrap()
+        .iter()
+        .unwrap()
+        .map(|r| {
+            Document::try_from(r.unwrap())
+                .unwrap()
+                .get_str("_id")
+                .unwrap()
+                .to_string()
+        })
+        .collect()
+}
+
+fn sorted(mut ids: Vec<String>) -> Vec<String> {
+    ids.sort();
+    ids
+}
+
+#[test]
+fn typed_index_matches_full_scan_results() {
+    let (plain, _d1) = temp_db();
+    seed_values(&plain);
+    let (indexed, _d2) = indexed_values();
+
+    let filters = [
+        eq_filter("v", Bson::Null),
+        eq_filter("v", Bson::String("a".into())),
+        eq_filter("price", decimal("10.5").into()),
+        eq_filter("ref", uuid(2)),
+        eq_filter("ref", bytes(b"\x01\x02")),
+        eq_filter(
+            "ref",
+            Bson::ObjectId(bson::oid::ObjectId::from_bytes([7; 12])),
+        ),
+        eq_filter("addr", Bson::Document(doc! { "city": "Austin" })),
+        rawdoc! { "price": { "$gt": decimal("10") } },
+        rawdoc! { "price": { "$gte": decimal("9"), "$lt": decimal("11") } },
+    ];
+    for filter in filters {
+        let expected = sorted(find_ids(&plain, filter.clone(), FindOptions::default()));
+        let actual = sorted(find_ids(&indexed, filter.clone(), FindOptions::default()));
+        assert_eq!(actual, expected, "filter {filter:?}");
+    }
+}
+
+#[test]
+fn null_matches_explicit_null_and_missing() {
+    let (db, _dir) = indexed_values();
+    let ids = find_ids(&db, eq_filter("v", Bson::Null), FindOptions::default());
+    assert_eq!(sorted(ids), vec!["v3", "v5"]);
+}
+
+#[test]
+fn string_equality_is_not_a_prefix_match() {
+    let (db, _dir) = indexed_values();
+    let ids = find_ids(
+        &db,
+        eq_filter("v", Bson::String("a".into())),
+        FindOptions::default(),
+    );
+    assert_eq!(ids, vec!["v1"]);
+}
+
+#[test]
+fn decimal_sort_matches_full_scan() {
+    let (plain, _d1) = temp_db();
+    seed_values(&plain);
+    let (indexed, _d2) = indexed_values();
+
+    for direction in [SortDirection::Asc, SortDirection::Desc] {
+        let options = FindOptions {
+            sort: vec![Sort {
+                field: "price".into(),
+                direction,
+            }],
+            take: Some(2),
+            ..Default::default()
+        };
+        let filter = rawdoc! { "price": { "$gte": decimal("0") } };
+        assert_eq!(
+            find_ids(&indexed, filter.clone(), options.clone()),
+            find_ids(&plain, filter, options),
+        );
+    }
+}
+
+fn seed_numbers(db: &Database<MemoryStore>) {
+    create_collection(db, COLLECTION);
+    let mut txn = db.begin(false).unwrap();
+    txn.insert_many(
+        DEFAULT_CF,
+        COLLECTION,
+        vec![
+            doc! { "_id": "n1", "n": 5 },
+            doc! { "_id": "n2", "n": 5i64 },
+            doc! { "_id": "n3", "n": 5.0 },
+            doc! { "_id": "n4", "n": 5.5 },
+            doc! { "_id": "n5", "n": -2 },
+            doc! { "_id": "n6", "n": 1i64 << 60 },
+            doc! { "_id": "n7", "n": (1i64 << 60) + 1 },
+            doc! { "_id": "n8", "n": f64::NAN },
+        ],
+    )
+    .unwrap()
+    .drain()
+    .unwrap();
+    txn.commit().unwrap();
+}
+
+#[test]
+fn mixed_numeric_index_matches_full_scan() {
+    let (plain, _d1) = temp_db();
+    seed_numbers(&plain);
+    let (indexed, _d2) = temp_db();
+    seed_numbers(&indexed);
+    let mut txn = indexed.begin(false).unwrap();
+    txn.create_index(DEFAULT_CF, COLLECTION, "n").unwrap();
+    txn.commit().unwrap();
+
+    let filters = [
+        eq_filter("n", Bson::Int32(5)),
+        eq_filter("n", Bson::Double(5.0)),
+        eq_filter("n", Bson::Int64((1 << 60) + 1)),
+        rawdoc! { "n": { "$gt": 5i64 } },
+        rawdoc! { "n": { "$gte": 4.5, "$lt": 6 } },
+        rawdoc! { "n": { "$lte": 5.0 } },
+        rawdoc! { "n": { "$gt": (1i64 << 60) as f64 } },
+    ];
+    for filter in filters {
+        let expected = sorted(find_ids(&plain, filter.clone(), FindOptions::default()));
+        let actual = sorted(find_ids(&indexed, filter.clone(), FindOptions::default()));
+        assert_eq!(actual, expected, "filter {filter:?}");
+    }
+}
@@ -0,0 +1,48 @@
+mod common;
+use common::*;
+
+use bson::{doc, rawdoc};
+use slate_db::{CollectionConfig, DEFAULT_CF, DatabaseBuilder};
+use slate_store::{MemoryStore, MetricsStore, StoreOp};
+
+// ── Metrics tests ───────────────────────────────────────────────
+
+#[test]
+fn database_exposes_store_metrics() {
+    let db = DatabaseBuilder::new()
+        .open(MetricsStore::new(MemoryStore::new()))
+        .unwrap();
+
+    let mut txn = db.begin(false).unwrap();
+    txn.create_collection(&CollectionConfig {
+        name: COLLECTION.to_string(),
+        ..Default::default()
+    })
+    .unwrap();
+    txn.insert_one(
+        DEFAULT_CF,
+        COLLECTION,
+        doc! { "_id": "acct-1", "name": "Acme", "status": "active" },
+    )
+    .unwrap()
+    .drain()
+    .unwrap();
+    txn.commit().unwrap();
+
+    let txn = db.begin(true).unwrap();
+    assert!(
+        txn.find_one(DEFAULT_CF, COLLECTION, rawdoc! { "status": "active" })
+            .unwrap()
+            .is_some()
+    );
+    drop(txn);
+
+    let metrics = db.metrics();
+    let cf = &metrics.column_families[DEFAULT_CF];
+    assert!(cf.values().any(|op| op.bytes_written > 0));
+    assert!(cf.values().any(|op| op.keys_scanned > 0));
+    assert!(metrics.commit.calls >= 1);
+
+    db.reset_metrics();
+    assert!(db.metrics().op(DEFAULT_CF, StoreOp::Put).is_none());
+}
@@ -0,0 +1,224 @@
+mod common;
+use common::*;
+
+use bson::{Bson, Document, RawDocumentBuf, doc, rawdoc};
+use slate_db::{DEFAULT_CF, Database};
+use slate_query::{FindOptions, Sort, SortDirection};
+use slate_store::MemoryStore;
+
+// ── Multikey index tests ────────────────────────────────────────
+
+fn seed_posts(db: &Database<MemoryStore>) {
+    create_collection(db, COLLECTION);
+    let mut txn = db.begin(false).unwrap();
+    txn.insert_many(
+        DEFAULT_CF,
+        COLLECTION,
+        vec![
+            doc! { "_id": "p1", "tags": ["rust", "db"], "scores": [3, 9] },
+            doc! { "_id": "p2", "tags": ["go"], "scores": [5] },
+            doc! { "_id": "p3", "tags": "rust", "scores": 7 },
+            doc! { "_id": "p4", "tags": ["rust", "rust"], "scores": [1, 8, 10] },
+            doc! { "_id": "p5", "title": "untagged" },
+        ],
+    )
+    .unwrap()
+    .drain()
+    .unwrap();
+    txn.commit().unwrap();
+}
+
+fn indexed_posts() -> (Database<MemoryStore>, tempfile::TempDir) {
+    let (db, dir) = temp_db();
+    seed_posts(&db);
+    let mut txn = db.begin(false).unwrap();
+    txn.create_index(DEFAULT_CF, COLLECTION, "tags").unwrap();
+    txn.create_index(DEFAULT_CF, COLLECTION, "scores").unwrap();
+    txn.commit().unwrap();
+    (db, dir)
+}
+
+fn find_docs(
+    db: &Database<MemoryStore>,
+    filter: RawDocumentBuf,
+    options: FindOptions,
+) -> Vec<Document> {
+    let txn = db.begin(true).unwrap();
+    txn.find(DEFAULT_CF, COLLECTION, filter, options)
+        .unwrap()
+        .iter()
+        .unwrap()
+        .map(|r| Document::try_from(r.unwrap()).unwrap())
+        .collect()
+}
+
+fn find_ids(
+    db: &Database<MemoryStore>,
+    filter: RawDocumentBuf,
+    options: FindOptions,
+) -> Vec<String> {
+    find_docs(db, filter, options)
+        .iter()
+        .map(|d| d.get_str("_id").unwrap().to_string())
+        .collect()
+}
+
+fn sorted(mut ids: Vec<String>) -> Vec<String> {
+    ids.sort();
+    ids
+}
+
+#[test]
+fn multikey_index_matches_full_scan_results() {
+    let (plain, _d1) = temp_db();
+    seed_posts(&plain);
+    let (indexed, _d2) = indexed_posts();
+
+    let filters = [
+        eq_filter("tags", Bson::String("rust".into())),
+        eq_filter("tags", Bson::String("go".into())),
+        rawdoc! { "scores": { "$gt": 6 } },
+        rawdoc! { "scores": { "$gte": 1, "$lte": 5 } },
+        rawdoc! { "$or": [{ "tags": "db" }, { "scores": 5 }] },
+    ];
+    for filter in filters {
+        let expected = sorted(find_ids(&plain, filter.clone(), FindOptions::default()));
+        let actual = find_ids(&indexed, filter.clone(), FindOptions::default());
+        assert_eq!(sorted(actual.clone()), expected, "filter {filter:?}");
+        // Each document comes back once, however many elements matched.
+        assert_eq!(actual.len(), expected.len(), "filter {filter:?}");
+    }
+}
+
+#[test]
+fn multikey_index_dedupes_documents_under_take() {
+    let (db, _dir) = indexed_posts();
+    // p4 has two elements in range; it must not use up two slots.
+    let ids = find_ids(
+        &db,
+        rawdoc! { "scores": { "$gte": 8 } },
+        FindOptions {
+            take: Some(2),
+            ..Default::default()
+        },
+    );
+    assert_eq!(sorted(ids), vec!["p1", "p4"]);
+}
+
+#[test]
+fn multikey_projection_returns_whole_array() {
+    let (db, _dir) = indexed_posts();
+    let docs = find_docs(
+        &db,
+        eq_filter("tags", Bson::String("rust".into())),
+        FindOptions {
+            columns: Some(vec!["_id".into(), "tags".into()]),
+            ..Default::default()
+        },
+    );
+    let p1 = docs
+        .iter()
+        .find(|d| d.get_str("_id").unwrap() == "p1")
+        .unwrap();
+    assert_eq!(p1.get_array("tags").unwrap().len(), 2);
+}
+
+#[test]
+fn multikey_sort_with_take_matches_full_scan() {
+    let (plain, _d1) = temp_db();
+    seed_posts(&plain);
+    let (indexed, _d2) = indexed_posts();
+
+    for direction in [SortDirection::Asc, SortDirection::Desc] {
+        let options = FindOptions {
+            sort: vec![Sort {
+                field: "scores".into(),
+                direction,
+            }],
+            take: Some(3),
+            ..Default::default()
+        };
+        assert_eq!(
+            find_ids(&indexed, rawdoc! {}, options.clone()),
+            find_ids(&plain, rawdoc! {}, options),
+        );
+    }
+}
+
+#[test]
+fn multikey_index_follows_element_updates() {
+    let (db, _dir) = indexed_posts();
+    let txn = db.begin(false).unwrap();
+    txn.update_one(
+        DEFAULT_CF,
+        COLLECTION,
+        doc! { "_id": "p1" },
+        doc! { "$set": { "tags": ["db", "sql"] } },
+    )
+    .unwrap()
+    .drain()
+    .unwrap();
+    txn.update_one(
+        DEFAULT_CF,
+        COLLECTION,
+        doc! { "_id": "p2" },
+        doc! { "$push": { "tags": "rust" } },
+    )
+    .unwrap()
+    .drain()
+    .unwrap();
+    txn.commit().unwrap();
+
+    let rust = find_ids(
+        &db,
+        eq_filter("tags", Bson::String("rust".into())),
+        FindOptions::default(),
+    );
+    assert_eq!(sorted(rust), vec!["p2", "p3", "p4"]);
+    let sql = find_ids(
+        &db,
+        eq_filter("tags", Bson::String("sql".into())),
+        FindOptions::default(),
+    );
+    assert_eq!(sql, vec!["p1"]);
+}
+
+#[test]
+fn multikey_range_bounds_may_match_different_elements() {
+    let (plain, _d1) = temp_db();
+    let (indexed, _d2) = temp_db();
+    for db in [&plain, &indexed] {
+        create_collection(db, COLLECTION);
+        let mut txn = db.begin(false).unwrap();
+        txn.insert_many(
+            DEFAULT_CF,
+            COLLECTION,
+            vec![
+                doc! { "_id": "a", "kind": "x", "tags": [1, 10] },
+                doc! { "_id": "b", "kind": "x", "tags": [6, 7] },
+            ],
+        )
+        .unwrap()
+        .drain()
+        .unwrap();
+        txn.commit().unwrap();
+    }
+    let mut txn = indexed.begin(false).unwrap();
+    txn.create_index(DEFAULT_CF, COLLECTION, "tags").unwrap();
+    txn.create_index(DEFAULT_CF, COLLECTION, ["kind", "tags"])
+        .unwrap();
+    txn.commit().unwrap();
+
+    // 1 > 3 fails but 10 > 3 holds, and 1 < 5 holds: "a" matches with no
+    // element between the bounds.
+    let filters = [
+        rawdoc! { "tags": { "$gt": 3, "$lt": 5 } },
+        rawdoc! { "kind": "x", "tags": { "$gt": 3, "$lt": 5 } },
+    ];
+    for filter in filters {
+        let expected = find_ids(&plain, filter.clone(), FindOptions::default());
+        assert_eq!(expected, vec!["a"], "filter {filter:?}");
+        let actual = find_ids(&indexed, filter.clone(), FindOptions::default());
+        assert_eq!(actual, expected, "filter {filter:?}");
+    }
+}
@@ -0,0 +1,115 @@
+mod common;
+use common::*;
+
+use bson::{RawDocumentBuf, doc, rawdoc};
+use slate_db::{DEFAULT_CF, Database, DbError, IndexConfig};
+use slate_query::FindOptions;
+use slate_store::MemoryStore;
+
+// ── Partial index tests ─────────────────────────────────────────
+
+/// Index `status` over active accounts only.
+fn index_active_status(db: &Database<MemoryStore>) {
+    let mut txn = db.begin(false).unwrap();
+    let index = IndexConfig::new(["status"]).with_filter(rawdoc! { "active": true });
+    txn.create_index(DEFAULT_CF, COLLECTION, index).unwrap();
+    txn.commit().unwrap();
+}
+
+fn find_ids(db: &Database<MemoryStore>, filter: RawDocumentBuf) -> Vec<String> {
+    let txn = db.begin(true).unwrap();
+    let mut ids: Vec<String> = txn
+        .find(DEFAULT_CF, COLLECTION, filter, FindOptions::default())
+        .unwrap()
+        .iter()
+        .unwrap()
+        .map(|r| r.unwrap().get_str("_id").unwrap().to_string())
+        .collect();
+    ids.sort();
+    ids
+}
+
+#[test]
+fn partial_index_only_holds_matching_documents() {
+    let (db, _dir) = temp_db();
+    seed_records(&db);
+    index_active_status(&db);
+
+    let stats = db.collection_storage_stats(DEFAULT_CF, COLLECTION).unwrap();
+    assert_eq!(stats.indexes[0].0, "status");
+    // acct-3 and acct-5 are inactive.
+    assert_eq!(stats.indexes[0].1.keys, 3);
+}
+
+#[test]
+fn partial_index_queries_match_full_scan_results() {
+    let (plain, _d1) = temp_db();
+    seed_records(&plain);
+    let (indexed, _d2) = temp_db();
+    seed_records(&indexed);
+    index_active_status(&indexed);
+
+    let filters = [
+        // Implies the index filter — served by the index.
+        rawdoc! { "status": "active", "active": true },
+        // Does not — must still see the inactive acct-5.
+        rawdoc! { "status": "active" },
+        rawdoc! { "$or": [{ "status": "active" }, { "status": "rejected" }] },
+    ];
+    for filter in filters {
+        assert_eq!(
+            find_ids(&indexed, filter.clone()),
+            find_ids(&plain, filter.clone()),
+            "filter {filter:?}"
+        );
+    }
+}
+
+#[test]
+fn partial_index_follows_membership_changes() {
+    let (db, _dir) = temp_db();
+    seed_records(&db);
+    index_active_status(&db);
+
+    let txn = db.begin(false).unwrap();
+    txn.update_one(
+        DEFAULT_CF,
+        COLLECTION,
+        doc! { "_id": "acct-1" },
+        doc! { "$set": { "active": false } },
+    )
+    .unwrap()
+    .drain()
+    .unwrap();
+    txn.update_one(
+        DEFAULT_CF,
+        COLLECTION,
+        doc! { "_id": "acct-5" },
+        doc! { "$set": { "active": true } },
+    )
+    .unwrap()
+    .drain()
+    .unwrap();
+    txn.commit().unwrap();
+
+    assert_eq!(
+        find_ids(&db, rawdoc! { "status": "active", "active": true }),
+        vec!["acct-4", "acct-5"]
+    );
+    let stats = db.collection_storage_stats(DEFAULT_CF, COLLECTION).unwrap();
+    assert_eq!(stats.indexes[0].1.keys, 3);
+}
+
+#[test]
+fn partial_index_rejects_invalid_filter() {
+    let (db, _dir) = temp_db();
+    seed_records(&db);
+
+    let mut txn = db.begin(false).unwrap();
+    let index = IndexConfig::new(["status"]).with_filter(rawdoc! { "$nope": 1 });
+    let result = txn.create_index(DEFAULT_CF, COLLECTION, index);
+    assert!(
+        matches!(result, Err(DbError::InvalidQuery(_))),
+        "got {result:?}"
+    );
+}
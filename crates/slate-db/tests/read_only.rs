@@ -0,0 +1,36 @@
+mod common;
+use common::*;
+
+use bson::rawdoc;
+use slate_db::{DEFAULT_CF, DatabaseBuilder, DbError};
+use slate_store::{MemoryStore, SnapshotStore, Store, StoreError};
+
+// ── read-only store tests ───────────────────────────────────────
+
+#[test]
+fn read_only_store_opens_for_queries() {
+    let store = MemoryStore::new();
+    store.create_cf("_sys_").unwrap();
+    let store = store.snapshot().unwrap();
+    assert!(store.is_read_only());
+
+    let db = DatabaseBuilder::new()
+        .with_sweep(std::time::Duration::from_millis(10))
+        .open(store)
+        .unwrap();
+    assert!(db.list_collections().unwrap().is_empty());
+
+    let result = db.begin(false).map(|_| ());
+    assert!(
+        matches!(result, Err(DbError::Store(StoreError::ReadOnly))),
+        "got {result:?}"
+    );
+}
+
+#[test]
+fn read_only_store_without_catalog_cf_opens() {
+    let store = MemoryStore::new().snapshot().unwrap();
+    let db = DatabaseBuilder::new().open(store).unwrap();
+    let txn = db.begin(true).unwrap();
+    assert!(txn.count(DEFAULT_CF, COLLECTION, rawdoc! {}).is_err());
+}
@@ -0,0 +1,115 @@
+mod common;
+use common::*;
+
+use bson::{doc, rawdoc};
+use slate_db::DEFAULT_CF;
+
+// ── Savepoint tests ─────────────────────────────────────────────
+
+fn count(db: &slate_db::Database<slate_store::MemoryStore>) -> u64 {
+    let txn = db.begin(true).unwrap();
+    txn.count(DEFAULT_CF, COLLECTION, rawdoc! {}).unwrap()
+}
+
+#[test]
+fn failed_batch_rolls_back_to_savepoint() {
+    let (db, _dir) = temp_db();
+    create_collection(&db, COLLECTION);
+
+    let mut txn = db.begin(false).unwrap();
+    txn.create_index(DEFAULT_CF, COLLECTION, "status").unwrap();
+    txn.insert_one(DEFAULT_CF, COLLECTION, doc! { "_id": "a", "status": "ok" })
+        .unwrap()
+        .drain()
+        .unwrap();
+
+    // Third document collides with the first; the whole batch is undone.
+    let mut sp = txn.savepoint().unwrap();
+    let result = sp
+        .insert_many(
+            DEFAULT_CF,
+            COLLECTION,
+            vec![
+                doc! { "_id": "b", "status": "bad" },
+                doc! { "_id": "c", "status": "bad" },
+                doc! { "_id": "a", "status": "bad" },
+            ],
+        )
+        .and_then(|cursor| cursor.drain());
+    assert!(result.is_err());
+    sp.rollback().unwrap();
+
+    let mut sp = txn.savepoint().unwrap();
+    sp.insert_one(DEFAULT_CF, COLLECTION, doc! { "_id": "d", "status": "ok" })
+        .unwrap()
+        .drain()
+        .unwrap();
+    sp.release().unwrap();
+    txn.commit().unwrap();
+
+    assert_eq!(count(&db), 2);
+    let txn = db.begin(true).unwrap();
+    assert_eq!(
+        txn.count(DEFAULT_CF, COLLECTION, rawdoc! { "status": "bad" })
+            .unwrap(),
+        0
+    );
+    assert_eq!(
+        txn.count(DEFAULT_CF, COLLECTION, rawdoc! { "status": "ok" })
+            .unwrap(),
+        2
+    );
+}
+
+#[test]
+fn dropped_savepoint_rolls_back() {
+    let (db, _dir) = temp_db();
+    create_collection(&db, COLLECTION);
+
+    let mut txn = db.begin(false).unwrap();
+    {
+        let mut sp = txn.savepoint().unwrap();
+        sp.insert_one(DEFAULT_CF, COLLECTION, doc! { "_id": "a" })
+            .unwrap()
+            .drain()
+            .unwrap();
+    }
+    txn.commit().unwrap();
+
+    assert_eq!(count(&db), 0);
+}
+
+#[test]
+fn nested_savepoints() {
+    let (db, _dir) = temp_db();
+    create_collection(&db, COLLECTION);
+
+    let mut txn = db.begin(false).unwrap();
+    let mut outer = txn.savepoint().unwrap();
+    outer
+        .insert_one(DEFAULT_CF, COLLECTION, doc! { "_id": "outer" })
+        .unwrap()
+        .drain()
+        .unwrap();
+    let mut inner = outer.savepoint().unwrap();
+    inner
+        .insert_one(DEFAULT_CF, COLLECTION, doc! { "_id": "inner" })
+        .unwrap()
+        .drain()
+        .unwrap();
+    inner.rollback().unwrap();
+    outer.release().unwrap();
+    txn.commit().unwrap();
+
+    let txn = db.begin(true).unwrap();
+    assert!(
+        txn.find_one(DEFAULT_CF, COLLECTION, rawdoc! { "_id": "outer" })
+            .unwrap()
+            .is_some()
+    );
+    assert!(
+        txn.find_one(DEFAULT_CF, COLLECTION, rawdoc! { "_id": "inner" })
+            .unwrap()
+            .is_none()
+    );
+}
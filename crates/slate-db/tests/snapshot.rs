@@ -0,0 +1,143 @@
+mod common;
+use common::*;
+
+use bson::{Bson, doc, rawdoc};
+use slate_db::DEFAULT_CF;
+use slate_query::DistinctOptions;
+
+// ── snapshot tests ──────────────────────────────────────────────
+
+#[test]
+fn snapshot_ignores_later_writes() {
+    let (db, _dir) = temp_db();
+    seed_records(&db);
+    let snap = db.snapshot().unwrap();
+
+    let txn = db.begin(false).unwrap();
+    txn.delete_one(DEFAULT_CF, COLLECTION, doc! { "_id": "acct-1" })
+        .unwrap()
+        .drain()
+        .unwrap();
+    txn.update_one(
+        DEFAULT_CF,
+        COLLECTION,
+        doc! { "_id": "acct-2" },
+        doc! { "$set": { "status": "archived" } },
+    )
+    .unwrap()
+    .drain()
+    .unwrap();
+    txn.commit().unwrap();
+
+    let txn = snap.begin().unwrap();
+    assert_eq!(txn.count(DEFAULT_CF, COLLECTION, rawdoc! {}).unwrap(), 5);
+    let found = txn
+        .find_one(DEFAULT_CF, COLLECTION, doc! { "_id": "acct-2" })
+        .unwrap()
+        .unwrap();
+    assert_ne!(found.get_str("status").unwrap(), "archived");
+
+    let statuses = txn
+        .distinct(
+            DEFAULT_CF,
+            COLLECTION,
+            "status",
+            rawdoc! {},
+            DistinctOptions::default(),
+        )
+        .unwrap();
+    let statuses = statuses.as_array().unwrap();
+    assert!(
+        !statuses
+            .into_iter()
+            .any(|v| v.unwrap().as_str() == Some("archived"))
+    );
+
+    let txn = db.begin(true).unwrap();
+    assert_eq!(txn.count(DEFAULT_CF, COLLECTION, rawdoc! {}).unwrap(), 4);
+}
+
+#[test]
+fn snapshot_queries_agree_with_each_other() {
+    let (db, _dir) = temp_db();
+    seed_records(&db);
+    let snap = db.snapshot().unwrap();
+
+    let before = snap
+        .begin()
+        .unwrap()
+        .count(
+            DEFAULT_CF,
+            COLLECTION,
+            eq_filter("status", Bson::String("active".into())),
+        )
+        .unwrap();
+
+    let mut txn = db.begin(false).unwrap();
+    txn.insert_one(
+        DEFAULT_CF,
+        COLLECTION,
+        doc! { "_id": "acct-6", "status": "active" },
+    )
+    .unwrap()
+    .drain()
+    .unwrap();
+    txn.commit().unwrap();
+
+    let after = snap
+        .begin()
+        .unwrap()
+        .count(
+            DEFAULT_CF,
+            COLLECTION,
+            eq_filter("status", Bson::String("active".into())),
+        )
+        .unwrap();
+    assert_eq!(before, after);
+}
+
+#[test]
+fn snapshot_sees_collections_at_snapshot_time() {
+    let (db, _dir) = temp_db();
+    create_collection(&db, COLLECTION);
+    let snap = db.snapshot().unwrap();
+    create_collection(&db, "later");
+
+    let names: Vec<String> = snap
+        .list_collections()
+        .unwrap()
+        .into_iter()
+        .map(|(_, name)| name)
+        .collect();
+    assert_eq!(names, vec![COLLECTION.to_string()]);
+}
+
+#[test]
+fn snapshot_rejects_writes() {
+    let (db, _dir) = temp_db();
+    seed_records(&db);
+    let snap = db.snapshot().unwrap();
+
+    let mut txn = snap.begin().unwrap();
+    let result = txn
+        .insert_one(DEFAULT_CF, COLLECTION, doc! { "_id": "acct-6" })
+        .and_then(|cursor| cursor.drain());
+    assert!(result.is_err());
+}
+
+#[test]
+fn snapshot_can_move_to_another_thread() {
+    let (db, _dir) = temp_db();
+    seed_records(&db);
+    let snap = db.snapshot().unwrap();
+
+    let count = std::thread::spawn(move || {
+        snap.begin()
+            .unwrap()
+            .count(DEFAULT_CF, COLLECTION, rawdoc! {})
+            .unwrap()
+    })
+    .join()
+    .unwrap();
+    assert_eq!(count, 5);
+}
@@ -0,0 +1,82 @@
+mod common;
+use common::*;
+
+use bson::doc;
+use slate_db::{DEFAULT_CF, DbError};
+
+// ── Storage stats tests ─────────────────────────────────────────
+
+fn index_status(db: &slate_db::Database<slate_store::MemoryStore>) {
+    let mut txn = db.begin(false).unwrap();
+    txn.create_index(DEFAULT_CF, COLLECTION, "status").unwrap();
+    txn.commit().unwrap();
+}
+
+#[test]
+fn stats_report_records_and_each_index() {
+    let (db, _dir) = temp_db();
+    seed_records(&db);
+    index_status(&db);
+
+    let stats = db.collection_storage_stats(DEFAULT_CF, COLLECTION).unwrap();
+    assert_eq!(stats.records.keys, 5);
+    assert!(stats.records.bytes > 0);
+
+    let fields: Vec<&str> = stats.indexes.iter().map(|(f, _)| f.as_str()).collect();
+    assert_eq!(fields, vec!["status", "ttl"]);
+    assert_eq!(stats.indexes[0].1.keys, 5);
+    // No seeded document has a TTL; each gets a missing-value entry.
+    assert_eq!(stats.indexes[1].1.keys, 5);
+}
+
+#[test]
+fn stats_are_scoped_to_the_collection() {
+    let (db, _dir) = temp_db();
+    seed_records(&db);
+    create_collection(&db, "accounts_archive");
+
+    let mut txn = db.begin(false).unwrap();
+    txn.insert_one(DEFAULT_CF, "accounts_archive", doc! { "_id": "old-1" })
+        .unwrap()
+        .drain()
+        .unwrap();
+    txn.commit().unwrap();
+
+    let stats = db.collection_storage_stats(DEFAULT_CF, COLLECTION).unwrap();
+    assert_eq!(stats.records.keys, 5);
+    let stats = db
+        .collection_storage_stats(DEFAULT_CF, "accounts_archive")
+        .unwrap();
+    assert_eq!(stats.records.keys, 1);
+}
+
+#[test]
+fn stats_shrink_after_deletes_and_compaction() {
+    let (db, _dir) = temp_db();
+    seed_records(&db);
+    index_status(&db);
+    let before = db.collection_storage_stats(DEFAULT_CF, COLLECTION).unwrap();
+
+    let txn = db.begin(false).unwrap();
+    txn.delete_many(DEFAULT_CF, COLLECTION, doc! { "status": "active" })
+        .unwrap()
+        .drain()
+        .unwrap();
+    txn.commit().unwrap();
+    db.compact_collection(DEFAULT_CF, COLLECTION).unwrap();
+
+    let after = db.collection_storage_stats(DEFAULT_CF, COLLECTION).unwrap();
+    assert!(after.records.keys < before.records.keys);
+    assert!(after.records.bytes < before.records.bytes);
+    assert_eq!(after.indexes[0].1.keys, after.records.keys);
+}
+
+#[test]
+fn stats_for_missing_collection_fail() {
+    let (db, _dir) = temp_db();
+    let result = db.collection_storage_stats(DEFAULT_CF, "missing");
+    assert!(
+        matches!(result, Err(DbError::CollectionNotFound(_))),
+        "got {result:?}"
+    );
+}
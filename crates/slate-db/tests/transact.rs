@@ -0,0 +1,187 @@
+mod common;
+use common::*;
+
+use bson::doc;
+use slate_db::{DatabaseBuilder, DbError, RetryPolicy, DEFAULT_CF};
+use slate_store::MemoryStore;
+
+// ── transact tests ──────────────────────────────────────────────
+
+fn no_backoff(max_attempts: u32) -> RetryPolicy {
+    RetryPolicy {
+        max_attempts,
+        initial_backoff: std::time::Duration::ZERO,
+        max_backoff: std::time::Duration::ZERO,
+    }
+}
+
+#[test]
+fn transact_commits_closure_writes() {
+    let (db, _dir) = temp_db();
+    create_collection(&db, COLLECTION);
+
+    let inserted = db
+        .transact(|txn| {
+            txn.insert_one(DEFAULT_CF, COLLECTION, doc! { "_id": "a", "name": "Alice" })?
+                .drain()
+        })
+        .unwrap();
+    assert_eq!(inserted, 1);
+
+    let txn = db.begin(true).unwrap();
+    let found = txn.find_one(DEFAULT_CF, COLLECTION, doc! { "_id": "a" }).unwrap();
+    assert!(found.is_some());
+}
+
+#[test]
+fn transact_retries_on_conflict() {
+    let db = DatabaseBuilder::new()
+        .with_retry_policy(no_backoff(3))
+        .open(MemoryStore::new())
+        .unwrap();
+    create_collection(&db, COLLECTION);
+
+    let mut calls = 0;
+    db.transact(|txn| {
+        calls += 1;
+        txn.insert_one(DEFAULT_CF, COLLECTION, doc! { "_id": format!("attempt-{calls}") })?
+            .drain()?;
+        if calls < 3 {
+            return Err(DbError::Conflict("simulated".into()));
+        }
+        Ok(())
+    })
+    .unwrap();
+    assert_eq!(calls, 3);
+
+    // Only the successful attempt's write is committed.
+    let txn = db.begin(true).unwrap();
+    let count = txn.count(DEFAULT_CF, COLLECTION, doc! {}).unwrap();
+    assert_eq!(count, 1);
+    assert!(
+        txn.find_one(DEFAULT_CF, COLLECTION, doc! { "_id": "attempt-3" })
+            .unwrap()
+            .is_some()
+    );
+}
+
+#[test]
+fn transact_gives_up_after_max_attempts() {
+    let db = DatabaseBuilder::new()
+        .with_retry_policy(no_backoff(2))
+        .open(MemoryStore::new())
+        .unwrap();
+
+    let mut calls = 0;
+    let result: Result<(), DbError> = db.transact(|_txn| {
+        calls += 1;
+        Err(DbError::Conflict("simulated".into()))
+    });
+    assert!(matches!(result, Err(DbError::Conflict(_))));
+    assert_eq!(calls, 2);
+}
+
+#[test]
+fn transact_does_not_retry_other_errors() {
+    let (db, _dir) = temp_db();
+
+    let mut calls = 0;
+    let result: Result<(), DbError> = db.transact(|_txn| {
+        calls += 1;
+        Err(DbError::InvalidQuery("boom".into()))
+    });
+    assert!(matches!(result, Err(DbError::InvalidQuery(_))));
+    assert_eq!(calls, 1);
+}
+
+// ── Concurrent writers (MemoryStore) ────────────────────────────
+
+#[test]
+fn concurrent_writers_to_different_collections_both_commit() {
+    let (db, _dir) = temp_db();
+    create_collection(&db, "a");
+    create_collection(&db, "b");
+
+    let mut txn1 = db.begin(false).unwrap();
+    let mut txn2 = db.begin(false).unwrap();
+    txn1.insert_one(DEFAULT_CF, "a", doc! { "_id": "1" })
+        .unwrap()
+        .drain()
+        .unwrap();
+    txn2.insert_one(DEFAULT_CF, "b", doc! { "_id": "1" })
+        .unwrap()
+        .drain()
+        .unwrap();
+    txn1.commit().unwrap();
+    txn2.commit().unwrap();
+
+    let txn = db.begin(true).unwrap();
+    assert_eq!(txn.count(DEFAULT_CF, "a", doc! {}).unwrap(), 1);
+    assert_eq!(txn.count(DEFAULT_CF, "b", doc! {}).unwrap(), 1);
+}
+
+#[test]
+fn concurrent_updates_to_same_document_conflict() {
+    let (db, _dir) = temp_db();
+    create_collection(&db, COLLECTION);
+    db.transact(|txn| {
+        txn.insert_one(DEFAULT_CF, COLLECTION, doc! { "_id": "a", "n": 0 })?
+            .drain()
+    })
+    .unwrap();
+
+    let txn1 = db.begin(false).unwrap();
+    let txn2 = db.begin(false).unwrap();
+    txn1.update_one(DEFAULT_CF, COLLECTION, doc! { "_id": "a" }, doc! { "n": 1 })
+        .unwrap()
+        .drain()
+        .unwrap();
+    txn2.update_one(DEFAULT_CF, COLLECTION, doc! { "_id": "a" }, doc! { "n": 2 })
+        .unwrap()
+        .drain()
+        .unwrap();
+    txn1.commit().unwrap();
+
+    let result = txn2.commit();
+    assert!(matches!(result, Err(DbError::Conflict(_))), "got {result:?}");
+}
+
+#[test]
+fn transact_retries_real_conflict() {
+    let db = DatabaseBuilder::new()
+        .with_retry_policy(no_backoff(3))
+        .open(MemoryStore::new())
+        .unwrap();
+    create_collection(&db, COLLECTION);
+    db.transact(|txn| {
+        txn.insert_one(DEFAULT_CF, COLLECTION, doc! { "_id": "a", "n": 0 })?
+            .drain()
+    })
+    .unwrap();
+
+    let mut calls = 0;
+    db.transact(|txn| {
+        calls += 1;
+        let found = txn.find_one(DEFAULT_CF, COLLECTION, doc! { "_id": "a" })?;
+        let n = found.unwrap().get_i32("n").unwrap();
+        if calls == 1 {
+            // A competing writer commits after this attempt read the document.
+            let other = db.begin(false)?;
+            other
+                .update_one(DEFAULT_CF, COLLECTION, doc! { "_id": "a" }, doc! { "n": 10 })?
+                .drain()?;
+            other.commit()?;
+        }
+        txn.update_one(DEFAULT_CF, COLLECTION, doc! { "_id": "a" }, doc! { "n": n + 1 })?
+            .drain()
+    })
+    .unwrap();
+    assert_eq!(calls, 2);
+
+    let txn = db.begin(true).unwrap();
+    let found = txn
+        .find_one(DEFAULT_CF, COLLECTION, doc! { "_id": "a" })
+        .unwrap()
+        .unwrap();
+    assert_eq!(found.get_i32("n").unwrap(), 11);
+}
@@ -0,0 +1,209 @@
+mod common;
+use common::*;
+
+use bson::doc;
+use slate_db::{Cursor, DEFAULT_CF, Database, DbError, IndexConfig};
+use slate_store::MemoryStore;
+
+// ── Unique index tests ──────────────────────────────────────────
+
+/// Collection of users with a unique index on `email`.
+fn users_db() -> (Database<MemoryStore>, tempfile::TempDir) {
+    let (db, dir) = temp_db();
+    create_collection(&db, COLLECTION);
+    let mut txn = db.begin(false).unwrap();
+    txn.create_index(DEFAULT_CF, COLLECTION, IndexConfig::new(["email"]).unique())
+        .unwrap();
+    txn.insert_many(
+        DEFAULT_CF,
+        COLLECTION,
+        vec![
+            doc! { "_id": "u1", "email": "ada@example.com" },
+            doc! { "_id": "u2", "email": "bob@example.com" },
+        ],
+    )
+    .unwrap()
+    .drain()
+    .unwrap();
+    txn.commit().unwrap();
+    (db, dir)
+}
+
+fn is_duplicate(result: Result<Cursor<'_, '_, MemoryStore>, DbError>) -> bool {
+    matches!(
+        result.and_then(|cursor| cursor.drain()),
+        Err(DbError::DuplicateKey(_))
+    )
+}
+
+#[test]
+fn unique_index_rejects_duplicate_inserts() {
+    let (db, _dir) = users_db();
+    let mut txn = db.begin(false).unwrap();
+    assert!(is_duplicate(txn.insert_many(
+        DEFAULT_CF,
+        COLLECTION,
+        vec![doc! { "_id": "u3", "email": "ada@example.com" }],
+    )));
+    // Two new documents colliding with each other in one batch.
+    assert!(is_duplicate(txn.insert_many(
+        DEFAULT_CF,
+        COLLECTION,
+        vec![
+            doc! { "_id": "u4", "email": "cy@example.com" },
+            doc! { "_id": "u5", "email": "cy@example.com" },
+        ],
+    )));
+}
+
+#[test]
+fn unique_index_rejects_duplicate_upserts_and_merges() {
+    let (db, _dir) = users_db();
+    let txn = db.begin(false).unwrap();
+    assert!(is_duplicate(txn.upsert_many(
+        DEFAULT_CF,
+        COLLECTION,
+        vec![doc! { "_id": "u2", "email": "ada@example.com" }],
+    )));
+    assert!(is_duplicate(txn.merge_many(
+        DEFAULT_CF,
+        COLLECTION,
+        vec![doc! { "_id": "u2", "email": "ada@example.com" }],
+    )));
+    // Rewriting a document with its own value is not a conflict.
+    txn.upsert_many(
+        DEFAULT_CF,
+        COLLECTION,
+        vec![doc! { "_id": "u1", "email": "ada@example.com", "name": "Ada" }],
+    )
+    .unwrap()
+    .drain()
+    .unwrap();
+    txn.merge_many(
+        DEFAULT_CF,
+        COLLECTION,
+        vec![doc! { "_id": "u2", "name": "Bob" }],
+    )
+    .unwrap()
+    .drain()
+    .unwrap();
+}
+
+#[test]
+fn unique_index_rejects_duplicate_updates() {
+    let (db, _dir) = users_db();
+    let txn = db.begin(false).unwrap();
+    assert!(is_duplicate(txn.update_one(
+        DEFAULT_CF,
+        COLLECTION,
+        doc! { "_id": "u2" },
+        doc! { "$set": { "email": "ada@example.com" } },
+    )));
+    assert!(is_duplicate(txn.update_many(
+        DEFAULT_CF,
+        COLLECTION,
+        doc! { "email": { "$exists": true } },
+        doc! { "$set": { "email": "same@example.com" } },
+    )));
+
+    // Moving a value frees it for another document.
+    txn.update_one(
+        DEFAULT_CF,
+        COLLECTION,
+        doc! { "_id": "u1" },
+        doc! { "$set": { "email": "ada@example.org" } },
+    )
+    .unwrap()
+    .drain()
+    .unwrap();
+    txn.update_one(
+        DEFAULT_CF,
+        COLLECTION,
+        doc! { "_id": "u2" },
+        doc! { "$set": { "email": "ada@example.com" } },
+    )
+    .unwrap()
+    .drain()
+    .unwrap();
+    txn.commit().unwrap();
+}
+
+#[test]
+fn unique_index_creation_fails_on_existing_duplicates() {
+    let (db, _dir) = temp_db();
+    seed_records(&db);
+
+    // Three seed accounts share status "active".
+    let mut txn = db.begin(false).unwrap();
+    let result = txn.create_index(
+        DEFAULT_CF,
+        COLLECTION,
+        IndexConfig::new(["status"]).unique(),
+    );
+    assert!(
+        matches!(result, Err(DbError::DuplicateKey(_))),
+        "got {result:?}"
+    );
+    let indexes = txn.list_indexes(DEFAULT_CF, COLLECTION).unwrap();
+    assert!(!indexes.contains(&"status".to_string()));
+
+    txn.create_index(DEFAULT_CF, COLLECTION, IndexConfig::new(["name"]).unique())
+        .unwrap();
+    txn.commit().unwrap();
+}
+
+#[test]
+fn unique_index_allows_missing_values_but_not_repeated_nulls() {
+    let (db, _dir) = users_db();
+    let mut txn = db.begin(false).unwrap();
+    txn.insert_many(
+        DEFAULT_CF,
+        COLLECTION,
+        vec![
+            doc! { "_id": "u3" },
+            doc! { "_id": "u4" },
+            doc! { "_id": "u5", "email": null },
+        ],
+    )
+    .unwrap()
+    .drain()
+    .unwrap();
+    // An explicit null is a value like any other.
+    assert!(is_duplicate(txn.insert_many(
+        DEFAULT_CF,
+        COLLECTION,
+        vec![doc! { "_id": "u6", "email": null }],
+    )));
+}
+
+#[test]
+fn reindex_keeps_unique_option() {
+    let (db, _dir) = users_db();
+    let mut txn = db.begin(false).unwrap();
+    txn.reindex(DEFAULT_CF, COLLECTION, "email").unwrap();
+    assert!(is_duplicate(txn.insert_many(
+        DEFAULT_CF,
+        COLLECTION,
+        vec![doc! { "_id": "u3", "email": "ada@example.com" }],
+    )));
+}
+
+#[test]
+fn concurrent_inserts_of_one_value_commit_once() {
+    let (db, _dir) = users_db();
+    let mut first = db.begin(false).unwrap();
+    let mut second = db.begin(false).unwrap();
+    for (txn, id) in [(&mut first, "u3"), (&mut second, "u4")] {
+        txn.insert_many(
+            DEFAULT_CF,
+            COLLECTION,
+            vec![doc! { "_id": id, "email": "cy@example.com" }],
+        )
+        .unwrap()
+        .drain()
+        .unwrap();
+    }
+    // Neither sees the other's entry, so both pass the unique check.
+    assert!(first.commit().is_ok());
+    assert!(matches!(second.commit(), Err(DbError::Conflict(_))));
+}
@@ -2,7 +2,10 @@ use std::borrow::Cow;
 use std::hash::{Hash, Hasher};
 
 use bson::raw::{RawBsonRef, RawDocument};
-use bson::spec::ElementType;
+use bson::spec::{BinarySubtype, ElementType};
+use bson::{Decimal128, RawBson};
+
+use super::component;
 
 /// Length-prefixed header size: 1 type byte + 2 length bytes.
 const LP_HEADER: usize = 3;
@@ -57,6 +60,114 @@ fn decode_f64_sortable(b: [u8; 8]) -> f64 {
     f64::from_bits(bits)
 }
 
+// Index components give Int32, Int64 and Double one numeric order: the
+// sortable bytes of the nearest double, then how far an integer lies from
+// it as a sortable i16. Doubles and every integer within 2^53 have no
+// remainder, so 5, 5i64 and 5.0 encode alike; a larger integer between
+// two doubles sorts by its remainder. -0.0 equals 0.0, and NaN, which no
+// filter matches, has no encoding.
+
+pub(super) fn encode_number_sortable(value: RawBsonRef<'_>) -> Option<[u8; 10]> {
+    let (approx, remainder) = match value {
+        RawBsonRef::Int32(n) => (n as f64, 0),
+        RawBsonRef::Int64(n) => {
+            let approx = n as f64;
+            (approx, (n as i128 - approx as i128) as i16)
+        }
+        RawBsonRef::Double(f) if !f.is_nan() => (f, 0),
+        _ => return None,
+    };
+    let mut out = [0; 10];
+    out[..8].copy_from_slice(&encode_f64_sortable(approx + 0.0));
+    out[8..].copy_from_slice(&((remainder as u16) ^ 0x8000).to_be_bytes());
+    Some(out)
+}
+
+/// Decode a numeric component as Int64 when it holds an integer in range,
+/// else as Double.
+pub(super) fn decode_number_sortable(b: &[u8]) -> Option<RawBson> {
+    let b: [u8; 10] = b.try_into().ok()?;
+    let approx = decode_f64_sortable(b[..8].try_into().ok()?);
+    let remainder = (u16::from_be_bytes([b[8], b[9]]) ^ 0x8000) as i16;
+    let exact = approx as i128 + remainder as i128;
+    let integral = approx.fract() == 0.0 && (-(2f64.powi(63))..2f64.powi(63)).contains(&approx);
+    Some(if remainder != 0 || integral {
+        RawBson::Int64(i64::try_from(exact).ok()?)
+    } else {
+        RawBson::Double(approx)
+    })
+}
+
+// Decimal128 values sort by a class byte (NaN < -Inf < negative < zero <
+// positive < +Inf), then for finite values the adjusted exponent and the
+// coefficient scaled to 34 digits. Negative values invert both, so larger
+// magnitudes sort first. Equal values in different cohorts (1.5, 1.50)
+// encode alike; decoding yields the shortest coefficient.
+
+const DECIMAL_BIAS: i32 = 6176;
+const DECIMAL_MAX_EXPONENT: i32 = 6111;
+const DECIMAL_DIGITS: u32 = 34;
+
+fn encode_decimal128_sortable(d: Decimal128) -> Vec<u8> {
+    let bits = u128::from_le_bytes(d.bytes());
+    let negative = bits >> 127 == 1;
+    match (bits >> 122) & 0x1F {
+        0x1F => return vec![0],
+        0x1E => return vec![if negative { 1 } else { 5 }],
+        _ => {}
+    }
+    // The large-coefficient form always exceeds 10^34 - 1, which makes
+    // it non-canonical: it reads as zero, as does any coefficient past that.
+    let (exponent, coefficient) = if (bits >> 125) & 0b11 == 0b11 {
+        (0, 0)
+    } else {
+        (
+            ((bits >> 113) & 0x3FFF) as i32 - DECIMAL_BIAS,
+            bits & ((1 << 113) - 1),
+        )
+    };
+    if coefficient == 0 || coefficient >= 10u128.pow(DECIMAL_DIGITS) {
+        return vec![3];
+    }
+    let digits = coefficient.ilog10() + 1;
+    let adjusted = (exponent + digits as i32 - 1 + DECIMAL_BIAS) as u16;
+    let scaled = coefficient * 10u128.pow(DECIMAL_DIGITS - digits);
+    let mut out = Vec::with_capacity(19);
+    out.push(if negative { 2 } else { 4 });
+    out.extend_from_slice(&adjusted.to_be_bytes());
+    out.extend_from_slice(&scaled.to_be_bytes());
+    if negative {
+        out[1..].iter_mut().for_each(|b| *b = !*b);
+    }
+    out
+}
+
+fn decode_decimal128_sortable(b: &[u8]) -> Option<Decimal128> {
+    let bits: u128 = match *b.first()? {
+        0 => 0b11111 << 122,
+        1 => (1 << 127) | (0b11110 << 122),
+        3 => (DECIMAL_BIAS as u128) << 113,
+        5 => 0b11110 << 122,
+        class @ (2 | 4) if b.len() == 19 => {
+            let mut body: [u8; 18] = b[1..].try_into().ok()?;
+            if class == 2 {
+                body.iter_mut().for_each(|b| *b = !*b);
+            }
+            let adjusted = u16::from_be_bytes([body[0], body[1]]) as i32 - DECIMAL_BIAS;
+            let mut coefficient = u128::from_be_bytes(body[2..].try_into().ok()?);
+            let mut exponent = adjusted - (DECIMAL_DIGITS as i32 - 1);
+            while exponent < DECIMAL_MAX_EXPONENT && coefficient % 10 == 0 {
+                coefficient /= 10;
+                exponent += 1;
+            }
+            let sign = if class == 2 { 1u128 << 127 } else { 0 };
+            sign | (((exponent + DECIMAL_BIAS) as u128) << 113) | coefficient
+        }
+        _ => return None,
+    };
+    Some(Decimal128::from_bytes(bits.to_le_bytes()))
+}
+
 /// A raw BSON value: type tag + value bytes.
 ///
 /// This is the shared representation for doc_id and index values.
@@ -64,8 +175,11 @@ fn decode_f64_sortable(b: [u8; 8]) -> f64 {
 /// only the raw value data (not the tag).
 ///
 /// Construction from `RawBsonRef`:
-/// - `ObjectId`, `String` → zero-alloc (borrows from source)
-/// - `Int32`, `Int64`, `Double`, `DateTime`, `Boolean` → small owned allocation
+/// - `String` → zero-alloc (borrows from source)
+/// - `Null` → empty bytes
+/// - `ObjectId`, `Int32`, `Int64`, `Double`, `DateTime`, `Boolean`,
+///   `Binary`, `Decimal128` → small owned allocation
+/// - `Document`, `Array` → the index components of their contents
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct BsonValue<'a> {
     pub tag: ElementType,
@@ -83,7 +197,7 @@ impl<'a> BsonValue<'a> {
     /// Create a `BsonValue` from a `RawBsonRef`.
     ///
     /// Returns `None` for types that aren't supported as field values
-    /// (Document, Array, Null, Binary, etc.).
+    /// (Timestamp, Regex, etc.), and for documents or arrays holding them.
     pub fn from_raw_bson_ref(val: RawBsonRef<'a>) -> Option<Self> {
         match val {
             RawBsonRef::ObjectId(oid) => Some(BsonValue {
@@ -114,13 +228,47 @@ impl<'a> BsonValue<'a> {
                 tag: ElementType::Boolean,
                 bytes: Cow::Owned(vec![b as u8]),
             }),
+            RawBsonRef::Null => Some(BsonValue {
+                tag: ElementType::Null,
+                bytes: Cow::Borrowed(&[]),
+            }),
+            RawBsonRef::Binary(bin) => Some(BsonValue {
+                tag: ElementType::Binary,
+                bytes: Cow::Owned([&[u8::from(bin.subtype)], bin.bytes].concat()),
+            }),
+            RawBsonRef::Decimal128(d) => Some(BsonValue {
+                tag: ElementType::Decimal128,
+                bytes: Cow::Owned(encode_decimal128_sortable(d)),
+            }),
+            RawBsonRef::Document(doc) => {
+                let mut bytes = Vec::new();
+                for element in doc {
+                    let (name, value) = element.ok()?;
+                    component::write_str(&mut bytes, name.as_str());
+                    component::write_nested(&mut bytes, value)?;
+                }
+                Some(BsonValue {
+                    tag: ElementType::EmbeddedDocument,
+                    bytes: Cow::Owned(bytes),
+                })
+            }
+            RawBsonRef::Array(arr) => {
+                let mut bytes = Vec::new();
+                for element in arr {
+                    component::write_nested(&mut bytes, element.ok()?)?;
+                }
+                Some(BsonValue {
+                    tag: ElementType::Array,
+                    bytes: Cow::Owned(bytes),
+                })
+            }
             _ => None,
         }
     }
 
     /// Create a `BsonValue` from an owned `bson::Bson`.
     ///
-    /// Returns `None` for types that aren't supported (Document, Array, Null, etc.).
+    /// Returns `None` for types that aren't supported (Timestamp, Regex, etc.).
     pub fn from_bson(val: &bson::Bson) -> Option<BsonValue<'static>> {
         match val {
             bson::Bson::ObjectId(oid) => Some(BsonValue {
@@ -151,43 +299,24 @@ impl<'a> BsonValue<'a> {
                 tag: ElementType::Boolean,
                 bytes: Cow::Owned(vec![*b as u8]),
             }),
-            _ => None,
-        }
-    }
-
-    /// Extract a field from a `RawDocument` as a `BsonValue`.
-    ///
-    /// Supports dotted paths for nested documents (e.g. `"address.city"`).
-    /// For array multi-key fields (containing `[]`), use [`extract_all`].
-    pub fn extract(doc: &'a RawDocument, field: &str) -> Option<Self> {
-        if !field.contains('.') {
-            return match doc.get(field) {
-                Ok(Some(val)) => Self::from_raw_bson_ref(val),
-                _ => None,
-            };
-        }
-
-        // Walk dotted path through nested documents
-        let mut segments = field.split('.');
-        let first = segments.next()?;
-        let mut current = match doc.get(first) {
-            Ok(Some(val)) => val,
-            _ => return None,
-        };
-
-        for seg in segments {
-            match current {
-                RawBsonRef::Document(d) => {
-                    current = match d.get(seg) {
-                        Ok(Some(val)) => val,
-                        _ => return None,
-                    };
-                }
-                _ => return None,
+            bson::Bson::Null => Some(BsonValue {
+                tag: ElementType::Null,
+                bytes: Cow::Borrowed(&[]),
+            }),
+            bson::Bson::Binary(bin) => Some(BsonValue {
+                tag: ElementType::Binary,
+                bytes: Cow::Owned([&[u8::from(bin.subtype)], bin.bytes.as_slice()].concat()),
+            }),
+            bson::Bson::Decimal128(d) => Some(BsonValue {
+                tag: ElementType::Decimal128,
+                bytes: Cow::Owned(encode_decimal128_sortable(*d)),
+            }),
+            bson::Bson::Document(_) | bson::Bson::Array(_) => {
+                let raw = RawBson::try_from(val.clone()).ok()?;
+                BsonValue::from_raw_bson_ref(raw.as_raw_bson_ref()).map(BsonValue::into_owned)
             }
+            _ => None,
         }
-
-        Self::from_raw_bson_ref(current)
     }
 
     /// Construct from a known type tag and raw value bytes.
@@ -278,6 +407,21 @@ impl<'a> BsonValue<'a> {
             ElementType::Boolean => {
                 bson::RawBson::Boolean(self.bytes.first().is_some_and(|&v| v != 0))
             }
+            ElementType::Null => bson::RawBson::Null,
+            ElementType::Binary => {
+                let (&subtype, bytes) = self.bytes.split_first()?;
+                bson::RawBson::Binary(bson::Binary {
+                    subtype: BinarySubtype::from(subtype),
+                    bytes: bytes.to_vec(),
+                })
+            }
+            ElementType::Decimal128 => {
+                bson::RawBson::Decimal128(decode_decimal128_sortable(&self.bytes)?)
+            }
+            ElementType::EmbeddedDocument => {
+                bson::RawBson::Document(component::decode_document(&self.bytes)?)
+            }
+            ElementType::Array => bson::RawBson::Array(component::decode_array(&self.bytes)?),
             _ => return None,
         })
     }
@@ -320,13 +464,18 @@ impl std::fmt::Display for BsonValue<'_> {
                 let b = self.bytes.first().is_some_and(|&v| v != 0);
                 write!(f, "{b}")
             }
+            ElementType::Null => write!(f, "null"),
+            ElementType::Decimal128 => match decode_decimal128_sortable(&self.bytes) {
+                Some(d) => write!(f, "{d}"),
+                None => write!(f, "<invalid decimal128>"),
+            },
             _ => write!(f, "<bson 0x{:02x}>", self.tag as u8),
         }
     }
 }
 
 /// Error returned when a `RawBsonRef` cannot be converted to a `BsonValue`
-/// (e.g. Null, Document, Array, Binary).
+/// (e.g. Timestamp, Regex).
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct UnsupportedBsonType;
 
@@ -348,21 +497,37 @@ impl<'a> TryFrom<RawBsonRef<'a>> for BsonValue<'a> {
 
 // ── Multi-key extraction (free functions to avoid impl<'a> lifetime issues) ──
 
+/// Resolve a dotted path through nested documents.
+pub(crate) fn lookup<'a>(doc: &'a RawDocument, field: &str) -> Option<RawBsonRef<'a>> {
+    let mut segments = field.split('.');
+    let mut current = doc.get(segments.next()?).ok()??;
+    for seg in segments {
+        match current {
+            RawBsonRef::Document(d) => current = d.get(seg).ok()??,
+            _ => return None,
+        }
+    }
+    Some(current)
+}
+
 /// Extract all values for a field path, supporting multi-key (array) indexing.
 ///
-/// The path may contain `[]` segments to traverse arrays:
+/// A path that ends at an array yields each element, the way filters
+/// match arrays element-wise. The path may also contain `[]` segments to
+/// traverse arrays:
 /// - `"status"` → `[BsonValue("active")]` (single value)
 /// - `"address.city"` → `[BsonValue("Austin")]` (nested document)
-/// - `"tags.[]"` → `[BsonValue("rust"), BsonValue("db")]` (array elements)
+/// - `"tags"` / `"tags.[]"` → `[BsonValue("rust"), BsonValue("db")]` (array elements)
 /// - `"items.[].sku"` → `[BsonValue("A1"), BsonValue("B2")]` (nested array)
 ///
 /// Returns owned values since we may need to traverse array elements.
 pub fn extract_all(doc: &RawDocument, field: &str) -> Vec<BsonValue<'static>> {
     if !field.contains("[]") {
-        return match BsonValue::extract(doc, field) {
-            Some(v) => vec![v.into_owned()],
-            None => vec![],
-        };
+        let mut results = Vec::new();
+        if let Some(value) = lookup(doc, field) {
+            collect_from_value(value, &[], 0, &mut results);
+        }
+        return results;
     }
 
     let segments: Vec<&str> = field.split('.').collect();
@@ -396,17 +561,22 @@ fn collect_from_value(
     out: &mut Vec<BsonValue<'static>>,
 ) {
     if idx >= segments.len() {
-        // Terminal: try to convert to BsonValue
+        // Terminal: try to convert to BsonValue. Filters don't match null
+        // elements against null, so nulls are left out.
         match value {
             RawBsonRef::Array(arr) => {
                 for v in arr.into_iter().flatten() {
-                    if let Some(bv) = BsonValue::from_raw_bson_ref(v) {
+                    if let Some(bv) = BsonValue::from_raw_bson_ref(v)
+                        && bv.tag != ElementType::Null
+                    {
                         out.push(bv.into_owned());
                     }
                 }
             }
             _ => {
-                if let Some(bv) = BsonValue::from_raw_bson_ref(value) {
+                if let Some(bv) = BsonValue::from_raw_bson_ref(value)
+                    && bv.tag != ElementType::Null
+                {
                     out.push(bv.into_owned());
                 }
             }
@@ -498,7 +668,31 @@ mod tests {
 
     #[test]
     fn unsupported_type_returns_none() {
-        assert!(BsonValue::from_raw_bson_ref(RawBsonRef::Null).is_none());
+        let ts = bson::Timestamp {
+            time: 1,
+            increment: 1,
+        };
+        assert!(BsonValue::from_raw_bson_ref(RawBsonRef::Timestamp(ts)).is_none());
+        let doc = bson::rawdoc! { "ts": ts };
+        assert!(BsonValue::from_raw_bson_ref(RawBsonRef::Document(&doc)).is_none());
+    }
+
+    #[test]
+    fn null_binary_and_decimal_roundtrip() {
+        let values = [
+            bson::Bson::Null,
+            bson::Bson::Binary(bson::Binary::from_uuid(bson::Uuid::new())),
+            bson::Bson::Decimal128("-12.5".parse().unwrap()),
+            bson::Bson::Document(bson::doc! { "a": "x", "b": [true, null] }),
+        ];
+        for value in values {
+            let bv = BsonValue::from_bson(&value).unwrap();
+            let raw = bv.to_raw_bson().unwrap();
+            assert_eq!(bson::Bson::try_from(raw).unwrap(), value);
+        }
+        let null = BsonValue::from_raw_bson_ref(RawBsonRef::Null).unwrap();
+        assert_eq!(null.tag, ElementType::Null);
+        assert!(null.bytes.is_empty());
     }
 
     #[test]
@@ -531,11 +725,11 @@ mod tests {
     #[test]
     fn extract_from_document() {
         let doc = bson::rawdoc! { "_id": "abc", "age": 25 };
-        let id = BsonValue::extract(&doc, "_id").unwrap();
+        let id = extract_all(&doc, "_id").remove(0);
         assert_eq!(id.tag, ElementType::String);
         assert_eq!(&*id.bytes, b"abc");
 
-        let age = BsonValue::extract(&doc, "age").unwrap();
+        let age = extract_all(&doc, "age").remove(0);
         assert_eq!(age.tag, ElementType::Int32);
         assert_eq!(&*age.bytes, &encode_i32_sortable(25));
     }
@@ -543,7 +737,7 @@ mod tests {
     #[test]
     fn extract_missing_field() {
         let doc = bson::rawdoc! { "_id": "abc" };
-        assert!(BsonValue::extract(&doc, "missing").is_none());
+        assert!(extract_all(&doc, "missing").is_empty());
     }
 
     #[test]
@@ -581,7 +775,7 @@ mod tests {
         let val: BsonValue<'_> = RawBsonRef::Int32(7).try_into().unwrap();
         assert_eq!(val.tag, ElementType::Int32);
 
-        let err = BsonValue::try_from(RawBsonRef::Null);
+        let err = BsonValue::try_from(RawBsonRef::Undefined);
         assert!(err.is_err());
     }
 
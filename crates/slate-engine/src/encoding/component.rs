@@ -0,0 +1,513 @@
+use std::cmp::Ordering;
+
+use bson::raw::{RawBsonRef, RawDocument};
+use bson::spec::ElementType;
+use bson::{Bson, RawArrayBuf, RawBson, RawDocumentBuf};
+
+use super::bson_value::{self, BsonValue};
+
+// ── Index value components ─────────────────────────────────────
+//
+// Every index entry stores its value as a component, and a compound
+// entry one per field, concatenated:
+//
+//   component = `[rank][escaped sortable bytes]\x00\x01`
+//
+// Inside a component every `0x00` byte is written as `0x00 0xFF`, and
+// `0x00 0x01` ends it. That keeps components self-delimiting without a
+// length prefix, which would order `"b"` before `"aa"`: byte order over
+// a concatenation is the order of the first component, then the second,
+// and so on. The sortable bytes come from `BsonValue`, except for numbers,
+// which share one encoding across types (see `encode_number_sortable`).
+//
+// The rank orders values of different types:
+//
+//   0x00  missing field
+//   0x01  null
+//   0x10  Int32, Int64 and Double, by numeric value (5, 5i64 and 5.0 are equal)
+//   0x12  Decimal128 (1.5 and 1.50 are equal)
+//   0x20  String
+//   0x30  embedded document: field by field, name then value
+//   0x31  array: element by element
+//   0x40  Binary: subtype, then bytes (UUIDs are subtype 4)
+//   0x50  ObjectId
+//   0x60  Boolean
+//   0x70  DateTime
+//   0xFF  anything else, and documents or arrays holding it
+//
+// Within a rank, a value sorts before any longer value it is a prefix of
+// (`"a"` < `"ab"`, `[1]` < `[1, 2]`). Range scans are bracketed by the
+// rank of their bounds, so they never cross into another type and never
+// reach `0xFF`, whose values all encode alike.
+
+pub(crate) const MISSING: u8 = 0x00;
+pub(crate) const NULL: u8 = 0x01;
+const NUMBER: u8 = 0x10;
+const DECIMAL128: u8 = 0x12;
+const STRING: u8 = 0x20;
+const DOCUMENT: u8 = 0x30;
+const ARRAY: u8 = 0x31;
+const BINARY: u8 = 0x40;
+const OBJECT_ID: u8 = 0x50;
+const BOOLEAN: u8 = 0x60;
+pub(crate) const DATE_TIME: u8 = 0x70;
+const UNSUPPORTED: u8 = 0xFF;
+
+const ESCAPE: u8 = 0x00;
+const ESCAPED_ZERO: u8 = 0xFF;
+const TERMINATOR: u8 = 0x01;
+
+fn rank(tag: ElementType) -> u8 {
+    match tag {
+        ElementType::Null => NULL,
+        ElementType::Double | ElementType::Int32 | ElementType::Int64 => NUMBER,
+        ElementType::Decimal128 => DECIMAL128,
+        ElementType::String => STRING,
+        ElementType::EmbeddedDocument => DOCUMENT,
+        ElementType::Array => ARRAY,
+        ElementType::Binary => BINARY,
+        ElementType::ObjectId => OBJECT_ID,
+        ElementType::Boolean => BOOLEAN,
+        ElementType::DateTime => DATE_TIME,
+        _ => UNSUPPORTED,
+    }
+}
+
+fn tag(rank: u8) -> Option<ElementType> {
+    Some(match rank {
+        NULL => ElementType::Null,
+        DECIMAL128 => ElementType::Decimal128,
+        STRING => ElementType::String,
+        DOCUMENT => ElementType::EmbeddedDocument,
+        ARRAY => ElementType::Array,
+        BINARY => ElementType::Binary,
+        OBJECT_ID => ElementType::ObjectId,
+        BOOLEAN => ElementType::Boolean,
+        DATE_TIME => ElementType::DateTime,
+        _ => return None,
+    })
+}
+
+/// Whether a query value can be encoded as a component: any value
+/// except the unsupported types, including inside documents and arrays.
+pub fn is_indexable(value: &Bson) -> bool {
+    encode_value(value).is_some()
+}
+
+/// Compare a stored value against a query value in index order. `None`
+/// when they have different ranks or neither has a sortable encoding, so
+/// filters and index scans agree on which values compare at all.
+pub fn compare_values(stored: RawBsonRef<'_>, query: &Bson) -> Option<Ordering> {
+    let stored = encode(Some(stored));
+    let query = encode_value(query)?;
+    (stored[0] == query[0] && stored[0] != UNSUPPORTED).then(|| stored.cmp(&query))
+}
+
+/// Compare two stored values in index order, like [`compare_values`].
+pub fn compare_raw_values(a: RawBsonRef<'_>, b: RawBsonRef<'_>) -> Option<Ordering> {
+    let a = encode(Some(a));
+    let b = encode(Some(b));
+    (a[0] == b[0] && a[0] != UNSUPPORTED).then(|| a.cmp(&b))
+}
+
+/// The components `field` contributes to an index for `doc`, each with
+/// the BSON type it came from: one per element of an array, or one for
+/// the value. A missing field has a component of its own. Null elements
+/// and empty arrays get an unsupported one, since filters don't match
+/// them against null.
+pub(crate) fn field_components(doc: &RawDocument, field: &str) -> Vec<(ElementType, Vec<u8>)> {
+    match bson_value::lookup(doc, field) {
+        Some(RawBsonRef::Array(arr)) => {
+            let elements: Vec<_> = arr
+                .into_iter()
+                .flatten()
+                .map(|element| match element {
+                    RawBsonRef::Null => (ElementType::Null, unsupported()),
+                    element => (element.element_type(), encode(Some(element))),
+                })
+                .collect();
+            if elements.is_empty() {
+                vec![(ElementType::Null, unsupported())]
+            } else {
+                elements
+            }
+        }
+        Some(value) => vec![(value.element_type(), encode(Some(value)))],
+        None => vec![(ElementType::Null, encode(None))],
+    }
+}
+
+/// Encode a stored value as a component; `None` is a missing field.
+pub(crate) fn encode(value: Option<RawBsonRef<'_>>) -> Vec<u8> {
+    let mut buf = Vec::new();
+    match value {
+        None => buf.extend_from_slice(&[MISSING, ESCAPE, TERMINATOR]),
+        Some(value) => {
+            if write_nested(&mut buf, value).is_none() {
+                buf = unsupported();
+            }
+        }
+    }
+    buf
+}
+
+/// Encode a query value as a component. Returns `None` for values that
+/// are not [indexable](is_indexable).
+pub(crate) fn encode_value(value: &Bson) -> Option<Vec<u8>> {
+    let mut buf = Vec::new();
+    write_value(&mut buf, &BsonValue::from_bson(value)?)?;
+    Some(buf)
+}
+
+/// Encode an extracted value as a component.
+pub(crate) fn encode_bson_value(value: BsonValue<'_>) -> Vec<u8> {
+    let mut buf = Vec::new();
+    match write_value(&mut buf, &value) {
+        Some(()) => buf,
+        None => unsupported(),
+    }
+}
+
+/// The rank of a query value alone, for bounding a range to values of
+/// the same type.
+pub(crate) fn type_prefix(value: &Bson) -> Option<u8> {
+    Some(encode_value(value)?[0])
+}
+
+/// Whether `value_bytes` is a single component that stands in for no
+/// value: a missing field or an unsupported one.
+pub(crate) fn is_placeholder(value_bytes: &[u8]) -> bool {
+    matches!(value_bytes, [MISSING | UNSUPPORTED, ESCAPE, TERMINATOR])
+}
+
+/// Append the component of a value found inside a document. Returns
+/// `None`, leaving `buf` partly written, if it has no sortable encoding.
+pub(crate) fn write_nested(buf: &mut Vec<u8>, value: RawBsonRef<'_>) -> Option<()> {
+    write_value(buf, &BsonValue::from_raw_bson_ref(value)?)
+}
+
+/// Append a string component, as used for the field names of a document.
+pub(crate) fn write_str(buf: &mut Vec<u8>, s: &str) {
+    write_value(
+        buf,
+        &BsonValue::from_parts(ElementType::String, s.as_bytes()),
+    )
+    .expect("strings are sortable");
+}
+
+/// Decode the component at the start of `bytes`, returning the value and
+/// the bytes after it. Missing and unsupported values decode as null.
+pub(crate) fn decode(bytes: &[u8]) -> Option<(RawBson, &[u8])> {
+    let rank = *bytes.first()?;
+    let mut payload = Vec::new();
+    let mut i = 1;
+    loop {
+        match (*bytes.get(i)?, bytes.get(i + 1).copied()) {
+            (ESCAPE, Some(TERMINATOR)) => break,
+            (ESCAPE, Some(ESCAPED_ZERO)) => {
+                payload.push(0);
+                i += 2;
+            }
+            (ESCAPE, _) => return None,
+            (b, _) => {
+                payload.push(b);
+                i += 1;
+            }
+        }
+    }
+    let rest = &bytes[i + 2..];
+    let value = match rank {
+        MISSING | UNSUPPORTED => RawBson::Null,
+        NUMBER => bson_value::decode_number_sortable(&payload)?,
+        rank => BsonValue::from_parts(tag(rank)?, &payload).to_raw_bson()?,
+    };
+    Some((value, rest))
+}
+
+/// Decode the payload of a document component: name and value
+/// components in turn.
+pub(crate) fn decode_document(mut bytes: &[u8]) -> Option<RawDocumentBuf> {
+    let mut doc = RawDocumentBuf::new();
+    while !bytes.is_empty() {
+        let (RawBson::String(name), rest) = decode(bytes)? else {
+            return None;
+        };
+        let (value, rest) = decode(rest)?;
+        doc.append(bson::raw::CString::try_from(name.as_str()).ok()?, value);
+        bytes = rest;
+    }
+    Some(doc)
+}
+
+/// Decode the payload of an array component: one component per element.
+pub(crate) fn decode_array(mut bytes: &[u8]) -> Option<RawArrayBuf> {
+    let mut arr = RawArrayBuf::new();
+    while !bytes.is_empty() {
+        let (value, rest) = decode(bytes)?;
+        arr.push(value);
+        bytes = rest;
+    }
+    Some(arr)
+}
+
+fn unsupported() -> Vec<u8> {
+    vec![UNSUPPORTED, ESCAPE, TERMINATOR]
+}
+
+/// Append the component of `value`. Returns `None`, writing nothing, for
+/// a number without a place in the numeric order (NaN).
+fn write_value(buf: &mut Vec<u8>, value: &BsonValue<'_>) -> Option<()> {
+    let rank = rank(value.tag);
+    let number = match rank {
+        NUMBER => Some(bson_value::encode_number_sortable(
+            value.to_raw_bson()?.as_raw_bson_ref(),
+        )?),
+        _ => None,
+    };
+    let bytes = number.as_ref().map_or(&*value.bytes, |n| n.as_slice());
+    buf.push(rank);
+    for &b in bytes {
+        if b == ESCAPE {
+            buf.extend_from_slice(&[ESCAPE, ESCAPED_ZERO]);
+        } else {
+            buf.push(b);
+        }
+    }
+    buf.extend_from_slice(&[ESCAPE, TERMINATOR]);
+    Some(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bson::spec::BinarySubtype;
+    use bson::{Binary, Decimal128, doc};
+
+    fn value(v: Bson) -> Vec<u8> {
+        encode_value(&v).unwrap()
+    }
+
+    fn decimal(s: &str) -> Bson {
+        Bson::Decimal128(s.parse::<Decimal128>().unwrap())
+    }
+
+    fn binary(subtype: BinarySubtype, bytes: &[u8]) -> Bson {
+        Bson::Binary(Binary {
+            subtype,
+            bytes: bytes.to_vec(),
+        })
+    }
+
+    #[test]
+    fn types_sort_in_rank_order() {
+        let ordered = [
+            encode(None),
+            value(Bson::Null),
+            value(Bson::Int32(-5)),
+            value(Bson::Double(1e300)),
+            value(decimal("-1E+6000")),
+            value(Bson::String(String::new())),
+            value(Bson::Document(doc! {})),
+            value(Bson::Array(vec![])),
+            value(binary(BinarySubtype::Generic, &[])),
+            value(Bson::ObjectId(bson::oid::ObjectId::from_bytes([0; 12]))),
+            value(Bson::Boolean(false)),
+            value(Bson::DateTime(bson::DateTime::MIN)),
+            encode(Some(RawBsonRef::Timestamp(bson::Timestamp {
+                time: 0,
+                increment: 0,
+            }))),
+        ];
+        for pair in ordered.windows(2) {
+            assert!(pair[0] < pair[1], "{:?} < {:?}", pair[0], pair[1]);
+        }
+    }
+
+    #[test]
+    fn numbers_sort_by_value_across_types() {
+        let big = 1i64 << 60;
+        let ordered = [
+            value(Bson::Double(f64::NEG_INFINITY)),
+            value(Bson::Int64(i64::MIN)),
+            value(Bson::Double(-2.5)),
+            value(Bson::Int32(-2)),
+            value(Bson::Double(0.5)),
+            value(Bson::Int64(big)),
+            value(Bson::Int64(big + 1)),
+            value(Bson::Double(big as f64 + 512.0)),
+            value(Bson::Int64(i64::MAX)),
+            value(Bson::Double(f64::INFINITY)),
+        ];
+        for pair in ordered.windows(2) {
+            assert!(pair[0] < pair[1], "{:?} < {:?}", pair[0], pair[1]);
+        }
+
+        assert_eq!(value(Bson::Int32(5)), value(Bson::Int64(5)));
+        assert_eq!(value(Bson::Int32(5)), value(Bson::Double(5.0)));
+        assert_eq!(value(Bson::Double(-0.0)), value(Bson::Double(0.0)));
+
+        for n in [
+            Bson::Int64(i64::MAX),
+            Bson::Int64(big + 1),
+            Bson::Double(0.5),
+        ] {
+            let encoded = value(n.clone());
+            let (decoded, _) = decode(&encoded).unwrap();
+            assert_eq!(Bson::try_from(decoded).unwrap(), n);
+        }
+    }
+
+    #[test]
+    fn missing_and_null_are_distinct() {
+        let doc = bson::rawdoc! { "n": null };
+        let missing = field_components(&doc, "m");
+        let null = field_components(&doc, "n");
+        assert_eq!(missing[0].1, encode(None));
+        assert_eq!(null[0].1, value(Bson::Null));
+        assert!(missing[0].1 < null[0].1);
+        assert!(is_placeholder(&missing[0].1));
+        assert!(!is_placeholder(&null[0].1));
+    }
+
+    #[test]
+    fn decimals_sort_numerically() {
+        let ordered = [
+            "NaN",
+            "-Infinity",
+            "-1E+10",
+            "-2.5",
+            "-0.001",
+            "0",
+            "1E-6176",
+            "0.5",
+            "1",
+            "1.5",
+            "10",
+            "9.999999999999999999999999999999999E+6144",
+            "Infinity",
+        ];
+        let encoded: Vec<_> = ordered.iter().map(|s| value(decimal(s))).collect();
+        for (pair, names) in encoded.windows(2).zip(ordered.windows(2)) {
+            assert!(pair[0] < pair[1], "{} < {}", names[0], names[1]);
+        }
+        assert_eq!(value(decimal("1.5")), value(decimal("1.50")));
+        assert_eq!(value(decimal("0")), value(decimal("-0E+3")));
+        assert_eq!(value(decimal("1E+1")), value(decimal("10")));
+    }
+
+    #[test]
+    fn decimals_decode_to_an_equal_value() {
+        for s in [
+            "1.5",
+            "-2.5",
+            "0",
+            "1E+6111",
+            "1E-6176",
+            "-Infinity",
+            "NaN",
+            "12345678901234567891",
+        ] {
+            let encoded = value(decimal(s));
+            let (decoded, rest) = decode(&encoded).unwrap();
+            assert!(rest.is_empty());
+            let RawBson::Decimal128(d) = decoded else {
+                panic!("expected a decimal for {s}");
+            };
+            assert_eq!(d.to_string(), s.parse::<Decimal128>().unwrap().to_string());
+        }
+        let (decoded, _) = decode(&value(decimal("1.50"))).unwrap();
+        assert_eq!(decoded, RawBson::Decimal128("1.5".parse().unwrap()));
+    }
+
+    #[test]
+    fn binary_sorts_by_subtype_then_bytes() {
+        let uuid = bson::Uuid::parse_str("00000000-0000-4000-8000-000000000001").unwrap();
+        let ordered = [
+            value(binary(BinarySubtype::Generic, &[9, 9])),
+            value(binary(BinarySubtype::Generic, &[9, 9, 0])),
+            value(binary(BinarySubtype::Uuid, &[0; 16])),
+            value(Bson::Binary(Binary::from_uuid(uuid))),
+            value(binary(BinarySubtype::UserDefined(0x80), &[0])),
+        ];
+        for pair in ordered.windows(2) {
+            assert!(pair[0] < pair[1]);
+        }
+        let (decoded, _) = decode(&ordered[3]).unwrap();
+        assert_eq!(
+            decoded,
+            RawBson::try_from(Bson::Binary(Binary::from_uuid(uuid))).unwrap()
+        );
+    }
+
+    #[test]
+    fn documents_and_arrays_roundtrip_and_sort_by_content() {
+        let nested = doc! { "a": 1, "b": { "c": [null, "x\0y", 2.5] } };
+        let encoded = value(Bson::Document(nested.clone()));
+        let (decoded, rest) = decode(&encoded).unwrap();
+        assert!(rest.is_empty());
+        let RawBson::Document(decoded) = decoded else {
+            panic!("expected a document");
+        };
+        // Int32 is widened inside documents too.
+        assert_eq!(
+            decoded,
+            bson::rawdoc! { "a": 1i64, "b": { "c": [null, "x\0y", 2.5] } }
+        );
+
+        assert_eq!(
+            value(Bson::Document(doc! { "a": 1 })),
+            value(Bson::Document(doc! { "a": 1i64 }))
+        );
+        assert!(value(Bson::Document(doc! { "a": 1 })) < value(Bson::Document(doc! { "a": 2 })));
+        assert!(value(Bson::Document(doc! { "a": 9 })) < value(Bson::Document(doc! { "b": 1 })));
+        assert!(
+            value(Bson::Document(doc! { "a": 1 })) < value(Bson::Document(doc! { "a": 1, "b": 1 }))
+        );
+        assert!(value(Bson::Array(vec![1.into()])) < value(Bson::Array(vec![1.into(), 0.into()])));
+        assert!(value(Bson::Array(vec![1.into(), 5.into()])) < value(Bson::Array(vec![2.into()])));
+    }
+
+    #[test]
+    fn unsupported_values_are_not_indexable() {
+        let ts = Bson::Timestamp(bson::Timestamp {
+            time: 1,
+            increment: 1,
+        });
+        assert!(!is_indexable(&ts));
+        assert!(!is_indexable(&Bson::Document(doc! { "t": ts.clone() })));
+        assert!(is_indexable(&Bson::Document(doc! { "a": 1 })));
+        assert!(is_indexable(&Bson::Null));
+        assert!(!is_indexable(&Bson::Double(f64::NAN)));
+        assert!(!is_indexable(&Bson::Array(vec![Bson::Double(f64::NAN)])));
+        assert!(is_placeholder(&encode(Some(RawBsonRef::Timestamp(
+            bson::Timestamp {
+                time: 1,
+                increment: 1,
+            }
+        )))));
+    }
+
+    #[test]
+    fn compare_values_only_within_a_rank() {
+        let doc =
+            bson::rawdoc! { "s": "b", "n": 3, "id": bson::oid::ObjectId::from_bytes([1; 12]) };
+        let s = doc.get("s").unwrap().unwrap();
+        let n = doc.get("n").unwrap().unwrap();
+        let id = doc.get("id").unwrap().unwrap();
+        assert_eq!(
+            compare_values(s, &Bson::String("a".into())),
+            Some(Ordering::Greater)
+        );
+        assert_eq!(compare_values(n, &Bson::Int64(3)), Some(Ordering::Equal));
+        assert_eq!(compare_values(n, &Bson::String("3".into())), None);
+        assert_eq!(
+            compare_values(
+                id,
+                &Bson::ObjectId(bson::oid::ObjectId::from_bytes([1; 12]))
+            ),
+            Some(Ordering::Equal)
+        );
+        assert_eq!(compare_raw_values(s, s), Some(Ordering::Equal));
+        assert_eq!(compare_raw_values(n, s), None);
+    }
+}
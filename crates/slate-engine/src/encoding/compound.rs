@@ -0,0 +1,186 @@
+use bson::RawArrayBuf;
+use bson::RawBson;
+use bson::raw::{RawBsonRef, RawDocument};
+
+use super::bson_value;
+use super::component;
+use crate::error::EngineError;
+
+// ── Compound index values ──────────────────────────────────────
+//
+// A compound index entry holds one component per indexed field, in index
+// order, concatenated into the value slot of the index key:
+//
+//   `{component_1}{component_2}...{component_n}`
+//
+// Components are self-delimiting and order-preserving (see `component`),
+// so byte order over the concatenation is the order of the first field,
+// then the second, and so on.
+//
+// A field holding an array contributes each element in turn, so a document
+// gets one entry per element. At most one field may hold an array: writes
+// and backfill reject parallel arrays with `check_arrays`, since their
+// entries would be the cross product of the elements. An empty array, like
+// an element of an unsortable type, is unsupported.
+
+/// Metadata type byte of a compound index entry. Single-field entries
+/// store the BSON type of their value there instead.
+pub(crate) const COMPOUND_TYPE: u8 = 0x00;
+
+/// Reject `doc` if more than one of `fields` holds an array.
+pub(crate) fn check_arrays(
+    doc: &RawDocument,
+    index: &str,
+    fields: &[&str],
+) -> Result<(), EngineError> {
+    let mut arrays = fields
+        .iter()
+        .filter(|field| matches!(bson_value::lookup(doc, field), Some(RawBsonRef::Array(_))));
+    if let (Some(first), Some(second)) = (arrays.next(), arrays.next()) {
+        return Err(EngineError::InvalidDocument(format!(
+            "compound index '{index}' cannot index parallel arrays '{first}' and '{second}'"
+        )));
+    }
+    Ok(())
+}
+
+/// Encode the components of `fields` in `doc`: one value per combination
+/// of the elements of array fields, and exactly one without arrays. Stored
+/// documents passed [`check_arrays`], so at most one field expands.
+pub(crate) fn encode_document(doc: &RawDocument, fields: &[&str]) -> Vec<Vec<u8>> {
+    let mut values = vec![Vec::new()];
+    for field in fields {
+        let components = component::field_components(doc, field);
+        values = values
+            .iter()
+            .flat_map(|prefix| {
+                components
+                    .iter()
+                    .map(move |(_, c)| [prefix.as_slice(), c].concat())
+            })
+            .collect();
+    }
+    values
+}
+
+/// Decode every component of a compound value into an array, with
+/// missing and unsupported values as null.
+pub(crate) fn decode(mut bytes: &[u8]) -> Option<RawBson> {
+    let mut out = RawArrayBuf::new();
+    while !bytes.is_empty() {
+        let (value, rest) = component::decode(bytes)?;
+        out.push(value);
+        bytes = rest;
+    }
+    Some(RawBson::Array(out))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bson::Bson;
+    use component::encode_value;
+
+    fn encode(doc: &RawDocument, fields: &[&str]) -> Vec<u8> {
+        encode_document(doc, fields).remove(0)
+    }
+
+    #[test]
+    fn components_decode_in_order() {
+        let doc = bson::rawdoc! { "status": "active", "n": 7, "nested": { "x": true } };
+        let bytes = encode(&doc, &["status", "n", "nested.x", "missing"]);
+        let decoded = decode(&bytes).unwrap();
+        let RawBson::Array(arr) = decoded else {
+            panic!("expected array");
+        };
+        let values: Vec<RawBson> = arr.into_iter().map(|v| RawBson::from(v.unwrap())).collect();
+        assert_eq!(
+            values,
+            vec![
+                RawBson::String("active".into()),
+                RawBson::Int64(7),
+                RawBson::Boolean(true),
+                RawBson::Null,
+            ]
+        );
+    }
+
+    #[test]
+    fn order_follows_first_component_then_second() {
+        let a = encode(&bson::rawdoc! { "s": "b", "n": 1 }, &["s", "n"]);
+        let b = encode(&bson::rawdoc! { "s": "aa", "n": 9 }, &["s", "n"]);
+        assert!(b < a, "\"aa\" sorts before \"b\" regardless of length");
+
+        let c = encode(&bson::rawdoc! { "s": "a", "n": 2 }, &["s", "n"]);
+        let d = encode(&bson::rawdoc! { "s": "a", "n": 10 }, &["s", "n"]);
+        assert!(c < d);
+
+        let prefix = encode(&bson::rawdoc! { "s": "a" }, &["s"]);
+        let longer = encode(&bson::rawdoc! { "s": "a\0" }, &["s"]);
+        assert!(prefix < longer);
+        assert!(!longer.starts_with(&prefix));
+    }
+
+    #[test]
+    fn int_widths_encode_alike() {
+        let narrow = encode(&bson::rawdoc! { "n": 5 }, &["n"]);
+        let wide = encode(&bson::rawdoc! { "n": 5i64 }, &["n"]);
+        assert_eq!(narrow, wide);
+        assert_eq!(encode_value(&Bson::Int32(5)).unwrap(), narrow);
+    }
+
+    #[test]
+    fn missing_sorts_before_null() {
+        let missing = encode(&bson::rawdoc! {}, &["n"]);
+        let null = encode(&bson::rawdoc! { "n": null }, &["n"]);
+        let value = encode(&bson::rawdoc! { "n": -100 }, &["n"]);
+        assert!(missing < null);
+        assert!(null < value);
+        assert_eq!(encode_value(&Bson::Null).unwrap(), null);
+    }
+
+    #[test]
+    fn array_fields_expand_to_one_value_per_element() {
+        let doc = bson::rawdoc! { "s": "a", "tags": ["x", "y"], "n": 1 };
+        let values = encode_document(&doc, &["s", "tags", "n"]);
+        let x = encode(
+            &bson::rawdoc! { "s": "a", "tags": "x", "n": 1 },
+            &["s", "tags", "n"],
+        );
+        let y = encode(
+            &bson::rawdoc! { "s": "a", "tags": "y", "n": 1 },
+            &["s", "tags", "n"],
+        );
+        assert_eq!(values, vec![x, y]);
+
+        let empty = encode_document(&bson::rawdoc! { "tags": [] }, &["tags"]);
+        assert_eq!(empty.len(), 1);
+        assert_ne!(empty[0], encode_value(&Bson::Null).unwrap());
+    }
+
+    #[test]
+    fn parallel_arrays_are_rejected() {
+        let fields = ["tags", "scores"];
+        let one = bson::rawdoc! { "tags": ["x", "y"], "scores": 1 };
+        assert!(check_arrays(&one, "tags+scores", &fields).is_ok());
+        let both = bson::rawdoc! { "tags": ["x", "y"], "scores": [1, 2] };
+        assert!(matches!(
+            check_arrays(&both, "tags+scores", &fields),
+            Err(EngineError::InvalidDocument(_))
+        ));
+    }
+
+    #[test]
+    fn embedded_documents_are_components() {
+        let doc = bson::rawdoc! { "s": "a", "addr": { "city": "Austin", "zip": 78701 } };
+        let bytes = encode(&doc, &["s", "addr"]);
+        let RawBson::Array(arr) = decode(&bytes).unwrap() else {
+            panic!("expected array");
+        };
+        let values: Vec<RawBson> = arr.into_iter().map(|v| RawBson::from(v.unwrap())).collect();
+        assert_eq!(
+            values[1],
+            RawBson::Document(bson::rawdoc! { "city": "Austin", "zip": 78701i64 })
+        );
+    }
+}
@@ -1,10 +1,14 @@
 use std::borrow::Cow;
 
-use bson::raw::RawDocument;
+use bson::raw::{RawBsonRef, RawDocument};
 use bson::spec::ElementType;
 
 use super::bson_value::{self, BsonValue};
+use super::component;
+use super::compound::{self, COMPOUND_TYPE};
 use super::key::Key;
+use crate::error::EngineError;
+use crate::traits::IndexConfig;
 
 // ── IndexRecord ──────────────────────────────────────────────
 //
@@ -13,6 +17,11 @@ use super::key::Key;
 // Key layout:  `i\0{collection}\0{field}\0{value_bytes}{doc_id_lp}`
 // Metadata:    `[type_byte]` or `[type_byte][8-byte LE TTL]`
 //
+// The value bytes of a single-field entry are one component (see
+// `component`) and the type byte is the BSON type of the value, or null
+// for a missing field. For a compound index, `{field}` is the index name (`a+b`), the value
+// bytes are its encoded components, and the type byte is `COMPOUND_TYPE`.
+//
 // Follows the Record pattern: raw owned bytes + pre-parsed offsets
 // for O(1) typed access.
 
@@ -65,7 +74,8 @@ impl IndexRecord {
         })
     }
 
-    /// Encode a new index record from components (write side).
+    /// Encode a new index record from components (write side). The value
+    /// is written as a single component.
     pub fn encode(
         collection: &str,
         field: &str,
@@ -73,15 +83,51 @@ impl IndexRecord {
         value: &BsonValue<'_>,
         ttl_millis: Option<i64>,
     ) -> Self {
-        let val_bytes: &[u8] = &value.bytes;
+        Self::encode_raw(
+            collection,
+            field,
+            doc_id,
+            &component::encode_bson_value(value.clone()),
+            value.tag as u8,
+            ttl_millis,
+        )
+    }
+
+    /// Encode a compound index record. `index` is the index name and
+    /// `value_bytes` the concatenated components of its fields.
+    pub fn encode_compound(
+        collection: &str,
+        index: &str,
+        doc_id: &BsonValue<'_>,
+        value_bytes: &[u8],
+        ttl_millis: Option<i64>,
+    ) -> Self {
+        Self::encode_raw(
+            collection,
+            index,
+            doc_id,
+            value_bytes,
+            COMPOUND_TYPE,
+            ttl_millis,
+        )
+    }
+
+    fn encode_raw(
+        collection: &str,
+        field: &str,
+        doc_id: &BsonValue<'_>,
+        val_bytes: &[u8],
+        type_byte: u8,
+        ttl_millis: Option<i64>,
+    ) -> Self {
         let metadata = match ttl_millis {
             Some(millis) => {
                 let mut m = Vec::with_capacity(9);
-                m.push(value.tag as u8);
+                m.push(type_byte);
                 m.extend_from_slice(&millis.to_le_bytes());
                 m
             }
-            None => vec![value.tag as u8],
+            None => vec![type_byte],
         };
 
         let index_key = Key::Index(
@@ -106,7 +152,11 @@ impl IndexRecord {
 
     /// Compute index records for a document.
     ///
-    /// Returns one `IndexRecord` per indexed field value. Each record
+    /// `indexes` holds index names. A single-field index produces one
+    /// record per indexed value — each element of an array, or a missing
+    /// field's placeholder; a compound
+    /// index (`a+b`) produces one record holding a component for each of
+    /// its fields, per combination of array elements. Each record
     /// contains the encoded index key and metadata (including TTL if present).
     pub fn from_document(
         collection: &str,
@@ -116,14 +166,60 @@ impl IndexRecord {
         ttl_millis: Option<i64>,
     ) -> Vec<IndexRecord> {
         let mut entries = Vec::new();
-        for field in indexes {
-            for val in bson_value::extract_all(doc, field) {
-                entries.push(IndexRecord::encode(collection, field, doc_id, &val, ttl_millis));
+        for name in indexes {
+            if name.contains(IndexConfig::SEPARATOR) {
+                let fields: Vec<&str> = name.split(IndexConfig::SEPARATOR).collect();
+                for value_bytes in compound::encode_document(doc, &fields) {
+                    entries.push(IndexRecord::encode_compound(
+                        collection,
+                        name,
+                        doc_id,
+                        &value_bytes,
+                        ttl_millis,
+                    ));
+                }
+                continue;
+            }
+            if name.contains("[]") {
+                for val in bson_value::extract_all(doc, name) {
+                    entries.push(IndexRecord::encode(collection, name, doc_id, &val, ttl_millis));
+                }
+                continue;
+            }
+            for (tag, value_bytes) in component::field_components(doc, name) {
+                entries.push(IndexRecord::encode_raw(
+                    collection,
+                    name,
+                    doc_id,
+                    &value_bytes,
+                    tag as u8,
+                    ttl_millis,
+                ));
             }
         }
         entries
     }
 
+    /// Whether a field of `index` holds an array in `doc`, giving the
+    /// document an entry per element. Paths with `[]` segments aren't
+    /// checked: they always index elements.
+    pub fn holds_array(doc: &RawDocument, index: &str) -> bool {
+        index
+            .split(IndexConfig::SEPARATOR)
+            .filter(|field| !field.contains("[]"))
+            .any(|field| matches!(bson_value::lookup(doc, field), Some(RawBsonRef::Array(_))))
+    }
+
+    /// Reject `doc` if it holds arrays in more than one field of the
+    /// compound index `index`. Single-field indexes accept any document.
+    pub fn check_arrays(doc: &RawDocument, index: &str) -> Result<(), EngineError> {
+        if !index.contains(IndexConfig::SEPARATOR) {
+            return Ok(());
+        }
+        let fields: Vec<&str> = index.split(IndexConfig::SEPARATOR).collect();
+        compound::check_arrays(doc, index, &fields)
+    }
+
     // ── Typed accessors ─────────────────────────────────────────
 
     /// The collection name.
@@ -144,7 +240,7 @@ impl IndexRecord {
         )
     }
 
-    /// Raw sortable-encoded value bytes (no type tag).
+    /// The encoded value components.
     pub fn value_bytes(&self) -> &[u8] {
         &self.index_key[self.value_start..self.doc_id_start]
     }
@@ -164,9 +260,9 @@ impl IndexRecord {
     /// Convert the indexed value to `RawBson`.
     ///
     /// Reconstructs the value from the type tag (metadata) and raw bytes (key).
+    /// A compound entry yields an array with one value per field.
     pub fn value_bson(&self) -> Option<bson::RawBson> {
-        let tag = ElementType::from(self.type_byte())?;
-        BsonValue::from_parts(tag, self.value_bytes()).to_raw_bson()
+        decode_value(self.type_byte(), self.value_bytes())
     }
 
     // ── TTL ─────────────────────────────────────────────────────
@@ -198,6 +294,28 @@ impl IndexRecord {
 
 }
 
+/// Decode the value of an index entry from its metadata type byte and
+/// value bytes. Numbers share one encoding in the key, so the type byte
+/// restores their width.
+pub(crate) fn decode_value(type_byte: u8, value_bytes: &[u8]) -> Option<bson::RawBson> {
+    if type_byte == COMPOUND_TYPE {
+        return compound::decode(value_bytes);
+    }
+    let (value, rest) = component::decode(value_bytes)?;
+    if !rest.is_empty() {
+        return None;
+    }
+    match value {
+        bson::RawBson::Int64(n) if type_byte == ElementType::Int32 as u8 => {
+            Some(bson::RawBson::Int32(i32::try_from(n).ok()?))
+        }
+        bson::RawBson::Int64(n) if type_byte == ElementType::Double as u8 => {
+            Some(bson::RawBson::Double(n as f64))
+        }
+        value => Some(value),
+    }
+}
+
 /// O(1) TTL expiry check on raw index metadata bytes.
 ///
 /// Metadata layout: `[type_byte]` (1 byte, no TTL) or
@@ -232,6 +350,9 @@ mod tests {
         .encode_index(value_bytes)
     }
 
+    /// `b"Alice"` encoded as a string component.
+    const ALICE: &[u8] = b"\x20Alice\x00\x01";
+
     fn str_id(s: &str) -> BsonValue<'static> {
         BsonValue {
             tag: ElementType::String,
@@ -242,7 +363,7 @@ mod tests {
     #[test]
     fn from_pair_parses_index_entry() {
         let doc_id = str_id("doc1");
-        let value_bytes = b"Alice";
+        let value_bytes = ALICE;
         let key_bytes = make_index_key("users", "name", value_bytes, &doc_id);
         let metadata = vec![ElementType::String as u8];
 
@@ -257,7 +378,7 @@ mod tests {
     #[test]
     fn from_pair_returns_none_for_empty_metadata() {
         let doc_id = str_id("doc1");
-        let key_bytes = make_index_key("users", "name", b"Alice", &doc_id);
+        let key_bytes = make_index_key("users", "name", ALICE, &doc_id);
         assert!(IndexRecord::from_pair(key_bytes, vec![]).is_none());
     }
 
@@ -278,7 +399,7 @@ mod tests {
         assert_eq!(record.collection().unwrap(), "users");
         assert_eq!(record.field().unwrap(), "name");
         assert_eq!(record.doc_id().unwrap(), doc_id);
-        assert_eq!(record.value_bytes(), b"Alice");
+        assert_eq!(record.value_bytes(), ALICE);
         assert_eq!(record.type_byte(), ElementType::String as u8);
     }
 
@@ -319,7 +440,7 @@ mod tests {
         };
         let encoded = IndexRecord::encode("users", "name", &doc_id, &value, None);
 
-        let key_bytes = make_index_key("users", "name", b"Alice", &doc_id);
+        let key_bytes = make_index_key("users", "name", ALICE, &doc_id);
         let metadata = vec![ElementType::String as u8];
         let parsed = IndexRecord::from_pair(key_bytes, metadata).unwrap();
 
@@ -330,7 +451,7 @@ mod tests {
     #[test]
     fn value_bson_reconstructs_string() {
         let doc_id = str_id("doc1");
-        let key_bytes = make_index_key("users", "name", b"Alice", &doc_id);
+        let key_bytes = make_index_key("users", "name", ALICE, &doc_id);
         let metadata = vec![ElementType::String as u8];
 
         let record = IndexRecord::from_pair(key_bytes, metadata).unwrap();
@@ -341,7 +462,7 @@ mod tests {
     #[test]
     fn doc_id_bson_converts() {
         let doc_id = str_id("doc1");
-        let key_bytes = make_index_key("users", "name", b"Alice", &doc_id);
+        let key_bytes = make_index_key("users", "name", ALICE, &doc_id);
         let metadata = vec![0x02];
 
         let record = IndexRecord::from_pair(key_bytes, metadata).unwrap();
@@ -371,7 +492,7 @@ mod tests {
         metadata.extend_from_slice(&1_000i64.to_le_bytes());
 
         let doc_id = str_id("doc1");
-        let key_bytes = make_index_key("users", "name", b"Alice", &doc_id);
+        let key_bytes = make_index_key("users", "name", ALICE, &doc_id);
 
         let record = IndexRecord::from_pair(key_bytes, metadata).unwrap();
         assert!(record.is_expired(2_000));
@@ -381,7 +502,7 @@ mod tests {
     #[test]
     fn is_expired_without_ttl() {
         let doc_id = str_id("doc1");
-        let key_bytes = make_index_key("users", "name", b"Alice", &doc_id);
+        let key_bytes = make_index_key("users", "name", ALICE, &doc_id);
         let metadata = vec![0x02];
 
         let record = IndexRecord::from_pair(key_bytes, metadata).unwrap();
@@ -426,13 +547,30 @@ mod tests {
     }
 
     #[test]
-    fn from_document_missing_field_produces_nothing() {
+    fn from_document_missing_field_produces_placeholder() {
         let doc = bson::rawdoc! { "_id": "doc1", "age": 30 };
         let doc_id = str_id("doc1");
         let indexes = vec!["name".into()];
 
         let entries = IndexRecord::from_document("test", &indexes, &doc, &doc_id, None);
-        assert!(entries.is_empty());
+        assert_eq!(entries.len(), 1);
+        assert!(component::is_placeholder(entries[0].value_bytes()));
+        assert_eq!(entries[0].type_byte(), ElementType::Null as u8);
+        assert_eq!(entries[0].value_bson().unwrap(), bson::RawBson::Null);
+    }
+
+    #[test]
+    fn value_bson_keeps_numeric_type() {
+        let doc = bson::rawdoc! { "_id": "doc1", "a": 7, "b": 7i64, "c": 7.0 };
+        let doc_id = str_id("doc1");
+        let indexes = vec!["a".into(), "b".into(), "c".into()];
+
+        let entries = IndexRecord::from_document("test", &indexes, &doc, &doc_id, None);
+        assert_eq!(entries[0].value_bytes(), entries[1].value_bytes());
+        assert_eq!(entries[0].value_bytes(), entries[2].value_bytes());
+        assert_eq!(entries[0].value_bson().unwrap(), bson::RawBson::Int32(7));
+        assert_eq!(entries[1].value_bson().unwrap(), bson::RawBson::Int64(7));
+        assert_eq!(entries[2].value_bson().unwrap(), bson::RawBson::Double(7.0));
     }
 
     #[test]
@@ -476,4 +614,29 @@ mod tests {
         assert_eq!(a.len(), b.len());
         assert_ne!(a[0].key_bytes(), b[0].key_bytes());
     }
+
+    #[test]
+    fn from_document_compound_produces_one_record() {
+        let doc = bson::rawdoc! { "_id": "doc1", "status": "active", "age": 30 };
+        let doc_id = str_id("doc1");
+        let indexes = vec!["status+age".into(), "status".into()];
+
+        let entries = IndexRecord::from_document("test", &indexes, &doc, &doc_id, None);
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].field().unwrap(), "status+age");
+        assert_eq!(entries[0].type_byte(), COMPOUND_TYPE);
+        assert_eq!(entries[0].doc_id().unwrap(), doc_id);
+        let bson::RawBson::Array(values) = entries[0].value_bson().unwrap() else {
+            panic!("expected an array of components");
+        };
+        let values: Vec<bson::RawBson> = values.into_iter().map(|v| v.unwrap().into()).collect();
+        assert_eq!(
+            values,
+            vec![
+                bson::RawBson::String("active".into()),
+                bson::RawBson::Int64(30)
+            ]
+        );
+    }
 }
@@ -10,6 +10,7 @@ const INDEX_TAG: u8 = b'i';
 const TRIGGER_TAG: u8 = b't';
 const VALIDATOR_TAG: u8 = b'v';
 const DERIVED_TAG: u8 = b'd';
+const UNIQUE_TAG: u8 = b'u';
 const SEP: u8 = 0x00;
 
 fn function_tag(kind: FunctionKind) -> u8 {
@@ -72,9 +73,15 @@ pub(crate) fn split_trailing_doc_id(bytes: &[u8]) -> Option<(&[u8], BsonValue<'_
 /// - `FunctionConfig(kind, cf, collection, name)` — function metadata in `_sys_`
 /// - `Index(collection, field, doc_id)` — value-first index entry (`i` tag)
 /// - `Record(collection, doc_id)` — document record addressing
+/// - `Unique(collection, index, value)` — guard of a unique index value
+///   (`u` tag), holding the length-prefixed doc_id of its owner
 ///
 /// `doc_id` is encoded as `[bson_type: 1][len: 2 BE][id_bytes]` in keys,
 /// and stored as the full encoded block (type + length + bytes) in the enum.
+///
+/// For a compound index the `field` slot holds the index name
+/// (`status+created_at`) and the value bytes hold one self-delimiting
+/// component per field; see `encoding::compound`.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Key<'a> {
     Collection(Cow<'a, str>, Cow<'a, str>),
@@ -82,6 +89,7 @@ pub enum Key<'a> {
     FunctionConfig(FunctionKind, Cow<'a, str>, Cow<'a, str>, Cow<'a, str>),
     Index(Cow<'a, str>, Cow<'a, str>, BsonValue<'a>),
     Record(Cow<'a, str>, BsonValue<'a>),
+    Unique(Cow<'a, str>, Cow<'a, str>, Cow<'a, [u8]>),
 }
 
 impl<'a> Key<'a> {
@@ -92,6 +100,7 @@ impl<'a> Key<'a> {
     /// - `FunctionConfig`: `{tag}\x00{cf}\x00{collection}\x00{name}`
     /// - `Record`: `r\x00{collection}\x00[doc_id_encoded]`
     /// - `Index` (no value): `i\x00{collection}\x00{field}\x00\x00[doc_id_encoded]`
+    /// - `Unique`: `u\x00{collection}\x00{index}\x00{value_bytes}`
     ///
     /// For `Index` keys with value bytes, use [`encode_index`](Key::encode_index).
     pub fn encode(&self) -> Vec<u8> {
@@ -151,6 +160,19 @@ impl<'a> Key<'a> {
                 doc_id.write_length_prefixed(&mut buf);
                 buf
             }
+            Key::Unique(collection, index, value_bytes) => {
+                let mut buf = Vec::with_capacity(
+                    2 + collection.len() + 1 + index.len() + 1 + value_bytes.len(),
+                );
+                buf.push(UNIQUE_TAG);
+                buf.push(SEP);
+                buf.extend_from_slice(collection.as_bytes());
+                buf.push(SEP);
+                buf.extend_from_slice(index.as_bytes());
+                buf.push(SEP);
+                buf.extend_from_slice(value_bytes);
+                buf
+            }
         }
     }
 
@@ -233,6 +255,19 @@ impl<'a> Key<'a> {
                     Cow::Borrowed(field),
                 ))
             }
+            UNIQUE_TAG => {
+                // u\x00{collection}\x00{index}\x00{value_bytes}
+                let first_sep = rest.iter().position(|&b| b == SEP)?;
+                let collection = std::str::from_utf8(&rest[..first_sep]).ok()?;
+                let after_collection = &rest[first_sep + 1..];
+                let second_sep = after_collection.iter().position(|&b| b == SEP)?;
+                let index = std::str::from_utf8(&after_collection[..second_sep]).ok()?;
+                Some(Key::Unique(
+                    Cow::Borrowed(collection),
+                    Cow::Borrowed(index),
+                    Cow::Borrowed(&after_collection[second_sep + 1..]),
+                ))
+            }
             INDEX_TAG => {
                 let (collection, field, _value_bytes, bv) = parse_index_rest(rest)?;
                 Some(Key::Index(
@@ -294,15 +329,18 @@ impl<'a> Key<'a> {
 /// - `Record(collection)` — all document records in a collection (`r\x00{collection}\x00`)
 /// - `IndexField(collection, field)` — index entries for a field (`i\x00{collection}\x00{field}\x00`)
 /// - `IndexValue(collection, field, value)` — index entries for a specific value
+/// - `Unique(collection, index)` — guards of a unique index (`u\x00{collection}\x00{index}\x00`)
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum KeyPrefix<'a> {
     Collection,
     CollectionByCf(Cow<'a, str>),
     IndexConfig(Cow<'a, str>, Cow<'a, str>),
     FunctionConfig(FunctionKind, Cow<'a, str>, Cow<'a, str>),
+    AllRecords,
     Record(Cow<'a, str>),
     IndexField(Cow<'a, str>, Cow<'a, str>),
     IndexValue(Cow<'a, str>, Cow<'a, str>, &'a [u8]),
+    Unique(Cow<'a, str>, Cow<'a, str>),
 }
 
 impl<'a> KeyPrefix<'a> {
@@ -337,6 +375,7 @@ impl<'a> KeyPrefix<'a> {
                 buf.push(SEP);
                 buf
             }
+            KeyPrefix::AllRecords => vec![RECORD_TAG, SEP],
             KeyPrefix::Record(collection) => {
                 let mut buf = Vec::with_capacity(2 + collection.len() + 1);
                 buf.push(RECORD_TAG);
@@ -367,6 +406,16 @@ impl<'a> KeyPrefix<'a> {
                 buf.extend_from_slice(value);
                 buf
             }
+            KeyPrefix::Unique(collection, index) => {
+                let mut buf = Vec::with_capacity(2 + collection.len() + 1 + index.len() + 1);
+                buf.push(UNIQUE_TAG);
+                buf.push(SEP);
+                buf.extend_from_slice(collection.as_bytes());
+                buf.push(SEP);
+                buf.extend_from_slice(index.as_bytes());
+                buf.push(SEP);
+                buf
+            }
         }
     }
 }
@@ -484,6 +533,20 @@ mod tests {
         assert_eq!(parsed_value, value_bytes);
     }
 
+    #[test]
+    fn unique_key_roundtrip() {
+        let key = Key::Unique(
+            Cow::Borrowed("users"),
+            Cow::Borrowed("email"),
+            Cow::Borrowed(&[0x02, 0x00, b'a', 0x00]),
+        );
+        let bytes = key.encode();
+        assert!(bytes.starts_with(
+            &KeyPrefix::Unique(Cow::Borrowed("users"), Cow::Borrowed("email")).encode()
+        ));
+        assert_eq!(Key::decode(&bytes).unwrap(), key);
+    }
+
     #[test]
     fn function_config_key_roundtrip() {
         for kind in [
@@ -1,11 +1,13 @@
 pub mod bson_value;
+pub mod component;
+pub mod compound;
 pub mod index_record;
 pub mod key;
 pub mod record;
 
 pub use index_record::IndexRecord;
 pub use key::{Key, KeyPrefix};
-pub use record::Record;
+pub use record::{Codec, Compression, Record};
 
 // ── Raw BSON skip primitive ─────────────────────────────────────
 
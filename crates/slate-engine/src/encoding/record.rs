@@ -1,3 +1,6 @@
+use std::fmt;
+use std::sync::Arc;
+
 use bson::raw::{RawDocument, RawDocumentBuf};
 
 use super::skip_bson_value;
@@ -6,12 +9,189 @@ use crate::error::{EncodingError, EngineError};
 // ── Wire format ───────────────────────────────────────────────
 //
 // Layout:
-//   [0x00][BSON...]                       — no TTL
-//   [0x01][8-byte LE i64 millis][BSON...] — has TTL
+//   [0x00][BSON...]                                   — no TTL
+//   [0x01][8-byte LE i64 millis][BSON...]             — has TTL
+//   [0x02][4-byte LE raw len][LZ4 block...]           — LZ4, no TTL
+//   [0x03][8-byte LE i64 millis][4-byte LE raw len][LZ4 block...]
+//   [0x04][4-byte LE raw len][zstd frame...]          — zstd, no TTL
+//   [0x05][8-byte LE i64 millis][4-byte LE raw len][zstd frame...]
+//
+// The low bit of the tag marks a TTL header, which always sits uncompressed
+// at bytes 1..9 so expiry checks never have to decompress.
 
 const TAG_NO_TTL: u8 = 0x00;
 const TAG_TTL: u8 = 0x01;
+const TAG_LZ4: u8 = 0x02;
+const TAG_ZSTD: u8 = 0x04;
+const TTL_FLAG: u8 = TAG_TTL;
+const MAX_TAG: u8 = 0x05;
 const TTL_SIZE: usize = 8;
+const RAW_LEN_SIZE: usize = 4;
+
+// ── Compression ───────────────────────────────────────────────
+
+/// Compression applied to record payloads, configured per collection.
+///
+/// An optional dictionary (e.g. trained with `zstd --train` on sample
+/// documents) lets small, repetitive documents compress well. Records only
+/// take a compressed tag when that actually saves space, so mixed records
+/// within a collection are normal.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum Compression {
+    #[default]
+    None,
+    Lz4 { dictionary: Option<Arc<[u8]>> },
+    /// Requires the `zstd` feature.
+    Zstd {
+        level: i32,
+        dictionary: Option<Arc<[u8]>>,
+    },
+}
+
+impl Compression {
+    /// Check that this build can encode and decode the configured codec.
+    pub(crate) fn validate(&self) -> Result<(), EngineError> {
+        match self {
+            #[cfg(not(feature = "zstd"))]
+            Compression::Zstd { .. } => Err(EngineError::InvalidDocument(
+                "zstd compression requires the `zstd` feature".into(),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    fn dictionary(&self) -> &[u8] {
+        match self {
+            Compression::Lz4 { dictionary } | Compression::Zstd { dictionary, .. } => {
+                dictionary.as_deref().unwrap_or_default()
+            }
+            Compression::None => &[],
+        }
+    }
+}
+
+/// A [`Compression`] ready to encode and decode records.
+///
+/// A zstd dictionary is digested once here rather than for every record,
+/// so build one per collection and share it; the engine caches them.
+pub struct Codec {
+    compression: Compression,
+    #[cfg(feature = "zstd")]
+    zstd: Option<ZstdDictionaries>,
+}
+
+#[cfg(feature = "zstd")]
+struct ZstdDictionaries {
+    encoder: zstd::dict::EncoderDictionary<'static>,
+    decoder: zstd::dict::DecoderDictionary<'static>,
+}
+
+impl Codec {
+    /// Reads and writes records uncompressed; still decodes compressed
+    /// records written without a dictionary.
+    pub const NONE: Codec = Codec {
+        compression: Compression::None,
+        #[cfg(feature = "zstd")]
+        zstd: None,
+    };
+
+    pub fn new(compression: Compression) -> Self {
+        #[cfg(feature = "zstd")]
+        let zstd = match &compression {
+            Compression::Zstd {
+                level,
+                dictionary: Some(dictionary),
+            } => Some(ZstdDictionaries {
+                encoder: zstd::dict::EncoderDictionary::copy(dictionary, *level),
+                decoder: zstd::dict::DecoderDictionary::copy(dictionary),
+            }),
+            _ => None,
+        };
+        Self {
+            compression,
+            #[cfg(feature = "zstd")]
+            zstd,
+        }
+    }
+
+    pub fn compression(&self) -> &Compression {
+        &self.compression
+    }
+
+    /// Compress `raw`, returning the codec tag and payload, or `None` when
+    /// compression is off or would not shrink the data.
+    fn compress(&self, raw: &[u8]) -> Option<(u8, Vec<u8>)> {
+        let (tag, payload) = match &self.compression {
+            Compression::None => return None,
+            Compression::Lz4 { .. } => (
+                TAG_LZ4,
+                lz4_flex::block::compress_with_dict(raw, self.compression.dictionary()),
+            ),
+            #[cfg(feature = "zstd")]
+            Compression::Zstd { level, .. } => {
+                let payload = match &self.zstd {
+                    Some(dictionaries) => {
+                        zstd::bulk::Compressor::with_prepared_dictionary(&dictionaries.encoder)
+                            .and_then(|mut c| c.compress(raw))
+                    }
+                    None => zstd::bulk::compress(raw, *level),
+                };
+                (TAG_ZSTD, payload.ok()?)
+            }
+            #[cfg(not(feature = "zstd"))]
+            Compression::Zstd { .. } => return None,
+        };
+        (RAW_LEN_SIZE + payload.len() < raw.len()).then_some((tag, payload))
+    }
+
+    /// Decompress a payload written under `codec` (a tag with the TTL bit clear).
+    fn decompress(&self, codec: u8, payload: &[u8]) -> Result<Vec<u8>, EngineError> {
+        let malformed = |msg: String| EngineError::from(EncodingError::MalformedRecord(msg));
+        if payload.len() < RAW_LEN_SIZE {
+            return Err(malformed("truncated compressed header".into()));
+        }
+        let raw_len = u32::from_le_bytes(payload[..RAW_LEN_SIZE].try_into().unwrap()) as usize;
+        let data = &payload[RAW_LEN_SIZE..];
+        let raw = match codec {
+            TAG_LZ4 => {
+                lz4_flex::block::decompress_with_dict(data, raw_len, self.compression.dictionary())
+                    .map_err(|e| malformed(format!("lz4: {e}")))?
+            }
+            #[cfg(feature = "zstd")]
+            TAG_ZSTD => match &self.zstd {
+                Some(dictionaries) => {
+                    zstd::bulk::Decompressor::with_prepared_dictionary(&dictionaries.decoder)
+                        .and_then(|mut d| d.decompress(data, raw_len))
+                }
+                None => zstd::bulk::decompress(data, raw_len),
+            }
+            .map_err(|e| malformed(format!("zstd: {e}")))?,
+            #[cfg(not(feature = "zstd"))]
+            TAG_ZSTD => {
+                return Err(malformed(
+                    "zstd-compressed record (enable the `zstd` feature)".into(),
+                ));
+            }
+            tag => return Err(malformed(format!("unknown tag: 0x{tag:02X}"))),
+        };
+        if raw.len() != raw_len {
+            return Err(malformed("decompressed length mismatch".into()));
+        }
+        Ok(raw)
+    }
+}
+
+impl Default for Codec {
+    fn default() -> Self {
+        Self::NONE
+    }
+}
+
+impl fmt::Debug for Codec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.compression.fmt(f)
+    }
+}
 
 // ── RecordEncoder ─────────────────────────────────────────────
 
@@ -24,6 +204,7 @@ enum TtlSource<'a> {
 /// Builder for encoding a BSON document into a [`Record`].
 pub struct RecordEncoder<'a> {
     ttl: TtlSource<'a>,
+    codec: Option<&'a Codec>,
 }
 
 impl<'a> RecordEncoder<'a> {
@@ -31,6 +212,7 @@ impl<'a> RecordEncoder<'a> {
     pub fn with_ttl(self, millis: i64) -> Self {
         RecordEncoder {
             ttl: TtlSource::Explicit(millis),
+            ..self
         }
     }
 
@@ -38,6 +220,15 @@ impl<'a> RecordEncoder<'a> {
     pub fn with_ttl_at_path(self, path: &'a str) -> Self {
         RecordEncoder {
             ttl: TtlSource::FromPath(path),
+            ..self
+        }
+    }
+
+    /// Compress the BSON payload when it saves space.
+    pub fn with_codec(self, codec: &'a Codec) -> Self {
+        RecordEncoder {
+            codec: Some(codec),
+            ..self
         }
     }
 
@@ -50,40 +241,55 @@ impl<'a> RecordEncoder<'a> {
         };
 
         let bson_bytes = doc.as_bytes();
+        let compressed = self.codec.and_then(|c| c.compress(bson_bytes));
+        let (codec, payload_len) = match &compressed {
+            Some((codec, payload)) => (*codec, RAW_LEN_SIZE + payload.len()),
+            None => (TAG_NO_TTL, bson_bytes.len()),
+        };
+
+        let header_len = 1 + if ttl_millis.is_some() { TTL_SIZE } else { 0 };
+        let mut bytes = Vec::with_capacity(header_len + payload_len);
         match ttl_millis {
             Some(millis) => {
-                let bson_start = 1 + TTL_SIZE;
-                let mut bytes = Vec::with_capacity(bson_start + bson_bytes.len());
-                bytes.push(TAG_TTL);
+                bytes.push(codec | TTL_FLAG);
                 bytes.extend_from_slice(&millis.to_le_bytes());
-                bytes.extend_from_slice(bson_bytes);
-                Record { bytes, bson_start }
+            }
+            None => bytes.push(codec),
+        }
+        let decoded = match compressed {
+            Some((_, payload)) => {
+                bytes.extend_from_slice(&(bson_bytes.len() as u32).to_le_bytes());
+                bytes.extend_from_slice(&payload);
+                Some(bson_bytes.to_vec())
             }
             None => {
-                let mut bytes = Vec::with_capacity(1 + bson_bytes.len());
-                bytes.push(TAG_NO_TTL);
                 bytes.extend_from_slice(bson_bytes);
-                Record {
-                    bytes,
-                    bson_start: 1,
-                }
+                None
             }
+        };
+        Record {
+            bytes,
+            bson_start: header_len,
+            decoded,
         }
     }
 }
 
 // ── Record ────────────────────────────────────────────────────
 
-/// A stored record: header + raw BSON document bytes.
+/// A stored record: header + raw (optionally compressed) BSON document bytes.
 ///
 /// Holds the raw storage bytes and lazily validates the BSON
-/// portion on access via [`doc()`](Record::doc).
+/// portion on access via [`doc()`](Record::doc). Compressed records are
+/// decompressed once, when constructed.
 ///
 /// Construct via [`Record::encoder()`] for writes,
 /// or [`Record::from_bytes()`] for reads.
 pub struct Record {
     bytes: Vec<u8>,
     bson_start: usize,
+    /// Decompressed BSON, for records stored with a compressed tag.
+    decoded: Option<Vec<u8>>,
 }
 
 impl Record {
@@ -91,35 +297,51 @@ impl Record {
     pub fn encoder() -> RecordEncoder<'static> {
         RecordEncoder {
             ttl: TtlSource::None,
+            codec: None,
         }
     }
 
     /// Wrap raw storage bytes as a Record.
+    ///
+    /// Compressed records written without a dictionary decode here too; use
+    /// [`Record::from_bytes_with`] for collections that have one.
     pub fn from_bytes(bytes: Vec<u8>) -> Result<Self, EngineError> {
+        Self::from_bytes_with(bytes, &Codec::NONE)
+    }
+
+    /// Wrap raw storage bytes as a Record, decompressing with the
+    /// collection's codec if the record is compressed.
+    pub fn from_bytes_with(bytes: Vec<u8>, codec: &Codec) -> Result<Self, EngineError> {
         if bytes.is_empty() {
             return Err(EncodingError::MalformedRecord("empty record".into()).into());
         }
-        let bson_start = match bytes[0] {
-            TAG_NO_TTL => 1,
-            TAG_TTL => {
-                let header_len = 1 + TTL_SIZE;
-                if bytes.len() < header_len {
-                    return Err(
-                        EncodingError::MalformedRecord("truncated TTL header".into()).into(),
-                    );
-                }
-                header_len
-            }
-            tag => {
-                return Err(
-                    EncodingError::MalformedRecord(format!("unknown tag: 0x{tag:02X}")).into(),
-                );
+        let tag = bytes[0];
+        if tag > MAX_TAG {
+            return Err(
+                EncodingError::MalformedRecord(format!("unknown tag: 0x{tag:02X}")).into(),
+            );
+        }
+        let bson_start = if tag & TTL_FLAG != 0 {
+            let header_len = 1 + TTL_SIZE;
+            if bytes.len() < header_len {
+                return Err(EncodingError::MalformedRecord("truncated TTL header".into()).into());
             }
+            header_len
+        } else {
+            1
         };
-        Ok(Record { bytes, bson_start })
+        let decoded = match tag & !TTL_FLAG {
+            TAG_NO_TTL => None,
+            tag => Some(codec.decompress(tag, &bytes[bson_start..])?),
+        };
+        Ok(Record {
+            bytes,
+            bson_start,
+            decoded,
+        })
     }
 
-    /// Raw storage bytes (header + BSON).
+    /// Raw storage bytes (header + BSON, compressed or not).
     pub fn as_bytes(&self) -> &[u8] {
         &self.bytes
     }
@@ -129,26 +351,28 @@ impl Record {
         self.bytes
     }
 
+    /// Whether the payload is stored compressed.
+    pub fn is_compressed(&self) -> bool {
+        self.decoded.is_some()
+    }
+
     /// Borrow the BSON document portion, validating on access.
     pub fn doc(&self) -> Result<&RawDocument, EngineError> {
-        RawDocument::from_bytes(&self.bytes[self.bson_start..])
-            .map_err(|e| EncodingError::Bson(e).into())
+        let bson = match &self.decoded {
+            Some(decoded) => decoded.as_slice(),
+            None => &self.bytes[self.bson_start..],
+        };
+        RawDocument::from_bytes(bson).map_err(|e| EncodingError::Bson(e).into())
     }
 
     /// Read the TTL millis from the record header.
     pub fn ttl_millis(&self) -> Option<i64> {
-        if self.bytes[0] == TAG_TTL && self.bytes.len() >= 1 + TTL_SIZE {
-            Some(i64::from_le_bytes(
-                self.bytes[1..1 + TTL_SIZE].try_into().unwrap(),
-            ))
-        } else {
-            None
-        }
+        Record::ttl_millis_raw(&self.bytes)
     }
 
     /// Extract TTL millis from raw record bytes without full decode.
     pub fn ttl_millis_raw(data: &[u8]) -> Option<i64> {
-        if data.len() > TTL_SIZE && data[0] == TAG_TTL {
+        if data.len() > TTL_SIZE && data[0] <= MAX_TAG && data[0] & TTL_FLAG != 0 {
             Some(i64::from_le_bytes(
                 data[1..1 + TTL_SIZE].try_into().unwrap(),
             ))
@@ -168,9 +392,14 @@ impl TryFrom<Record> for RawDocumentBuf {
     type Error = EngineError;
 
     fn try_from(mut record: Record) -> Result<Self, Self::Error> {
-        record.bytes.drain(..record.bson_start);
-        RawDocumentBuf::from_bytes(record.bytes)
-            .map_err(|e| EncodingError::Bson(e).into())
+        let bytes = match record.decoded {
+            Some(decoded) => decoded,
+            None => {
+                record.bytes.drain(..record.bson_start);
+                record.bytes
+            }
+        };
+        RawDocumentBuf::from_bytes(bytes).map_err(|e| EncodingError::Bson(e).into())
     }
 }
 
@@ -352,6 +581,126 @@ mod tests {
         assert_eq!(record.as_bytes()[0], TAG_NO_TTL);
     }
 
+    fn repetitive_doc_with(extra: bson::Document) -> RawDocumentBuf {
+        let mut doc = bson::doc! { "_id": "a" };
+        for i in 0..50 {
+            doc.insert(
+                format!("field_{i}"),
+                "the quick brown fox jumps over the lazy dog",
+            );
+        }
+        doc.extend(extra);
+        RawDocumentBuf::try_from(&doc).unwrap()
+    }
+
+    fn repetitive_doc() -> RawDocumentBuf {
+        repetitive_doc_with(bson::Document::new())
+    }
+
+    fn lz4() -> Codec {
+        Codec::new(Compression::Lz4 { dictionary: None })
+    }
+
+    #[test]
+    fn lz4_roundtrip() {
+        let doc = repetitive_doc();
+        let codec = lz4();
+        let record = Record::encoder().with_codec(&codec).encode(&doc);
+        assert_eq!(record.as_bytes()[0], TAG_LZ4);
+        assert!(record.is_compressed());
+        assert!(record.as_bytes().len() < doc.as_bytes().len());
+        assert_eq!(record.doc().unwrap(), doc.as_ref());
+
+        let restored = Record::from_bytes_with(record.into_bytes(), &codec).unwrap();
+        assert_eq!(RawDocumentBuf::try_from(restored).unwrap(), doc);
+    }
+
+    #[test]
+    fn lz4_with_ttl_keeps_header_uncompressed() {
+        let dt = bson::DateTime::from_millis(5_000);
+        let doc = repetitive_doc_with(bson::doc! { "ttl": dt });
+        let codec = lz4();
+        let record = Record::encoder()
+            .with_ttl_at_path("ttl")
+            .with_codec(&codec)
+            .encode(&doc);
+        assert_eq!(record.as_bytes()[0], TAG_LZ4 | TAG_TTL);
+        assert_eq!(record.ttl_millis(), Some(5_000));
+        assert!(Record::is_expired(record.as_bytes(), 6_000));
+        assert!(!Record::is_expired(record.as_bytes(), 4_000));
+        let restored = Record::from_bytes_with(record.into_bytes(), &codec).unwrap();
+        assert_eq!(restored.doc().unwrap(), doc.as_ref());
+    }
+
+    #[test]
+    fn incompressible_doc_stays_uncompressed() {
+        let doc = bson::rawdoc! { "_id": "a", "n": 1 };
+        let codec = lz4();
+        let record = Record::encoder().with_codec(&codec).encode(&doc);
+        assert_eq!(record.as_bytes()[0], TAG_NO_TTL);
+        assert!(!record.is_compressed());
+        assert_eq!(record.doc().unwrap(), doc.as_ref());
+    }
+
+    #[test]
+    fn lz4_with_dictionary_roundtrip() {
+        let doc = repetitive_doc();
+        let dictionary: Arc<[u8]> = Arc::from(repetitive_doc().as_bytes());
+        let codec = Codec::new(Compression::Lz4 {
+            dictionary: Some(dictionary),
+        });
+        let with_dict = Record::encoder().with_codec(&codec).encode(&doc);
+        let without = Record::encoder().with_codec(&lz4()).encode(&doc);
+        assert!(with_dict.as_bytes().len() < without.as_bytes().len());
+
+        let restored = Record::from_bytes_with(with_dict.into_bytes(), &codec).unwrap();
+        assert_eq!(restored.doc().unwrap(), doc.as_ref());
+    }
+
+    #[test]
+    fn truncated_compressed_record_errors() {
+        let doc = repetitive_doc();
+        let record = Record::encoder().with_codec(&lz4()).encode(&doc);
+        let mut bytes = record.into_bytes();
+        bytes.truncate(bytes.len() / 2);
+        assert!(Record::from_bytes_with(bytes, &lz4()).is_err());
+    }
+
+    #[test]
+    fn unknown_tag_errors() {
+        assert!(Record::from_bytes(vec![0x7F, 0, 0, 0, 0]).is_err());
+        assert!(!Record::is_expired(&[0x7F; 16], i64::MAX));
+    }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn zstd_roundtrip() {
+        let doc = repetitive_doc();
+        let codec = Codec::new(Compression::Zstd {
+            level: 3,
+            dictionary: None,
+        });
+        let record = Record::encoder().with_codec(&codec).encode(&doc);
+        assert_eq!(record.as_bytes()[0], TAG_ZSTD);
+        let restored = Record::from_bytes_with(record.into_bytes(), &codec).unwrap();
+        assert_eq!(RawDocumentBuf::try_from(restored).unwrap(), doc);
+    }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn zstd_with_dictionary_roundtrip() {
+        let doc = repetitive_doc();
+        let dictionary: Arc<[u8]> = Arc::from(repetitive_doc().as_bytes());
+        let codec = Codec::new(Compression::Zstd {
+            level: 3,
+            dictionary: Some(dictionary),
+        });
+        let record = Record::encoder().with_codec(&codec).encode(&doc);
+        assert_eq!(record.as_bytes()[0], TAG_ZSTD);
+        let restored = Record::from_bytes_with(record.into_bytes(), &codec).unwrap();
+        assert_eq!(restored.doc().unwrap(), doc.as_ref());
+    }
+
     #[test]
     fn with_ttl_at_nested_path_non_document_intermediate() {
         let doc = bson::rawdoc! { "_id": "a", "meta": "not a doc" };
@@ -41,12 +41,14 @@ impl From<bson::error::Error> for EncodingError {
 #[derive(Debug)]
 pub enum EngineError {
     Store(StoreError),
+    Conflict(String),
     InvalidKey(String),
     Encoding(EncodingError),
     CollectionNotFound(String),
     DuplicateKey(String),
     InvalidDocument(String),
     IndexExists(String),
+    InvalidIndex(String),
     FunctionExists(String),
 }
 
@@ -54,12 +56,14 @@ impl fmt::Display for EngineError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Self::Store(e) => write!(f, "store error: {e}"),
+            Self::Conflict(msg) => write!(f, "write conflict: {msg}"),
             Self::InvalidKey(msg) => write!(f, "invalid key: {msg}"),
             Self::Encoding(e) => write!(f, "encoding error: {e}"),
             Self::CollectionNotFound(name) => write!(f, "collection not found: {name}"),
             Self::DuplicateKey(id) => write!(f, "duplicate key: {id}"),
             Self::InvalidDocument(msg) => write!(f, "invalid document: {msg}"),
             Self::IndexExists(desc) => write!(f, "index already exists: {desc}"),
+            Self::InvalidIndex(msg) => write!(f, "invalid index: {msg}"),
             Self::FunctionExists(desc) => write!(f, "function already exists: {desc}"),
         }
     }
@@ -69,7 +73,10 @@ impl std::error::Error for EngineError {}
 
 impl From<StoreError> for EngineError {
     fn from(e: StoreError) -> Self {
-        Self::Store(e)
+        match e {
+            StoreError::Conflict(msg) => Self::Conflict(msg),
+            other => Self::Store(other),
+        }
     }
 }
 
@@ -1,8 +1,13 @@
+use std::borrow::Cow;
 use std::collections::HashMap;
 
+use bson::RawDocument;
+
 use crate::encoding::bson_value::BsonValue;
-use crate::encoding::{IndexRecord, Record};
+use crate::encoding::component;
+use crate::encoding::{Codec, IndexRecord, Record};
 use crate::error::EngineError;
+use crate::traits::IndexFilter;
 
 /// The result of an index diff computation.
 ///
@@ -12,27 +17,39 @@ pub struct IndexChanges {
     pub puts: Vec<(Vec<u8>, Vec<u8>)>,
     /// Index keys to delete.
     pub deletes: Vec<Vec<u8>>,
+    /// Indexes the new document reaches through an array, giving it an
+    /// entry per element.
+    pub multikey: Vec<String>,
 }
 
 /// Pure-computation builder for computing index diffs.
 ///
 /// Accumulates property paths and optional old record data, then
 /// computes the diff between old and new index entries without
-/// any store access.
+/// any store access. A path may name a compound index (`a+b`), whose
+/// single entry changes whenever any of its fields does. A path with an
+/// [`IndexFilter`] only gets entries for documents the filter matches, so
+/// a document moving in or out of a partial index gains or loses them.
+/// An array value gets an entry per element, so adding or removing an
+/// element puts or deletes just that element's entry. A missing field gets
+/// an entry of its own, except on a sparse path.
 ///
 /// # Example
 ///
 /// ```ignore
 /// let changes = IndexDiff::new(&record, &doc_id)
 ///     .with_property_paths(&handle.indexes)
-///     .with_property_path("ttl")
+///     .with_sparse_path("ttl")
 ///     .diff("users")?;
 /// ```
 pub struct IndexDiff<'a> {
     record: Option<&'a Record>,
     doc_id: &'a BsonValue<'a>,
     old_record: Option<&'a [u8]>,
+    codec: Option<&'a Codec>,
     property_paths: Vec<String>,
+    sparse_paths: Vec<String>,
+    filters: &'a [IndexFilter],
 }
 
 impl<'a> IndexDiff<'a> {
@@ -41,7 +58,10 @@ impl<'a> IndexDiff<'a> {
             record: Some(record),
             doc_id,
             old_record: None,
+            codec: None,
             property_paths: Vec::new(),
+            sparse_paths: Vec::new(),
+            filters: &[],
         }
     }
 
@@ -51,7 +71,10 @@ impl<'a> IndexDiff<'a> {
             record: None,
             doc_id,
             old_record: None,
+            codec: None,
             property_paths: Vec::new(),
+            sparse_paths: Vec::new(),
+            filters: &[],
         }
     }
 
@@ -60,6 +83,13 @@ impl<'a> IndexDiff<'a> {
         self
     }
 
+    /// Codec needed to decode a compressed old record.
+    pub fn with_codec(mut self, codec: &'a Codec) -> Self {
+        self.codec = Some(codec);
+        self
+    }
+
+    #[cfg(test)]
     pub fn with_property_path(mut self, path: &str) -> Self {
         self.property_paths.push(path.to_string());
         self
@@ -70,30 +100,88 @@ impl<'a> IndexDiff<'a> {
         self
     }
 
+    /// A path indexed only where documents hold a value, like the TTL
+    /// path. Ignored if it is already a property path.
+    pub fn with_sparse_path(mut self, path: &str) -> Self {
+        if !self.property_paths.iter().any(|p| p == path) {
+            self.sparse_paths.push(path.to_string());
+        }
+        self
+    }
+
+    /// Filters of partial indexes among the property paths.
+    pub fn with_filters(mut self, filters: &'a [IndexFilter]) -> Self {
+        self.filters = filters;
+        self
+    }
+
+    /// The property paths `doc` has entries for.
+    fn paths_for(&self, doc: &RawDocument) -> Cow<'_, [String]> {
+        if self.filters.is_empty() {
+            return Cow::Borrowed(&self.property_paths);
+        }
+        let included = |path: &String| {
+            self.filters
+                .iter()
+                .find(|f| f.index() == path)
+                .is_none_or(|f| f.matches(doc))
+        };
+        Cow::Owned(
+            self.property_paths
+                .iter()
+                .filter(|p| included(p))
+                .cloned()
+                .collect(),
+        )
+    }
+
+    /// Index entries of `doc` across the property and sparse paths.
+    fn entries_for(
+        &self,
+        collection: &str,
+        doc: &RawDocument,
+        ttl: Option<i64>,
+    ) -> Vec<IndexRecord> {
+        let mut entries =
+            IndexRecord::from_document(collection, &self.paths_for(doc), doc, self.doc_id, ttl);
+        entries.extend(
+            IndexRecord::from_document(collection, &self.sparse_paths, doc, self.doc_id, ttl)
+                .into_iter()
+                .filter(|e| !component::is_placeholder(e.value_bytes())),
+        );
+        entries
+    }
+
     /// Compute the index diff between old and new documents.
     ///
     /// Pure computation — no store access. Returns the puts and deletes
     /// needed to bring the index entries in sync with the new document.
     pub fn diff(&self, collection: &str) -> Result<IndexChanges, EngineError> {
-        if self.property_paths.is_empty() {
+        if self.property_paths.is_empty() && self.sparse_paths.is_empty() {
             return Ok(IndexChanges {
                 puts: Vec::new(),
                 deletes: Vec::new(),
+                multikey: Vec::new(),
             });
         }
 
         // Build new index entries (empty when deleting).
+        let mut multikey = Vec::new();
         let new_entries = match self.record {
             Some(record) => {
                 let new_doc = record.doc()?;
                 let new_ttl = record.ttl_millis();
-                IndexRecord::from_document(
-                    collection,
-                    &self.property_paths,
-                    new_doc,
-                    self.doc_id,
-                    new_ttl,
-                )
+                let paths = self.paths_for(new_doc);
+                for path in paths.iter() {
+                    IndexRecord::check_arrays(new_doc, path)?;
+                }
+                multikey.extend(
+                    paths
+                        .iter()
+                        .filter(|p| IndexRecord::holds_array(new_doc, p))
+                        .cloned(),
+                );
+                self.entries_for(collection, new_doc, new_ttl)
             }
             None => Vec::new(),
         };
@@ -101,16 +189,11 @@ impl<'a> IndexDiff<'a> {
         // Build old index entries (if old record exists).
         let old_entries = match self.old_record {
             Some(data) => {
-                let old_rec = Record::from_bytes(data.to_vec())?;
+                let old_rec =
+                    Record::from_bytes_with(data.to_vec(), self.codec.unwrap_or(&Codec::NONE))?;
                 let old_ttl = old_rec.ttl_millis();
                 let old_doc = old_rec.doc()?;
-                IndexRecord::from_document(
-                    collection,
-                    &self.property_paths,
-                    old_doc,
-                    self.doc_id,
-                    old_ttl,
-                )
+                self.entries_for(collection, old_doc, old_ttl)
             }
             None => Vec::new(),
         };
@@ -143,7 +226,11 @@ impl<'a> IndexDiff<'a> {
             }
         }
 
-        Ok(IndexChanges { puts, deletes })
+        Ok(IndexChanges {
+            puts,
+            deletes,
+            multikey,
+        })
     }
 }
 
@@ -233,7 +320,7 @@ mod tests {
         let changes = IndexDiff::new(&new_record, &doc_id)
             .with_old_record(Some(&old_bytes))
             .with_property_path("name")
-            .with_property_path("ttl")
+            .with_sparse_path("ttl")
             .diff("test")
             .unwrap();
 
@@ -257,7 +344,7 @@ mod tests {
         let changes = IndexDiff::new(&new_record, &doc_id)
             .with_old_record(Some(&old_bytes))
             .with_property_path("name")
-            .with_property_path("ttl")
+            .with_sparse_path("ttl")
             .diff("test")
             .unwrap();
 
@@ -291,10 +378,58 @@ mod tests {
             .diff("test")
             .unwrap();
 
-        assert!(changes.puts.is_empty());
+        // Only the missing field's placeholder; see
+        // diff_missing_field_gets_placeholder.
+        assert_eq!(changes.puts.len(), 1);
         assert!(changes.deletes.is_empty());
     }
 
+    #[test]
+    fn diff_missing_field_gets_placeholder() {
+        let doc_id = str_id("d1");
+        let without = make_record(&bson::rawdoc! { "_id": "d1", "name": "Alice" });
+        let with = make_record(&bson::rawdoc! { "_id": "d1", "name": "Alice", "email": "a@x.io" });
+        let placeholder = |key: &[u8]| {
+            let (_, value_bytes) = crate::encoding::Key::decode_index(key).unwrap();
+            component::is_placeholder(value_bytes)
+        };
+
+        // A property path indexes a missing field, so an equality scan for
+        // null finds the document.
+        let changes = IndexDiff::new(&without, &doc_id)
+            .with_property_path("email")
+            .diff("test")
+            .unwrap();
+        assert_eq!(changes.puts.len(), 1);
+        assert!(placeholder(&changes.puts[0].0));
+
+        // A sparse path doesn't.
+        let changes = IndexDiff::new(&without, &doc_id)
+            .with_sparse_path("email")
+            .diff("test")
+            .unwrap();
+        assert!(changes.puts.is_empty());
+
+        // Setting the field replaces the placeholder, and unsetting it
+        // brings the placeholder back.
+        let without_bytes = without.as_bytes().to_vec();
+        let changes = IndexDiff::new(&with, &doc_id)
+            .with_old_record(Some(&without_bytes))
+            .with_property_path("email")
+            .diff("test")
+            .unwrap();
+        assert!(placeholder(&changes.deletes[0]));
+        assert!(!placeholder(&changes.puts[0].0));
+        let with_bytes = with.as_bytes().to_vec();
+        let changes = IndexDiff::new(&without, &doc_id)
+            .with_old_record(Some(&with_bytes))
+            .with_property_path("email")
+            .diff("test")
+            .unwrap();
+        assert!(!placeholder(&changes.deletes[0]));
+        assert!(placeholder(&changes.puts[0].0));
+    }
+
     #[test]
     fn diff_field_missing_in_both() {
         let doc = bson::rawdoc! { "_id": "d1", "name": "Alice" };
@@ -330,9 +465,9 @@ mod tests {
             .diff("test")
             .unwrap();
 
-        // name unchanged → no change; email added → 1 put
+        // name unchanged → no change; email added → its placeholder is replaced
         assert_eq!(changes.puts.len(), 1);
-        assert!(changes.deletes.is_empty());
+        assert_eq!(changes.deletes.len(), 1);
     }
 
     #[test]
@@ -352,8 +487,8 @@ mod tests {
             .diff("test")
             .unwrap();
 
-        // name unchanged → no change; email removed → 1 delete
-        assert!(changes.puts.is_empty());
+        // name unchanged → no change; email removed → replaced by a placeholder
+        assert_eq!(changes.puts.len(), 1);
         assert_eq!(changes.deletes.len(), 1);
     }
 
@@ -371,7 +506,7 @@ mod tests {
         let changes = IndexDiff::new(&new_record, &doc_id)
             .with_old_record(Some(&old_bytes))
             .with_property_path("name")
-            .with_property_path("ttl")
+            .with_sparse_path("ttl")
             .diff("test")
             .unwrap();
 
@@ -396,4 +531,104 @@ mod tests {
 
         assert_eq!(changes.puts.len(), 2);
     }
+
+    #[test]
+    fn diff_compound_index_replaces_entry_when_any_field_changes() {
+        let old_doc = bson::rawdoc! { "_id": "d1", "status": "active", "age": 30 };
+        let new_doc = bson::rawdoc! { "_id": "d1", "status": "active", "age": 31 };
+        let doc_id = str_id("d1");
+
+        let old_record = make_record(&old_doc);
+        let old_bytes = old_record.as_bytes().to_vec();
+
+        let new_record = make_record(&new_doc);
+        let changes = IndexDiff::new(&new_record, &doc_id)
+            .with_old_record(Some(&old_bytes))
+            .with_property_path("status")
+            .with_property_path("status+age")
+            .diff("test")
+            .unwrap();
+
+        // status unchanged → no change; the compound entry moves.
+        assert_eq!(changes.puts.len(), 1);
+        assert_eq!(changes.deletes.len(), 1);
+    }
+
+    #[test]
+    fn diff_partial_index_follows_filter_membership() {
+        let filters = [IndexFilter::new(
+            "customer_id",
+            bson::rawdoc! { "archived": false },
+            std::sync::Arc::new(|doc: &RawDocument| {
+                doc.get_bool("archived").is_ok_and(|archived| !archived)
+            }),
+        )];
+        let doc_id = str_id("o1");
+        let live =
+            make_record(&bson::rawdoc! { "_id": "o1", "customer_id": "c1", "archived": false });
+        let archived =
+            make_record(&bson::rawdoc! { "_id": "o1", "customer_id": "c1", "archived": true });
+        let live_bytes = live.as_bytes().to_vec();
+        let archived_bytes = archived.as_bytes().to_vec();
+
+        // Excluded document: no entry.
+        let changes = IndexDiff::new(&archived, &doc_id)
+            .with_property_path("customer_id")
+            .with_filters(&filters)
+            .diff("orders")
+            .unwrap();
+        assert!(changes.puts.is_empty());
+
+        // Archiving a live document removes its entry.
+        let changes = IndexDiff::new(&archived, &doc_id)
+            .with_old_record(Some(&live_bytes))
+            .with_property_path("customer_id")
+            .with_filters(&filters)
+            .diff("orders")
+            .unwrap();
+        assert!(changes.puts.is_empty());
+        assert_eq!(changes.deletes.len(), 1);
+
+        // Un-archiving adds it back.
+        let changes = IndexDiff::new(&live, &doc_id)
+            .with_old_record(Some(&archived_bytes))
+            .with_property_path("customer_id")
+            .with_filters(&filters)
+            .diff("orders")
+            .unwrap();
+        assert_eq!(changes.puts.len(), 1);
+        assert!(changes.deletes.is_empty());
+    }
+
+    #[test]
+    fn diff_array_elements_added_and_removed() {
+        let doc_id = str_id("d1");
+        let old_record = make_record(&bson::rawdoc! { "_id": "d1", "tags": ["a", "b"] });
+        let old_bytes = old_record.as_bytes().to_vec();
+
+        let new_record = make_record(&bson::rawdoc! { "_id": "d1", "tags": ["b", "c"] });
+        let changes = IndexDiff::new(&new_record, &doc_id)
+            .with_old_record(Some(&old_bytes))
+            .with_property_path("tags")
+            .with_property_path("name")
+            .diff("test")
+            .unwrap();
+
+        // "b" is untouched; "c" is added and "a" removed.
+        assert_eq!(changes.puts.len(), 1);
+        assert_eq!(changes.deletes.len(), 1);
+        assert_eq!(changes.multikey, vec!["tags".to_string()]);
+
+        // Back to a scalar: the remaining element entries go.
+        let scalar = make_record(&bson::rawdoc! { "_id": "d1", "tags": "b" });
+        let new_bytes = new_record.as_bytes().to_vec();
+        let changes = IndexDiff::new(&scalar, &doc_id)
+            .with_old_record(Some(&new_bytes))
+            .with_property_path("tags")
+            .diff("test")
+            .unwrap();
+        assert!(changes.puts.is_empty());
+        assert_eq!(changes.deletes.len(), 1);
+        assert!(changes.multikey.is_empty());
+    }
 }
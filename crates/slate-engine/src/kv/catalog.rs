@@ -1,15 +1,21 @@
 use std::borrow::Cow;
+use std::collections::HashMap;
+use std::ops::Bound;
 
-use slate_store::{Store, Transaction};
+use bson::RawDocumentBuf;
+use slate_store::{Store, Transaction, increment_prefix};
 
+use crate::encoding::component;
 use crate::encoding::{IndexRecord, Key, KeyPrefix, Record};
 use crate::error::EngineError;
+use crate::index_sync::IndexChanges;
 use crate::traits::{
-    Catalog, CollectionHandle, CreateCollectionOptions, FunctionEntry, FunctionKind,
+    Catalog, CollectionHandle, CreateCollectionOptions, FunctionEntry, FunctionKind, IndexConfig,
+    IndexFilter,
 };
 
-use super::transaction::KvTransaction;
-use super::{CollectionMeta, SYS_CF};
+use super::transaction::{KvTransaction, unique_guard};
+use super::{CollectionMeta, CompressionMeta, INDEX_FORMAT, IndexMeta, SYS_CF};
 
 // ── Catalog helpers ─────────────────────────────────────────────
 
@@ -36,6 +42,10 @@ impl<'a, S: Store + 'a> KvTransaction<'a, S> {
     }
 
     /// Load collection metadata from the sys CF.
+    ///
+    /// The read is conflict-tracked, so a write transaction that resolved
+    /// the collection before an index build started fails to commit (see
+    /// [`CollectionMeta::index_builds`]).
     pub(crate) fn load_collection_meta(
         &self,
         cf: &str,
@@ -45,41 +55,302 @@ impl<'a, S: Store + 'a> KvTransaction<'a, S> {
         let key = Key::Collection(Cow::Borrowed(cf), Cow::Borrowed(name)).encode();
         let value = self
             .txn
-            .get(&sys, &key)?
+            .get_for_update(&sys, &key)?
             .ok_or_else(|| EngineError::CollectionNotFound(format!("{cf}:{name}")))?;
         bson::deserialize_from_slice(&value)
             .map_err(|e| EngineError::InvalidDocument(format!("invalid collection meta: {e}")))
     }
 
-    /// Load index field names for a collection from the sys CF.
-    pub(crate) fn load_indexes(
+    /// Bump the collection's index build counter, so writers that resolved
+    /// the collection earlier fail to commit (see
+    /// [`CollectionMeta::index_builds`]).
+    fn bump_index_builds(&self, cf: &str, collection: &str) -> Result<(), EngineError> {
+        let mut collection_meta = self.load_collection_meta(cf, collection)?;
+        collection_meta.index_builds += 1;
+        let sys = self.sys_cf()?;
+        let key = Key::Collection(Cow::Borrowed(cf), Cow::Borrowed(collection)).encode();
+        let blob = bson::serialize_to_vec(&collection_meta)
+            .map_err(|e| EngineError::InvalidDocument(format!("failed to serialize meta: {e}")))?;
+        self.txn.put(&sys, &key, &blob)?;
+        Ok(())
+    }
+
+    /// Mark index `name` as building from the first record, in the current
+    /// key format, keeping its filter and flags. Its old entries and guards
+    /// must already be gone.
+    pub(crate) fn restart_index_build(
         &self,
         cf: &str,
         collection: &str,
-    ) -> Result<Vec<String>, EngineError> {
+        name: &str,
+    ) -> Result<(), EngineError> {
+        let sys = self.sys_cf()?;
+        let config_key = Key::IndexConfig(
+            Cow::Borrowed(cf),
+            Cow::Borrowed(collection),
+            Cow::Borrowed(name),
+        )
+        .encode();
+        let value = self
+            .txn
+            .get(&sys, &config_key)?
+            .ok_or_else(|| EngineError::InvalidIndex(format!("no index {collection}.{name}")))?;
+        let mut meta = IndexMeta::from_bytes(&value)?;
+        self.bump_index_builds(cf, collection)?;
+        meta.multikey = false;
+        meta.format = INDEX_FORMAT;
+        meta.building = Some(bson::Binary {
+            subtype: bson::spec::BinarySubtype::Generic,
+            bytes: Vec::new(),
+        });
+        self.txn.put(&sys, &config_key, &meta.to_bytes()?)?;
+        Ok(())
+    }
+
+    /// Load index names and definitions for a collection from the sys CF.
+    pub(crate) fn load_index_metas(
+        &self,
+        cf: &str,
+        collection: &str,
+    ) -> Result<Vec<(String, IndexMeta)>, EngineError> {
         let sys = self.sys_cf()?;
         let prefix =
             KeyPrefix::IndexConfig(Cow::Borrowed(cf), Cow::Borrowed(collection)).encode();
         let iter = self.txn.scan_prefix(&sys, &prefix)?;
-        let mut fields = Vec::new();
+        let mut indexes = Vec::new();
         for result in iter {
-            let (key_bytes, _) = result?;
+            let (key_bytes, value) = result?;
             let key = Key::decode(&key_bytes);
             if let Some(Key::IndexConfig(_, _, field)) = key {
-                fields.push(field.into_owned());
+                indexes.push((field.into_owned(), IndexMeta::from_bytes(&value)?));
             }
         }
-        Ok(fields)
+        Ok(indexes)
     }
-}
 
-// ── Catalog impl ───────────────────────────────────────────────
+    /// Flag `indexes` as multikey in the sys CF, unless the handle already
+    /// knows they are. The flag is written once per index.
+    pub(crate) fn mark_multikey(
+        &self,
+        handle: &CollectionHandle<<S::Txn<'a> as Transaction>::Cf>,
+        indexes: &[String],
+    ) -> Result<(), EngineError> {
+        for index in indexes.iter().filter(|index| !handle.is_multikey(index)) {
+            let sys = self.sys_cf()?;
+            let key = Key::IndexConfig(
+                Cow::Borrowed(handle.cf_name()),
+                Cow::Borrowed(handle.name()),
+                Cow::Borrowed(index),
+            )
+            .encode();
+            let Some(value) = self.txn.get(&sys, &key)? else {
+                continue;
+            };
+            let mut meta = IndexMeta::from_bytes(&value)?;
+            if !meta.multikey {
+                meta.multikey = true;
+                self.txn.put(&sys, &key, &meta.to_bytes()?)?;
+            }
+        }
+        Ok(())
+    }
 
-impl<'a, S: Store + 'a> Catalog for KvTransaction<'a, S> {
-    fn collection(&self, cf: &str, name: &str) -> Result<CollectionHandle<Self::Cf>, EngineError> {
+    /// Load index names for a collection from the sys CF.
+    pub(crate) fn load_indexes(
+        &self,
+        cf: &str,
+        collection: &str,
+    ) -> Result<Vec<String>, EngineError> {
+        let indexes = self.load_index_metas(cf, collection)?;
+        Ok(indexes.into_iter().map(|(name, _)| name).collect())
+    }
+
+    /// Build the entries of index `name` from the existing records, and
+    /// whether any of them came from an array. Fails on the first
+    /// duplicate value of a unique index.
+    fn backfill_index(
+        &self,
+        cf: &str,
+        collection: &str,
+        name: &str,
+        unique: bool,
+        filter: Option<&IndexFilter>,
+    ) -> Result<(Vec<IndexRecord>, bool), EngineError> {
+        let meta = self.load_collection_meta(cf, collection)?;
+        let codec = meta.codec(cf, collection, &self.codecs)?;
+        let cf_handle = self.txn.cf(cf)?;
+        let record_prefix = KeyPrefix::Record(Cow::Borrowed(collection)).encode();
+        let records: Vec<(Vec<u8>, Vec<u8>)> = self
+            .txn
+            .scan_prefix(&cf_handle, &record_prefix)?
+            .collect::<Result<_, _>>()?;
+
+        let indexes = vec![name.to_string()];
+        let mut entries = Vec::new();
+        let mut multikey = false;
+        // Value bytes → doc_id of the live record that holds them.
+        let mut seen: HashMap<Vec<u8>, String> = HashMap::new();
+        for (key_bytes, value_bytes) in &records {
+            let Some(Key::Record(_, doc_id)) = Key::decode(key_bytes) else {
+                continue;
+            };
+            let record = Record::from_bytes_with(value_bytes.clone(), &codec)?;
+            let ttl = record.ttl_millis();
+            let doc = record.doc()?;
+            if filter.is_some_and(|f| !f.matches(doc)) {
+                continue;
+            }
+            IndexRecord::check_arrays(doc, name)?;
+            multikey |= IndexRecord::holds_array(doc, name);
+            let record_entries =
+                IndexRecord::from_document(collection, &indexes, doc, &doc_id, ttl);
+            if unique && !Record::is_expired(value_bytes, self.now_millis) {
+                let id = doc_id.to_string();
+                // Documents without the field may share the index.
+                let valued = record_entries
+                    .iter()
+                    .filter(|e| !component::is_placeholder(e.value_bytes()));
+                for entry in valued {
+                    match seen.get(entry.value_bytes()) {
+                        Some(other) if *other != id => {
+                            return Err(EngineError::DuplicateKey(format!(
+                                "{collection}.{name} (conflicts with {other} and {id})"
+                            )));
+                        }
+                        Some(_) => {}
+                        None => {
+                            seen.insert(entry.value_bytes().to_vec(), id.clone());
+                        }
+                    }
+                }
+            }
+            entries.extend(record_entries);
+        }
+        Ok((entries, multikey))
+    }
+
+    fn put_index_entries(
+        &self,
+        cf: &<S::Txn<'a> as Transaction>::Cf,
+        entries: &[IndexRecord],
+    ) -> Result<(), EngineError> {
+        if !entries.is_empty() {
+            let refs: Vec<(&[u8], &[u8])> = entries
+                .iter()
+                .map(|e| (e.key_bytes(), e.metadata()))
+                .collect();
+            self.txn.put_batch(cf, &refs)?;
+        }
+        Ok(())
+    }
+
+    /// Point the guard of every value in `entries`, the entries of a unique
+    /// index, at the document holding it.
+    fn put_unique_guards(
+        &self,
+        cf: &<S::Txn<'a> as Transaction>::Cf,
+        collection: &str,
+        entries: &[IndexRecord],
+    ) -> Result<(), EngineError> {
+        for entry in entries {
+            if let Some((guard, owner)) = unique_guard(collection, entry.key_bytes()) {
+                self.txn.put(cf, &guard, &owner)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Delete the entries of index `name` and the guards of its values.
+    fn delete_index_entries(
+        &self,
+        cf: &<S::Txn<'a> as Transaction>::Cf,
+        collection: &str,
+        name: &str,
+    ) -> Result<(), EngineError> {
+        let idx_prefix =
+            KeyPrefix::IndexField(Cow::Borrowed(collection), Cow::Borrowed(name)).encode();
+        self.delete_prefix(cf, &idx_prefix)?;
+        let guard_prefix =
+            KeyPrefix::Unique(Cow::Borrowed(collection), Cow::Borrowed(name)).encode();
+        self.delete_prefix(cf, &guard_prefix)
+    }
+
+    /// Validate a new index and compile its filter. Returns the key of its
+    /// config in the sys CF, which must not exist yet.
+    fn new_index(
+        &self,
+        cf: &str,
+        collection: &str,
+        index: &IndexConfig,
+    ) -> Result<(Vec<u8>, Option<IndexFilter>), EngineError> {
+        index.validate()?;
+        let name = index.name();
+        let filter = index
+            .filter
+            .clone()
+            .map(|document| self.compile_index_filter(&name, document))
+            .transpose()?;
+        let sys = self.sys_cf()?;
+        let config_key = Key::IndexConfig(
+            Cow::Borrowed(cf),
+            Cow::Borrowed(collection),
+            Cow::Borrowed(&name),
+        )
+        .encode();
+        if self.txn.get(&sys, &config_key)?.is_some() {
+            return Err(EngineError::IndexExists(format!("{collection}.{name}")));
+        }
+        Ok((config_key, filter))
+    }
+
+    /// Compile the filter of a partial index with the engine's compiler.
+    fn compile_index_filter(
+        &self,
+        index: &str,
+        document: RawDocumentBuf,
+    ) -> Result<IndexFilter, EngineError> {
+        let compiler = self.index_filter.as_ref().ok_or_else(|| {
+            EngineError::InvalidIndex(format!(
+                "index '{index}' has a filter but no filter compiler is installed"
+            ))
+        })?;
+        let matches = compiler(&document)?;
+        Ok(IndexFilter::new(index, document, matches))
+    }
+
+    /// Resolve a collection handle, compiling its partial index filters.
+    fn load_handle(
+        &self,
+        cf: &str,
+        name: &str,
+    ) -> Result<CollectionHandle<<S::Txn<'a> as Transaction>::Cf>, EngineError> {
         let meta = self.load_collection_meta(cf, name)?;
-        let indexes = self.load_indexes(cf, name)?;
+        let mut indexes = Vec::new();
+        let mut filters = Vec::new();
+        let mut unique = Vec::new();
+        let mut multikey = Vec::new();
+        let mut outdated = Vec::new();
+        let mut building = Vec::new();
+        for (index, index_meta) in self.load_index_metas(cf, name)? {
+            if let Some(document) = index_meta.filter {
+                filters.push(self.compile_index_filter(&index, document)?);
+            }
+            if index_meta.unique {
+                unique.push(index.clone());
+            }
+            if index_meta.multikey {
+                multikey.push(index.clone());
+            }
+            if index_meta.format < INDEX_FORMAT {
+                outdated.push(index.clone());
+            }
+            if index_meta.building.is_some() {
+                building.push(index.clone());
+            }
+            indexes.push(index);
+        }
         let cf_handle = self.txn.cf(cf)?;
+        let codec = meta.codec(cf, name, &self.codecs)?;
         Ok(CollectionHandle::new(
             name.to_string(),
             cf.to_string(),
@@ -87,7 +358,21 @@ impl<'a, S: Store + 'a> Catalog for KvTransaction<'a, S> {
             indexes,
             meta.pk,
             meta.ttl,
-        ))
+        )
+        .with_index_filters(filters)
+        .with_unique_indexes(unique)
+        .with_multikey_indexes(multikey)
+        .with_outdated_indexes(outdated)
+        .with_building_indexes(building)
+        .with_codec(codec))
+    }
+}
+
+// ── Catalog impl ───────────────────────────────────────────────
+
+impl<'a, S: Store + 'a> Catalog for KvTransaction<'a, S> {
+    fn collection(&self, cf: &str, name: &str) -> Result<CollectionHandle<Self::Cf>, EngineError> {
+        self.load_handle(cf, name)
     }
 
     fn list_collections(
@@ -107,21 +392,10 @@ impl<'a, S: Store + 'a> Catalog for KvTransaction<'a, S> {
                 entries.push((cf_name.into_owned(), name.into_owned()));
             }
         }
-        let mut handles = Vec::new();
-        for (cf_name, name) in entries {
-            let meta = self.load_collection_meta(&cf_name, &name)?;
-            let indexes = self.load_indexes(&cf_name, &name)?;
-            let cf_handle = self.txn.cf(&cf_name)?;
-            handles.push(CollectionHandle::new(
-                name,
-                cf_name,
-                cf_handle,
-                indexes,
-                meta.pk,
-                meta.ttl,
-            ));
-        }
-        Ok(handles)
+        entries
+            .iter()
+            .map(|(cf_name, name)| self.load_handle(cf_name, name))
+            .collect()
     }
 
     fn create_collection(
@@ -139,12 +413,16 @@ impl<'a, S: Store + 'a> Catalog for KvTransaction<'a, S> {
                 "pk_path must be a top-level field (dot-paths are not supported)".into(),
             ));
         }
+        let compression = options.compression.clone().unwrap_or_default();
+        compression.validate()?;
         let meta = CollectionMeta {
             pk,
             ttl: options
                 .ttl_path
                 .clone()
                 .unwrap_or_else(|| "ttl".to_string()),
+            compression: CompressionMeta::from_compression(&compression),
+            index_builds: 0,
         };
         let sys = self.sys_cf()?;
         let key = Key::Collection(Cow::Borrowed(cf), Cow::Borrowed(name)).encode();
@@ -172,9 +450,7 @@ impl<'a, S: Store + 'a> Catalog for KvTransaction<'a, S> {
         // Delete all index entries (catalog indexes + ttl).
         let indexes = self.load_indexes(cf, name)?;
         for field in &indexes {
-            let idx_prefix =
-                KeyPrefix::IndexField(Cow::Borrowed(name), Cow::Borrowed(field)).encode();
-            self.delete_prefix(&cf_handle, &idx_prefix)?;
+            self.delete_index_entries(&cf_handle, name, field)?;
         }
         let ttl_prefix =
             KeyPrefix::IndexField(Cow::Borrowed(name), Cow::Borrowed(&meta.ttl)).encode();
@@ -208,67 +484,188 @@ impl<'a, S: Store + 'a> Catalog for KvTransaction<'a, S> {
         &mut self,
         cf: &str,
         collection: &str,
-        field: &str,
+        index: impl Into<IndexConfig>,
     ) -> Result<(), EngineError> {
-        self.load_collection_meta(cf, collection)?;
+        let index = index.into();
+        let name = index.name();
+        let (config_key, filter) = self.new_index(cf, collection, &index)?;
         let cf_handle = self.txn.cf(cf)?;
+        let sys = self.sys_cf()?;
+
+        let (entries, multikey) =
+            self.backfill_index(cf, collection, &name, index.unique, filter.as_ref())?;
+
+        // Nothing is written until the existing data is known to fit.
+        let meta = IndexMeta {
+            filter: index.filter,
+            unique: index.unique,
+            multikey,
+            format: INDEX_FORMAT,
+            building: None,
+        };
+        self.txn.put(&sys, &config_key, &meta.to_bytes()?)?;
+        if meta.unique {
+            self.put_unique_guards(&cf_handle, collection, &entries)?;
+        }
+        self.put_index_entries(&cf_handle, &entries)
+    }
 
-        // Check for duplicate index.
+    fn start_index_build(
+        &mut self,
+        cf: &str,
+        collection: &str,
+        index: impl Into<IndexConfig>,
+    ) -> Result<(), EngineError> {
+        let index = index.into();
+        let (config_key, _) = self.new_index(cf, collection, &index)?;
+        self.bump_index_builds(cf, collection)?;
+        let sys = self.sys_cf()?;
+
+        let meta = IndexMeta {
+            filter: index.filter,
+            unique: index.unique,
+            multikey: false,
+            format: INDEX_FORMAT,
+            building: Some(bson::Binary {
+                subtype: bson::spec::BinarySubtype::Generic,
+                bytes: Vec::new(),
+            }),
+        };
+        self.txn.put(&sys, &config_key, &meta.to_bytes()?)?;
+        Ok(())
+    }
+
+    fn continue_index_build(
+        &mut self,
+        cf: &str,
+        collection: &str,
+        name: &str,
+        batch: usize,
+    ) -> Result<bool, EngineError> {
         let sys = self.sys_cf()?;
         let config_key = Key::IndexConfig(
             Cow::Borrowed(cf),
             Cow::Borrowed(collection),
-            Cow::Borrowed(field),
+            Cow::Borrowed(name),
         )
         .encode();
-        if self.txn.get(&sys, &config_key)?.is_some() {
-            return Err(EngineError::IndexExists(format!("{collection}.{field}")));
-        }
-        self.txn.put(&sys, &config_key, &[])?;
+        let value = self
+            .txn
+            .get(&sys, &config_key)?
+            .ok_or_else(|| EngineError::InvalidIndex(format!("no index {collection}.{name}")))?;
+        let mut meta = IndexMeta::from_bytes(&value)?;
+        let Some(mut cursor) = meta.building.take() else {
+            return Ok(true);
+        };
+        let handle = self.load_handle(cf, collection)?;
 
-        // Backfill: scan all existing records and create index entries.
         let record_prefix = KeyPrefix::Record(Cow::Borrowed(collection)).encode();
-        let records: Vec<(Vec<u8>, Vec<u8>)> = self
+        let start = if cursor.bytes.is_empty() {
+            Bound::Included(record_prefix.as_slice())
+        } else {
+            Bound::Excluded(cursor.bytes.as_slice())
+        };
+        let upper = increment_prefix(&record_prefix);
+        let end = match &upper {
+            Some(upper) => Bound::Excluded(upper.as_slice()),
+            None => Bound::Unbounded,
+        };
+        let keys: Vec<Vec<u8>> = self
             .txn
-            .scan_prefix(&cf_handle, &record_prefix)?
+            .scan_range(handle.cf(), start, end, false)?
+            .take(batch)
+            .map(|r| r.map(|(k, _)| k))
             .collect::<Result<_, _>>()?;
 
-        let indexes = vec![field.to_string()];
-        for (key_bytes, value_bytes) in &records {
-            let Some(Key::Record(_, doc_id)) = Key::decode(key_bytes) else {
+        // Read the records back with tracked reads: scans aren't
+        // conflict-checked, so a write racing this batch would otherwise
+        // leave a stale entry behind.
+        let values = keys
+            .iter()
+            .map(|key| self.txn.get_for_update(handle.cf(), key))
+            .collect::<Result<Vec<_>, _>>()?;
+        let indexes = vec![name.to_string()];
+        for (key, value) in keys.iter().zip(values) {
+            let (Some(Key::Record(_, doc_id)), Some(value)) = (Key::decode(key), value) else {
                 continue;
             };
-            let record = Record::from_bytes(value_bytes.clone())?;
-            let ttl = record.ttl_millis();
+            let record = Record::from_bytes_with(value, handle.codec())?;
             let doc = record.doc()?;
-            let entries =
-                IndexRecord::from_document(collection, &indexes, doc, &doc_id, ttl);
-            if !entries.is_empty() {
-                let refs: Vec<(&[u8], &[u8])> = entries
+            if handle.index_filter(name).is_some_and(|f| !f.matches(doc)) {
+                continue;
+            }
+            IndexRecord::check_arrays(doc, name)?;
+            meta.multikey |= IndexRecord::holds_array(doc, name);
+            let puts =
+                IndexRecord::from_document(collection, &indexes, doc, &doc_id, record.ttl_millis())
                     .iter()
-                    .map(|e| (e.key_bytes(), e.metadata()))
+                    .map(|e| (e.key_bytes().to_vec(), e.metadata().to_vec()))
                     .collect();
-                self.txn.put_batch(&cf_handle, &refs)?;
-            }
+            // One record at a time, so the unique check sees the entries
+            // written before it in this batch.
+            let changes = IndexChanges {
+                puts,
+                deletes: Vec::new(),
+                multikey: Vec::new(),
+            };
+            self.apply_index_changes(&handle, &changes)?;
         }
 
-        Ok(())
+        let ready = keys.len() < batch;
+        if !ready && let Some(last) = keys.last() {
+            cursor.bytes = last.clone();
+            meta.building = Some(cursor);
+        }
+        self.txn.put(&sys, &config_key, &meta.to_bytes()?)?;
+        Ok(ready)
     }
 
-    fn drop_index(&mut self, cf: &str, collection: &str, field: &str) -> Result<(), EngineError> {
+    fn reindex(&mut self, cf: &str, collection: &str, name: &str) -> Result<(), EngineError> {
+        let sys = self.sys_cf()?;
+        let config_key = Key::IndexConfig(
+            Cow::Borrowed(cf),
+            Cow::Borrowed(collection),
+            Cow::Borrowed(name),
+        )
+        .encode();
+        let value = self
+            .txn
+            .get(&sys, &config_key)?
+            .ok_or_else(|| EngineError::InvalidIndex(format!("no index {collection}.{name}")))?;
+        let mut meta = IndexMeta::from_bytes(&value)?;
+        let filter = meta
+            .filter
+            .clone()
+            .map(|document| self.compile_index_filter(name, document))
+            .transpose()?;
         let cf_handle = self.txn.cf(cf)?;
 
-        // Delete all index entries for this field.
-        let idx_prefix =
-            KeyPrefix::IndexField(Cow::Borrowed(collection), Cow::Borrowed(field)).encode();
-        self.delete_prefix(&cf_handle, &idx_prefix)?;
+        let (entries, multikey) =
+            self.backfill_index(cf, collection, name, meta.unique, filter.as_ref())?;
+
+        self.delete_index_entries(&cf_handle, collection, name)?;
+        meta.multikey = multikey;
+        meta.format = INDEX_FORMAT;
+        meta.building = None;
+        self.txn.put(&sys, &config_key, &meta.to_bytes()?)?;
+        if meta.unique {
+            self.put_unique_guards(&cf_handle, collection, &entries)?;
+        }
+        self.put_index_entries(&cf_handle, &entries)
+    }
+
+    fn drop_index(&mut self, cf: &str, collection: &str, name: &str) -> Result<(), EngineError> {
+        let cf_handle = self.txn.cf(cf)?;
+
+        // Delete all index entries for this index.
+        self.delete_index_entries(&cf_handle, collection, name)?;
 
         // Delete the index config key from _sys_.
         let sys = self.sys_cf()?;
         let key = Key::IndexConfig(
             Cow::Borrowed(cf),
             Cow::Borrowed(collection),
-            Cow::Borrowed(field),
+            Cow::Borrowed(name),
         )
         .encode();
         self.txn.delete(&sys, &key)?;
@@ -358,3 +755,205 @@ impl<'a, S: Store + 'a> Catalog for KvTransaction<'a, S> {
         Ok(entries)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use bson::raw::RawBsonRef;
+    use bson::{Bson, RawBson, rawdoc};
+    use slate_store::MemoryStore;
+
+    use super::*;
+    use crate::kv::{DEFAULT_CF, KvEngine};
+    use crate::traits::{Engine, EngineTransaction, IndexRange};
+
+    fn doc_ids(engine: &KvEngine<MemoryStore>, range: IndexRange<'_>) -> Vec<RawBson> {
+        let txn = engine.begin(true).unwrap();
+        let handle = txn.collection(DEFAULT_CF, "c").unwrap();
+        txn.scan_index(&handle, "n", range, false)
+            .unwrap()
+            .map(|e| e.unwrap().doc_id().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn migrate_rebuilds_outdated_indexes() {
+        let engine = KvEngine::new(MemoryStore::new());
+        let mut txn = engine.begin(false).unwrap();
+        txn.create_collection(DEFAULT_CF, "c", &Default::default())
+            .unwrap();
+        let handle = txn.collection(DEFAULT_CF, "c").unwrap();
+        for doc in [
+            rawdoc! { "_id": "a", "n": 5 },
+            rawdoc! { "_id": "b", "n": 5.5 },
+            rawdoc! { "_id": "c", "n": 7i64 },
+        ] {
+            txn.put_nx(&handle, &doc).unwrap();
+        }
+        txn.create_index(DEFAULT_CF, "c", "n").unwrap();
+
+        // Mark the index as written by an older version, with an entry the
+        // current format wouldn't produce.
+        let sys = txn.sys_cf().unwrap();
+        let config_key = Key::IndexConfig(
+            Cow::Borrowed(DEFAULT_CF),
+            Cow::Borrowed("c"),
+            Cow::Borrowed("n"),
+        )
+        .encode();
+        txn.txn.put(&sys, &config_key, &[]).unwrap();
+        let stale = IndexRecord::from_document(
+            "c",
+            &["n".to_string()],
+            &rawdoc! { "n": "stale" },
+            &crate::encoding::bson_value::BsonValue::from_bson(&Bson::String("a".into())).unwrap(),
+            None,
+        );
+        txn.put_index_entries(handle.cf(), &stale).unwrap();
+        txn.commit().unwrap();
+
+        let txn = engine.begin(true).unwrap();
+        assert!(txn.collection(DEFAULT_CF, "c").unwrap().is_outdated("n"));
+        txn.rollback().unwrap();
+
+        assert_eq!(
+            engine.migrate_indexes().unwrap(),
+            vec![(DEFAULT_CF.to_string(), "c".to_string(), "n".to_string())]
+        );
+        assert!(engine.migrate_indexes().unwrap().is_empty());
+
+        // The old entries are gone, and the index is rebuilt in batches.
+        let txn = engine.begin(true).unwrap();
+        let handle = txn.collection(DEFAULT_CF, "c").unwrap();
+        assert!(!handle.is_outdated("n"));
+        assert!(handle.is_building("n"));
+        txn.rollback().unwrap();
+        assert!(doc_ids(&engine, IndexRange::Full).is_empty());
+        let mut txn = engine.begin(false).unwrap();
+        while !txn.continue_index_build(DEFAULT_CF, "c", "n", 2).unwrap() {}
+        txn.commit().unwrap();
+
+        let txn = engine.begin(true).unwrap();
+        assert!(!txn.collection(DEFAULT_CF, "c").unwrap().is_building("n"));
+        txn.rollback().unwrap();
+        assert_eq!(doc_ids(&engine, IndexRange::Full).len(), 3);
+        let lower = Bson::Int64(5);
+        let upper = Bson::Double(7.0);
+        assert_eq!(
+            doc_ids(
+                &engine,
+                IndexRange::Range {
+                    lower: Some((&lower, true)),
+                    upper: Some((&upper, false)),
+                }
+            ),
+            vec![RawBson::String("a".into()), RawBson::String("b".into())]
+        );
+    }
+
+    #[test]
+    fn migrate_keeps_colliding_unique_index_building() {
+        let engine = KvEngine::new(MemoryStore::new());
+        let mut txn = engine.begin(false).unwrap();
+        txn.create_collection(DEFAULT_CF, "c", &Default::default())
+            .unwrap();
+        let handle = txn.collection(DEFAULT_CF, "c").unwrap();
+        for doc in [
+            rawdoc! { "_id": "a", "n": 5 },
+            rawdoc! { "_id": "b", "n": 5.0 },
+        ] {
+            txn.put_nx(&handle, &doc).unwrap();
+        }
+        txn.create_index(DEFAULT_CF, "c", "n").unwrap();
+
+        // An older version kept 5 and 5.0 apart in a unique index.
+        let sys = txn.sys_cf().unwrap();
+        let config_key = Key::IndexConfig(
+            Cow::Borrowed(DEFAULT_CF),
+            Cow::Borrowed("c"),
+            Cow::Borrowed("n"),
+        )
+        .encode();
+        let meta = IndexMeta {
+            unique: true,
+            ..Default::default()
+        };
+        txn.txn
+            .put(&sys, &config_key, &meta.to_bytes().unwrap())
+            .unwrap();
+        txn.commit().unwrap();
+
+        assert_eq!(engine.migrate_indexes().unwrap().len(), 1);
+        let mut txn = engine.begin(false).unwrap();
+        let result = txn.continue_index_build(DEFAULT_CF, "c", "n", 10);
+        assert!(matches!(result, Err(EngineError::DuplicateKey(_))));
+        txn.rollback().unwrap();
+
+        let txn = engine.begin(true).unwrap();
+        let handle = txn.collection(DEFAULT_CF, "c").unwrap();
+        assert!(handle.is_building("n"));
+        assert!(!handle.is_outdated("n"));
+        txn.rollback().unwrap();
+        assert!(engine.migrate_indexes().unwrap().is_empty());
+    }
+
+    #[test]
+    fn index_build_resumes_and_keeps_up_with_writes() {
+        let engine = KvEngine::new(MemoryStore::new());
+        let mut txn = engine.begin(false).unwrap();
+        txn.create_collection(DEFAULT_CF, "c", &Default::default())
+            .unwrap();
+        let handle = txn.collection(DEFAULT_CF, "c").unwrap();
+        for (id, n) in [("a", 1), ("b", 2), ("c", 3), ("d", 4), ("e", 5)] {
+            txn.put_nx(&handle, &rawdoc! { "_id": id, "n": n }).unwrap();
+        }
+        txn.start_index_build(DEFAULT_CF, "c", "n").unwrap();
+        txn.commit().unwrap();
+
+        let mut txn = engine.begin(false).unwrap();
+        assert!(!txn.continue_index_build(DEFAULT_CF, "c", "n", 2).unwrap());
+        txn.commit().unwrap();
+
+        // Writes on both sides of the resume cursor.
+        let txn = engine.begin(false).unwrap();
+        let handle = txn.collection(DEFAULT_CF, "c").unwrap();
+        assert!(handle.is_building("n"));
+        txn.put(&handle, &rawdoc! { "_id": "a", "n": 10 }).unwrap();
+        txn.put(&handle, &rawdoc! { "_id": "d", "n": 40 }).unwrap();
+        txn.delete(&handle, &RawBsonRef::String("e")).unwrap();
+        txn.put_nx(&handle, &rawdoc! { "_id": "f", "n": 6 })
+            .unwrap();
+        txn.commit().unwrap();
+
+        // A batch that read a record changed since fails to commit.
+        let mut txn = engine.begin(false).unwrap();
+        assert!(!txn.continue_index_build(DEFAULT_CF, "c", "n", 2).unwrap());
+        let writer = engine.begin(false).unwrap();
+        let handle = writer.collection(DEFAULT_CF, "c").unwrap();
+        writer
+            .put(&handle, &rawdoc! { "_id": "c", "n": 30 })
+            .unwrap();
+        writer.commit().unwrap();
+        assert!(matches!(txn.commit(), Err(EngineError::Conflict(_))));
+
+        let mut batches = 0;
+        loop {
+            let mut txn = engine.begin(false).unwrap();
+            let ready = txn.continue_index_build(DEFAULT_CF, "c", "n", 2).unwrap();
+            txn.commit().unwrap();
+            batches += 1;
+            if ready {
+                break;
+            }
+        }
+        assert_eq!(batches, 2);
+
+        let txn = engine.begin(true).unwrap();
+        assert!(!txn.collection(DEFAULT_CF, "c").unwrap().is_building("n"));
+        txn.rollback().unwrap();
+        let ids: Vec<RawBson> = ["b", "f", "a", "c", "d"]
+            .into_iter()
+            .map(|id| RawBson::String(id.into()))
+            .collect();
+        assert_eq!(doc_ids(&engine, IndexRange::Full), ids);
+    }
+}
@@ -3,19 +3,32 @@ mod transaction;
 
 pub use transaction::KvTransaction;
 
-use std::sync::Arc;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::ops::Bound;
+use std::sync::{Arc, Mutex};
 use std::time::{SystemTime, UNIX_EPOCH};
 
+use bson::RawDocument;
 use serde::{Deserialize, Serialize};
 use std::path::Path;
 
-use slate_store::{BackupStore, Store};
+use slate_store::{
+    ApproximateSize, BackupInfo, BackupPolicy, BackupStore, ForkableStore, IncrementalBackupStore,
+    InstrumentedStore, MetricsSnapshot, SnapshotStore, Store, increment_prefix,
+};
 
+use crate::encoding::{Codec, Compression, Key, KeyPrefix};
 use crate::error::EngineError;
+use crate::traits::{Catalog, Engine, EngineTransaction, IndexFilterCompiler, IndexFilterFn};
 
 pub const SYS_CF: &str = "_sys_";
 pub const DEFAULT_CF: &str = "default_cf";
 
+/// Eviction candidates fetched at a time while bringing a column family
+/// back within its memory budget.
+const EVICTION_BATCH: usize = 64;
+
 /// Serializable collection metadata stored in the `_sys_` CF.
 ///
 /// The column family is encoded in the key, not in this struct.
@@ -23,6 +36,163 @@ pub const DEFAULT_CF: &str = "default_cf";
 pub(crate) struct CollectionMeta {
     pub pk: String,
     pub ttl: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compression: Option<CompressionMeta>,
+    /// Bumped when an index build starts. Every transaction reads this key
+    /// with a conflict-tracked read (`get_for_update`) when it resolves the
+    /// collection. A writer that resolved it before the build began
+    /// therefore fails to commit, instead of committing without maintaining
+    /// the new index.
+    #[serde(default)]
+    pub index_builds: u64,
+}
+
+impl CollectionMeta {
+    /// The collection's record codec, reusing the cached one while it still
+    /// matches the stored settings.
+    pub(crate) fn codec(
+        &self,
+        cf: &str,
+        collection: &str,
+        cache: &CodecCache,
+    ) -> Result<Arc<Codec>, EngineError> {
+        let key = (cf.to_string(), collection.to_string());
+        let mut cache = cache.lock().unwrap();
+        if let Some(codec) = cache.get(&key)
+            && CompressionMeta::matches(self.compression.as_ref(), codec.compression())
+        {
+            return Ok(Arc::clone(codec));
+        }
+        let compression = match &self.compression {
+            None => Compression::None,
+            Some(meta) => meta.to_compression()?,
+        };
+        let codec = Arc::new(Codec::new(compression));
+        cache.insert(key, Arc::clone(&codec));
+        Ok(codec)
+    }
+}
+
+/// Prepared record codecs by `(cf, collection)`, shared by an engine's
+/// transactions so a compression dictionary is digested once.
+pub(crate) type CodecCache = Arc<Mutex<HashMap<(String, String), Arc<Codec>>>>;
+
+/// Key format of the index entries this version writes. Format 2 gives
+/// Int32, Int64 and Double one numeric order; indexes with an older format
+/// are rebuilt by [`KvEngine::migrate_indexes`].
+pub(crate) const INDEX_FORMAT: u32 = 2;
+
+/// Serializable index definition, stored as the value of the index config
+/// key in the `_sys_` CF. An empty value, as written before indexes had
+/// options, reads as a plain index.
+#[derive(Serialize, Deserialize, Default)]
+pub(crate) struct IndexMeta {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filter: Option<bson::RawDocumentBuf>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub unique: bool,
+    /// Set once any document reaches the index through an array; never cleared.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub multikey: bool,
+    /// [`INDEX_FORMAT`] the entries were written with; 0 before it was recorded.
+    #[serde(default)]
+    pub format: u32,
+    /// Set while the index is being built: the key of the last record
+    /// backfilled, empty before the first batch. Cleared once it is ready.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub building: Option<bson::Binary>,
+}
+
+impl IndexMeta {
+    pub(crate) fn from_bytes(bytes: &[u8]) -> Result<Self, EngineError> {
+        if bytes.is_empty() {
+            return Ok(Self::default());
+        }
+        bson::deserialize_from_slice(bytes)
+            .map_err(|e| EngineError::InvalidDocument(format!("invalid index meta: {e}")))
+    }
+
+    pub(crate) fn to_bytes(&self) -> Result<Vec<u8>, EngineError> {
+        bson::serialize_to_vec(self).map_err(|e| {
+            EngineError::InvalidDocument(format!("failed to serialize index meta: {e}"))
+        })
+    }
+}
+
+/// Serialized form of [`Compression`] inside [`CollectionMeta`].
+#[derive(Serialize, Deserialize)]
+pub(crate) struct CompressionMeta {
+    codec: String,
+    #[serde(default)]
+    level: i32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    dictionary: Option<bson::Binary>,
+}
+
+impl CompressionMeta {
+    pub(crate) fn from_compression(compression: &Compression) -> Option<Self> {
+        let (codec, level, dictionary) = match compression {
+            Compression::None => return None,
+            Compression::Lz4 { dictionary } => ("lz4", 0, dictionary),
+            Compression::Zstd { level, dictionary } => ("zstd", *level, dictionary),
+        };
+        Some(Self {
+            codec: codec.to_string(),
+            level,
+            dictionary: dictionary.as_ref().map(|d| bson::Binary {
+                subtype: bson::spec::BinarySubtype::Generic,
+                bytes: d.to_vec(),
+            }),
+        })
+    }
+
+    fn to_compression(&self) -> Result<Compression, EngineError> {
+        let dictionary = self
+            .dictionary
+            .as_ref()
+            .map(|d| Arc::from(d.bytes.as_slice()));
+        match self.codec.as_str() {
+            "lz4" => Ok(Compression::Lz4 { dictionary }),
+            "zstd" => Ok(Compression::Zstd {
+                level: self.level,
+                dictionary,
+            }),
+            codec => Err(EngineError::InvalidDocument(format!(
+                "unknown compression codec '{codec}'"
+            ))),
+        }
+    }
+
+    /// Whether `meta` describes `compression`.
+    fn matches(meta: Option<&Self>, compression: &Compression) -> bool {
+        let same_dictionary = |meta: &Self, dictionary: &Option<Arc<[u8]>>| {
+            meta.dictionary.as_ref().map(|d| d.bytes.as_slice()) == dictionary.as_deref()
+        };
+        match (meta, compression) {
+            (None, Compression::None) => true,
+            (Some(meta), Compression::Lz4 { dictionary }) => {
+                meta.codec == "lz4" && same_dictionary(meta, dictionary)
+            }
+            (Some(meta), Compression::Zstd { level, dictionary }) => {
+                meta.codec == "zstd" && meta.level == *level && same_dictionary(meta, dictionary)
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Approximate storage used by one collection, from
+/// [`KvEngine::collection_storage_stats`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CollectionStorageStats {
+    /// Record keys and their encoded documents.
+    pub records: ApproximateSize,
+    /// Entries of each index, by indexed field. The TTL index is included
+    /// under the collection's TTL path.
+    pub indexes: Vec<(String, ApproximateSize)>,
+    /// Records evicted to keep the column family within its memory budget,
+    /// since the engine was opened.
+    pub evictions: u64,
 }
 
 fn default_clock() -> i64 {
@@ -37,26 +207,252 @@ fn default_clock() -> i64 {
 pub struct KvEngine<S> {
     store: S,
     clock: Arc<dyn Fn() -> i64 + Send + Sync>,
+    /// Compiles partial index filters; see [`KvEngine::with_index_filter`].
+    index_filter: Option<IndexFilterCompiler>,
+    /// Evicted record counts by `(cf, collection)`.
+    evictions: Mutex<HashMap<(String, String), u64>>,
+    codecs: CodecCache,
 }
 
 impl<S: Store> KvEngine<S> {
     pub fn new(store: S) -> Self {
-        let _ = store.create_cf(SYS_CF);
+        ensure_sys_cf(&store);
         Self {
             store,
             clock: Arc::new(default_clock),
+            index_filter: None,
+            evictions: Mutex::new(HashMap::new()),
+            codecs: CodecCache::default(),
         }
     }
 
     pub fn with_clock(store: S, clock: impl Fn() -> i64 + Send + Sync + 'static) -> Self {
-        let _ = store.create_cf(SYS_CF);
+        ensure_sys_cf(&store);
         Self {
             store,
             clock: Arc::new(clock),
+            index_filter: None,
+            evictions: Mutex::new(HashMap::new()),
+            codecs: CodecCache::default(),
+        }
+    }
+
+    /// Install the compiler for partial index filter documents. Without
+    /// one, creating or loading a partial index fails.
+    pub fn with_index_filter(
+        mut self,
+        compiler: impl Fn(&RawDocument) -> Result<IndexFilterFn, EngineError> + Send + Sync + 'static,
+    ) -> Self {
+        self.index_filter = Some(Arc::new(compiler));
+        self
+    }
+}
+
+/// Create the catalog CF on first open. A read-only store can't create it;
+/// if it's missing there, catalog reads fail when they resolve the CF.
+fn ensure_sys_cf<S: Store>(store: &S) {
+    if !store.is_read_only() {
+        let _ = store.create_cf(SYS_CF);
+    }
+}
+
+impl<S: Store> KvEngine<S> {
+    /// Estimate the bytes and keys held by a collection's records and by
+    /// each of its indexes.
+    pub fn collection_storage_stats(
+        &self,
+        cf: &str,
+        collection: &str,
+    ) -> Result<CollectionStorageStats, EngineError> {
+        let fields = self.index_fields(cf, collection)?;
+        let evictions = self
+            .evictions
+            .lock()
+            .unwrap()
+            .get(&(cf.to_string(), collection.to_string()))
+            .copied()
+            .unwrap_or(0);
+        let mut stats = CollectionStorageStats {
+            records: self.store.approximate_size(cf, record_span(collection))?,
+            indexes: Vec::with_capacity(fields.len()),
+            evictions,
+        };
+        for field in fields {
+            let size = self
+                .store
+                .approximate_size(cf, index_span(collection, &field))?;
+            stats.indexes.push((field, size));
+        }
+        Ok(stats)
+    }
+
+    /// Compact the key ranges holding a collection's records and indexes,
+    /// reclaiming space left behind by deletes.
+    pub fn compact_collection(&self, cf: &str, collection: &str) -> Result<(), EngineError> {
+        let fields = self.index_fields(cf, collection)?;
+        self.store.compact_range(cf, record_span(collection))?;
+        for field in fields {
+            self.store
+                .compact_range(cf, index_span(collection, &field))?;
+        }
+        Ok(())
+    }
+
+    /// Restart every index written in an older key format as a build from
+    /// its first record. Returns the `(cf, collection, index)` of each, for
+    /// the caller to finish batch by batch with
+    /// [`Catalog::continue_index_build`].
+    ///
+    /// Scans and writes assume the current format, so run this before
+    /// using a store written by an older version, while nothing else writes
+    /// to it: the old entries are deleted outside any transaction. Until its
+    /// build finishes the planner doesn't use an index, so a unique index
+    /// whose values collide in the current format, like `5` and `5.0`,
+    /// stays building rather than failing here.
+    pub fn migrate_indexes(&self) -> Result<Vec<(String, String, String)>, EngineError> {
+        let txn = self.begin(true)?;
+        let outdated: Vec<(String, String, String)> = txn
+            .list_collections(None)?
+            .iter()
+            .flat_map(|handle| {
+                handle
+                    .indexes()
+                    .iter()
+                    .filter(|index| handle.is_outdated(index))
+                    .map(|index| {
+                        (
+                            handle.cf_name().to_string(),
+                            handle.name().to_string(),
+                            index.clone(),
+                        )
+                    })
+            })
+            .collect();
+        txn.rollback()?;
+
+        for (cf, collection, index) in &outdated {
+            self.store.delete_range(cf, index_span(collection, index))?;
+            self.store.delete_range(cf, guard_span(collection, index))?;
+            let txn = self.begin(false)?;
+            txn.restart_index_build(cf, collection, index)?;
+            txn.commit()?;
+        }
+        Ok(outdated)
+    }
+
+    /// Evict the least recently used records from every column family over
+    /// its memory budget until it fits again. Returns how many were evicted.
+    ///
+    /// Records go through the same delete path as a user delete, so their
+    /// index entries go with them. Stores without budgets have nothing to
+    /// evict, which makes this a cheap call after every commit.
+    pub fn evict_over_budget(&self) -> Result<u64, EngineError> {
+        let mut evicted = 0;
+        for (cf, _) in self.store.budget_overruns() {
+            evicted += self.evict_cf(&cf)?;
+        }
+        Ok(evicted)
+    }
+
+    fn evict_cf(&self, cf: &str) -> Result<u64, EngineError> {
+        let prefix = KeyPrefix::AllRecords.encode();
+        let mut evicted = 0;
+        loop {
+            let Some(overrun) = self.overrun(cf) else {
+                return Ok(evicted);
+            };
+            let keys = self
+                .store
+                .eviction_candidates(cf, &prefix, EVICTION_BATCH)?;
+
+            // Free at least `overrun` bytes of records, least recently used
+            // first. Their index entries free a little more on top.
+            let txn = self.begin(false)?;
+            let mut handles = HashMap::new();
+            let mut counts: HashMap<String, u64> = HashMap::new();
+            let mut freed = 0u64;
+            for key in &keys {
+                let Some(Key::Record(collection, id)) = Key::decode(key) else {
+                    continue;
+                };
+                if !handles.contains_key(collection.as_ref()) {
+                    let handle = match txn.collection(cf, &collection) {
+                        Ok(handle) => Some(handle),
+                        Err(EngineError::CollectionNotFound(_)) => None,
+                        Err(e) => return Err(e),
+                    };
+                    handles.insert(collection.to_string(), handle);
+                }
+                let Some(handle) = &handles[collection.as_ref()] else {
+                    continue;
+                };
+                if let Some(bytes) = txn.delete_record(handle, &id)? {
+                    freed += bytes;
+                    *counts.entry(collection.into_owned()).or_default() += 1;
+                    if freed >= overrun {
+                        break;
+                    }
+                }
+            }
+            txn.commit()?;
+
+            if counts.is_empty() {
+                // Nothing evictable is left; the rest of the CF isn't records.
+                return Ok(evicted);
+            }
+            let mut evictions = self.evictions.lock().unwrap();
+            for (collection, n) in counts {
+                evicted += n;
+                *evictions.entry((cf.to_string(), collection)).or_default() += n;
+            }
+        }
+    }
+
+    /// Bytes `cf` is over its memory budget by, if it is.
+    fn overrun(&self, cf: &str) -> Option<u64> {
+        self.store
+            .budget_overruns()
+            .into_iter()
+            .find_map(|(name, over)| (name == cf).then_some(over))
+    }
+
+    /// The collection's indexed fields followed by its TTL path, which the
+    /// engine always indexes.
+    fn index_fields(&self, cf: &str, collection: &str) -> Result<Vec<String>, EngineError> {
+        let txn = self.begin(true)?;
+        let handle = txn.collection(cf, collection)?;
+        let mut fields = handle.indexes().to_vec();
+        if !fields.iter().any(|f| f == handle.ttl_path()) {
+            fields.push(handle.ttl_path().to_string());
         }
+        txn.rollback()?;
+        Ok(fields)
     }
 }
 
+/// Key range covering every record of `collection`.
+fn record_span(collection: &str) -> (Bound<Vec<u8>>, Bound<Vec<u8>>) {
+    prefix_span(KeyPrefix::Record(Cow::Borrowed(collection)).encode())
+}
+
+/// Key range covering every entry of the index on `field`.
+fn index_span(collection: &str, field: &str) -> (Bound<Vec<u8>>, Bound<Vec<u8>>) {
+    prefix_span(KeyPrefix::IndexField(Cow::Borrowed(collection), Cow::Borrowed(field)).encode())
+}
+
+/// Key range covering every unique guard of the index on `field`.
+fn guard_span(collection: &str, field: &str) -> (Bound<Vec<u8>>, Bound<Vec<u8>>) {
+    prefix_span(KeyPrefix::Unique(Cow::Borrowed(collection), Cow::Borrowed(field)).encode())
+}
+
+fn prefix_span(prefix: Vec<u8>) -> (Bound<Vec<u8>>, Bound<Vec<u8>>) {
+    let end = match increment_prefix(&prefix) {
+        Some(upper) => Bound::Excluded(upper),
+        None => Bound::Unbounded,
+    };
+    (Bound::Included(prefix), end)
+}
+
 impl<S: Store + BackupStore> KvEngine<S> {
     pub fn backup(&self, dest: &Path) -> Result<(), EngineError> {
         self.store.backup(dest)?;
@@ -64,6 +460,71 @@ impl<S: Store + BackupStore> KvEngine<S> {
     }
 }
 
+impl<S: IncrementalBackupStore> KvEngine<S> {
+    pub fn create_backup(
+        &self,
+        backup_dir: &Path,
+        policy: &BackupPolicy,
+    ) -> Result<BackupInfo, EngineError> {
+        Ok(self.store.create_backup(backup_dir, policy)?)
+    }
+
+    pub fn list_backups(backup_dir: &Path) -> Result<Vec<BackupInfo>, EngineError> {
+        Ok(S::list_backups(backup_dir)?)
+    }
+
+    pub fn verify_backup(backup_dir: &Path, backup_id: u32) -> Result<(), EngineError> {
+        S::verify_backup(backup_dir, backup_id)?;
+        Ok(())
+    }
+
+    pub fn restore(backup_dir: &Path, backup_id: u32, dest: &Path) -> Result<(), EngineError> {
+        S::restore(backup_dir, backup_id, dest)?;
+        Ok(())
+    }
+}
+
+impl<S: SnapshotStore> KvEngine<S> {
+    /// An engine over a point-in-time snapshot of the store.
+    ///
+    /// The snapshot's clock is frozen at the moment it is taken, so TTL
+    /// expiry is evaluated the same way by every read against it.
+    pub fn snapshot(&self) -> Result<KvEngine<S::Snapshot>, EngineError> {
+        let now_millis = (self.clock)();
+        Ok(KvEngine {
+            store: self.store.snapshot()?,
+            clock: Arc::new(move || now_millis),
+            index_filter: self.index_filter.clone(),
+            evictions: Mutex::new(HashMap::new()),
+            codecs: Arc::clone(&self.codecs),
+        })
+    }
+}
+
+impl<S: ForkableStore> KvEngine<S> {
+    /// An independent, writable engine over a fork of the store, sharing
+    /// this engine's clock. Eviction counts start from zero.
+    pub fn fork(&self) -> Result<KvEngine<S>, EngineError> {
+        Ok(KvEngine {
+            store: self.store.fork()?,
+            clock: Arc::clone(&self.clock),
+            index_filter: self.index_filter.clone(),
+            evictions: Mutex::new(HashMap::new()),
+            codecs: Arc::clone(&self.codecs),
+        })
+    }
+}
+
+impl<S: InstrumentedStore> KvEngine<S> {
+    pub fn store_metrics(&self) -> MetricsSnapshot {
+        self.store.metrics()
+    }
+
+    pub fn reset_store_metrics(&self) {
+        self.store.reset_metrics()
+    }
+}
+
 impl<S: Store> crate::traits::Engine for KvEngine<S> {
     type Txn<'a>
         = KvTransaction<'a, S>
@@ -73,6 +534,11 @@ impl<S: Store> crate::traits::Engine for KvEngine<S> {
     fn begin(&self, read_only: bool) -> Result<Self::Txn<'_>, EngineError> {
         let now_millis = (self.clock)();
         let txn = self.store.begin(read_only)?;
-        Ok(KvTransaction { txn, now_millis })
+        Ok(KvTransaction {
+            txn,
+            now_millis,
+            index_filter: self.index_filter.clone(),
+            codecs: Arc::clone(&self.codecs),
+        })
     }
 }
@@ -1,26 +1,102 @@
 use std::borrow::Cow;
-use std::cmp::Ordering;
+use std::ops::Bound;
+use std::sync::Arc;
 
 use bson::raw::{RawBsonRef, RawDocument, RawDocumentBuf};
-use slate_store::{Store, StoreError, Transaction};
+use slate_store::{Store, Transaction, increment_prefix};
 
 use crate::encoding::bson_value::BsonValue;
+use crate::encoding::component;
 use crate::encoding::index_record::is_index_expired;
 use crate::encoding::{IndexRecord, Key, KeyPrefix, Record};
 use crate::error::EngineError;
 use crate::index_sync::{IndexChanges, IndexDiff};
-use crate::traits::{CollectionHandle, EngineTransaction, IndexEntry, IndexRange};
+use crate::traits::{
+    CollectionHandle, EngineTransaction, IndexEntry, IndexFilterCompiler, IndexRange,
+};
 use crate::validate::validate_raw_document;
 
+use super::CodecCache;
+
 // ── KvTransaction ──────────────────────────────────────────────
 
 pub struct KvTransaction<'a, S: Store + 'a> {
     pub(crate) txn: S::Txn<'a>,
     pub(crate) now_millis: i64,
+    pub(crate) index_filter: Option<IndexFilterCompiler>,
+    pub(crate) codecs: CodecCache,
 }
 
 // ── Private helpers ─────────────────────────────────────────────
 
+/// The guard key of the value an index entry holds, and the owner to store
+/// in it. `None` for keys that aren't index entries and for placeholders,
+/// which any number of documents may share.
+pub(crate) fn unique_guard(collection: &str, index_key: &[u8]) -> Option<(Vec<u8>, Vec<u8>)> {
+    let (Key::Index(_, index, doc_id), value_bytes) = Key::decode_index(index_key)? else {
+        return None;
+    };
+    if component::is_placeholder(value_bytes) {
+        return None;
+    }
+    let guard = Key::Unique(
+        Cow::Borrowed(collection),
+        Cow::Borrowed(&index),
+        Cow::Borrowed(value_bytes),
+    )
+    .encode();
+    let mut owner = Vec::with_capacity(3 + doc_id.bytes.len());
+    doc_id.write_length_prefixed(&mut owner);
+    Some((guard, owner))
+}
+
+/// Key span `[start, end)` of an index scan: entries whose leading
+/// components equal `prefix`, with the next component within the bounds.
+/// A range only reaches values of its bounds' type, so bounds of two
+/// different types select nothing.
+#[allow(clippy::type_complexity)]
+fn value_span(
+    field_prefix: &[u8],
+    prefix: &[bson::Bson],
+    lower: Option<(&bson::Bson, bool)>,
+    upper: Option<(&bson::Bson, bool)>,
+) -> Result<(Vec<u8>, Option<Vec<u8>>), EngineError> {
+    let unindexable =
+        |v: &bson::Bson| EngineError::InvalidKey(format!("value {v} cannot be indexed"));
+    let encode = |v: &bson::Bson| component::encode_value(v).ok_or_else(|| unindexable(v));
+    let type_edge = |v: &bson::Bson, offset: u8| -> Result<Vec<u8>, EngineError> {
+        let tag = component::type_prefix(v).ok_or_else(|| unindexable(v))?;
+        Ok(vec![tag + offset])
+    };
+
+    let mut base = field_prefix.to_vec();
+    for value in prefix {
+        base.extend_from_slice(&encode(value)?);
+    }
+
+    if let (Some((lb, _)), Some((ub, _))) = (lower, upper)
+        && type_edge(lb, 0)? != type_edge(ub, 0)?
+    {
+        return Ok((base.clone(), Some(base)));
+    }
+
+    let start = match (lower, upper) {
+        (Some((lb, true)), _) => [base.as_slice(), &encode(lb)?].concat(),
+        (Some((lb, false)), _) => {
+            increment_prefix(&[base.as_slice(), &encode(lb)?].concat()).unwrap_or_default()
+        }
+        (None, Some((ub, _))) => [base.as_slice(), &type_edge(ub, 0)?].concat(),
+        (None, None) => base.clone(),
+    };
+    let end = match (lower, upper) {
+        (_, Some((ub, true))) => increment_prefix(&[base.as_slice(), &encode(ub)?].concat()),
+        (_, Some((ub, false))) => Some([base.as_slice(), &encode(ub)?].concat()),
+        (Some((lb, _)), None) => Some([base.as_slice(), &type_edge(lb, 1)?].concat()),
+        (None, None) => increment_prefix(&base),
+    };
+    Ok((start, end))
+}
+
 impl<'a, S: Store + 'a> KvTransaction<'a, S> {
     pub(crate) fn extract_pk(
         &self,
@@ -29,9 +105,14 @@ impl<'a, S: Store + 'a> KvTransaction<'a, S> {
     ) -> Result<BsonValue<'static>, EngineError> {
         match doc.get(handle.pk_path()) {
             Ok(Some(val)) => {
-                let bv = BsonValue::from_raw_bson_ref(val).ok_or_else(|| {
-                    EngineError::InvalidDocument("unsupported pk type".into())
-                })?;
+                let unsupported = || EngineError::InvalidDocument("unsupported pk type".into());
+                if matches!(
+                    val,
+                    RawBsonRef::Null | RawBsonRef::Array(_) | RawBsonRef::Document(_)
+                ) {
+                    return Err(unsupported());
+                }
+                let bv = BsonValue::from_raw_bson_ref(val).ok_or_else(unsupported)?;
                 Ok(BsonValue {
                     tag: bv.tag,
                     bytes: Cow::Owned(bv.bytes.into_owned()),
@@ -44,11 +125,91 @@ impl<'a, S: Store + 'a> KvTransaction<'a, S> {
         }
     }
 
+    /// Reject index puts that would give a value of a unique index to a
+    /// second document. Entries of expired records don't count.
+    fn check_unique(
+        &self,
+        handle: &CollectionHandle<<S::Txn<'a> as Transaction>::Cf>,
+        puts: &[(Vec<u8>, Vec<u8>)],
+    ) -> Result<(), EngineError> {
+        if handle.unique_indexes().is_empty() {
+            return Ok(());
+        }
+        for (key, _) in puts {
+            let Some((Key::Index(_, index, doc_id), value_bytes)) = Key::decode_index(key) else {
+                continue;
+            };
+            // Documents without the field may share the index.
+            if !handle.is_unique(&index) || component::is_placeholder(value_bytes) {
+                continue;
+            }
+            let prefix = KeyPrefix::IndexValue(
+                Cow::Borrowed(handle.name()),
+                Cow::Borrowed(&index),
+                value_bytes,
+            )
+            .encode();
+            for result in self.txn.scan_prefix(handle.cf(), &prefix)? {
+                let (other_key, metadata) = result?;
+                let Some((Key::Index(_, _, other_id), _)) = Key::decode_index(&other_key) else {
+                    continue;
+                };
+                if other_id != doc_id && !is_index_expired(&metadata, self.now_millis)
+                {
+                    return Err(EngineError::DuplicateKey(format!(
+                        "{}.{index} (conflicts with {other_id})",
+                        handle.name()
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Point the guards of the unique values in `changes` at their owners.
+    ///
+    /// Scans aren't conflict-checked, so two transactions putting the same
+    /// value both pass [`check_unique`](Self::check_unique). Both also
+    /// write the value's guard, so one of them fails to commit with a
+    /// conflict on every backend.
+    fn update_unique_guards(
+        &self,
+        handle: &CollectionHandle<<S::Txn<'a> as Transaction>::Cf>,
+        changes: &IndexChanges,
+    ) -> Result<(), EngineError> {
+        if handle.unique_indexes().is_empty() {
+            return Ok(());
+        }
+        let is_unique = |key: &[u8]| match Key::decode_index(key) {
+            Some((Key::Index(_, index, _), _)) => handle.is_unique(&index),
+            _ => false,
+        };
+        for key in changes.deletes.iter().filter(|key| is_unique(key)) {
+            let Some((guard, owner)) = unique_guard(handle.name(), key) else {
+                continue;
+            };
+            // Another document may own the value by now.
+            if self.txn.get(handle.cf(), &guard)?.as_deref() == Some(owner.as_slice()) {
+                self.txn.delete(handle.cf(), &guard)?;
+            }
+        }
+        for (key, _) in changes.puts.iter().filter(|(key, _)| is_unique(key)) {
+            if let Some((guard, owner)) = unique_guard(handle.name(), key) {
+                self.txn.put(handle.cf(), &guard, &owner)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Write index changes, enforcing unique indexes first so a rejected
+    /// write leaves nothing behind.
     pub(crate) fn apply_index_changes(
         &self,
         handle: &CollectionHandle<<S::Txn<'a> as Transaction>::Cf>,
         changes: &IndexChanges,
     ) -> Result<(), EngineError> {
+        self.check_unique(handle, &changes.puts)?;
+        self.update_unique_guards(handle, changes)?;
         if !changes.deletes.is_empty() {
             let refs: Vec<&[u8]> = changes.deletes.iter().map(|k| k.as_slice()).collect();
             self.txn.delete_batch(handle.cf(), &refs)?;
@@ -63,6 +224,29 @@ impl<'a, S: Store + 'a> KvTransaction<'a, S> {
         }
         Ok(())
     }
+
+    /// Delete the record for `doc_id` and its index entries. Returns the
+    /// record's key and value bytes, or `None` if there was no such record.
+    pub(crate) fn delete_record(
+        &self,
+        handle: &CollectionHandle<<S::Txn<'a> as Transaction>::Cf>,
+        doc_id: &BsonValue<'_>,
+    ) -> Result<Option<u64>, EngineError> {
+        let encoded = Key::encode_record_key(handle.name(), doc_id);
+        let Some(data) = self.txn.get(handle.cf(), &encoded)? else {
+            return Ok(None);
+        };
+        let changes = IndexDiff::for_delete(doc_id)
+            .with_old_record(Some(data.as_slice()))
+            .with_codec(handle.codec())
+            .with_property_paths(handle.indexes())
+            .with_filters(handle.index_filters())
+            .with_sparse_path(handle.ttl_path())
+            .diff(handle.name())?;
+        self.apply_index_changes(handle, &changes)?;
+        self.txn.delete(handle.cf(), &encoded)?;
+        Ok(Some((encoded.len() + data.len()) as u64))
+    }
 }
 
 // ── EngineTransaction impl ──────────────────────────────────────
@@ -81,7 +265,10 @@ impl<'a, S: Store + 'a> EngineTransaction for KvTransaction<'a, S> {
         match self.txn.get(handle.cf(), &encoded)? {
             None => Ok(None),
             Some(data) if Record::is_expired(&data, self.now_millis) => Ok(None),
-            Some(data) => Ok(Some(RawDocumentBuf::try_from(Record::from_bytes(data)?)?)),
+            Some(data) => Ok(Some(RawDocumentBuf::try_from(Record::from_bytes_with(
+                data,
+                handle.codec(),
+            )?)?)),
         }
     }
 
@@ -97,6 +284,7 @@ impl<'a, S: Store + 'a> EngineTransaction for KvTransaction<'a, S> {
         let encoded_key = Key::encode_record_key(handle.name(), &doc_id);
         let record = Record::encoder()
             .with_ttl_at_path(handle.ttl_path())
+            .with_codec(handle.codec())
             .encode(doc);
         let old_data = self.txn.get(handle.cf(), &encoded_key)?;
 
@@ -110,11 +298,14 @@ impl<'a, S: Store + 'a> EngineTransaction for KvTransaction<'a, S> {
 
         let changes = IndexDiff::new(&record, &doc_id)
             .with_old_record(old_data.as_deref())
+            .with_codec(handle.codec())
             .with_property_paths(handle.indexes())
-            .with_property_path(handle.ttl_path())
+            .with_filters(handle.index_filters())
+            .with_sparse_path(handle.ttl_path())
             .diff(handle.name())?;
 
         self.apply_index_changes(handle, &changes)?;
+        self.mark_multikey(handle, &changes.multikey)?;
         self.txn
             .put(handle.cf(), &encoded_key, record.as_bytes())?;
 
@@ -141,15 +332,19 @@ impl<'a, S: Store + 'a> EngineTransaction for KvTransaction<'a, S> {
 
         let record = Record::encoder()
             .with_ttl_at_path(handle.ttl_path())
+            .with_codec(handle.codec())
             .encode(doc);
 
         let changes = IndexDiff::new(&record, &doc_id)
             .with_old_record(old_data.as_deref())
+            .with_codec(handle.codec())
             .with_property_paths(handle.indexes())
-            .with_property_path(handle.ttl_path())
+            .with_filters(handle.index_filters())
+            .with_sparse_path(handle.ttl_path())
             .diff(handle.name())?;
 
         self.apply_index_changes(handle, &changes)?;
+        self.mark_multikey(handle, &changes.multikey)?;
         self.txn
             .put(handle.cf(), &encoded_key, record.as_bytes())?;
 
@@ -163,20 +358,7 @@ impl<'a, S: Store + 'a> EngineTransaction for KvTransaction<'a, S> {
     ) -> Result<(), EngineError> {
         let doc_id = BsonValue::from_raw_bson_ref(*doc_id)
             .ok_or_else(|| EngineError::InvalidDocument("unsupported _id type".into()))?;
-        let encoded = Key::encode_record_key(handle.name(), &doc_id);
-
-        let old_data = self.txn.get(handle.cf(), &encoded)?;
-        if let Some(ref data) = old_data {
-            let changes = IndexDiff::for_delete(&doc_id)
-                .with_old_record(Some(data.as_slice()))
-                .with_property_paths(handle.indexes())
-                .with_property_path(handle.ttl_path())
-                .diff(handle.name())?;
-
-            self.apply_index_changes(handle, &changes)?;
-            self.txn.delete(handle.cf(), &encoded)?;
-        }
-
+        self.delete_record(handle, &doc_id)?;
         Ok(())
     }
 
@@ -188,15 +370,16 @@ impl<'a, S: Store + 'a> EngineTransaction for KvTransaction<'a, S> {
         EngineError,
     > {
         let now = self.now_millis;
+        let codec = Arc::clone(handle.codec());
         let prefix = KeyPrefix::Record(Cow::Borrowed(handle.name())).encode();
         let iter = self.txn.scan_prefix(handle.cf(), &prefix)?;
         Ok(Box::new(iter.filter_map(move |result| match result {
-            Err(e) => Some(Err(EngineError::Store(e))),
+            Err(e) => Some(Err(EngineError::from(e))),
             Ok((_key_bytes, value_bytes)) => {
                 if Record::is_expired(&value_bytes, now) {
                     return None;
                 }
-                match Record::from_bytes(value_bytes)
+                match Record::from_bytes_with(value_bytes, &codec)
                     .and_then(RawDocumentBuf::try_from)
                 {
                     Ok(doc) => Some(Ok(doc)),
@@ -219,42 +402,34 @@ impl<'a, S: Store + 'a> EngineTransaction for KvTransaction<'a, S> {
 
         let field_prefix =
             KeyPrefix::IndexField(Cow::Borrowed(collection), Cow::Borrowed(field)).encode();
-        let eq_encoded = match &range {
-            IndexRange::Eq(val) => BsonValue::from_bson(val).map(|bv| bv.bytes.into_owned()),
-            _ => None,
-        };
-        let prefix = match &eq_encoded {
-            Some(bytes) => KeyPrefix::IndexValue(
-                Cow::Borrowed(collection),
-                Cow::Borrowed(field),
-                bytes,
-            )
-            .encode(),
-            None => field_prefix.clone(),
-        };
 
-        #[allow(clippy::type_complexity)]
-        let bounds: Option<(Option<Vec<u8>>, bool, Option<Vec<u8>>, bool)> = match range {
-            IndexRange::Range { lower, upper } => Some((
-                lower
-                    .as_ref()
-                    .and_then(|(v, _)| BsonValue::from_bson(v).map(|bv| bv.bytes.into_owned())),
-                lower.as_ref().is_some_and(|(_, incl)| *incl),
-                upper
-                    .as_ref()
-                    .and_then(|(v, _)| BsonValue::from_bson(v).map(|bv| bv.bytes.into_owned())),
-                upper.as_ref().is_some_and(|(_, incl)| *incl),
-            )),
-            _ => None,
+        // Every scan resolves to an exact key span up front: components are
+        // self-delimiting and order-preserving, so no per-entry check is needed.
+        let (start, end) = match range {
+            IndexRange::Full => (field_prefix.clone(), increment_prefix(&field_prefix)),
+            // Filters match null against a missing field too; both sort
+            // ahead of every value.
+            IndexRange::Eq(bson::Bson::Null) => (
+                [field_prefix.as_slice(), &[component::MISSING]].concat(),
+                Some([field_prefix.as_slice(), &[component::NULL + 1]].concat()),
+            ),
+            IndexRange::Eq(value) => {
+                value_span(&field_prefix, std::slice::from_ref(value), None, None)?
+            }
+            IndexRange::Range { lower, upper } => value_span(&field_prefix, &[], lower, upper)?,
+            IndexRange::Compound {
+                prefix,
+                lower,
+                upper,
+            } => value_span(&field_prefix, prefix, lower, upper)?,
         };
-
-        #[allow(clippy::type_complexity)]
-        let mut iter: Box<dyn Iterator<Item = Result<(Vec<u8>, Vec<u8>), StoreError>> + 'b> =
-            if reverse {
-                self.txn.scan_prefix_rev(handle.cf(), &prefix)?
-            } else {
-                self.txn.scan_prefix(handle.cf(), &prefix)?
-            };
+        let end = match &end {
+            Some(end) => Bound::Excluded(end.as_slice()),
+            None => Bound::Unbounded,
+        };
+        let mut iter = self
+            .txn
+            .scan_range(handle.cf(), Bound::Included(&start), end, reverse)?;
 
         let field_prefix_len = field_prefix.len();
         let mut done = false;
@@ -268,7 +443,7 @@ impl<'a, S: Store + 'a> EngineTransaction for KvTransaction<'a, S> {
                 match result {
                     Err(e) => {
                         done = true;
-                        return Some(Err(EngineError::Store(e)));
+                        return Some(Err(EngineError::from(e)));
                     }
                     Ok((key_bytes, metadata_bytes)) => {
                         let entry = match IndexEntry::from_raw(
@@ -285,34 +460,6 @@ impl<'a, S: Store + 'a> EngineTransaction for KvTransaction<'a, S> {
                             }
                         };
 
-                        if let Some((ref lower, lower_inc, ref upper, upper_inc)) = bounds {
-                            let value_bytes = entry.value_bytes();
-                            if let Some(lb) = lower {
-                                let cmp = value_bytes.cmp(lb.as_slice());
-                                if cmp == Ordering::Less
-                                    || (cmp == Ordering::Equal && !lower_inc)
-                                {
-                                    if reverse {
-                                        done = true;
-                                        return None;
-                                    }
-                                    continue;
-                                }
-                            }
-                            if let Some(ub) = upper {
-                                let cmp = value_bytes.cmp(ub.as_slice());
-                                if cmp == Ordering::Greater
-                                    || (cmp == Ordering::Equal && !upper_inc)
-                                {
-                                    if !reverse {
-                                        done = true;
-                                        return None;
-                                    }
-                                    continue;
-                                }
-                            }
-                        }
-
                         if entry.is_expired(ttl) {
                             continue;
                         }
@@ -336,11 +483,13 @@ impl<'a, S: Store + 'a> EngineTransaction for KvTransaction<'a, S> {
         handle: &CollectionHandle<Self::Cf>,
         as_of_millis: i64,
     ) -> Result<u64, EngineError> {
-        let ttl_prefix = KeyPrefix::IndexField(
+        // Only DateTime entries carry a TTL; they sort by time.
+        let mut ttl_prefix = KeyPrefix::IndexField(
             Cow::Borrowed(handle.name()),
             Cow::Borrowed(handle.ttl_path()),
         )
         .encode();
+        ttl_prefix.push(component::DATE_TIME);
         let iter = self.txn.scan_prefix(handle.cf(), &ttl_prefix)?;
 
         let mut expired_ids: Vec<BsonValue<'static>> = Vec::new();
@@ -363,16 +512,7 @@ impl<'a, S: Store + 'a> EngineTransaction for KvTransaction<'a, S> {
 
         let mut deleted = 0u64;
         for doc_id in &expired_ids {
-            let encoded = Key::encode_record_key(handle.name(), doc_id);
-            let old_data = self.txn.get(handle.cf(), &encoded)?;
-            if let Some(ref data) = old_data {
-                let changes = IndexDiff::for_delete(doc_id)
-                    .with_old_record(Some(data.as_slice()))
-                    .with_property_paths(handle.indexes())
-                    .with_property_path(handle.ttl_path())
-                    .diff(handle.name())?;
-                self.apply_index_changes(handle, &changes)?;
-                self.txn.delete(handle.cf(), &encoded)?;
+            if self.delete_record(handle, doc_id)?.is_some() {
                 deleted += 1;
             }
         }
@@ -380,6 +520,18 @@ impl<'a, S: Store + 'a> EngineTransaction for KvTransaction<'a, S> {
         Ok(deleted)
     }
 
+    fn set_savepoint(&mut self) -> Result<(), EngineError> {
+        Ok(self.txn.set_savepoint()?)
+    }
+
+    fn rollback_to_savepoint(&mut self) -> Result<(), EngineError> {
+        Ok(self.txn.rollback_to_savepoint()?)
+    }
+
+    fn release_savepoint(&mut self) -> Result<(), EngineError> {
+        Ok(self.txn.release_savepoint()?)
+    }
+
     fn commit(self) -> Result<(), EngineError> {
         Ok(self.txn.commit()?)
     }
@@ -5,10 +5,12 @@ mod kv;
 mod traits;
 mod validate;
 
-pub use encoding::skip_bson_value;
+pub use encoding::component::{compare_raw_values, compare_values, is_indexable};
+pub use encoding::{Compression, skip_bson_value};
 pub use error::{EncodingError, EngineError};
-pub use kv::{DEFAULT_CF, KvEngine};
+pub use kv::{CollectionStorageStats, DEFAULT_CF, KvEngine};
 pub use traits::{
     Catalog, CollectionHandle, CreateCollectionOptions, Engine, EngineTransaction, FunctionEntry,
-    FunctionKind, IndexEntry, IndexRange, runtime_tag,
+    FunctionKind, IndexConfig, IndexEntry, IndexFilter, IndexFilterCompiler, IndexFilterFn,
+    IndexRange, runtime_tag,
 };
@@ -4,6 +4,7 @@ use std::sync::Arc;
 use bson::raw::{RawBsonRef, RawDocument, RawDocumentBuf};
 use bson::RawBson;
 
+use crate::encoding::{Codec, Compression};
 use crate::error::EngineError;
 
 // ── Function types ──────────────────────────────────────────
@@ -30,15 +31,77 @@ pub struct FunctionEntry {
     pub source: Vec<u8>,
 }
 
+// ── Partial index filters ───────────────────────────────────
+
+/// Membership test of a partial index, compiled from its filter document.
+pub type IndexFilterFn = Arc<dyn Fn(&RawDocument) -> bool + Send + Sync>;
+
+/// Compiles a partial index filter document into an [`IndexFilterFn`].
+///
+/// The engine stores filters as opaque documents. The layer that owns the
+/// filter language installs a compiler with
+/// [`KvEngine::with_index_filter`](crate::KvEngine::with_index_filter).
+pub type IndexFilterCompiler =
+    Arc<dyn Fn(&RawDocument) -> Result<IndexFilterFn, EngineError> + Send + Sync>;
+
+/// The filter of a partial index: only documents it matches get entries.
+#[derive(Clone)]
+pub struct IndexFilter {
+    index: String,
+    document: RawDocumentBuf,
+    matches: IndexFilterFn,
+}
+
+impl IndexFilter {
+    pub fn new(index: impl Into<String>, document: RawDocumentBuf, matches: IndexFilterFn) -> Self {
+        Self {
+            index: index.into(),
+            document,
+            matches,
+        }
+    }
+
+    /// Name of the filtered index.
+    pub fn index(&self) -> &str {
+        &self.index
+    }
+
+    /// The filter document the index was created with.
+    pub fn document(&self) -> &RawDocument {
+        &self.document
+    }
+
+    /// Whether `doc` belongs in the index.
+    pub fn matches(&self, doc: &RawDocument) -> bool {
+        (self.matches)(doc)
+    }
+}
+
+impl fmt::Debug for IndexFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IndexFilter")
+            .field("index", &self.index)
+            .field("document", &self.document)
+            .finish()
+    }
+}
+
 // ── CollectionHandle ────────────────────────────────────────
 
+#[derive(Clone)]
 struct CollectionHandleInner<Cf> {
     name: String,
     cf_name: String,
     cf: Cf,
     indexes: Vec<String>,
+    index_filters: Vec<IndexFilter>,
+    unique_indexes: Vec<String>,
+    multikey_indexes: Vec<String>,
+    outdated_indexes: Vec<String>,
+    building_indexes: Vec<String>,
     pk_path: String,
     ttl_path: String,
+    codec: Arc<Codec>,
 }
 
 /// A resolved collection handle with a live CF reference.
@@ -63,8 +126,14 @@ impl<Cf: fmt::Debug> fmt::Debug for CollectionHandle<Cf> {
             .field("cf_name", &self.inner.cf_name)
             .field("cf", &self.inner.cf)
             .field("indexes", &self.inner.indexes)
+            .field("index_filters", &self.inner.index_filters)
+            .field("unique_indexes", &self.inner.unique_indexes)
+            .field("multikey_indexes", &self.inner.multikey_indexes)
+            .field("outdated_indexes", &self.inner.outdated_indexes)
+            .field("building_indexes", &self.inner.building_indexes)
             .field("pk_path", &self.inner.pk_path)
             .field("ttl_path", &self.inner.ttl_path)
+            .field("compression", self.inner.codec.compression())
             .finish()
     }
 }
@@ -84,12 +153,60 @@ impl<Cf: Clone> CollectionHandle<Cf> {
                 cf_name,
                 cf,
                 indexes,
+                index_filters: Vec::new(),
+                unique_indexes: Vec::new(),
+                multikey_indexes: Vec::new(),
+                outdated_indexes: Vec::new(),
+                building_indexes: Vec::new(),
                 pk_path,
                 ttl_path,
+                codec: Arc::new(Codec::NONE),
             }),
         }
     }
 
+    /// Set the filters of the collection's partial indexes.
+    pub fn with_index_filters(mut self, filters: Vec<IndexFilter>) -> Self {
+        Arc::make_mut(&mut self.inner).index_filters = filters;
+        self
+    }
+
+    /// Set which of the collection's indexes are unique.
+    pub fn with_unique_indexes(mut self, indexes: Vec<String>) -> Self {
+        Arc::make_mut(&mut self.inner).unique_indexes = indexes;
+        self
+    }
+
+    /// Set which of the collection's indexes hold array elements.
+    pub fn with_multikey_indexes(mut self, indexes: Vec<String>) -> Self {
+        Arc::make_mut(&mut self.inner).multikey_indexes = indexes;
+        self
+    }
+
+    /// Set which of the collection's indexes were written in an older key
+    /// format.
+    pub fn with_outdated_indexes(mut self, indexes: Vec<String>) -> Self {
+        Arc::make_mut(&mut self.inner).outdated_indexes = indexes;
+        self
+    }
+
+    /// Set which of the collection's indexes are still being built.
+    pub fn with_building_indexes(mut self, indexes: Vec<String>) -> Self {
+        Arc::make_mut(&mut self.inner).building_indexes = indexes;
+        self
+    }
+
+    /// Set the record compression used to encode and decode documents.
+    pub fn with_compression(self, compression: Compression) -> Self {
+        self.with_codec(Arc::new(Codec::new(compression)))
+    }
+
+    /// Share an already prepared codec, avoiding a new one per handle.
+    pub(crate) fn with_codec(mut self, codec: Arc<Codec>) -> Self {
+        Arc::make_mut(&mut self.inner).codec = codec;
+        self
+    }
+
     pub fn name(&self) -> &str {
         &self.inner.name
     }
@@ -106,6 +223,45 @@ impl<Cf: Clone> CollectionHandle<Cf> {
         &self.inner.indexes
     }
 
+    /// Filters of the partial indexes among [`indexes`](Self::indexes).
+    pub fn index_filters(&self) -> &[IndexFilter] {
+        &self.inner.index_filters
+    }
+
+    /// The filter of `index`, if it is a partial index.
+    pub fn index_filter(&self, index: &str) -> Option<&IndexFilter> {
+        self.inner.index_filters.iter().find(|f| f.index == index)
+    }
+
+    /// The unique indexes among [`indexes`](Self::indexes).
+    pub fn unique_indexes(&self) -> &[String] {
+        &self.inner.unique_indexes
+    }
+
+    /// Whether `index` rejects a value already held by another document.
+    pub fn is_unique(&self, index: &str) -> bool {
+        self.inner.unique_indexes.iter().any(|i| i == index)
+    }
+
+    /// Whether `index` may hold several entries per document, one per
+    /// array element. Its entries don't reproduce the stored value, and a
+    /// document can appear at several positions in index order.
+    pub fn is_multikey(&self, index: &str) -> bool {
+        index.contains("[]") || self.inner.multikey_indexes.iter().any(|i| i == index)
+    }
+
+    /// Whether `index` was written in an older key format and must be
+    /// [reindexed](Catalog::reindex) before scans can rely on it.
+    pub fn is_outdated(&self, index: &str) -> bool {
+        self.inner.outdated_indexes.iter().any(|i| i == index)
+    }
+
+    /// Whether `index` is still being built. Writes keep it up to date, but
+    /// records not yet backfilled have no entries, so scans can't rely on it.
+    pub fn is_building(&self, index: &str) -> bool {
+        self.inner.building_indexes.iter().any(|i| i == index)
+    }
+
     pub fn pk_path(&self) -> &str {
         &self.inner.pk_path
     }
@@ -113,6 +269,14 @@ impl<Cf: Clone> CollectionHandle<Cf> {
     pub fn ttl_path(&self) -> &str {
         &self.inner.ttl_path
     }
+
+    pub fn compression(&self) -> &Compression {
+        self.inner.codec.compression()
+    }
+
+    pub(crate) fn codec(&self) -> &Arc<Codec> {
+        &self.inner.codec
+    }
 }
 
 // ── Engine + Transaction traits ─────────────────────────────
@@ -196,6 +360,16 @@ pub trait EngineTransaction {
         as_of_millis: i64,
     ) -> Result<u64, EngineError>;
 
+    // ── Savepoints ─────────────────────────────────────────────
+
+    /// Mark the current state so later writes can be undone without
+    /// aborting the transaction. Savepoints nest as a stack.
+    fn set_savepoint(&mut self) -> Result<(), EngineError>;
+    /// Undo every write since the most recent savepoint and pop it.
+    fn rollback_to_savepoint(&mut self) -> Result<(), EngineError>;
+    /// Pop the most recent savepoint, keeping its writes.
+    fn release_savepoint(&mut self) -> Result<(), EngineError>;
+
     // ── Lifecycle ──────────────────────────────────────────────
 
     fn commit(self) -> Result<(), EngineError>;
@@ -209,13 +383,23 @@ pub trait EngineTransaction {
 pub enum IndexRange<'a> {
     /// Scan all entries for the field.
     Full,
-    /// Exact value match (narrow prefix scan).
+    /// Exact value match (narrow prefix scan). Null matches both null
+    /// and a missing field, the way filters do.
     Eq(&'a bson::Bson),
-    /// One- or two-sided range with inclusive/exclusive bounds.
+    /// One- or two-sided range with inclusive/exclusive bounds. Only
+    /// values of the bounds' type are in range.
     Range {
         lower: Option<(&'a bson::Bson, bool)>,
         upper: Option<(&'a bson::Bson, bool)>,
     },
+    /// Compound index: exact values for the leading fields, then an optional
+    /// range on the next field. Null in `prefix` matches an explicit null,
+    /// not a missing field. A range only reaches values of the bounds' type.
+    Compound {
+        prefix: &'a [bson::Bson],
+        lower: Option<(&'a bson::Bson, bool)>,
+        upper: Option<(&'a bson::Bson, bool)>,
+    },
 }
 
 /// A raw index scan entry with lazy decoding.
@@ -255,7 +439,7 @@ impl IndexEntry {
         })
     }
 
-    /// Raw sortable-encoded value bytes (no type tag).
+    /// The encoded value components.
     #[inline]
     pub(crate) fn value_bytes(&self) -> &[u8] {
         &self.key[self.value_start..self.doc_id_start]
@@ -267,6 +451,11 @@ impl IndexEntry {
         crate::encoding::index_record::is_index_expired(&self.metadata, now_millis)
     }
 
+    /// The encoded doc_id, for comparing entries without decoding it.
+    pub fn doc_id_bytes(&self) -> &[u8] {
+        &self.key[self.doc_id_start..]
+    }
+
     /// Lazily decode the doc_id to `RawBson`.
     pub fn doc_id(&self) -> Result<RawBson, EngineError> {
         let (bv, _) = crate::encoding::bson_value::BsonValue::parse_length_prefixed(
@@ -277,16 +466,136 @@ impl IndexEntry {
             .ok_or_else(|| EngineError::InvalidKey("unsupported doc_id type in index key".into()))
     }
 
-    /// Lazily decode the indexed value to `RawBson`.
+    /// Lazily decode the indexed value to `RawBson`. For a compound index
+    /// this is an array holding one value per field.
     pub fn value(&self) -> Result<RawBson, EngineError> {
-        let tag = bson::spec::ElementType::from(self.metadata[0])
-            .ok_or_else(|| EngineError::InvalidKey("unknown type byte in index metadata".into()))?;
-        crate::encoding::bson_value::BsonValue::from_parts(tag, self.value_bytes())
-            .to_raw_bson()
+        crate::encoding::index_record::decode_value(self.metadata[0], self.value_bytes())
             .ok_or_else(|| EngineError::InvalidKey("malformed value in index key".into()))
     }
 }
 
+/// Definition of a secondary index.
+///
+/// An index covers one field or, for a compound index, several fields in
+/// key order. The catalog identifies an index by its [name](Self::name):
+/// the fields joined by `+`, e.g. `status+created_at`. Converting from a
+/// string parses such a name, so `"email"` is a single-field index.
+///
+/// A partial index has a [filter](Self::with_filter): a filter document
+/// that decides which documents get an entry. A [unique](Self::unique)
+/// index allows at most one document per indexed value.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexConfig {
+    pub fields: Vec<String>,
+    pub filter: Option<RawDocumentBuf>,
+    pub unique: bool,
+}
+
+impl IndexConfig {
+    /// Separator between the fields of a compound index name.
+    pub const SEPARATOR: char = '+';
+
+    pub fn new<I, F>(fields: I) -> Self
+    where
+        I: IntoIterator<Item = F>,
+        F: Into<String>,
+    {
+        Self {
+            fields: fields.into_iter().map(Into::into).collect(),
+            filter: None,
+            unique: false,
+        }
+    }
+
+    /// Only index documents matching `filter`.
+    pub fn with_filter(mut self, filter: RawDocumentBuf) -> Self {
+        self.filter = Some(filter);
+        self
+    }
+
+    /// Reject writes that would give two documents the same indexed value.
+    pub fn unique(mut self) -> Self {
+        self.unique = true;
+        self
+    }
+
+    /// Parse an index name back into its fields.
+    pub fn from_name(name: &str) -> Self {
+        Self::new(name.split(Self::SEPARATOR))
+    }
+
+    /// The catalog name of the index.
+    pub fn name(&self) -> String {
+        self.fields.join(&Self::SEPARATOR.to_string())
+    }
+
+    /// Whether the index covers more than one field.
+    pub fn is_compound(&self) -> bool {
+        self.fields.len() > 1
+    }
+
+    /// Check that the index can be created: at least one field, no empty or
+    /// repeated fields, no field containing [`SEPARATOR`](Self::SEPARATOR)
+    /// (the name must parse back into the same fields), and no `[]` array
+    /// paths in a compound index.
+    pub fn validate(&self) -> Result<(), EngineError> {
+        let invalid = |msg: String| Err(EngineError::InvalidIndex(msg));
+        if self.fields.is_empty() {
+            return invalid("an index needs at least one field".into());
+        }
+        for (i, field) in self.fields.iter().enumerate() {
+            if field.is_empty() || field.contains(Self::SEPARATOR) {
+                return invalid(format!("invalid index field '{field}'"));
+            }
+            if self.fields[..i].contains(field) {
+                return invalid(format!("field '{field}' appears twice"));
+            }
+            if self.is_compound() && field.contains("[]") {
+                return invalid(format!(
+                    "compound index field '{field}' cannot traverse arrays"
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl From<&str> for IndexConfig {
+    fn from(name: &str) -> Self {
+        Self::from_name(name)
+    }
+}
+
+impl From<String> for IndexConfig {
+    fn from(name: String) -> Self {
+        Self::from_name(&name)
+    }
+}
+
+impl From<&String> for IndexConfig {
+    fn from(name: &String) -> Self {
+        Self::from_name(name)
+    }
+}
+
+impl From<&[&str]> for IndexConfig {
+    fn from(fields: &[&str]) -> Self {
+        Self::new(fields.iter().copied())
+    }
+}
+
+impl<const N: usize> From<[&str; N]> for IndexConfig {
+    fn from(fields: [&str; N]) -> Self {
+        Self::new(fields)
+    }
+}
+
+impl From<Vec<String>> for IndexConfig {
+    fn from(fields: Vec<String>) -> Self {
+        Self::new(fields)
+    }
+}
+
 /// Options for creating a new collection. All fields are optional
 /// and fall back to engine defaults when `None`.
 #[derive(Debug, Clone, Default)]
@@ -295,6 +604,8 @@ pub struct CreateCollectionOptions {
     pub pk_path: Option<String>,
     /// TTL field path. Defaults to `"ttl"`.
     pub ttl_path: Option<String>,
+    /// Record payload compression. Defaults to none.
+    pub compression: Option<Compression>,
 }
 
 /// Catalog operations for collection and index metadata.
@@ -321,10 +632,41 @@ pub trait Catalog: EngineTransaction {
 
     fn drop_collection(&mut self, cf: &str, name: &str) -> Result<(), EngineError>;
 
-    fn create_index(&mut self, cf: &str, collection: &str, field: &str)
-        -> Result<(), EngineError>;
+    /// Create an index and backfill it from the existing records.
+    fn create_index(
+        &mut self,
+        cf: &str,
+        collection: &str,
+        index: impl Into<IndexConfig>,
+    ) -> Result<(), EngineError>;
+
+    /// Drop an index by [name](IndexConfig::name) and remove its entries.
+    fn drop_index(&mut self, cf: &str, collection: &str, name: &str) -> Result<(), EngineError>;
+
+    /// Rebuild an index's entries from the records in the current key
+    /// format, keeping its options.
+    fn reindex(&mut self, cf: &str, collection: &str, name: &str) -> Result<(), EngineError>;
+
+    /// Create an index in the building state, without entries. Writes
+    /// maintain it from here on; [`continue_index_build`](Self::continue_index_build)
+    /// backfills the existing records.
+    fn start_index_build(
+        &mut self,
+        cf: &str,
+        collection: &str,
+        index: impl Into<IndexConfig>,
+    ) -> Result<(), EngineError>;
 
-    fn drop_index(&mut self, cf: &str, collection: &str, field: &str) -> Result<(), EngineError>;
+    /// Backfill up to `batch` records past the build's resume cursor and
+    /// advance it. Returns `true` once every record is indexed and the index
+    /// is ready, including when it already was.
+    fn continue_index_build(
+        &mut self,
+        cf: &str,
+        collection: &str,
+        name: &str,
+        batch: usize,
+    ) -> Result<bool, EngineError>;
 
     /// Store a named function (trigger, validator, or computed field) for a collection.
     fn create_function(
@@ -0,0 +1,213 @@
+use std::collections::BTreeSet;
+use std::panic::{AssertUnwindSafe, catch_unwind};
+
+use bson::rawdoc;
+use slate_engine::{
+    Catalog, DEFAULT_CF, Engine, EngineError, EngineTransaction, IndexRange, KvEngine,
+};
+use slate_store::{Fault, FaultPlan, FaultyStore, MemoryStore, StoreOp};
+
+const INDEXED: [&str; 2] = ["name", "tier"];
+
+/// Engine over a fault-injecting store, seeded with a few indexed records.
+fn seeded_engine() -> (KvEngine<FaultyStore<MemoryStore>>, FaultyStore<MemoryStore>) {
+    let faults = FaultyStore::new(MemoryStore::new());
+    let engine = KvEngine::new(faults.clone());
+    let mut txn = engine.begin(false).unwrap();
+    txn.create_collection(DEFAULT_CF, "c", &Default::default())
+        .unwrap();
+    for field in INDEXED {
+        txn.create_index(DEFAULT_CF, "c", field).unwrap();
+    }
+    let handle = txn.collection(DEFAULT_CF, "c").unwrap();
+    txn.put(&handle, &rawdoc! { "_id": "a", "name": "Alice", "tier": 1 })
+        .unwrap();
+    txn.put(&handle, &rawdoc! { "_id": "b", "name": "Bob", "tier": 2 })
+        .unwrap();
+    txn.put(&handle, &rawdoc! { "_id": "c", "name": "Carol" })
+        .unwrap();
+    txn.commit().unwrap();
+    (engine, faults)
+}
+
+/// A transaction touching every index maintenance path: update with changed
+/// values, field removal, insert, put_nx, and delete.
+fn workload(engine: &KvEngine<FaultyStore<MemoryStore>>) -> Result<(), EngineError> {
+    let txn = engine.begin(false)?;
+    let handle = txn.collection(DEFAULT_CF, "c")?;
+    txn.put(
+        &handle,
+        &rawdoc! { "_id": "a", "name": "Alicia", "tier": 3 },
+    )?;
+    txn.put(&handle, &rawdoc! { "_id": "b", "name": "Bob" })?;
+    txn.put(&handle, &rawdoc! { "_id": "d", "name": "Dave", "tier": 1 })?;
+    txn.put_nx(&handle, &rawdoc! { "_id": "e", "tier": 2 })?;
+    txn.delete(&handle, &bson::raw::RawBsonRef::String("c"))?;
+    txn.commit()
+}
+
+/// Every record as a debug string, for comparing whole-collection states.
+fn records(engine: &KvEngine<FaultyStore<MemoryStore>>) -> BTreeSet<String> {
+    let txn = engine.begin(true).unwrap();
+    let handle = txn.collection(DEFAULT_CF, "c").unwrap();
+    txn.scan(&handle)
+        .unwrap()
+        .map(|doc| format!("{:?}", doc.unwrap()))
+        .collect()
+}
+
+/// Assert that every index holds exactly one entry per record, with the
+/// record's current value or null where it lacks the field — no orphans,
+/// no gaps.
+fn assert_consistent(engine: &KvEngine<FaultyStore<MemoryStore>>) {
+    let txn = engine.begin(true).unwrap();
+    let handle = txn.collection(DEFAULT_CF, "c").unwrap();
+    let docs: Vec<_> = txn.scan(&handle).unwrap().map(|d| d.unwrap()).collect();
+    for field in INDEXED {
+        let expected: BTreeSet<String> = docs
+            .iter()
+            .map(|doc| {
+                let value = doc
+                    .get(field)
+                    .unwrap()
+                    .map_or(bson::RawBson::Null, bson::RawBson::from);
+                let id = doc.get("_id").unwrap().unwrap();
+                format!("{value:?}/{:?}", bson::RawBson::from(id))
+            })
+            .collect();
+        let actual: BTreeSet<String> = txn
+            .scan_index(&handle, field, IndexRange::Full, false)
+            .unwrap()
+            .map(|entry| {
+                let entry = entry.unwrap();
+                format!("{:?}/{:?}", entry.value().unwrap(), entry.doc_id().unwrap())
+            })
+            .collect();
+        assert_eq!(actual, expected, "index on {field} out of sync");
+    }
+}
+
+/// Run the workload with `plan` armed and check the outcome is all-or-nothing
+/// with consistent indexes.
+fn run_with_plan(plan: FaultPlan) -> bool {
+    let (engine, faults) = seeded_engine();
+    let before = records(&engine);
+    faults.arm(plan);
+    let result = workload(&engine);
+    let fired = faults.fired() > 0;
+    faults.disarm();
+
+    assert_consistent(&engine);
+    let after = records(&engine);
+    if result.is_err() {
+        assert_eq!(after, before, "failed transaction left partial writes");
+    } else {
+        assert_ne!(after, before);
+    }
+    fired
+}
+
+// ── Write failures ──────────────────────────────────────────
+
+#[test]
+fn failed_writes_never_leave_indexes_inconsistent() {
+    for op in [
+        StoreOp::Put,
+        StoreOp::PutBatch,
+        StoreOp::Delete,
+        StoreOp::DeleteBatch,
+    ] {
+        // Walk the failure point across every call until the plan no
+        // longer fires (i.e. the workload makes fewer than `nth` calls).
+        for nth in 1.. {
+            if !run_with_plan(FaultPlan::new().on_cf(DEFAULT_CF, op, nth, Fault::Error)) {
+                assert!(nth > 1, "workload never calls {op}");
+                break;
+            }
+        }
+    }
+}
+
+#[test]
+fn partial_batches_never_leave_indexes_inconsistent() {
+    for op in [StoreOp::PutBatch, StoreOp::DeleteBatch] {
+        for nth in 1.. {
+            if !run_with_plan(FaultPlan::new().on(op, nth, Fault::PartialBatch(1))) {
+                assert!(nth > 1, "workload never calls {op}");
+                break;
+            }
+        }
+    }
+}
+
+#[test]
+fn failed_read_never_leaves_indexes_inconsistent() {
+    for nth in 1.. {
+        if !run_with_plan(FaultPlan::new().on_cf(DEFAULT_CF, StoreOp::Get, nth, Fault::Error)) {
+            break;
+        }
+    }
+}
+
+#[test]
+fn failed_commit_after_partial_batch_discards_everything() {
+    assert!(run_with_plan(
+        FaultPlan::new().partial_batch(1, 1).fail_commit(1)
+    ));
+
+    // Even if the caller ignores the batch error and commits anyway, the
+    // failed commit must not publish the half-written batch.
+    let (engine, faults) = seeded_engine();
+    let before = records(&engine);
+    faults.arm(FaultPlan::new().partial_batch(1, 1).fail_commit(1));
+    let txn = engine.begin(false).unwrap();
+    let handle = txn.collection(DEFAULT_CF, "c").unwrap();
+    assert!(
+        txn.put(&handle, &rawdoc! { "_id": "a", "name": "Zed", "tier": 9 })
+            .is_err()
+    );
+    assert!(txn.commit().is_err());
+    faults.disarm();
+
+    assert_consistent(&engine);
+    assert_eq!(records(&engine), before);
+}
+
+// ── Corruption and crashes ──────────────────────────────────
+
+#[test]
+fn corrupted_old_record_fails_update_instead_of_orphaning_entries() {
+    let (engine, faults) = seeded_engine();
+    let before = records(&engine);
+    // The first data-CF read inside `put` is the old record used for the
+    // index diff.
+    faults.arm(FaultPlan::new().on_cf(DEFAULT_CF, StoreOp::Get, 1, Fault::Corrupt));
+
+    let txn = engine.begin(false).unwrap();
+    let handle = txn.collection(DEFAULT_CF, "c").unwrap();
+    let result = txn.put(&handle, &rawdoc! { "_id": "a", "name": "Zed", "tier": 9 });
+    assert!(
+        matches!(result, Err(EngineError::Encoding(_))),
+        "{result:?}"
+    );
+    drop(txn);
+    assert_eq!(faults.fired(), 1);
+    faults.disarm();
+
+    assert_consistent(&engine);
+    assert_eq!(records(&engine), before);
+}
+
+#[test]
+fn panic_mid_transaction_leaves_indexes_consistent() {
+    for op in [StoreOp::PutBatch, StoreOp::Put, StoreOp::Commit] {
+        let (engine, faults) = seeded_engine();
+        let before = records(&engine);
+        faults.arm(FaultPlan::new().panic_at(op, 1));
+        assert!(catch_unwind(AssertUnwindSafe(|| workload(&engine))).is_err());
+        faults.disarm();
+
+        assert_consistent(&engine);
+        assert_eq!(records(&engine), before);
+    }
+}
@@ -20,6 +20,19 @@ fn count_index<Txn: EngineTransaction>(
         .count()
 }
 
+/// Count index entries for a field that hold a value, leaving out the
+/// null entries of documents without it.
+fn count_valued<Txn: EngineTransaction>(
+    txn: &Txn,
+    handle: &slate_engine::CollectionHandle<Txn::Cf>,
+    field: &str,
+) -> usize {
+    txn.scan_index(handle, field, IndexRange::Full, false)
+        .unwrap()
+        .filter(|entry| entry.as_ref().unwrap().value().unwrap() != bson::RawBson::Null)
+        .count()
+}
+
 // ── put_nx creates index entries ────────────────────────────
 
 #[test]
@@ -41,7 +54,7 @@ fn put_nx_creates_index_entries() {
 }
 
 #[test]
-fn put_nx_no_indexed_field_creates_no_entries() {
+fn put_nx_no_indexed_field_creates_null_entry() {
     let engine = engine();
     let mut txn = engine.begin(false).unwrap();
     txn.create_collection(DEFAULT_CF, "c", &Default::default()).unwrap();
@@ -54,7 +67,15 @@ fn put_nx_no_indexed_field_creates_no_entries() {
 
     let txn = engine.begin(true).unwrap();
     let handle = txn.collection(DEFAULT_CF, "c").unwrap();
-    assert_eq!(count_index(&txn, &handle, "name"), 0);
+    assert_eq!(count_index(&txn, &handle, "name"), 1);
+    assert_eq!(count_valued(&txn, &handle, "name"), 0);
+    let null = bson::Bson::Null;
+    let entries: Vec<_> = txn
+        .scan_index(&handle, "name", IndexRange::Eq(&null), false)
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(entries.len(), 1);
     txn.rollback().unwrap();
 }
 
@@ -130,7 +151,7 @@ fn put_overwrite_removing_field_deletes_entry() {
 
     let txn = engine.begin(true).unwrap();
     let handle = txn.collection(DEFAULT_CF, "c").unwrap();
-    assert_eq!(count_index(&txn, &handle, "name"), 0);
+    assert_eq!(count_valued(&txn, &handle, "name"), 0);
     txn.rollback().unwrap();
 }
 
@@ -146,7 +167,7 @@ fn put_overwrite_adding_field_creates_entry() {
 
     let doc1 = bson::rawdoc! { "_id": "a", "age": 30 };
     txn.put(&handle, &doc1).unwrap();
-    assert_eq!(count_index(&txn, &handle, "name"), 0);
+    assert_eq!(count_valued(&txn, &handle, "name"), 0);
 
     let doc2 = bson::rawdoc! { "_id": "a", "name": "Alice" };
     txn.put(&handle, &doc2).unwrap();
@@ -154,7 +175,7 @@ fn put_overwrite_adding_field_creates_entry() {
 
     let txn = engine.begin(true).unwrap();
     let handle = txn.collection(DEFAULT_CF, "c").unwrap();
-    assert_eq!(count_index(&txn, &handle, "name"), 1);
+    assert_eq!(count_valued(&txn, &handle, "name"), 1);
     txn.rollback().unwrap();
 }
 
@@ -259,7 +280,7 @@ fn index_on_nested_path_missing_parent() {
 
     let txn = engine.begin(true).unwrap();
     let handle = txn.collection(DEFAULT_CF, "c").unwrap();
-    assert_eq!(count_index(&txn, &handle, "address.city"), 0);
+    assert_eq!(count_valued(&txn, &handle, "address.city"), 0);
     txn.rollback().unwrap();
 }
 
@@ -598,3 +619,393 @@ fn many_docs_overwrite_all_exact_count() {
     assert_eq!(count_index(&txn, &handle, "v"), 50);
     txn.rollback().unwrap();
 }
+
+// ── Compound indexes ────────────────────────────────────────
+
+/// Collection "c" with a compound index on `status+n` over five docs.
+fn compound_engine() -> KvEngine<MemoryStore> {
+    let engine = engine();
+    let mut txn = engine.begin(false).unwrap();
+    txn.create_collection(DEFAULT_CF, "c", &Default::default()).unwrap();
+    let handle = txn.collection(DEFAULT_CF, "c").unwrap();
+    for (id, status, n) in [
+        ("a", "active", 3),
+        ("b", "active", 1),
+        ("c", "inactive", 2),
+        ("d", "active", 2),
+        ("e", "active", 10),
+    ] {
+        txn.put(&handle, &bson::rawdoc! { "_id": id, "status": status, "n": n })
+            .unwrap();
+    }
+    // Missing `n` still gets an entry, with a null component.
+    txn.put(&handle, &bson::rawdoc! { "_id": "f", "status": "active" })
+        .unwrap();
+    txn.create_index(DEFAULT_CF, "c", ["status", "n"]).unwrap();
+    txn.commit().unwrap();
+    engine
+}
+
+fn compound_ids<Txn: EngineTransaction>(
+    txn: &Txn,
+    handle: &slate_engine::CollectionHandle<Txn::Cf>,
+    range: IndexRange<'_>,
+    reverse: bool,
+) -> Vec<String> {
+    txn.scan_index(handle, "status+n", range, reverse)
+        .unwrap()
+        .map(|e| e.unwrap().doc_id().unwrap().as_str().unwrap().to_string())
+        .collect()
+}
+
+#[test]
+fn compound_index_is_cataloged_by_name() {
+    let engine = compound_engine();
+    let txn = engine.begin(true).unwrap();
+    let handle = txn.collection(DEFAULT_CF, "c").unwrap();
+    assert_eq!(handle.indexes(), ["status+n"]);
+    assert_eq!(count_index(&txn, &handle, "status+n"), 6);
+    txn.rollback().unwrap();
+}
+
+#[test]
+fn compound_prefix_scan_orders_by_next_field() {
+    let engine = compound_engine();
+    let txn = engine.begin(true).unwrap();
+    let handle = txn.collection(DEFAULT_CF, "c").unwrap();
+    let prefix = [bson::Bson::String("active".into())];
+    let range = || IndexRange::Compound {
+        prefix: &prefix,
+        lower: None,
+        upper: None,
+    };
+    assert_eq!(
+        compound_ids(&txn, &handle, range(), false),
+        ["f", "b", "d", "a", "e"]
+    );
+    assert_eq!(
+        compound_ids(&txn, &handle, range(), true),
+        ["e", "a", "d", "b", "f"]
+    );
+    txn.rollback().unwrap();
+}
+
+#[test]
+fn compound_range_after_prefix() {
+    let engine = compound_engine();
+    let txn = engine.begin(true).unwrap();
+    let handle = txn.collection(DEFAULT_CF, "c").unwrap();
+    let prefix = [bson::Bson::String("active".into())];
+    let two = bson::Bson::Int64(2);
+    let ten = bson::Bson::Int32(10);
+
+    let ids = compound_ids(
+        &txn,
+        &handle,
+        IndexRange::Compound {
+            prefix: &prefix,
+            lower: Some((&two, false)),
+            upper: Some((&ten, true)),
+        },
+        false,
+    );
+    assert_eq!(ids, ["a", "e"]);
+
+    // One-sided: the missing `n` of "f" is never in range.
+    let ids = compound_ids(
+        &txn,
+        &handle,
+        IndexRange::Compound {
+            prefix: &prefix,
+            lower: None,
+            upper: Some((&two, true)),
+        },
+        false,
+    );
+    assert_eq!(ids, ["b", "d"]);
+    txn.rollback().unwrap();
+}
+
+#[test]
+fn compound_index_follows_updates_and_deletes() {
+    let engine = compound_engine();
+    let txn = engine.begin(false).unwrap();
+    let handle = txn.collection(DEFAULT_CF, "c").unwrap();
+    txn.put(
+        &handle,
+        &bson::rawdoc! { "_id": "c", "status": "active", "n": 4 },
+    )
+    .unwrap();
+    txn.delete(&handle, &RawBsonRef::String("a")).unwrap();
+
+    let prefix = [bson::Bson::String("active".into())];
+    let ids = compound_ids(
+        &txn,
+        &handle,
+        IndexRange::Compound {
+            prefix: &prefix,
+            lower: None,
+            upper: None,
+        },
+        false,
+    );
+    assert_eq!(ids, ["f", "b", "d", "c", "e"]);
+    assert_eq!(count_index(&txn, &handle, "status+n"), 5);
+    txn.rollback().unwrap();
+}
+
+#[test]
+fn compound_index_rejects_repeated_or_array_fields() {
+    let engine = engine();
+    let mut txn = engine.begin(false).unwrap();
+    txn.create_collection(DEFAULT_CF, "c", &Default::default()).unwrap();
+    assert!(matches!(
+        txn.create_index(DEFAULT_CF, "c", ["a", "a"]),
+        Err(slate_engine::EngineError::InvalidIndex(_))
+    ));
+    assert!(matches!(
+        txn.create_index(DEFAULT_CF, "c", ["a", "tags.[]"]),
+        Err(slate_engine::EngineError::InvalidIndex(_))
+    ));
+    txn.create_index(DEFAULT_CF, "c", ["a", "b"]).unwrap();
+    assert!(matches!(
+        txn.create_index(DEFAULT_CF, "c", "a+b"),
+        Err(slate_engine::EngineError::IndexExists(_))
+    ));
+    txn.rollback().unwrap();
+}
+
+// ── Partial indexes ─────────────────────────────────────────
+
+/// Test filter compiler: a document matches when it has every field of the
+/// filter with an equal value.
+fn field_equality_filter(
+    filter: &bson::RawDocument,
+) -> Result<slate_engine::IndexFilterFn, slate_engine::EngineError> {
+    let filter = filter.to_owned();
+    Ok(Arc::new(move |doc: &bson::RawDocument| {
+        filter.iter().all(|element| {
+            let (key, value) = element.unwrap();
+            doc.get(key.as_str()).ok().flatten() == Some(value)
+        })
+    }))
+}
+
+fn partial_config() -> slate_engine::IndexConfig {
+    slate_engine::IndexConfig::new(["customer"]).with_filter(bson::rawdoc! { "archived": false })
+}
+
+/// Collection "orders" with a partial index on `customer` over live orders.
+fn partial_engine() -> KvEngine<MemoryStore> {
+    let engine = engine().with_index_filter(field_equality_filter);
+    let mut txn = engine.begin(false).unwrap();
+    txn.create_collection(DEFAULT_CF, "orders", &Default::default()).unwrap();
+    let handle = txn.collection(DEFAULT_CF, "orders").unwrap();
+    for (id, archived) in [("o1", false), ("o2", true), ("o3", true), ("o4", false)] {
+        txn.put(&handle, &bson::rawdoc! { "_id": id, "customer": "c1", "archived": archived })
+            .unwrap();
+    }
+    txn.create_index(DEFAULT_CF, "orders", partial_config()).unwrap();
+    txn.commit().unwrap();
+    engine
+}
+
+#[test]
+fn partial_index_backfills_matching_documents_only() {
+    let engine = partial_engine();
+    let txn = engine.begin(true).unwrap();
+    let handle = txn.collection(DEFAULT_CF, "orders").unwrap();
+    let filter = handle.index_filter("customer").unwrap();
+    assert_eq!(filter.document(), bson::rawdoc! { "archived": false }.as_ref());
+    assert_eq!(count_index(&txn, &handle, "customer"), 2);
+    txn.rollback().unwrap();
+}
+
+#[test]
+fn partial_index_follows_membership_changes() {
+    let engine = partial_engine();
+    let txn = engine.begin(false).unwrap();
+    let handle = txn.collection(DEFAULT_CF, "orders").unwrap();
+
+    // Archive o1, restore o2, insert an archived o5.
+    txn.put(&handle, &bson::rawdoc! { "_id": "o1", "customer": "c1", "archived": true })
+        .unwrap();
+    txn.put(&handle, &bson::rawdoc! { "_id": "o2", "customer": "c1", "archived": false })
+        .unwrap();
+    txn.put(&handle, &bson::rawdoc! { "_id": "o5", "customer": "c1", "archived": true })
+        .unwrap();
+    let ids: Vec<String> = txn
+        .scan_index(&handle, "customer", IndexRange::Full, false)
+        .unwrap()
+        .map(|e| e.unwrap().doc_id().unwrap().as_str().unwrap().to_string())
+        .collect();
+    assert_eq!(ids, ["o2", "o4"]);
+
+    txn.delete(&handle, &RawBsonRef::String("o4")).unwrap();
+    assert_eq!(count_index(&txn, &handle, "customer"), 1);
+    txn.commit().unwrap();
+}
+
+#[test]
+fn partial_index_needs_a_filter_compiler() {
+    let engine = engine();
+    let mut txn = engine.begin(false).unwrap();
+    txn.create_collection(DEFAULT_CF, "orders", &Default::default()).unwrap();
+    assert!(matches!(
+        txn.create_index(DEFAULT_CF, "orders", partial_config()),
+        Err(slate_engine::EngineError::InvalidIndex(_))
+    ));
+    txn.rollback().unwrap();
+}
+
+// ── Unique indexes ──────────────────────────────────────────
+
+fn is_duplicate<T: std::fmt::Debug>(result: Result<T, slate_engine::EngineError>) -> bool {
+    matches!(result, Err(slate_engine::EngineError::DuplicateKey(_)))
+}
+
+#[test]
+fn unique_index_rejects_value_of_another_document() {
+    let engine = engine();
+    let mut txn = engine.begin(false).unwrap();
+    txn.create_collection(DEFAULT_CF, "users", &Default::default()).unwrap();
+    let unique = slate_engine::IndexConfig::new(["email"]).unique();
+    txn.create_index(DEFAULT_CF, "users", unique).unwrap();
+    let handle = txn.collection(DEFAULT_CF, "users").unwrap();
+    assert!(handle.is_unique("email"));
+
+    txn.put(&handle, &bson::rawdoc! { "_id": "u1", "email": "ab" }).unwrap();
+    // Rewriting the owner and indexing a longer value are fine.
+    txn.put(&handle, &bson::rawdoc! { "_id": "u1", "email": "ab", "n": 1 }).unwrap();
+    txn.put(&handle, &bson::rawdoc! { "_id": "u2", "email": "abc" }).unwrap();
+
+    assert!(is_duplicate(txn.put(&handle, &bson::rawdoc! { "_id": "u3", "email": "ab" })));
+    assert!(is_duplicate(txn.put_nx(&handle, &bson::rawdoc! { "_id": "u3", "email": "ab" })));
+    assert!(is_duplicate(txn.put(&handle, &bson::rawdoc! { "_id": "u2", "email": "ab" })));
+
+    // Rejected writes leave neither a record nor index entries behind.
+    assert!(txn.get(&handle, &RawBsonRef::String("u3")).unwrap().is_none());
+    let u2 = txn.get(&handle, &RawBsonRef::String("u2")).unwrap().unwrap();
+    assert_eq!(u2.get_str("email").unwrap(), "abc");
+    assert_eq!(count_index(&txn, &handle, "email"), 2);
+    txn.rollback().unwrap();
+}
+
+#[test]
+fn unique_index_frees_value_when_owner_moves_away() {
+    let engine = engine();
+    let mut txn = engine.begin(false).unwrap();
+    txn.create_collection(DEFAULT_CF, "users", &Default::default()).unwrap();
+    let unique = slate_engine::IndexConfig::new(["email"]).unique();
+    txn.create_index(DEFAULT_CF, "users", unique).unwrap();
+    let handle = txn.collection(DEFAULT_CF, "users").unwrap();
+
+    txn.put(&handle, &bson::rawdoc! { "_id": "u1", "email": "a" }).unwrap();
+    txn.put(&handle, &bson::rawdoc! { "_id": "u1", "email": "b" }).unwrap();
+    txn.put(&handle, &bson::rawdoc! { "_id": "u2", "email": "a" }).unwrap();
+    txn.delete(&handle, &RawBsonRef::String("u2")).unwrap();
+    txn.put(&handle, &bson::rawdoc! { "_id": "u3", "email": "a" }).unwrap();
+    // Documents without the field aren't indexed, so any number may omit it.
+    txn.put(&handle, &bson::rawdoc! { "_id": "u4" }).unwrap();
+    txn.put(&handle, &bson::rawdoc! { "_id": "u5" }).unwrap();
+    txn.commit().unwrap();
+}
+
+#[test]
+fn unique_index_backfill_rejects_existing_duplicates() {
+    let engine = engine();
+    let mut txn = engine.begin(false).unwrap();
+    txn.create_collection(DEFAULT_CF, "users", &Default::default()).unwrap();
+    let handle = txn.collection(DEFAULT_CF, "users").unwrap();
+    txn.put(&handle, &bson::rawdoc! { "_id": "u1", "email": "a" }).unwrap();
+    txn.put(&handle, &bson::rawdoc! { "_id": "u2", "email": "a" }).unwrap();
+
+    let unique = slate_engine::IndexConfig::new(["email"]).unique();
+    assert!(is_duplicate(txn.create_index(DEFAULT_CF, "users", unique.clone())));
+    // Nothing was written: no config, no entries.
+    let handle = txn.collection(DEFAULT_CF, "users").unwrap();
+    assert!(handle.indexes().is_empty());
+    assert_eq!(count_index(&txn, &handle, "email"), 0);
+
+    txn.put(&handle, &bson::rawdoc! { "_id": "u2", "email": "b" }).unwrap();
+    txn.create_index(DEFAULT_CF, "users", unique).unwrap();
+    txn.commit().unwrap();
+}
+
+#[test]
+fn unique_index_ignores_expired_documents() {
+    let clock = Arc::new(AtomicI64::new(2_000_000));
+    let clock2 = clock.clone();
+    let engine = KvEngine::with_clock(MemoryStore::new(), move || {
+        clock2.load(Ordering::Relaxed)
+    });
+
+    let mut txn = engine.begin(false).unwrap();
+    txn.create_collection(DEFAULT_CF, "users", &Default::default()).unwrap();
+    let handle = txn.collection(DEFAULT_CF, "users").unwrap();
+    let expired = bson::DateTime::from_millis(1_000_000);
+    txn.put(&handle, &bson::rawdoc! { "_id": "u1", "email": "a", "ttl": expired }).unwrap();
+    txn.put(&handle, &bson::rawdoc! { "_id": "u2", "email": "a" }).unwrap();
+
+    let unique = slate_engine::IndexConfig::new(["email"]).unique();
+    txn.create_index(DEFAULT_CF, "users", unique).unwrap();
+    let handle = txn.collection(DEFAULT_CF, "users").unwrap();
+    txn.put(&handle, &bson::rawdoc! { "_id": "u3", "email": "b", "ttl": expired }).unwrap();
+    txn.put(&handle, &bson::rawdoc! { "_id": "u4", "email": "b" }).unwrap();
+    txn.commit().unwrap();
+}
+
+// ── Multikey indexes ────────────────────────────────────────
+
+#[test]
+fn array_values_get_an_entry_per_element() {
+    let engine = engine();
+    let mut txn = engine.begin(false).unwrap();
+    txn.create_collection(DEFAULT_CF, "c", &Default::default()).unwrap();
+    txn.create_index(DEFAULT_CF, "c", "tags").unwrap();
+    let handle = txn.collection(DEFAULT_CF, "c").unwrap();
+    assert!(!handle.is_multikey("tags"));
+
+    txn.put(&handle, &bson::rawdoc! { "_id": "a", "tags": "solo" }).unwrap();
+    assert!(!txn.collection(DEFAULT_CF, "c").unwrap().is_multikey("tags"));
+    let doc = bson::rawdoc! { "_id": "b", "tags": ["rust", "db", "rust"] };
+    txn.put(&handle, &doc).unwrap();
+    txn.commit().unwrap();
+
+    let txn = engine.begin(true).unwrap();
+    let handle = txn.collection(DEFAULT_CF, "c").unwrap();
+    assert!(handle.is_multikey("tags"));
+    assert_eq!(count_index(&txn, &handle, "tags"), 3);
+    let rust: Vec<_> = txn
+        .scan_index(
+            &handle,
+            "tags",
+            IndexRange::Eq(&bson::Bson::String("rust".into())),
+            false,
+        )
+        .unwrap()
+        .map(|e| e.unwrap().doc_id().unwrap())
+        .collect();
+    assert_eq!(rust, [bson::RawBson::String("b".into())]);
+    txn.rollback().unwrap();
+}
+
+#[test]
+fn backfill_flags_multikey_indexes() {
+    let engine = engine();
+    let mut txn = engine.begin(false).unwrap();
+    txn.create_collection(DEFAULT_CF, "c", &Default::default()).unwrap();
+    let handle = txn.collection(DEFAULT_CF, "c").unwrap();
+    txn.put(&handle, &bson::rawdoc! { "_id": "a", "tags": ["x", "y"], "n": 1 }).unwrap();
+    txn.create_index(DEFAULT_CF, "c", "tags").unwrap();
+    txn.create_index(DEFAULT_CF, "c", ["n", "tags"]).unwrap();
+    txn.create_index(DEFAULT_CF, "c", "n").unwrap();
+
+    let handle = txn.collection(DEFAULT_CF, "c").unwrap();
+    assert!(handle.is_multikey("tags"));
+    assert!(handle.is_multikey("n+tags"));
+    assert!(!handle.is_multikey("n"));
+    assert_eq!(count_index(&txn, &handle, "tags"), 2);
+    assert_eq!(count_index(&txn, &handle, "n+tags"), 2);
+    txn.rollback().unwrap();
+}
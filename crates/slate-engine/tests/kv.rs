@@ -1,7 +1,9 @@
 use bson::raw::RawBsonRef;
+use std::sync::Arc;
+
 use slate_engine::{
-    Catalog, CollectionHandle, Engine, EngineTransaction, FunctionKind, IndexRange, KvEngine,
-    DEFAULT_CF, runtime_tag,
+    Catalog, CollectionHandle, Compression, CreateCollectionOptions, Engine, EngineTransaction,
+    FunctionKind, IndexRange, KvEngine, DEFAULT_CF, runtime_tag,
 };
 use slate_store::MemoryStore;
 
@@ -206,7 +208,7 @@ fn create_index_backfills_existing_records() {
     txn.put(&handle, &doc2).unwrap();
     txn.put(&handle, &doc3).unwrap();
 
-    // Create index — should backfill a and b but not c.
+    // Create index — should backfill a and b, and a null entry for c.
     txn.create_index(DEFAULT_CF, "users", "email").unwrap();
 
     let handle = txn.collection(DEFAULT_CF, "users").unwrap();
@@ -217,7 +219,9 @@ fn create_index_backfills_existing_records() {
         .unwrap()
         .collect::<Result<_, _>>()
         .unwrap();
-    assert_eq!(entries.len(), 2);
+    assert_eq!(entries.len(), 3);
+    assert_eq!(entries[0].value().unwrap(), bson::RawBson::Null);
+    assert_eq!(entries[0].doc_id().unwrap(), bson::RawBson::String("c".into()));
     txn.commit().unwrap();
 }
 
@@ -296,6 +300,36 @@ fn put_maintains_index() {
     txn.rollback().unwrap();
 }
 
+#[test]
+fn scan_index_range_respects_bounds() {
+    let engine = engine();
+    let mut txn = engine.begin(false).unwrap();
+    txn.create_collection(DEFAULT_CF, "users", &Default::default()).unwrap();
+    txn.create_index(DEFAULT_CF, "users", "age").unwrap();
+    let handle = txn.collection(DEFAULT_CF, "users").unwrap();
+    for (id, age) in [("a", 10), ("b", 20), ("c", 30), ("d", 40), ("e", 50)] {
+        txn.put(&handle, &bson::rawdoc! { "_id": id, "age": age }).unwrap();
+    }
+    txn.commit().unwrap();
+
+    let txn = engine.begin(true).unwrap();
+    let handle = txn.collection(DEFAULT_CF, "users").unwrap();
+    let ids = |lower: Option<(&bson::Bson, bool)>, upper: Option<(&bson::Bson, bool)>, reverse| {
+        txn.scan_index(&handle, "age", IndexRange::Range { lower, upper }, reverse)
+            .unwrap()
+            .map(|e| e.unwrap().doc_id().unwrap().as_str().unwrap().to_string())
+            .collect::<Vec<_>>()
+    };
+
+    let (v20, v40) = (bson::Bson::Int32(20), bson::Bson::Int32(40));
+    assert_eq!(ids(Some((&v20, false)), None, false), vec!["c", "d", "e"]);
+    assert_eq!(ids(Some((&v20, true)), Some((&v40, false)), false), vec!["b", "c"]);
+    assert_eq!(ids(None, Some((&v40, true)), true), vec!["d", "c", "b", "a"]);
+    assert_eq!(ids(Some((&v20, false)), Some((&v40, false)), true), vec!["c"]);
+    assert!(ids(Some((&v40, true)), Some((&v20, true)), false).is_empty());
+    txn.rollback().unwrap();
+}
+
 #[test]
 fn put_overwrite_updates_index() {
     let engine = engine();
@@ -388,6 +422,145 @@ fn drop_collection_removes_index_entries() {
     txn.rollback().unwrap();
 }
 
+// ── Compression ──────────────────────────────────────────────
+
+fn bulky_doc(id: &str, status: &str) -> bson::RawDocumentBuf {
+    let mut doc = bson::doc! { "_id": id, "status": status };
+    for i in 0..40 {
+        doc.insert(format!("note_{i}"), "lorem ipsum dolor sit amet, consectetur");
+    }
+    bson::RawDocumentBuf::try_from(&doc).unwrap()
+}
+
+fn compressed_engine(compression: Compression) -> KvEngine<MemoryStore> {
+    let engine = engine();
+    let mut txn = engine.begin(false).unwrap();
+    let options = CreateCollectionOptions {
+        compression: Some(compression),
+        ..Default::default()
+    };
+    txn.create_collection(DEFAULT_CF, "users", &options).unwrap();
+    txn.commit().unwrap();
+    engine
+}
+
+#[test]
+fn compressed_collection_roundtrips_documents_and_indexes() {
+    let dictionary: Arc<[u8]> = Arc::from(bulky_doc("dict", "sample").as_bytes());
+    let engine = compressed_engine(Compression::Lz4 {
+        dictionary: Some(dictionary.clone()),
+    });
+
+    let mut txn = engine.begin(false).unwrap();
+    let handle = txn.collection(DEFAULT_CF, "users").unwrap();
+    assert_eq!(
+        handle.compression(),
+        &Compression::Lz4 {
+            dictionary: Some(dictionary)
+        }
+    );
+    txn.put(&handle, &bulky_doc("a", "active")).unwrap();
+    txn.put(&handle, &bulky_doc("b", "inactive")).unwrap();
+    // Backfill has to decompress existing records.
+    txn.create_index(DEFAULT_CF, "users", "status").unwrap();
+    txn.commit().unwrap();
+
+    let txn = engine.begin(false).unwrap();
+    let handle = txn.collection(DEFAULT_CF, "users").unwrap();
+    assert_eq!(
+        txn.get(&handle, &RawBsonRef::String("a")).unwrap().unwrap(),
+        bulky_doc("a", "active")
+    );
+    let scanned: Vec<_> = txn.scan(&handle).unwrap().map(|d| d.unwrap()).collect();
+    assert_eq!(scanned, vec![bulky_doc("a", "active"), bulky_doc("b", "inactive")]);
+
+    // Overwrite and delete diff index entries against the compressed old record.
+    txn.put(&handle, &bulky_doc("a", "inactive")).unwrap();
+    txn.delete(&handle, &RawBsonRef::String("b")).unwrap();
+    let inactive = bson::Bson::String("inactive".into());
+    let ids: Vec<_> = txn
+        .scan_index(&handle, "status", IndexRange::Eq(&inactive), false)
+        .unwrap()
+        .map(|e| e.unwrap().doc_id().unwrap().as_str().unwrap().to_string())
+        .collect();
+    assert_eq!(ids, vec!["a"]);
+    let active = bson::Bson::String("active".into());
+    assert_eq!(
+        txn.scan_index(&handle, "status", IndexRange::Eq(&active), false)
+            .unwrap()
+            .count(),
+        0
+    );
+    txn.commit().unwrap();
+}
+
+#[test]
+fn compressed_collection_handles_share_one_codec() {
+    let engine = compressed_engine(Compression::Lz4 {
+        dictionary: Some(Arc::from(bulky_doc("dict", "sample").as_bytes())),
+    });
+    let first = engine.begin(true).unwrap();
+    let second = engine.begin(true).unwrap();
+    let a = first.collection(DEFAULT_CF, "users").unwrap();
+    let b = second.collection(DEFAULT_CF, "users").unwrap();
+    assert!(std::ptr::eq(a.compression(), b.compression()));
+}
+
+#[test]
+fn compressed_collection_purges_expired_records() {
+    let engine = KvEngine::with_clock(MemoryStore::new(), || 10_000);
+    let mut txn = engine.begin(false).unwrap();
+    let options = CreateCollectionOptions {
+        compression: Some(Compression::Lz4 { dictionary: None }),
+        ..Default::default()
+    };
+    txn.create_collection(DEFAULT_CF, "users", &options).unwrap();
+    txn.create_index(DEFAULT_CF, "users", "ttl").unwrap();
+    let handle = txn.collection(DEFAULT_CF, "users").unwrap();
+
+    let mut doc = bson::Document::try_from(bulky_doc("a", "active")).unwrap();
+    doc.insert("ttl", bson::DateTime::from_millis(5_000));
+    txn.put(&handle, &bson::RawDocumentBuf::try_from(&doc).unwrap()).unwrap();
+    txn.put(&handle, &bulky_doc("b", "active")).unwrap();
+
+    assert!(txn.get(&handle, &RawBsonRef::String("a")).unwrap().is_none());
+    assert_eq!(txn.purge(&handle).unwrap(), 1);
+    assert_eq!(txn.scan(&handle).unwrap().count(), 1);
+    txn.commit().unwrap();
+}
+
+#[cfg(not(feature = "zstd"))]
+#[test]
+fn zstd_without_feature_is_rejected() {
+    let engine = engine();
+    let mut txn = engine.begin(false).unwrap();
+    let options = CreateCollectionOptions {
+        compression: Some(Compression::Zstd {
+            level: 3,
+            dictionary: None,
+        }),
+        ..Default::default()
+    };
+    assert!(txn.create_collection(DEFAULT_CF, "users", &options).is_err());
+}
+
+#[cfg(feature = "zstd")]
+#[test]
+fn zstd_collection_roundtrips_documents() {
+    let engine = compressed_engine(Compression::Zstd {
+        level: 3,
+        dictionary: None,
+    });
+    let txn = engine.begin(false).unwrap();
+    let handle = txn.collection(DEFAULT_CF, "users").unwrap();
+    txn.put(&handle, &bulky_doc("a", "active")).unwrap();
+    assert_eq!(
+        txn.get(&handle, &RawBsonRef::String("a")).unwrap().unwrap(),
+        bulky_doc("a", "active")
+    );
+    txn.commit().unwrap();
+}
+
 // ── Stale handle ─────────────────────────────────────────────
 
 #[test]
@@ -0,0 +1,904 @@
+//! Behavioral contracts every [`Store`] backend must satisfy.
+//!
+//! Each contract is a function that takes a freshly built, empty store and
+//! panics if the backend misbehaves. Stores are built by a factory that gets
+//! a scratch directory (removed afterwards), so disk-backed and in-memory
+//! backends use the same signature:
+//!
+//! ```ignore
+//! // One #[test] per contract:
+//! slate_store::conformance_tests!(|dir: &std::path::Path| {
+//!     MyStore::open(&dir.join("db")).unwrap()
+//! });
+//!
+//! // Or everything in one test:
+//! #[test]
+//! fn conformance() {
+//!     slate_store::conformance::run_all(|_dir| MyStore::new());
+//! }
+//! ```
+//!
+//! Stores implementing [`SnapshotStore`] also run the snapshot contracts,
+//! with `snapshot_conformance_tests!` or [`run_all_snapshots`].
+//!
+//! Contracts only assert behavior all built-in backends share. Whether CF
+//! creation rolls back with a transaction, and how concurrent writers
+//! conflict, differ between backends and are left to backend-specific tests,
+//! as are exact size estimates.
+
+use std::ops::Bound;
+use std::path::Path;
+
+use crate::error::StoreError;
+use crate::store::{ApproximateSize, SnapshotStore, Store, Transaction};
+
+const CF: &str = "conformance";
+
+/// Build a store in a fresh scratch directory and run one contract on it.
+pub fn run<S: Store>(factory: impl Fn(&Path) -> S, contract: fn(&S)) {
+    let dir = tempfile::tempdir().expect("failed to create scratch directory");
+    let store = factory(dir.path());
+    contract(&store);
+    drop(store);
+}
+
+/// Run every contract, each against its own freshly built store.
+pub fn run_all<S: Store>(factory: impl Fn(&Path) -> S) {
+    crate::__conformance_contracts!(__conformance_run; factory);
+}
+
+/// Run every snapshot contract, each against its own freshly built store.
+pub fn run_all_snapshots<S: SnapshotStore>(factory: impl Fn(&Path) -> S) {
+    crate::__snapshot_contracts!(__conformance_run; factory);
+}
+
+/// Generate one `#[test]` per contract for the store built by `$factory`,
+/// an `Fn(&Path) -> S` closure.
+#[macro_export]
+macro_rules! conformance_tests {
+    ($factory:expr $(,)?) => {
+        $crate::__conformance_contracts!(__conformance_test_fns; $factory);
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __conformance_contracts {
+    ($callback:ident; $factory:expr) => {
+        $crate::$callback!(
+            $factory;
+            put_and_get,
+            get_missing_key_returns_none,
+            put_and_delete,
+            put_batch_and_delete_batch,
+            multi_get_returns_values_in_key_order,
+            overwrite_key,
+            empty_value,
+            read_own_writes,
+            scan_prefix_returns_matching_pairs,
+            scan_prefix_rev_returns_reverse_order,
+            scan_prefix_handles_0xff_prefixes,
+            scan_prefix_empty_prefix_scans_everything,
+            scan_range_bounds,
+            scan_range_reverse,
+            scan_range_inverted_is_empty,
+            scan_range_sees_own_writes,
+            read_only_rejects_writes,
+            snapshot_isolation,
+            uncommitted_writes_are_invisible,
+            rollback_discards_writes,
+            dropped_transaction_discards_writes,
+            cf_isolation,
+            missing_cf_returns_error,
+            store_create_and_drop_cf,
+            create_cf_in_transaction,
+            drop_cf_in_transaction,
+            delete_range_half_open,
+            delete_range_inclusive_end,
+            delete_range_unbounded,
+            approximate_size_of_empty_range_is_zero,
+            approximate_size_missing_cf_returns_error,
+            compact_range_keeps_live_keys,
+            savepoint_rollback_undoes_later_writes,
+            savepoint_release_keeps_writes,
+            nested_savepoints_roll_back_innermost_first,
+            released_savepoint_rolls_back_with_its_parent,
+            savepoint_errors,
+        );
+    };
+}
+
+/// Generate one `#[test]` per snapshot contract for the [`SnapshotStore`]
+/// built by `$factory`.
+#[macro_export]
+macro_rules! snapshot_conformance_tests {
+    ($factory:expr $(,)?) => {
+        $crate::__snapshot_contracts!(__conformance_test_fns; $factory);
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __snapshot_contracts {
+    ($callback:ident; $factory:expr) => {
+        $crate::$callback!(
+            $factory;
+            snapshot_ignores_later_commits,
+            snapshot_is_consistent_across_cfs,
+            snapshot_rejects_writes,
+            snapshot_can_move_to_another_thread,
+        );
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __conformance_run {
+    ($factory:expr; $($name:ident),* $(,)?) => {
+        $( $crate::conformance::run(&$factory, $crate::conformance::$name); )*
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __conformance_test_fns {
+    ($factory:expr; $($name:ident),* $(,)?) => {
+        $(
+            #[test]
+            fn $name() {
+                $crate::conformance::run($factory, $crate::conformance::$name);
+            }
+        )*
+    };
+}
+
+// ── Helpers ─────────────────────────────────────────────────────
+
+fn setup<S: Store>(store: &S) {
+    store.create_cf(CF).unwrap();
+}
+
+fn write<S: Store>(store: &S, cf: &str, entries: &[(&[u8], &[u8])]) {
+    let txn = store.begin(false).unwrap();
+    let handle = txn.cf(cf).unwrap();
+    txn.put_batch(&handle, entries).unwrap();
+    txn.commit().unwrap();
+}
+
+fn read<S: Store>(store: &S, cf: &str, key: &[u8]) -> Option<Vec<u8>> {
+    let txn = store.begin(true).unwrap();
+    let handle = txn.cf(cf).unwrap();
+    txn.get(&handle, key).unwrap()
+}
+
+type Entries = Vec<(Vec<u8>, Vec<u8>)>;
+type Scan<'a> = Box<dyn Iterator<Item = Result<(Vec<u8>, Vec<u8>), StoreError>> + 'a>;
+
+fn collect(iter: Result<Scan<'_>, StoreError>) -> Entries {
+    iter.unwrap().map(Result::unwrap).collect()
+}
+
+fn keys(entries: Entries) -> Vec<Vec<u8>> {
+    entries.into_iter().map(|(k, _)| k).collect()
+}
+
+fn owned(keys: &[&[u8]]) -> Vec<Vec<u8>> {
+    keys.iter().map(|k| k.to_vec()).collect()
+}
+
+fn seed_range<S: Store>(store: &S) {
+    setup(store);
+    write(
+        store,
+        CF,
+        &[
+            (b"k1", b"k1"),
+            (b"k2", b"k2"),
+            (b"k3", b"k3"),
+            (b"k4", b"k4"),
+            (b"k5", b"k5"),
+        ],
+    );
+}
+
+fn range_keys<S: Store>(
+    store: &S,
+    start: Bound<&[u8]>,
+    end: Bound<&[u8]>,
+    reverse: bool,
+) -> Vec<Vec<u8>> {
+    let txn = store.begin(true).unwrap();
+    let cf = txn.cf(CF).unwrap();
+    keys(collect(txn.scan_range(&cf, start, end, reverse)))
+}
+
+// ── Point reads and writes ──────────────────────────────────────
+
+pub fn put_and_get<S: Store>(store: &S) {
+    setup(store);
+    let txn = store.begin(false).unwrap();
+    let cf = txn.cf(CF).unwrap();
+    txn.put(&cf, b"key1", b"value1").unwrap();
+    txn.commit().unwrap();
+
+    assert_eq!(read(store, CF, b"key1").unwrap(), b"value1");
+}
+
+pub fn get_missing_key_returns_none<S: Store>(store: &S) {
+    setup(store);
+    assert!(read(store, CF, b"nonexistent").is_none());
+}
+
+pub fn put_and_delete<S: Store>(store: &S) {
+    setup(store);
+    write(store, CF, &[(b"key1", b"value1")]);
+
+    let txn = store.begin(false).unwrap();
+    let cf = txn.cf(CF).unwrap();
+    txn.delete(&cf, b"key1").unwrap();
+    txn.commit().unwrap();
+
+    assert!(read(store, CF, b"key1").is_none());
+}
+
+pub fn put_batch_and_delete_batch<S: Store>(store: &S) {
+    setup(store);
+    write(
+        store,
+        CF,
+        &[
+            (b"accounts:1:email", b"a@test.com"),
+            (b"accounts:1:name", b"Alice"),
+            (b"accounts:1:status", b"active"),
+        ],
+    );
+    assert_eq!(read(store, CF, b"accounts:1:email").unwrap(), b"a@test.com");
+    assert_eq!(read(store, CF, b"accounts:1:name").unwrap(), b"Alice");
+    assert_eq!(read(store, CF, b"accounts:1:status").unwrap(), b"active");
+
+    let txn = store.begin(false).unwrap();
+    let cf = txn.cf(CF).unwrap();
+    txn.delete_batch(
+        &cf,
+        &[b"accounts:1:email", b"accounts:1:status", b"missing"],
+    )
+    .unwrap();
+    txn.commit().unwrap();
+
+    assert!(read(store, CF, b"accounts:1:email").is_none());
+    assert!(read(store, CF, b"accounts:1:name").is_some());
+    assert!(read(store, CF, b"accounts:1:status").is_none());
+}
+
+pub fn multi_get_returns_values_in_key_order<S: Store>(store: &S) {
+    setup(store);
+    write(store, CF, &[(b"k1", b"v1"), (b"k2", b"v2"), (b"k3", b"v3")]);
+
+    let txn = store.begin(true).unwrap();
+    let cf = txn.cf(CF).unwrap();
+    let results = txn
+        .multi_get(&cf, &[b"k3", b"k1", b"missing", b"k2"])
+        .unwrap();
+    assert_eq!(
+        results,
+        vec![
+            Some(b"v3".to_vec()),
+            Some(b"v1".to_vec()),
+            None,
+            Some(b"v2".to_vec())
+        ]
+    );
+}
+
+pub fn overwrite_key<S: Store>(store: &S) {
+    setup(store);
+    write(store, CF, &[(b"key1", b"old")]);
+    write(store, CF, &[(b"key1", b"new")]);
+    assert_eq!(read(store, CF, b"key1").unwrap(), b"new");
+}
+
+pub fn empty_value<S: Store>(store: &S) {
+    setup(store);
+    write(store, CF, &[(b"index:key", b"")]);
+    assert_eq!(read(store, CF, b"index:key").unwrap(), b"");
+}
+
+pub fn read_own_writes<S: Store>(store: &S) {
+    setup(store);
+    let txn = store.begin(false).unwrap();
+    let cf = txn.cf(CF).unwrap();
+    txn.put(&cf, b"prefix:a", b"1").unwrap();
+    txn.put(&cf, b"prefix:b", b"2").unwrap();
+
+    assert_eq!(txn.get(&cf, b"prefix:a").unwrap().unwrap(), b"1");
+    assert_eq!(
+        txn.multi_get(&cf, &[b"prefix:b"]).unwrap(),
+        vec![Some(b"2".to_vec())]
+    );
+    let found = keys(collect(txn.scan_prefix(&cf, b"prefix:")));
+    assert_eq!(found, owned(&[b"prefix:a", b"prefix:b"]));
+
+    txn.delete(&cf, b"prefix:a").unwrap();
+    assert!(txn.get(&cf, b"prefix:a").unwrap().is_none());
+    let found = keys(collect(txn.scan_prefix_rev(&cf, b"prefix:")));
+    assert_eq!(found, owned(&[b"prefix:b"]));
+}
+
+// ── Prefix scans ────────────────────────────────────────────────
+
+fn seed_accounts<S: Store>(store: &S) {
+    setup(store);
+    write(
+        store,
+        CF,
+        &[
+            (b"accounts:1:email", b"a@test.com"),
+            (b"accounts:1:name", b"Alice"),
+            (b"accounts:2:email", b"b@test.com"),
+            (b"other:1:foo", b"bar"),
+        ],
+    );
+}
+
+pub fn scan_prefix_returns_matching_pairs<S: Store>(store: &S) {
+    seed_accounts(store);
+    let txn = store.begin(true).unwrap();
+    let cf = txn.cf(CF).unwrap();
+
+    let entries = collect(txn.scan_prefix(&cf, b"accounts:1:"));
+    assert_eq!(
+        entries,
+        vec![
+            (b"accounts:1:email".to_vec(), b"a@test.com".to_vec()),
+            (b"accounts:1:name".to_vec(), b"Alice".to_vec()),
+        ]
+    );
+    assert_eq!(collect(txn.scan_prefix(&cf, b"accounts:")).len(), 3);
+    assert!(collect(txn.scan_prefix(&cf, b"contacts:")).is_empty());
+}
+
+pub fn scan_prefix_rev_returns_reverse_order<S: Store>(store: &S) {
+    seed_accounts(store);
+    let txn = store.begin(true).unwrap();
+    let cf = txn.cf(CF).unwrap();
+
+    let entries = collect(txn.scan_prefix_rev(&cf, b"accounts:1:"));
+    assert_eq!(
+        entries,
+        vec![
+            (b"accounts:1:name".to_vec(), b"Alice".to_vec()),
+            (b"accounts:1:email".to_vec(), b"a@test.com".to_vec()),
+        ]
+    );
+    let found = keys(collect(txn.scan_prefix_rev(&cf, b"accounts:")));
+    assert_eq!(
+        found,
+        owned(&[b"accounts:2:email", b"accounts:1:name", b"accounts:1:email"])
+    );
+    assert!(collect(txn.scan_prefix_rev(&cf, b"contacts:")).is_empty());
+}
+
+/// Prefixes ending in `0xFF` have no simple exclusive upper bound, and an
+/// all-`0xFF` prefix has none at all.
+pub fn scan_prefix_handles_0xff_prefixes<S: Store>(store: &S) {
+    setup(store);
+    write(
+        store,
+        CF,
+        &[
+            (b"\xfe\xff", b""),
+            (b"\xff", b""),
+            (b"\xff\x00", b""),
+            (b"\xff\xff", b""),
+            (b"\xff\xff\x01", b""),
+            (b"a\xff", b""),
+            (b"a\xff\x00", b""),
+            (b"b", b""),
+        ],
+    );
+    let txn = store.begin(true).unwrap();
+    let cf = txn.cf(CF).unwrap();
+
+    let all_ff: &[&[u8]] = &[b"\xff", b"\xff\x00", b"\xff\xff", b"\xff\xff\x01"];
+    assert_eq!(keys(collect(txn.scan_prefix(&cf, b"\xff"))), owned(all_ff));
+    let mut rev = owned(all_ff);
+    rev.reverse();
+    assert_eq!(keys(collect(txn.scan_prefix_rev(&cf, b"\xff"))), rev);
+
+    let double: &[&[u8]] = &[b"\xff\xff", b"\xff\xff\x01"];
+    assert_eq!(
+        keys(collect(txn.scan_prefix(&cf, b"\xff\xff"))),
+        owned(double)
+    );
+    let mut rev = owned(double);
+    rev.reverse();
+    assert_eq!(keys(collect(txn.scan_prefix_rev(&cf, b"\xff\xff"))), rev);
+
+    let trailing: &[&[u8]] = &[b"a\xff", b"a\xff\x00"];
+    assert_eq!(
+        keys(collect(txn.scan_prefix(&cf, b"a\xff"))),
+        owned(trailing)
+    );
+    let mut rev = owned(trailing);
+    rev.reverse();
+    assert_eq!(keys(collect(txn.scan_prefix_rev(&cf, b"a\xff"))), rev);
+}
+
+pub fn scan_prefix_empty_prefix_scans_everything<S: Store>(store: &S) {
+    setup(store);
+    write(store, CF, &[(b"a", b"1"), (b"b", b"2"), (b"\xff", b"3")]);
+    let txn = store.begin(true).unwrap();
+    let cf = txn.cf(CF).unwrap();
+
+    let all: &[&[u8]] = &[b"a", b"b", b"\xff"];
+    assert_eq!(keys(collect(txn.scan_prefix(&cf, b""))), owned(all));
+    let mut rev = owned(all);
+    rev.reverse();
+    assert_eq!(keys(collect(txn.scan_prefix_rev(&cf, b""))), rev);
+}
+
+// ── Range scans ─────────────────────────────────────────────────
+
+pub fn scan_range_bounds<S: Store>(store: &S) {
+    seed_range(store);
+    use Bound::{Excluded, Included, Unbounded};
+
+    let keys = range_keys(store, Included(b"k2"), Excluded(b"k4"), false);
+    assert_eq!(keys, owned(&[b"k2", b"k3"]));
+    let keys = range_keys(store, Excluded(b"k2"), Included(b"k4"), false);
+    assert_eq!(keys, owned(&[b"k3", b"k4"]));
+    let keys = range_keys(store, Excluded(b"k3"), Unbounded, false);
+    assert_eq!(keys, owned(&[b"k4", b"k5"]));
+    let keys = range_keys(store, Unbounded, Unbounded, false);
+    assert_eq!(keys.len(), 5);
+    // Bounds that fall between stored keys.
+    let keys = range_keys(store, Included(b"k1\xff"), Included(b"k3\x00"), false);
+    assert_eq!(keys, owned(&[b"k2", b"k3"]));
+}
+
+pub fn scan_range_reverse<S: Store>(store: &S) {
+    seed_range(store);
+    use Bound::{Excluded, Included, Unbounded};
+
+    let keys = range_keys(store, Excluded(b"k2"), Included(b"k4"), true);
+    assert_eq!(keys, owned(&[b"k4", b"k3"]));
+    let keys = range_keys(store, Included(b"k2"), Excluded(b"k4"), true);
+    assert_eq!(keys, owned(&[b"k3", b"k2"]));
+    let keys = range_keys(store, Unbounded, Excluded(b"k3"), true);
+    assert_eq!(keys, owned(&[b"k2", b"k1"]));
+    let keys = range_keys(store, Excluded(b"k1\xff"), Excluded(b"k3\x00"), true);
+    assert_eq!(keys, owned(&[b"k3", b"k2"]));
+}
+
+pub fn scan_range_inverted_is_empty<S: Store>(store: &S) {
+    seed_range(store);
+    use Bound::{Excluded, Included};
+
+    assert!(range_keys(store, Included(b"k4"), Included(b"k2"), false).is_empty());
+    assert!(range_keys(store, Excluded(b"k3"), Excluded(b"k3"), true).is_empty());
+    assert!(range_keys(store, Included(b"k3"), Excluded(b"k3"), false).is_empty());
+}
+
+pub fn scan_range_sees_own_writes<S: Store>(store: &S) {
+    seed_range(store);
+    let txn = store.begin(false).unwrap();
+    let cf = txn.cf(CF).unwrap();
+    txn.put(&cf, b"k25", b"new").unwrap();
+    txn.delete(&cf, b"k3").unwrap();
+
+    let found = keys(collect(txn.scan_range(
+        &cf,
+        Bound::Included(b"k2"),
+        Bound::Included(b"k4"),
+        false,
+    )));
+    assert_eq!(found, owned(&[b"k2", b"k25", b"k4"]));
+}
+
+// ── Transactions ────────────────────────────────────────────────
+
+pub fn read_only_rejects_writes<S: Store>(store: &S) {
+    setup(store);
+    let mut txn = store.begin(true).unwrap();
+    let cf = txn.cf(CF).unwrap();
+    let read_only = |result: Result<(), StoreError>| matches!(result, Err(StoreError::ReadOnly));
+
+    assert!(read_only(txn.put(&cf, b"key", b"value")));
+    assert!(read_only(txn.put_batch(&cf, &[(b"key", b"value")])));
+    assert!(read_only(txn.delete(&cf, b"key")));
+    assert!(read_only(txn.delete_batch(&cf, &[b"key"])));
+    assert!(read_only(txn.create_cf("other")));
+    assert!(read_only(txn.drop_cf(CF)));
+    assert!(read_only(txn.set_savepoint()));
+    drop(txn);
+
+    assert!(read(store, CF, b"key").is_none());
+}
+
+/// A transaction keeps reading the state it started from, even after
+/// another transaction commits.
+pub fn snapshot_isolation<S: Store>(store: &S) {
+    setup(store);
+    write(store, CF, &[(b"a", b"1"), (b"b", b"1")]);
+
+    let reader = store.begin(true).unwrap();
+    let cf = reader.cf(CF).unwrap();
+    assert_eq!(reader.get(&cf, b"a").unwrap().unwrap(), b"1");
+
+    let writer = store.begin(false).unwrap();
+    let wcf = writer.cf(CF).unwrap();
+    writer.put(&wcf, b"a", b"2").unwrap();
+    writer.delete(&wcf, b"b").unwrap();
+    writer.put(&wcf, b"c", b"2").unwrap();
+    writer.commit().unwrap();
+
+    assert_eq!(reader.get(&cf, b"a").unwrap().unwrap(), b"1");
+    assert_eq!(reader.get(&cf, b"b").unwrap().unwrap(), b"1");
+    assert!(reader.get(&cf, b"c").unwrap().is_none());
+    assert_eq!(
+        keys(collect(reader.scan_prefix(&cf, b""))),
+        owned(&[b"a", b"b"])
+    );
+    drop(reader);
+
+    assert_eq!(read(store, CF, b"a").unwrap(), b"2");
+    assert!(read(store, CF, b"b").is_none());
+}
+
+pub fn uncommitted_writes_are_invisible<S: Store>(store: &S) {
+    setup(store);
+    let writer = store.begin(false).unwrap();
+    let cf = writer.cf(CF).unwrap();
+    writer.put(&cf, b"pending", b"1").unwrap();
+
+    assert!(read(store, CF, b"pending").is_none());
+    writer.commit().unwrap();
+    assert!(read(store, CF, b"pending").is_some());
+}
+
+pub fn rollback_discards_writes<S: Store>(store: &S) {
+    setup(store);
+    write(store, CF, &[(b"key1", b"value1")]);
+
+    let txn = store.begin(false).unwrap();
+    let cf = txn.cf(CF).unwrap();
+    txn.put(&cf, b"key2", b"value2").unwrap();
+    txn.delete(&cf, b"key1").unwrap();
+    txn.rollback().unwrap();
+
+    assert!(read(store, CF, b"key1").is_some());
+    assert!(read(store, CF, b"key2").is_none());
+}
+
+pub fn dropped_transaction_discards_writes<S: Store>(store: &S) {
+    setup(store);
+    {
+        let txn = store.begin(false).unwrap();
+        let cf = txn.cf(CF).unwrap();
+        txn.put(&cf, b"abandoned", b"1").unwrap();
+    }
+    assert!(read(store, CF, b"abandoned").is_none());
+
+    // The backend is still writable afterwards.
+    write(store, CF, &[(b"key", b"value")]);
+    assert!(read(store, CF, b"key").is_some());
+}
+
+// ── Column families ─────────────────────────────────────────────
+
+pub fn cf_isolation<S: Store>(store: &S) {
+    store.create_cf("cf_a").unwrap();
+    store.create_cf("cf_b").unwrap();
+
+    let txn = store.begin(false).unwrap();
+    let cf_a = txn.cf("cf_a").unwrap();
+    let cf_b = txn.cf("cf_b").unwrap();
+    txn.put(&cf_a, b"key1", b"value_a").unwrap();
+    txn.put(&cf_b, b"key1", b"value_b").unwrap();
+    txn.put(&cf_a, b"only_a", b"1").unwrap();
+    txn.commit().unwrap();
+
+    assert_eq!(read(store, "cf_a", b"key1").unwrap(), b"value_a");
+    assert_eq!(read(store, "cf_b", b"key1").unwrap(), b"value_b");
+    assert!(read(store, "cf_b", b"only_a").is_none());
+}
+
+pub fn missing_cf_returns_error<S: Store>(store: &S) {
+    let txn = store.begin(true).unwrap();
+    assert!(txn.cf("nonexistent").is_err());
+}
+
+pub fn store_create_and_drop_cf<S: Store>(store: &S) {
+    store.create_cf("temp").unwrap();
+    // Creating an existing CF is a no-op that keeps its data.
+    write(store, "temp", &[(b"key1", b"value1")]);
+    store.create_cf("temp").unwrap();
+    assert_eq!(read(store, "temp", b"key1").unwrap(), b"value1");
+
+    store.drop_cf("temp").unwrap();
+    let txn = store.begin(true).unwrap();
+    assert!(txn.cf("temp").is_err());
+    drop(txn);
+
+    // A re-created CF starts empty.
+    store.create_cf("temp").unwrap();
+    assert!(read(store, "temp", b"key1").is_none());
+}
+
+pub fn create_cf_in_transaction<S: Store>(store: &S) {
+    let mut txn = store.begin(false).unwrap();
+    txn.create_cf("fresh").unwrap();
+    let cf = txn.cf("fresh").unwrap();
+    txn.put(&cf, b"key", b"value").unwrap();
+    assert_eq!(txn.get(&cf, b"key").unwrap().unwrap(), b"value");
+    txn.commit().unwrap();
+
+    assert_eq!(read(store, "fresh", b"key").unwrap(), b"value");
+}
+
+pub fn drop_cf_in_transaction<S: Store>(store: &S) {
+    store.create_cf("doomed").unwrap();
+    setup(store);
+    write(store, "doomed", &[(b"key", b"value")]);
+
+    let mut txn = store.begin(false).unwrap();
+    txn.drop_cf("doomed").unwrap();
+    let cf = txn.cf(CF).unwrap();
+    txn.put(&cf, b"survivor", b"1").unwrap();
+    txn.commit().unwrap();
+
+    let txn = store.begin(true).unwrap();
+    assert!(txn.cf("doomed").is_err());
+    drop(txn);
+    assert!(read(store, CF, b"survivor").is_some());
+}
+
+// ── delete_range ────────────────────────────────────────────────
+
+fn seed_abcde<S: Store>(store: &S) {
+    setup(store);
+    store.create_cf("other").unwrap();
+    let entries: &[(&[u8], &[u8])] = &[
+        (b"a", b"1"),
+        (b"b", b"2"),
+        (b"c", b"3"),
+        (b"d", b"4"),
+        (b"e", b"5"),
+    ];
+    write(store, CF, entries);
+    write(store, "other", entries);
+}
+
+fn remaining<S: Store>(store: &S, cf: &str) -> Vec<Vec<u8>> {
+    let txn = store.begin(true).unwrap();
+    let handle = txn.cf(cf).unwrap();
+    keys(collect(txn.scan_prefix(&handle, b"")))
+}
+
+pub fn delete_range_half_open<S: Store>(store: &S) {
+    seed_abcde(store);
+    store
+        .delete_range(CF, b"b".to_vec()..b"d".to_vec())
+        .unwrap();
+    assert_eq!(remaining(store, CF), owned(&[b"a", b"d", b"e"]));
+    // Other column families are untouched.
+    assert_eq!(remaining(store, "other").len(), 5);
+}
+
+pub fn delete_range_inclusive_end<S: Store>(store: &S) {
+    seed_abcde(store);
+    store
+        .delete_range(CF, b"a".to_vec()..=b"b".to_vec())
+        .unwrap();
+    assert_eq!(remaining(store, CF), owned(&[b"c", b"d", b"e"]));
+    store.delete_range(CF, b"d".to_vec()..).unwrap();
+    assert_eq!(remaining(store, CF), owned(&[b"c"]));
+}
+
+pub fn delete_range_unbounded<S: Store>(store: &S) {
+    seed_abcde(store);
+    store.delete_range(CF, ..).unwrap();
+    assert!(remaining(store, CF).is_empty());
+    assert_eq!(remaining(store, "other").len(), 5);
+}
+
+// ── Size and compaction ─────────────────────────────────────────
+
+pub fn approximate_size_of_empty_range_is_zero<S: Store>(store: &S) {
+    seed_abcde(store);
+    let size = store
+        .approximate_size(CF, b"x".to_vec()..b"z".to_vec())
+        .unwrap();
+    assert_eq!(size, ApproximateSize::default());
+}
+
+pub fn approximate_size_missing_cf_returns_error<S: Store>(store: &S) {
+    setup(store);
+    assert!(store.approximate_size("missing", ..).is_err());
+}
+
+pub fn compact_range_keeps_live_keys<S: Store>(store: &S) {
+    seed_abcde(store);
+    store
+        .delete_range(CF, b"b".to_vec()..b"d".to_vec())
+        .unwrap();
+    store.compact_range(CF, ..).unwrap();
+    assert_eq!(remaining(store, CF), owned(&[b"a", b"d", b"e"]));
+    assert_eq!(remaining(store, "other").len(), 5);
+}
+
+// ── Savepoints ──────────────────────────────────────────────────
+
+pub fn savepoint_rollback_undoes_later_writes<S: Store>(store: &S) {
+    setup(store);
+    write(store, CF, &[(b"kept", b"v1"), (b"doomed", b"v1")]);
+
+    let mut txn = store.begin(false).unwrap();
+    let cf = txn.cf(CF).unwrap();
+    txn.put(&cf, b"before", b"v").unwrap();
+    txn.set_savepoint().unwrap();
+    txn.put(&cf, b"kept", b"v2").unwrap();
+    txn.put(&cf, b"new", b"v").unwrap();
+    txn.delete(&cf, b"doomed").unwrap();
+    txn.rollback_to_savepoint().unwrap();
+    assert!(txn.get(&cf, b"new").unwrap().is_none());
+    txn.commit().unwrap();
+
+    assert_eq!(read(store, CF, b"before").unwrap(), b"v");
+    assert_eq!(read(store, CF, b"kept").unwrap(), b"v1");
+    assert_eq!(read(store, CF, b"doomed").unwrap(), b"v1");
+    assert!(read(store, CF, b"new").is_none());
+}
+
+pub fn savepoint_release_keeps_writes<S: Store>(store: &S) {
+    setup(store);
+    let mut txn = store.begin(false).unwrap();
+    let cf = txn.cf(CF).unwrap();
+    txn.set_savepoint().unwrap();
+    txn.put(&cf, b"key", b"value").unwrap();
+    txn.release_savepoint().unwrap();
+    assert!(matches!(
+        txn.rollback_to_savepoint(),
+        Err(StoreError::NoSavepoint)
+    ));
+    txn.commit().unwrap();
+
+    assert_eq!(read(store, CF, b"key").unwrap(), b"value");
+}
+
+pub fn nested_savepoints_roll_back_innermost_first<S: Store>(store: &S) {
+    setup(store);
+    let mut txn = store.begin(false).unwrap();
+    let cf = txn.cf(CF).unwrap();
+    txn.set_savepoint().unwrap();
+    txn.put_batch(&cf, &[(b"a", b"1"), (b"b", b"1")]).unwrap();
+    txn.set_savepoint().unwrap();
+    txn.put(&cf, b"c", b"1").unwrap();
+    txn.delete_batch(&cf, &[b"a"]).unwrap();
+
+    txn.rollback_to_savepoint().unwrap();
+    assert!(txn.get(&cf, b"a").unwrap().is_some());
+    assert!(txn.get(&cf, b"c").unwrap().is_none());
+
+    txn.rollback_to_savepoint().unwrap();
+    assert!(txn.get(&cf, b"a").unwrap().is_none());
+    assert!(txn.get(&cf, b"b").unwrap().is_none());
+}
+
+pub fn released_savepoint_rolls_back_with_its_parent<S: Store>(store: &S) {
+    setup(store);
+    let mut txn = store.begin(false).unwrap();
+    let cf = txn.cf(CF).unwrap();
+    txn.set_savepoint().unwrap();
+    txn.put(&cf, b"outer", b"1").unwrap();
+    txn.set_savepoint().unwrap();
+    txn.put(&cf, b"inner", b"1").unwrap();
+    txn.release_savepoint().unwrap();
+    txn.put(&cf, b"after", b"1").unwrap();
+
+    txn.rollback_to_savepoint().unwrap();
+    let found = keys(collect(txn.scan_prefix(&cf, b"")));
+    assert!(found.is_empty(), "{found:?}");
+}
+
+pub fn savepoint_errors<S: Store>(store: &S) {
+    setup(store);
+    let mut txn = store.begin(false).unwrap();
+    assert!(matches!(
+        txn.rollback_to_savepoint(),
+        Err(StoreError::NoSavepoint)
+    ));
+    assert!(matches!(
+        txn.release_savepoint(),
+        Err(StoreError::NoSavepoint)
+    ));
+    txn.rollback().unwrap();
+
+    let mut txn = store.begin(true).unwrap();
+    assert!(matches!(txn.set_savepoint(), Err(StoreError::ReadOnly)));
+}
+
+// ── Snapshots ───────────────────────────────────────────────────
+
+pub fn snapshot_ignores_later_commits<S: SnapshotStore>(store: &S) {
+    setup(store);
+    write(store, CF, &[(b"a", b"1"), (b"b", b"1")]);
+    let snapshot = store.snapshot().unwrap();
+
+    let txn = store.begin(false).unwrap();
+    let cf = txn.cf(CF).unwrap();
+    txn.put(&cf, b"a", b"2").unwrap();
+    txn.delete(&cf, b"b").unwrap();
+    txn.put(&cf, b"c", b"2").unwrap();
+    txn.commit().unwrap();
+
+    // Every read transaction on the snapshot sees the same data.
+    for _ in 0..2 {
+        assert_eq!(read(&snapshot, CF, b"a").unwrap(), b"1");
+        assert_eq!(read(&snapshot, CF, b"b").unwrap(), b"1");
+        assert!(read(&snapshot, CF, b"c").is_none());
+        assert_eq!(remaining(&snapshot, CF), owned(&[b"a", b"b"]));
+    }
+    assert_eq!(read(store, CF, b"a").unwrap(), b"2");
+}
+
+pub fn snapshot_is_consistent_across_cfs<S: SnapshotStore>(store: &S) {
+    store.create_cf("left").unwrap();
+    store.create_cf("right").unwrap();
+    let commit_pair = |value: &[u8]| {
+        let txn = store.begin(false).unwrap();
+        let left = txn.cf("left").unwrap();
+        let right = txn.cf("right").unwrap();
+        txn.put(&left, b"key", value).unwrap();
+        txn.put(&right, b"key", value).unwrap();
+        txn.commit().unwrap();
+    };
+    commit_pair(b"1");
+    let snapshot = store.snapshot().unwrap();
+    commit_pair(b"2");
+
+    // Open the CFs in the opposite order from the writer.
+    let txn = snapshot.begin(true).unwrap();
+    let right = txn.cf("right").unwrap();
+    let left = txn.cf("left").unwrap();
+    assert_eq!(txn.get(&right, b"key").unwrap().unwrap(), b"1");
+    assert_eq!(txn.get(&left, b"key").unwrap().unwrap(), b"1");
+}
+
+pub fn snapshot_rejects_writes<S: SnapshotStore>(store: &S) {
+    setup(store);
+    write(store, CF, &[(b"a", b"1")]);
+    let snapshot = store.snapshot().unwrap();
+
+    assert!(matches!(snapshot.begin(false), Err(StoreError::ReadOnly)));
+    assert!(matches!(
+        snapshot.create_cf("other"),
+        Err(StoreError::ReadOnly)
+    ));
+    assert!(matches!(snapshot.drop_cf(CF), Err(StoreError::ReadOnly)));
+    assert!(matches!(
+        snapshot.delete_range(CF, ..),
+        Err(StoreError::ReadOnly)
+    ));
+    let txn = snapshot.begin(true).unwrap();
+    let cf = txn.cf(CF).unwrap();
+    assert!(matches!(
+        txn.put(&cf, b"a", b"2"),
+        Err(StoreError::ReadOnly)
+    ));
+    drop(txn);
+
+    assert_eq!(read(store, CF, b"a").unwrap(), b"1");
+}
+
+pub fn snapshot_can_move_to_another_thread<S: SnapshotStore>(store: &S) {
+    setup(store);
+    write(store, CF, &[(b"a", b"1")]);
+    let snapshot = store.snapshot().unwrap();
+    write(store, CF, &[(b"a", b"2")]);
+
+    let value = std::thread::spawn(move || read(&snapshot, CF, b"a"))
+        .join()
+        .unwrap();
+    assert_eq!(value.unwrap(), b"1");
+}
@@ -0,0 +1,113 @@
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+
+use crate::error::StoreError;
+
+use super::key::KeyProvider;
+
+// ── Envelope ─────────────────────────────────────────────────
+//
+// [version: 1][key_id: u32 LE][nonce: 24][ciphertext + tag: len(value) + 16]
+//
+// The AAD is [cf_len: u32 LE][cf][key], binding each value to the slot it was
+// written to: a ciphertext copied to another key or column family fails to
+// decrypt instead of silently moving data around.
+
+const VERSION: u8 = 1;
+const NONCE_SIZE: usize = 24;
+const HEADER_SIZE: usize = 1 + 4 + NONCE_SIZE;
+
+/// AEAD sealing/opening of stored values, with a per-key-id cipher cache.
+pub(crate) struct Cipher {
+    provider: Arc<dyn KeyProvider>,
+    ciphers: RwLock<HashMap<u32, Arc<XChaCha20Poly1305>>>,
+}
+
+fn aad(cf: &str, key: &[u8]) -> Vec<u8> {
+    let mut aad = Vec::with_capacity(4 + cf.len() + key.len());
+    aad.extend_from_slice(&(cf.len() as u32).to_le_bytes());
+    aad.extend_from_slice(cf.as_bytes());
+    aad.extend_from_slice(key);
+    aad
+}
+
+impl Cipher {
+    pub(crate) fn new(provider: Arc<dyn KeyProvider>) -> Self {
+        Self {
+            provider,
+            ciphers: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub(crate) fn current_key_id(&self) -> u32 {
+        self.provider.current_key_id()
+    }
+
+    fn cipher(&self, id: u32) -> Result<Arc<XChaCha20Poly1305>, StoreError> {
+        if let Some(cipher) = self.ciphers.read().unwrap().get(&id) {
+            return Ok(Arc::clone(cipher));
+        }
+        let key = self.provider.key(id)?;
+        let cipher = Arc::new(XChaCha20Poly1305::new(&key.into()));
+        self.ciphers
+            .write()
+            .unwrap()
+            .insert(id, Arc::clone(&cipher));
+        Ok(cipher)
+    }
+
+    /// Encrypt `value` for `key` in `cf` with the provider's current key.
+    pub(crate) fn seal(&self, cf: &str, key: &[u8], value: &[u8]) -> Result<Vec<u8>, StoreError> {
+        let id = self.current_key_id();
+        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .cipher(id)?
+            .encrypt(
+                &nonce,
+                Payload {
+                    msg: value,
+                    aad: &aad(cf, key),
+                },
+            )
+            .map_err(|_| StoreError::Storage("encryption failed".into()))?;
+
+        let mut out = Vec::with_capacity(HEADER_SIZE + ciphertext.len());
+        out.push(VERSION);
+        out.extend_from_slice(&id.to_le_bytes());
+        out.extend_from_slice(&nonce);
+        out.extend_from_slice(&ciphertext);
+        Ok(out)
+    }
+
+    /// Decrypt a value previously produced by [`Cipher::seal`] for the same slot.
+    pub(crate) fn open(&self, cf: &str, key: &[u8], sealed: &[u8]) -> Result<Vec<u8>, StoreError> {
+        let id = Self::key_id(sealed)?;
+        let nonce = XNonce::from_slice(&sealed[5..HEADER_SIZE]);
+        self.cipher(id)?
+            .decrypt(
+                nonce,
+                Payload {
+                    msg: &sealed[HEADER_SIZE..],
+                    aad: &aad(cf, key),
+                },
+            )
+            .map_err(|_| {
+                StoreError::Storage(format!(
+                    "decryption failed in column family {cf}: wrong key or tampered value"
+                ))
+            })
+    }
+
+    /// Id of the key a sealed value was encrypted with.
+    pub(crate) fn key_id(sealed: &[u8]) -> Result<u32, StoreError> {
+        if sealed.len() < HEADER_SIZE || sealed[0] != VERSION {
+            return Err(StoreError::Storage(
+                "value is not an encrypted envelope".into(),
+            ));
+        }
+        Ok(u32::from_le_bytes(sealed[1..5].try_into().unwrap()))
+    }
+}
@@ -0,0 +1,73 @@
+use std::collections::HashMap;
+use std::sync::RwLock;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use crate::error::StoreError;
+
+/// A 256-bit data encryption key.
+pub type EncryptionKey = [u8; 32];
+
+/// Source of data encryption keys for an [`EncryptedStore`](super::EncryptedStore).
+///
+/// Every encrypted value records the id of the key that sealed it, so old
+/// values stay readable after the current key changes. A key id must always
+/// resolve to the same key bytes — the store caches ciphers by id.
+pub trait KeyProvider: Send + Sync {
+    /// Id of the key that new writes are encrypted with.
+    fn current_key_id(&self) -> u32;
+
+    /// Resolve a key by id. Called once per id; the result is cached.
+    fn key(&self, id: u32) -> Result<EncryptionKey, StoreError>;
+}
+
+/// In-process [`KeyProvider`] holding keys in memory.
+///
+/// Suitable when the application fetches key material itself (e.g. from the
+/// platform keychain) and hands it to the store. Rotate by adding a new key
+/// and making it current; existing values keep decrypting with the old key
+/// until they are re-encrypted.
+pub struct StaticKeyProvider {
+    keys: RwLock<HashMap<u32, EncryptionKey>>,
+    current: AtomicU32,
+}
+
+impl StaticKeyProvider {
+    /// Create a provider whose current key is `key` under `id`.
+    pub fn new(id: u32, key: EncryptionKey) -> Self {
+        Self {
+            keys: RwLock::new(HashMap::from([(id, key)])),
+            current: AtomicU32::new(id),
+        }
+    }
+
+    /// Register an additional key (e.g. a retired key still needed for reads).
+    pub fn add_key(&self, id: u32, key: EncryptionKey) {
+        self.keys.write().unwrap().insert(id, key);
+    }
+
+    /// Switch new writes to the key registered under `id`.
+    pub fn set_current(&self, id: u32) -> Result<(), StoreError> {
+        if !self.keys.read().unwrap().contains_key(&id) {
+            return Err(StoreError::Storage(format!(
+                "unknown encryption key id: {id}"
+            )));
+        }
+        self.current.store(id, Ordering::Release);
+        Ok(())
+    }
+}
+
+impl KeyProvider for StaticKeyProvider {
+    fn current_key_id(&self) -> u32 {
+        self.current.load(Ordering::Acquire)
+    }
+
+    fn key(&self, id: u32) -> Result<EncryptionKey, StoreError> {
+        self.keys
+            .read()
+            .unwrap()
+            .get(&id)
+            .copied()
+            .ok_or_else(|| StoreError::Storage(format!("unknown encryption key id: {id}")))
+    }
+}
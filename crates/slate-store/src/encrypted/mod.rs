@@ -0,0 +1,7 @@
+mod cipher;
+mod key;
+mod store;
+mod transaction;
+
+pub use key::{EncryptionKey, KeyProvider, StaticKeyProvider};
+pub use store::{EncryptedStore, RotationHandle};
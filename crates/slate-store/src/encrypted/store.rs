@@ -0,0 +1,305 @@
+use std::ops::RangeBounds;
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::thread;
+
+use crate::error::StoreError;
+use crate::store::{
+    ApproximateSize, BackupInfo, BackupPolicy, BackupStore, ForkableStore, IncrementalBackupStore,
+    SnapshotStore, Store, Transaction,
+};
+
+use super::cipher::Cipher;
+use super::key::KeyProvider;
+use super::transaction::EncryptedTransaction;
+
+/// Values re-encrypted per write transaction during rotation.
+const DEFAULT_ROTATION_BATCH_SIZE: usize = 1000;
+
+/// Attempts per rotation batch before a write conflict is surfaced.
+const ROTATION_CONFLICT_RETRIES: u32 = 8;
+
+/// Wraps any [`Store`] and encrypts values at rest.
+///
+/// Values are sealed with XChaCha20-Poly1305 on `put`/`put_batch` and opened
+/// on every read. Keys stay plaintext, so ordering, prefix scans, and index
+/// range scans behave exactly as on the wrapped backend. Each value records
+/// the id of the key that sealed it, so changing the provider's current key
+/// takes effect for new writes immediately while old values stay readable.
+///
+/// Cloning is cheap and shares the underlying store — keep a clone around to
+/// drive key rotation after handing one to `DatabaseBuilder::open`.
+///
+/// The wrapped store must start out empty (or already encrypted by this
+/// wrapper); plaintext values fail to decrypt.
+pub struct EncryptedStore<S> {
+    store: Arc<S>,
+    cipher: Arc<Cipher>,
+    rotation_batch_size: usize,
+}
+
+impl<S> Clone for EncryptedStore<S> {
+    fn clone(&self) -> Self {
+        Self {
+            store: Arc::clone(&self.store),
+            cipher: Arc::clone(&self.cipher),
+            rotation_batch_size: self.rotation_batch_size,
+        }
+    }
+}
+
+impl<S: Store> EncryptedStore<S> {
+    pub fn new(store: S, provider: impl KeyProvider + 'static) -> Self {
+        Self::with_provider(store, Arc::new(provider))
+    }
+
+    /// Like [`EncryptedStore::new`], for a provider the caller also holds on
+    /// to (e.g. to switch its current key).
+    pub fn with_provider(store: S, provider: Arc<dyn KeyProvider>) -> Self {
+        Self {
+            store: Arc::new(store),
+            cipher: Arc::new(Cipher::new(provider)),
+            rotation_batch_size: DEFAULT_ROTATION_BATCH_SIZE,
+        }
+    }
+
+    /// Set how many values each rotation transaction re-encrypts.
+    pub fn with_rotation_batch_size(mut self, size: usize) -> Self {
+        self.rotation_batch_size = size.max(1);
+        self
+    }
+
+    /// Re-encrypt every value in `cf` that is not sealed with the current key.
+    ///
+    /// Works through the CF in key order, one write transaction per batch, so
+    /// foreground writers are only ever blocked for a single batch. Returns the
+    /// number of values rewritten.
+    pub fn rotate_cf(&self, cf: &str) -> Result<u64, StoreError> {
+        self.rotate_cf_until(cf, &AtomicBool::new(false), &AtomicU64::new(0))
+    }
+
+    fn rotate_cf_until(
+        &self,
+        cf: &str,
+        stop: &AtomicBool,
+        progress: &AtomicU64,
+    ) -> Result<u64, StoreError> {
+        let mut cursor: Option<Vec<u8>> = None;
+        let mut total = 0;
+        loop {
+            if stop.load(Ordering::Relaxed) {
+                return Ok(total);
+            }
+            let mut attempt = 1;
+            let (next, rewritten) = loop {
+                let txn = self.begin(false)?;
+                let result = txn
+                    .reseal_batch(cf, cursor.as_deref(), self.rotation_batch_size)
+                    .and_then(|batch| txn.commit().map(|()| batch));
+                match result {
+                    Err(StoreError::Conflict(_)) if attempt < ROTATION_CONFLICT_RETRIES => {
+                        attempt += 1;
+                    }
+                    other => break other?,
+                }
+            };
+            total += rewritten;
+            progress.fetch_add(rewritten, Ordering::Relaxed);
+            match next {
+                Some(key) => cursor = Some(key),
+                None => return Ok(total),
+            }
+        }
+    }
+}
+
+impl<S: Store + Send + Sync + 'static> EncryptedStore<S> {
+    /// Re-encrypt `cfs` on a background thread. See [`EncryptedStore::rotate_cf`].
+    ///
+    /// Typical flow: register a new key with the provider, make it current,
+    /// then call this with every column family (for a `Database`, `_sys_` plus
+    /// the CFs from `list_collections`).
+    pub fn rotate_in_background(&self, cfs: Vec<String>) -> RotationHandle {
+        let store = self.clone();
+        let stop = Arc::new(AtomicBool::new(false));
+        let progress = Arc::new(AtomicU64::new(0));
+        let thread_stop = Arc::clone(&stop);
+        let thread_progress = Arc::clone(&progress);
+        let handle = thread::spawn(move || {
+            let mut total = 0;
+            for cf in &cfs {
+                total += store.rotate_cf_until(cf, &thread_stop, &thread_progress)?;
+            }
+            Ok(total)
+        });
+        RotationHandle {
+            stop,
+            progress,
+            handle: Some(handle),
+        }
+    }
+}
+
+/// Handle to a background key rotation started by
+/// [`EncryptedStore::rotate_in_background`]. Dropping it stops the rotation
+/// after the current batch.
+pub struct RotationHandle {
+    stop: Arc<AtomicBool>,
+    progress: Arc<AtomicU64>,
+    handle: Option<thread::JoinHandle<Result<u64, StoreError>>>,
+}
+
+impl RotationHandle {
+    /// Number of values re-encrypted so far.
+    pub fn progress(&self) -> u64 {
+        self.progress.load(Ordering::Relaxed)
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.handle.as_ref().is_none_or(|h| h.is_finished())
+    }
+
+    /// Ask the rotation to stop after the current batch and wait for it.
+    /// Rotation is resumable: calling `rotate_*` again skips values that
+    /// already use the current key.
+    pub fn stop(mut self) -> Result<u64, StoreError> {
+        self.stop.store(true, Ordering::Relaxed);
+        self.wait()
+    }
+
+    /// Wait for the rotation to finish, returning how many values it rewrote.
+    pub fn join(mut self) -> Result<u64, StoreError> {
+        self.wait()
+    }
+
+    fn wait(&mut self) -> Result<u64, StoreError> {
+        match self.handle.take() {
+            Some(h) => h
+                .join()
+                .map_err(|_| StoreError::Storage("key rotation thread panicked".into()))?,
+            None => Ok(self.progress()),
+        }
+    }
+}
+
+impl Drop for RotationHandle {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        let _ = self.wait();
+    }
+}
+
+impl<S: Store> Store for EncryptedStore<S> {
+    type Txn<'a>
+        = EncryptedTransaction<'a, S::Txn<'a>>
+    where
+        Self: 'a;
+
+    fn begin(&self, read_only: bool) -> Result<Self::Txn<'_>, StoreError> {
+        Ok(EncryptedTransaction {
+            inner: self.store.begin(read_only)?,
+            cipher: &self.cipher,
+        })
+    }
+
+    fn is_read_only(&self) -> bool {
+        self.store.is_read_only()
+    }
+
+    fn create_cf(&self, name: &str) -> Result<(), StoreError> {
+        self.store.create_cf(name)
+    }
+
+    fn drop_cf(&self, name: &str) -> Result<(), StoreError> {
+        self.store.drop_cf(name)
+    }
+
+    fn delete_range(&self, cf: &str, range: impl RangeBounds<Vec<u8>>) -> Result<(), StoreError> {
+        self.store.delete_range(cf, range)
+    }
+
+    /// Reports the size of the stored ciphertext.
+    fn approximate_size(
+        &self,
+        cf: &str,
+        range: impl RangeBounds<Vec<u8>>,
+    ) -> Result<ApproximateSize, StoreError> {
+        self.store.approximate_size(cf, range)
+    }
+
+    fn compact_range(&self, cf: &str, range: impl RangeBounds<Vec<u8>>) -> Result<(), StoreError> {
+        self.store.compact_range(cf, range)
+    }
+
+    fn budget_overruns(&self) -> Vec<(String, u64)> {
+        self.store.budget_overruns()
+    }
+
+    fn eviction_candidates(
+        &self,
+        cf: &str,
+        prefix: &[u8],
+        limit: usize,
+    ) -> Result<Vec<Vec<u8>>, StoreError> {
+        self.store.eviction_candidates(cf, prefix, limit)
+    }
+}
+
+/// Backups copy the wrapped store's files as-is, so they stay encrypted and
+/// are restored by wrapping the restored store with the same key provider.
+impl<S: BackupStore> BackupStore for EncryptedStore<S> {
+    fn backup(&self, dest: &Path) -> Result<(), StoreError> {
+        self.store.backup(dest)
+    }
+}
+
+/// Like [`BackupStore`], backups hold the ciphertext as stored.
+impl<S: IncrementalBackupStore> IncrementalBackupStore for EncryptedStore<S> {
+    fn create_backup(
+        &self,
+        backup_dir: &Path,
+        policy: &BackupPolicy,
+    ) -> Result<BackupInfo, StoreError> {
+        self.store.create_backup(backup_dir, policy)
+    }
+
+    fn list_backups(backup_dir: &Path) -> Result<Vec<BackupInfo>, StoreError> {
+        S::list_backups(backup_dir)
+    }
+
+    fn verify_backup(backup_dir: &Path, backup_id: u32) -> Result<(), StoreError> {
+        S::verify_backup(backup_dir, backup_id)
+    }
+
+    fn restore(backup_dir: &Path, backup_id: u32, dest: &Path) -> Result<(), StoreError> {
+        S::restore(backup_dir, backup_id, dest)
+    }
+}
+
+impl<S: ForkableStore> ForkableStore for EncryptedStore<S> {
+    /// The fork keeps using the same key provider.
+    fn fork(&self) -> Result<Self, StoreError> {
+        Ok(EncryptedStore {
+            store: Arc::new(self.store.fork()?),
+            cipher: Arc::clone(&self.cipher),
+            rotation_batch_size: self.rotation_batch_size,
+        })
+    }
+}
+
+/// The snapshot decrypts with the same key provider.
+impl<S: SnapshotStore> SnapshotStore for EncryptedStore<S>
+where
+    S::Snapshot: Sync,
+{
+    type Snapshot = EncryptedStore<S::Snapshot>;
+
+    fn snapshot(&self) -> Result<Self::Snapshot, StoreError> {
+        Ok(EncryptedStore {
+            store: Arc::new(self.store.snapshot()?),
+            cipher: Arc::clone(&self.cipher),
+            rotation_batch_size: self.rotation_batch_size,
+        })
+    }
+}
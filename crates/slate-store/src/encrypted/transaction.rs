@@ -0,0 +1,208 @@
+use std::ops::Bound;
+use std::sync::Arc;
+
+use crate::error::StoreError;
+use crate::store::Transaction;
+
+use super::cipher::Cipher;
+
+/// Column family handle for an [`EncryptedTransaction`].
+///
+/// Carries the CF name alongside the backend handle because the name is part
+/// of every value's associated data.
+#[derive(Clone)]
+pub struct EncryptedCf<C> {
+    inner: C,
+    name: Arc<str>,
+}
+
+/// Transaction over an [`EncryptedStore`](super::EncryptedStore).
+///
+/// Values are sealed on the way in and opened on the way out; keys, CF
+/// management, and the transaction lifecycle pass straight through.
+pub struct EncryptedTransaction<'a, T> {
+    pub(crate) inner: T,
+    pub(crate) cipher: &'a Cipher,
+}
+
+type EntryIter<'b> = Box<dyn Iterator<Item = Result<(Vec<u8>, Vec<u8>), StoreError>> + 'b>;
+
+impl<'a, T: Transaction> EncryptedTransaction<'a, T> {
+    /// Decrypt every value yielded by a backend scan.
+    fn open_iter<'b>(&self, cf: &EncryptedCf<T::Cf>, iter: EntryIter<'b>) -> EntryIter<'b>
+    where
+        'a: 'b,
+    {
+        let cipher = self.cipher;
+        let name = Arc::clone(&cf.name);
+        Box::new(iter.map(move |entry| {
+            let (key, value) = entry?;
+            let value = cipher.open(&name, &key, &value)?;
+            Ok((key, value))
+        }))
+    }
+}
+
+#[allow(clippy::type_complexity)]
+impl<'a, T: Transaction> Transaction for EncryptedTransaction<'a, T> {
+    type Cf = EncryptedCf<T::Cf>;
+
+    fn cf(&self, name: &str) -> Result<Self::Cf, StoreError> {
+        Ok(EncryptedCf {
+            inner: self.inner.cf(name)?,
+            name: Arc::from(name),
+        })
+    }
+
+    fn get(&self, cf: &Self::Cf, key: &[u8]) -> Result<Option<Vec<u8>>, StoreError> {
+        self.inner
+            .get(&cf.inner, key)?
+            .map(|sealed| self.cipher.open(&cf.name, key, &sealed))
+            .transpose()
+    }
+
+    fn get_for_update(&self, cf: &Self::Cf, key: &[u8]) -> Result<Option<Vec<u8>>, StoreError> {
+        self.inner
+            .get_for_update(&cf.inner, key)?
+            .map(|sealed| self.cipher.open(&cf.name, key, &sealed))
+            .transpose()
+    }
+
+    fn multi_get(&self, cf: &Self::Cf, keys: &[&[u8]]) -> Result<Vec<Option<Vec<u8>>>, StoreError> {
+        self.inner
+            .multi_get(&cf.inner, keys)?
+            .into_iter()
+            .zip(keys)
+            .map(|(sealed, key)| {
+                sealed
+                    .map(|sealed| self.cipher.open(&cf.name, key, &sealed))
+                    .transpose()
+            })
+            .collect()
+    }
+
+    fn scan_prefix<'b>(
+        &'b self,
+        cf: &Self::Cf,
+        prefix: &[u8],
+    ) -> Result<Box<dyn Iterator<Item = Result<(Vec<u8>, Vec<u8>), StoreError>> + 'b>, StoreError>
+    {
+        let iter = self.inner.scan_prefix(&cf.inner, prefix)?;
+        Ok(self.open_iter(cf, iter))
+    }
+
+    fn scan_prefix_rev<'b>(
+        &'b self,
+        cf: &Self::Cf,
+        prefix: &[u8],
+    ) -> Result<Box<dyn Iterator<Item = Result<(Vec<u8>, Vec<u8>), StoreError>> + 'b>, StoreError>
+    {
+        let iter = self.inner.scan_prefix_rev(&cf.inner, prefix)?;
+        Ok(self.open_iter(cf, iter))
+    }
+
+    fn scan_range<'b>(
+        &'b self,
+        cf: &Self::Cf,
+        start: Bound<&[u8]>,
+        end: Bound<&[u8]>,
+        reverse: bool,
+    ) -> Result<Box<dyn Iterator<Item = Result<(Vec<u8>, Vec<u8>), StoreError>> + 'b>, StoreError>
+    {
+        let iter = self.inner.scan_range(&cf.inner, start, end, reverse)?;
+        Ok(self.open_iter(cf, iter))
+    }
+
+    fn put(&self, cf: &Self::Cf, key: &[u8], value: &[u8]) -> Result<(), StoreError> {
+        let sealed = self.cipher.seal(&cf.name, key, value)?;
+        self.inner.put(&cf.inner, key, &sealed)
+    }
+
+    fn put_batch(&self, cf: &Self::Cf, entries: &[(&[u8], &[u8])]) -> Result<(), StoreError> {
+        let sealed = entries
+            .iter()
+            .map(|(key, value)| self.cipher.seal(&cf.name, key, value))
+            .collect::<Result<Vec<_>, _>>()?;
+        let entries: Vec<(&[u8], &[u8])> = entries
+            .iter()
+            .zip(&sealed)
+            .map(|((key, _), sealed)| (*key, sealed.as_slice()))
+            .collect();
+        self.inner.put_batch(&cf.inner, &entries)
+    }
+
+    fn delete(&self, cf: &Self::Cf, key: &[u8]) -> Result<(), StoreError> {
+        self.inner.delete(&cf.inner, key)
+    }
+
+    fn delete_batch(&self, cf: &Self::Cf, keys: &[&[u8]]) -> Result<(), StoreError> {
+        self.inner.delete_batch(&cf.inner, keys)
+    }
+
+    fn create_cf(&mut self, name: &str) -> Result<(), StoreError> {
+        self.inner.create_cf(name)
+    }
+
+    fn drop_cf(&mut self, name: &str) -> Result<(), StoreError> {
+        self.inner.drop_cf(name)
+    }
+
+    fn set_savepoint(&mut self) -> Result<(), StoreError> {
+        self.inner.set_savepoint()
+    }
+
+    fn rollback_to_savepoint(&mut self) -> Result<(), StoreError> {
+        self.inner.rollback_to_savepoint()
+    }
+
+    fn release_savepoint(&mut self) -> Result<(), StoreError> {
+        self.inner.release_savepoint()
+    }
+
+    fn commit(self) -> Result<(), StoreError> {
+        self.inner.commit()
+    }
+
+    fn rollback(self) -> Result<(), StoreError> {
+        self.inner.rollback()
+    }
+}
+
+impl<'a, T: Transaction> EncryptedTransaction<'a, T> {
+    /// Re-seal up to `limit` values after `cursor` whose key id is not the
+    /// current one. Returns the last key visited (the next cursor, or `None`
+    /// once the CF is exhausted) and how many values were rewritten.
+    pub(crate) fn reseal_batch(
+        &self,
+        cf: &str,
+        cursor: Option<&[u8]>,
+        limit: usize,
+    ) -> Result<(Option<Vec<u8>>, u64), StoreError> {
+        let handle = self.inner.cf(cf)?;
+        let start = cursor.map_or(Bound::Unbounded, Bound::Excluded);
+        let batch: Vec<(Vec<u8>, Vec<u8>)> = self
+            .inner
+            .scan_range(&handle, start, Bound::Unbounded, false)?
+            .take(limit)
+            .collect::<Result<_, _>>()?;
+
+        let current = self.cipher.current_key_id();
+        let mut rewritten = 0;
+        for (key, sealed) in &batch {
+            if Cipher::key_id(sealed)? == current {
+                continue;
+            }
+            let value = self.cipher.open(cf, key, sealed)?;
+            self.inner
+                .put(&handle, key, &self.cipher.seal(cf, key, &value)?)?;
+            rewritten += 1;
+        }
+
+        let next = if batch.len() < limit {
+            None
+        } else {
+            batch.last().map(|(key, _)| key.clone())
+        };
+        Ok((next, rewritten))
+    }
+}
@@ -4,6 +4,12 @@ use std::fmt;
 pub enum StoreError {
     TransactionConsumed,
     ReadOnly,
+    /// The transaction lost an optimistic concurrency check against a
+    /// concurrent writer. Retrying the whole transaction may succeed.
+    Conflict(String),
+    /// `rollback_to_savepoint` or `release_savepoint` was called with no
+    /// savepoint set.
+    NoSavepoint,
     Storage(String),
 }
 
@@ -12,6 +18,8 @@ impl fmt::Display for StoreError {
         match self {
             StoreError::TransactionConsumed => write!(f, "transaction already consumed"),
             StoreError::ReadOnly => write!(f, "cannot write in a read-only transaction"),
+            StoreError::Conflict(msg) => write!(f, "write conflict: {msg}"),
+            StoreError::NoSavepoint => write!(f, "no savepoint set"),
             StoreError::Storage(msg) => write!(f, "storage error: {msg}"),
         }
     }
@@ -0,0 +1,6 @@
+mod plan;
+mod store;
+mod transaction;
+
+pub use plan::{Fault, FaultPlan};
+pub use store::FaultyStore;
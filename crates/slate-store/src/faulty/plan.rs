@@ -0,0 +1,153 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::error::StoreError;
+use crate::metrics::StoreOp;
+
+/// What happens when a [`FaultPlan`] rule fires.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Fault {
+    /// Return `StoreError::Storage` without touching the wrapped store.
+    Error,
+    /// Apply the first `n` entries of a `put_batch`/`delete_batch`, then
+    /// return an error. Behaves like [`Fault::Error`] on other operations.
+    PartialBatch(usize),
+    /// Flip every bit of the value: on reads the caller gets corrupted bytes
+    /// back, on writes corrupted bytes are stored. Behaves like
+    /// [`Fault::Error`] on operations that carry no value.
+    Corrupt,
+    /// Panic, simulating a crash at this point.
+    Panic,
+}
+
+#[derive(Debug, Clone)]
+struct Rule {
+    op: StoreOp,
+    cf: Option<String>,
+    nth: u64,
+    fault: Fault,
+}
+
+/// Scripted failures for a [`FaultyStore`](super::FaultyStore).
+///
+/// Each rule fires once, on the `nth` (1-based) call of an operation,
+/// optionally restricted to one column family. Calls are counted from the
+/// moment the plan is armed.
+///
+/// ```ignore
+/// let plan = FaultPlan::new()
+///     .fail_put(3)                  // third `put` errors
+///     .partial_batch(1, 2)          // first `put_batch` applies 2 entries, then errors
+///     .fail_commit(1);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct FaultPlan {
+    rules: Vec<Rule>,
+}
+
+impl FaultPlan {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Inject `fault` on the `nth` call of `op`, in any column family.
+    pub fn on(mut self, op: StoreOp, nth: u64, fault: Fault) -> Self {
+        self.rules.push(Rule {
+            op,
+            cf: None,
+            nth,
+            fault,
+        });
+        self
+    }
+
+    /// Inject `fault` on the `nth` call of `op` against column family `cf`.
+    pub fn on_cf(mut self, cf: &str, op: StoreOp, nth: u64, fault: Fault) -> Self {
+        self.rules.push(Rule {
+            op,
+            cf: Some(cf.to_string()),
+            nth,
+            fault,
+        });
+        self
+    }
+
+    /// Fail the `nth` `put`.
+    pub fn fail_put(self, nth: u64) -> Self {
+        self.on(StoreOp::Put, nth, Fault::Error)
+    }
+
+    /// Apply only the first `applied` entries of the `nth` `put_batch`, then fail.
+    pub fn partial_batch(self, nth: u64, applied: usize) -> Self {
+        self.on(StoreOp::PutBatch, nth, Fault::PartialBatch(applied))
+    }
+
+    /// Fail the `nth` `commit`. The wrapped transaction is rolled back, so
+    /// nothing it wrote becomes visible.
+    pub fn fail_commit(self, nth: u64) -> Self {
+        self.on(StoreOp::Commit, nth, Fault::Error)
+    }
+
+    /// Return corrupted bytes from the `nth` `get`.
+    pub fn corrupt_get(self, nth: u64) -> Self {
+        self.on(StoreOp::Get, nth, Fault::Corrupt)
+    }
+
+    /// Panic on the `nth` call of `op`.
+    pub fn panic_at(self, op: StoreOp, nth: u64) -> Self {
+        self.on(op, nth, Fault::Panic)
+    }
+}
+
+/// A plan being executed: rules plus their call counters.
+pub(crate) struct ArmedPlan {
+    rules: Vec<(Rule, AtomicU64)>,
+    fired: AtomicU64,
+}
+
+impl ArmedPlan {
+    pub(crate) fn new(plan: FaultPlan) -> Self {
+        Self {
+            rules: plan
+                .rules
+                .into_iter()
+                .map(|rule| (rule, AtomicU64::new(0)))
+                .collect(),
+            fired: AtomicU64::new(0),
+        }
+    }
+
+    /// Count a call of `op` and return the fault to inject, if any. Every
+    /// matching rule counts the call; the first one that fires wins.
+    pub(crate) fn check(&self, op: StoreOp, cf: Option<&str>) -> Option<Fault> {
+        let mut fault = None;
+        for (rule, seen) in &self.rules {
+            if rule.op != op || (rule.cf.is_some() && rule.cf.as_deref() != cf) {
+                continue;
+            }
+            let n = seen.fetch_add(1, Ordering::Relaxed) + 1;
+            if n == rule.nth && fault.is_none() {
+                self.fired.fetch_add(1, Ordering::Relaxed);
+                fault = Some(rule.fault.clone());
+            }
+        }
+        if let Some(Fault::Panic) = fault {
+            panic!(
+                "injected panic at {op}{}",
+                cf.map_or(String::new(), |cf| format!(" in {cf}"))
+            );
+        }
+        fault
+    }
+
+    pub(crate) fn fired(&self) -> u64 {
+        self.fired.load(Ordering::Relaxed)
+    }
+}
+
+pub(crate) fn injected(op: StoreOp) -> StoreError {
+    StoreError::Storage(format!("injected fault at {op}"))
+}
+
+pub(crate) fn corrupt(value: &[u8]) -> Vec<u8> {
+    value.iter().map(|b| !b).collect()
+}
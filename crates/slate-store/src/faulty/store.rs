@@ -0,0 +1,176 @@
+use std::ops::RangeBounds;
+use std::path::Path;
+use std::sync::{Arc, RwLock};
+
+use crate::error::StoreError;
+use crate::metrics::StoreOp;
+use crate::store::{
+    ApproximateSize, BackupInfo, BackupPolicy, BackupStore, ForkableStore, IncrementalBackupStore,
+    SnapshotStore, Store,
+};
+
+use super::plan::{ArmedPlan, FaultPlan, injected};
+use super::transaction::FaultyTransaction;
+
+/// Wraps any [`Store`] and injects failures from a scriptable [`FaultPlan`].
+///
+/// Meant for crash-consistency tests: arm a plan, run a workload, and check
+/// that whatever survived is consistent. Without a plan every call passes
+/// straight through.
+///
+/// Cloning is cheap and shares both the store and the armed plan, so a test
+/// can keep a clone to re-arm the store after handing one to an engine.
+pub struct FaultyStore<S> {
+    store: Arc<S>,
+    plan: Arc<RwLock<Arc<ArmedPlan>>>,
+}
+
+impl<S> Clone for FaultyStore<S> {
+    fn clone(&self) -> Self {
+        Self {
+            store: Arc::clone(&self.store),
+            plan: Arc::clone(&self.plan),
+        }
+    }
+}
+
+impl<S: Store> FaultyStore<S> {
+    pub fn new(store: S) -> Self {
+        Self {
+            store: Arc::new(store),
+            plan: Arc::new(RwLock::new(Arc::new(ArmedPlan::new(FaultPlan::new())))),
+        }
+    }
+
+    /// Replace the current plan. Call counts start from zero.
+    ///
+    /// Transactions already open keep the plan they started with.
+    pub fn arm(&self, plan: FaultPlan) {
+        *self.plan.write().unwrap() = Arc::new(ArmedPlan::new(plan));
+    }
+
+    /// Stop injecting faults.
+    pub fn disarm(&self) {
+        self.arm(FaultPlan::new());
+    }
+
+    /// Number of faults the current plan has injected so far.
+    pub fn fired(&self) -> u64 {
+        self.plan.read().unwrap().fired()
+    }
+
+    fn armed(&self) -> Arc<ArmedPlan> {
+        Arc::clone(&self.plan.read().unwrap())
+    }
+}
+
+impl<S: Store> Store for FaultyStore<S> {
+    type Txn<'a>
+        = FaultyTransaction<S::Txn<'a>>
+    where
+        Self: 'a;
+
+    fn begin(&self, read_only: bool) -> Result<Self::Txn<'_>, StoreError> {
+        Ok(FaultyTransaction {
+            inner: self.store.begin(read_only)?,
+            plan: self.armed(),
+        })
+    }
+
+    fn is_read_only(&self) -> bool {
+        self.store.is_read_only()
+    }
+
+    fn create_cf(&self, name: &str) -> Result<(), StoreError> {
+        self.store.create_cf(name)
+    }
+
+    fn drop_cf(&self, name: &str) -> Result<(), StoreError> {
+        self.store.drop_cf(name)
+    }
+
+    fn delete_range(&self, cf: &str, range: impl RangeBounds<Vec<u8>>) -> Result<(), StoreError> {
+        if self.armed().check(StoreOp::DeleteRange, Some(cf)).is_some() {
+            return Err(injected(StoreOp::DeleteRange));
+        }
+        self.store.delete_range(cf, range)
+    }
+
+    fn approximate_size(
+        &self,
+        cf: &str,
+        range: impl RangeBounds<Vec<u8>>,
+    ) -> Result<ApproximateSize, StoreError> {
+        self.store.approximate_size(cf, range)
+    }
+
+    fn compact_range(&self, cf: &str, range: impl RangeBounds<Vec<u8>>) -> Result<(), StoreError> {
+        self.store.compact_range(cf, range)
+    }
+
+    fn budget_overruns(&self) -> Vec<(String, u64)> {
+        self.store.budget_overruns()
+    }
+
+    fn eviction_candidates(
+        &self,
+        cf: &str,
+        prefix: &[u8],
+        limit: usize,
+    ) -> Result<Vec<Vec<u8>>, StoreError> {
+        self.store.eviction_candidates(cf, prefix, limit)
+    }
+}
+
+impl<S: BackupStore> BackupStore for FaultyStore<S> {
+    fn backup(&self, dest: &Path) -> Result<(), StoreError> {
+        self.store.backup(dest)
+    }
+}
+
+impl<S: IncrementalBackupStore> IncrementalBackupStore for FaultyStore<S> {
+    fn create_backup(
+        &self,
+        backup_dir: &Path,
+        policy: &BackupPolicy,
+    ) -> Result<BackupInfo, StoreError> {
+        self.store.create_backup(backup_dir, policy)
+    }
+
+    fn list_backups(backup_dir: &Path) -> Result<Vec<BackupInfo>, StoreError> {
+        S::list_backups(backup_dir)
+    }
+
+    fn verify_backup(backup_dir: &Path, backup_id: u32) -> Result<(), StoreError> {
+        S::verify_backup(backup_dir, backup_id)
+    }
+
+    fn restore(backup_dir: &Path, backup_id: u32, dest: &Path) -> Result<(), StoreError> {
+        S::restore(backup_dir, backup_id, dest)
+    }
+}
+
+/// The snapshot shares the armed plan, so its reads can be failed too.
+impl<S: ForkableStore> ForkableStore for FaultyStore<S> {
+    /// The fork starts with no faults armed.
+    fn fork(&self) -> Result<Self, StoreError> {
+        Ok(FaultyStore {
+            store: Arc::new(self.store.fork()?),
+            plan: Arc::new(RwLock::new(Arc::new(ArmedPlan::new(FaultPlan::new())))),
+        })
+    }
+}
+
+impl<S: SnapshotStore> SnapshotStore for FaultyStore<S>
+where
+    S::Snapshot: Sync,
+{
+    type Snapshot = FaultyStore<S::Snapshot>;
+
+    fn snapshot(&self) -> Result<Self::Snapshot, StoreError> {
+        Ok(FaultyStore {
+            store: Arc::new(self.store.snapshot()?),
+            plan: Arc::clone(&self.plan),
+        })
+    }
+}
@@ -0,0 +1,193 @@
+use std::ops::Bound;
+use std::sync::Arc;
+
+use crate::error::StoreError;
+use crate::metrics::StoreOp;
+use crate::store::Transaction;
+
+use super::plan::{ArmedPlan, Fault, corrupt, injected};
+
+/// Column family handle for a [`FaultyTransaction`]. Carries the CF name so
+/// plan rules can target a single column family.
+#[derive(Clone)]
+pub struct FaultyCf<C> {
+    inner: C,
+    name: Arc<str>,
+}
+
+/// Transaction over a [`FaultyStore`](super::FaultyStore).
+pub struct FaultyTransaction<T> {
+    pub(crate) inner: T,
+    pub(crate) plan: Arc<ArmedPlan>,
+}
+
+impl<T: Transaction> FaultyTransaction<T> {
+    fn check(&self, op: StoreOp, cf: &FaultyCf<T::Cf>) -> Option<Fault> {
+        self.plan.check(op, Some(&cf.name))
+    }
+}
+
+#[allow(clippy::type_complexity)]
+impl<T: Transaction> Transaction for FaultyTransaction<T> {
+    type Cf = FaultyCf<T::Cf>;
+
+    fn cf(&self, name: &str) -> Result<Self::Cf, StoreError> {
+        Ok(FaultyCf {
+            inner: self.inner.cf(name)?,
+            name: Arc::from(name),
+        })
+    }
+
+    fn get(&self, cf: &Self::Cf, key: &[u8]) -> Result<Option<Vec<u8>>, StoreError> {
+        match self.check(StoreOp::Get, cf) {
+            None => self.inner.get(&cf.inner, key),
+            Some(Fault::Corrupt) => Ok(self.inner.get(&cf.inner, key)?.map(|v| corrupt(&v))),
+            Some(_) => Err(injected(StoreOp::Get)),
+        }
+    }
+
+    fn get_for_update(&self, cf: &Self::Cf, key: &[u8]) -> Result<Option<Vec<u8>>, StoreError> {
+        match self.check(StoreOp::Get, cf) {
+            None => self.inner.get_for_update(&cf.inner, key),
+            Some(Fault::Corrupt) => Ok(self
+                .inner
+                .get_for_update(&cf.inner, key)?
+                .map(|v| corrupt(&v))),
+            Some(_) => Err(injected(StoreOp::Get)),
+        }
+    }
+
+    fn multi_get(&self, cf: &Self::Cf, keys: &[&[u8]]) -> Result<Vec<Option<Vec<u8>>>, StoreError> {
+        match self.check(StoreOp::MultiGet, cf) {
+            None => self.inner.multi_get(&cf.inner, keys),
+            Some(Fault::Corrupt) => Ok(self
+                .inner
+                .multi_get(&cf.inner, keys)?
+                .into_iter()
+                .map(|v| v.map(|v| corrupt(&v)))
+                .collect()),
+            Some(_) => Err(injected(StoreOp::MultiGet)),
+        }
+    }
+
+    fn scan_prefix<'b>(
+        &'b self,
+        cf: &Self::Cf,
+        prefix: &[u8],
+    ) -> Result<Box<dyn Iterator<Item = Result<(Vec<u8>, Vec<u8>), StoreError>> + 'b>, StoreError>
+    {
+        if self.check(StoreOp::ScanPrefix, cf).is_some() {
+            return Err(injected(StoreOp::ScanPrefix));
+        }
+        self.inner.scan_prefix(&cf.inner, prefix)
+    }
+
+    fn scan_prefix_rev<'b>(
+        &'b self,
+        cf: &Self::Cf,
+        prefix: &[u8],
+    ) -> Result<Box<dyn Iterator<Item = Result<(Vec<u8>, Vec<u8>), StoreError>> + 'b>, StoreError>
+    {
+        if self.check(StoreOp::ScanPrefixRev, cf).is_some() {
+            return Err(injected(StoreOp::ScanPrefixRev));
+        }
+        self.inner.scan_prefix_rev(&cf.inner, prefix)
+    }
+
+    fn scan_range<'b>(
+        &'b self,
+        cf: &Self::Cf,
+        start: Bound<&[u8]>,
+        end: Bound<&[u8]>,
+        reverse: bool,
+    ) -> Result<Box<dyn Iterator<Item = Result<(Vec<u8>, Vec<u8>), StoreError>> + 'b>, StoreError>
+    {
+        if self.check(StoreOp::ScanRange, cf).is_some() {
+            return Err(injected(StoreOp::ScanRange));
+        }
+        self.inner.scan_range(&cf.inner, start, end, reverse)
+    }
+
+    fn put(&self, cf: &Self::Cf, key: &[u8], value: &[u8]) -> Result<(), StoreError> {
+        match self.check(StoreOp::Put, cf) {
+            None => self.inner.put(&cf.inner, key, value),
+            Some(Fault::Corrupt) => self.inner.put(&cf.inner, key, &corrupt(value)),
+            Some(_) => Err(injected(StoreOp::Put)),
+        }
+    }
+
+    fn put_batch(&self, cf: &Self::Cf, entries: &[(&[u8], &[u8])]) -> Result<(), StoreError> {
+        match self.check(StoreOp::PutBatch, cf) {
+            None => self.inner.put_batch(&cf.inner, entries),
+            Some(Fault::Corrupt) => {
+                let corrupted: Vec<Vec<u8>> = entries.iter().map(|(_, v)| corrupt(v)).collect();
+                let entries: Vec<(&[u8], &[u8])> = entries
+                    .iter()
+                    .zip(&corrupted)
+                    .map(|((k, _), v)| (*k, v.as_slice()))
+                    .collect();
+                self.inner.put_batch(&cf.inner, &entries)
+            }
+            Some(Fault::PartialBatch(n)) => {
+                self.inner
+                    .put_batch(&cf.inner, &entries[..n.min(entries.len())])?;
+                Err(injected(StoreOp::PutBatch))
+            }
+            Some(_) => Err(injected(StoreOp::PutBatch)),
+        }
+    }
+
+    fn delete(&self, cf: &Self::Cf, key: &[u8]) -> Result<(), StoreError> {
+        if self.check(StoreOp::Delete, cf).is_some() {
+            return Err(injected(StoreOp::Delete));
+        }
+        self.inner.delete(&cf.inner, key)
+    }
+
+    fn delete_batch(&self, cf: &Self::Cf, keys: &[&[u8]]) -> Result<(), StoreError> {
+        match self.check(StoreOp::DeleteBatch, cf) {
+            None => self.inner.delete_batch(&cf.inner, keys),
+            Some(Fault::PartialBatch(n)) => {
+                self.inner
+                    .delete_batch(&cf.inner, &keys[..n.min(keys.len())])?;
+                Err(injected(StoreOp::DeleteBatch))
+            }
+            Some(_) => Err(injected(StoreOp::DeleteBatch)),
+        }
+    }
+
+    fn create_cf(&mut self, name: &str) -> Result<(), StoreError> {
+        self.inner.create_cf(name)
+    }
+
+    fn drop_cf(&mut self, name: &str) -> Result<(), StoreError> {
+        self.inner.drop_cf(name)
+    }
+
+    fn set_savepoint(&mut self) -> Result<(), StoreError> {
+        self.inner.set_savepoint()
+    }
+
+    fn rollback_to_savepoint(&mut self) -> Result<(), StoreError> {
+        self.inner.rollback_to_savepoint()
+    }
+
+    fn release_savepoint(&mut self) -> Result<(), StoreError> {
+        self.inner.release_savepoint()
+    }
+
+    fn commit(self) -> Result<(), StoreError> {
+        if self.plan.check(StoreOp::Commit, None).is_some() {
+            self.inner.rollback()?;
+            return Err(injected(StoreOp::Commit));
+        }
+        self.inner.commit()
+    }
+
+    fn rollback(self) -> Result<(), StoreError> {
+        if self.plan.check(StoreOp::Rollback, None).is_some() {
+            return Err(injected(StoreOp::Rollback));
+        }
+        self.inner.rollback()
+    }
+}
@@ -1,23 +1,51 @@
 mod error;
+mod metrics;
 mod store;
 
 pub use error::StoreError;
-pub use store::{BackupStore, Store, Transaction};
+pub use metrics::{
+    InstrumentedStore, LatencyHistogram, MetricsSnapshot, MetricsStore, OpMetrics, StoreOp,
+};
+pub use store::{
+    ApproximateSize, BackupInfo, BackupPolicy, BackupStore, ForkableStore, IncrementalBackupStore,
+    SnapshotStore, Store, Transaction, increment_prefix,
+};
+
+#[cfg(feature = "testkit")]
+pub mod conformance;
+
+#[cfg(feature = "testkit")]
+mod faulty;
+
+#[cfg(feature = "testkit")]
+pub use faulty::{Fault, FaultPlan, FaultyStore};
 
 #[cfg(feature = "rocksdb")]
 mod rocks;
 
 #[cfg(feature = "rocksdb")]
-pub use rocks::RocksStore;
+pub use rocks::{RocksReadOnlyStore, RocksSnapshot, RocksStore};
 
 #[cfg(feature = "memory")]
 mod memory;
 
 #[cfg(feature = "memory")]
-pub use memory::MemoryStore;
+pub use memory::{DurableMemoryStore, MemorySnapshot, MemoryStore};
 
 #[cfg(feature = "redb")]
 mod redb_store;
 
 #[cfg(feature = "redb")]
-pub use redb_store::RedbStore;
+pub use redb_store::{RedbSnapshot, RedbStore};
+
+#[cfg(feature = "sqlite")]
+mod sqlite;
+
+#[cfg(feature = "sqlite")]
+pub use sqlite::{SqliteSnapshot, SqliteStore};
+
+#[cfg(feature = "encryption")]
+mod encrypted;
+
+#[cfg(feature = "encryption")]
+pub use encrypted::{EncryptedStore, EncryptionKey, KeyProvider, RotationHandle, StaticKeyProvider};
@@ -0,0 +1,161 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use super::store::{ColumnFamily, WriteSet};
+
+/// Byte budget and access recency for one column family of a bounded
+/// [`MemoryStore`](super::MemoryStore).
+///
+/// `used` counts key and value bytes of every entry in the CF. Every key
+/// also sits in a recency order, so eviction candidates come off its front
+/// without walking the CF. Keys already present when the budget is set
+/// start out oldest, in key order.
+pub(crate) struct CfBudget {
+    limit: u64,
+    used: AtomicU64,
+    recency: Mutex<Recency>,
+}
+
+/// Keys ordered by last use. Each key is allocated once and shared by both
+/// maps.
+#[derive(Default)]
+struct Recency {
+    clock: u64,
+    ticks: HashMap<Arc<[u8]>, u64>,
+    order: BTreeMap<u64, Arc<[u8]>>,
+}
+
+impl Recency {
+    fn new(data: Option<&ColumnFamily>) -> Self {
+        let mut recency = Self::default();
+        for (key, _) in data.into_iter().flat_map(|data| data.iter()) {
+            recency.insert(key);
+        }
+        recency
+    }
+
+    /// Mark `key` as just used if it is tracked. Returns whether it was.
+    fn touch(&mut self, key: &[u8]) -> bool {
+        let Some(tick) = self.ticks.get_mut(key) else {
+            return false;
+        };
+        self.clock += 1;
+        let key = self.order.remove(tick).expect("tracked key has a tick");
+        *tick = self.clock;
+        self.order.insert(self.clock, key);
+        true
+    }
+
+    /// Mark `key` as just used, tracking it if it is new.
+    fn insert(&mut self, key: &[u8]) {
+        if !self.touch(key) {
+            self.clock += 1;
+            let key: Arc<[u8]> = Arc::from(key);
+            self.ticks.insert(Arc::clone(&key), self.clock);
+            self.order.insert(self.clock, key);
+        }
+    }
+
+    fn remove(&mut self, key: &[u8]) {
+        if let Some(tick) = self.ticks.remove(key) {
+            self.order.remove(&tick);
+        }
+    }
+}
+
+impl CfBudget {
+    pub(crate) fn new(limit: u64, data: Option<&ColumnFamily>) -> Self {
+        let used = data.map_or(0, |data| data.iter().map(|(k, v)| entry_size(k, v)).sum());
+        Self {
+            limit,
+            used: AtomicU64::new(used),
+            recency: Mutex::new(Recency::new(data)),
+        }
+    }
+
+    /// Same limit and usage, for a forked store over `data`. Recency starts
+    /// over.
+    pub(crate) fn fork(&self, data: Option<&ColumnFamily>) -> Self {
+        Self {
+            limit: self.limit,
+            used: AtomicU64::new(self.used.load(Ordering::Relaxed)),
+            recency: Mutex::new(Recency::new(data)),
+        }
+    }
+
+    /// Bytes over the limit, or `0` if within it.
+    pub(crate) fn overrun(&self) -> u64 {
+        self.used.load(Ordering::Relaxed).saturating_sub(self.limit)
+    }
+
+    /// Mark `keys` as just used. Keys not in the CF are ignored.
+    pub(crate) fn touch<'k>(&self, keys: impl IntoIterator<Item = &'k [u8]>) {
+        let mut recency = self.recency.lock().unwrap();
+        for key in keys {
+            recency.touch(key);
+        }
+    }
+
+    /// Account for `writes` being applied on top of `current`. Written keys
+    /// count as used; deleted keys stop being tracked.
+    pub(crate) fn apply(&self, current: &ColumnFamily, writes: &WriteSet) {
+        let mut added = 0u64;
+        let mut removed = 0u64;
+        let mut recency = self.recency.lock().unwrap();
+        for (key, value) in writes {
+            if let Some(old) = current.get(key) {
+                removed += entry_size(key, old);
+            }
+            match value {
+                Some(value) => {
+                    added += entry_size(key, value);
+                    recency.insert(key);
+                }
+                None => recency.remove(key),
+            }
+        }
+        self.used.fetch_add(added, Ordering::Relaxed);
+        self.used.fetch_sub(removed, Ordering::Relaxed);
+    }
+
+    /// Account for entries removed outside a transaction (`delete_range`).
+    pub(crate) fn remove<'e>(&self, entries: impl IntoIterator<Item = (&'e Vec<u8>, &'e Vec<u8>)>) {
+        let mut removed = 0u64;
+        let mut recency = self.recency.lock().unwrap();
+        for (key, value) in entries {
+            removed += entry_size(key, value);
+            recency.remove(key);
+        }
+        self.used.fetch_sub(removed, Ordering::Relaxed);
+    }
+
+    /// Forget everything, for a dropped CF.
+    pub(crate) fn clear(&self) {
+        self.used.store(0, Ordering::Relaxed);
+        *self.recency.lock().unwrap() = Recency::default();
+    }
+
+    /// Up to `limit` keys of `data` under `prefix`, least recently used
+    /// first. Walks the recency order from the oldest key, so the cost is
+    /// `limit` plus any older keys outside `prefix`, not the size of the CF.
+    pub(crate) fn candidates(
+        &self,
+        data: &ColumnFamily,
+        prefix: &[u8],
+        limit: usize,
+    ) -> Vec<Vec<u8>> {
+        let recency = self.recency.lock().unwrap();
+        recency
+            .order
+            .values()
+            .filter(|key| key.starts_with(prefix) && data.contains_key(key.as_ref()))
+            .take(limit)
+            .map(|key| key.to_vec())
+            .collect()
+    }
+}
+
+fn entry_size(key: &[u8], value: &[u8]) -> u64 {
+    (key.len() + value.len()) as u64
+}
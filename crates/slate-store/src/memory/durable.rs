@@ -0,0 +1,345 @@
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::ops::{Bound, RangeBounds};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+
+use crate::error::StoreError;
+use crate::store::{SnapshotStore, Store, Transaction};
+
+use super::snapshot::{read_snapshot, write_snapshot};
+use super::store::{has_writes, MemorySnapshot, MemoryStore, TxnChanges};
+use super::transaction::{MemoryCf, MemoryTransaction};
+use super::wal::{Wal, WalOp};
+
+const SNAPSHOT_FILE: &str = "snapshot";
+const SNAPSHOT_TMP_FILE: &str = "snapshot.tmp";
+const WAL_FILE: &str = "wal";
+
+/// Default log size that triggers compaction into a snapshot (64 MiB).
+const DEFAULT_COMPACTION_THRESHOLD: u64 = 64 * 1024 * 1024;
+
+/// A [`MemoryStore`] whose commits are persisted to a write-ahead log.
+///
+/// Reads and writes run against the in-memory store exactly as before. On
+/// commit, once the transaction passes conflict validation, its write set is
+/// appended to `{dir}/wal` and fsync'd before it is published to readers. Opening the directory loads
+/// `{dir}/snapshot` and replays the log on top of it.
+///
+/// Once the log grows past the compaction threshold, the commit that crossed
+/// it folds the full store into a new snapshot and truncates the log.
+pub struct DurableMemoryStore {
+    mem: MemoryStore,
+    dir: PathBuf,
+    wal: Mutex<Wal>,
+    compaction_threshold: u64,
+}
+
+impl DurableMemoryStore {
+    /// Open (or create) a durable store in `dir`, replaying any existing data.
+    pub fn open(dir: impl AsRef<Path>) -> Result<Self, StoreError> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir).map_err(|e| StoreError::Storage(e.to_string()))?;
+
+        let mut cfs = match fs::read(dir.join(SNAPSHOT_FILE)) {
+            Ok(bytes) => read_snapshot(&bytes)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => HashMap::new(),
+            Err(e) => return Err(StoreError::Storage(e.to_string())),
+        };
+
+        let (wal, batches) = Wal::open(&dir.join(WAL_FILE))?;
+        for op in batches.into_iter().flatten() {
+            op.apply(&mut cfs);
+        }
+
+        Ok(Self {
+            mem: MemoryStore::from_cfs(cfs),
+            dir,
+            wal: Mutex::new(wal),
+            compaction_threshold: DEFAULT_COMPACTION_THRESHOLD,
+        })
+    }
+
+    /// Set the log size (in bytes) at which a commit triggers compaction.
+    pub fn with_compaction_threshold(mut self, bytes: u64) -> Self {
+        self.compaction_threshold = bytes;
+        self
+    }
+
+    /// Fold the current state into a snapshot and truncate the log.
+    ///
+    /// Waits for any in-progress commit to finish first.
+    pub fn compact(&self) -> Result<(), StoreError> {
+        let _guard = self.mem.lock_commits()?;
+        let mut wal = self.lock_wal()?;
+        self.compact_locked(&mut wal)
+    }
+
+    fn lock_wal(&self) -> Result<MutexGuard<'_, Wal>, StoreError> {
+        self.wal
+            .lock()
+            .map_err(|e| StoreError::Storage(format!("wal lock poisoned: {e}")))
+    }
+
+    /// Write the snapshot, then truncate the log. The caller holds both the
+    /// commit lock and the log lock, so no commit can slip in between.
+    ///
+    /// The snapshot is written to a temp file and renamed into place; a crash
+    /// before the truncate just replays log records the snapshot already
+    /// contains, which is harmless.
+    fn compact_locked(&self, wal: &mut Wal) -> Result<(), StoreError> {
+        let io_err = |e: std::io::Error| StoreError::Storage(format!("compaction failed: {e}"));
+        let tmp = self.dir.join(SNAPSHOT_TMP_FILE);
+
+        let cfs = self.mem.sorted_snapshot();
+        let file = File::create(&tmp).map_err(io_err)?;
+        write_snapshot(std::io::BufWriter::new(&file), &cfs)?;
+        file.sync_all().map_err(io_err)?;
+        fs::rename(&tmp, self.dir.join(SNAPSHOT_FILE)).map_err(io_err)?;
+        sync_dir(&self.dir).map_err(io_err)?;
+
+        wal.truncate()
+    }
+
+    /// Log `ops` and then apply them in memory via `apply`.
+    fn log_then<T>(
+        &self,
+        ops: &[WalOp],
+        apply: impl FnOnce() -> Result<T, StoreError>,
+    ) -> Result<T, StoreError> {
+        let mut wal = self.lock_wal()?;
+        wal.append(ops)?;
+        apply()
+    }
+
+    /// Validate, persist and publish a transaction's changes.
+    fn commit(&self, changes: TxnChanges) -> Result<(), StoreError> {
+        let _guard = self.mem.lock_commits()?;
+        self.mem.validate(&changes)?;
+
+        let mut ops = Vec::new();
+        for (name, cf) in &changes {
+            // A CF dropped after the transaction touched it is skipped by
+            // `MemoryStore::publish`, so there is nothing to log.
+            if self.mem.snapshot_cf(name).is_none() {
+                continue;
+            }
+            for (key, value) in &cf.writes {
+                ops.push(match value {
+                    Some(value) => WalOp::Put {
+                        cf: name.clone(),
+                        key: key.clone(),
+                        value: value.clone(),
+                    },
+                    None => WalOp::Delete {
+                        cf: name.clone(),
+                        key: key.clone(),
+                    },
+                });
+            }
+        }
+
+        let mut wal = self.lock_wal()?;
+        if !ops.is_empty() {
+            wal.append(&ops)?;
+        }
+        self.mem.publish(changes);
+
+        // The commit is already durable, so a failed compaction is not an
+        // error for this transaction; the next commit over the threshold retries.
+        if wal.len() >= self.compaction_threshold {
+            let _ = self.compact_locked(&mut wal);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(unix)]
+fn sync_dir(dir: &Path) -> std::io::Result<()> {
+    File::open(dir)?.sync_all()
+}
+
+#[cfg(not(unix))]
+fn sync_dir(_dir: &Path) -> std::io::Result<()> {
+    Ok(())
+}
+
+impl Store for DurableMemoryStore {
+    type Txn<'a> = DurableTransaction<'a>;
+
+    fn begin(&self, read_only: bool) -> Result<Self::Txn<'_>, StoreError> {
+        Ok(DurableTransaction {
+            inner: self.mem.begin(read_only)?,
+            store: self,
+        })
+    }
+
+    fn create_cf(&self, name: &str) -> Result<(), StoreError> {
+        if self.mem.snapshot_cf(name).is_some() {
+            return Ok(());
+        }
+        self.log_then(&[WalOp::CreateCf(name.to_string())], || {
+            self.mem.create_cf(name)
+        })
+    }
+
+    fn drop_cf(&self, name: &str) -> Result<(), StoreError> {
+        self.log_then(&[WalOp::DropCf(name.to_string())], || {
+            self.mem.drop_cf(name)
+        })
+    }
+
+    /// Holds the commit lock while logging and applying the deletes, so they
+    /// line up with commits in the log.
+    fn delete_range(&self, cf: &str, range: impl RangeBounds<Vec<u8>>) -> Result<(), StoreError> {
+        let _guard = self.mem.lock_commits()?;
+        let data = self
+            .mem
+            .snapshot_cf(cf)
+            .ok_or_else(|| StoreError::Storage(format!("column family not found: {cf}")))?;
+        let bounds = (range.start_bound().cloned(), range.end_bound().cloned());
+        let ops: Vec<WalOp> = data
+            .range((bounds.0.clone(), bounds.1.clone()))
+            .map(|(key, _)| WalOp::Delete {
+                cf: cf.to_string(),
+                key: key.clone(),
+            })
+            .collect();
+        if ops.is_empty() {
+            return Ok(());
+        }
+        self.log_then(&ops, || self.mem.delete_range_locked(cf, bounds))
+    }
+
+    /// The in-memory data needs no compaction, so this folds the log into a
+    /// snapshot instead; the range is ignored.
+    fn compact_range(&self, _cf: &str, _range: impl RangeBounds<Vec<u8>>) -> Result<(), StoreError> {
+        self.compact()
+    }
+}
+
+impl SnapshotStore for DurableMemoryStore {
+    type Snapshot = MemorySnapshot;
+
+    fn snapshot(&self) -> Result<MemorySnapshot, StoreError> {
+        self.mem.snapshot()
+    }
+}
+
+/// Transaction over a [`DurableMemoryStore`].
+///
+/// Delegates to [`MemoryTransaction`]; only `commit` and CF creation/removal
+/// go through the log.
+pub struct DurableTransaction<'a> {
+    inner: MemoryTransaction<'a>,
+    store: &'a DurableMemoryStore,
+}
+
+#[allow(clippy::type_complexity)]
+impl<'a> Transaction for DurableTransaction<'a> {
+    type Cf = MemoryCf;
+
+    fn cf(&self, name: &str) -> Result<Self::Cf, StoreError> {
+        self.inner.cf(name)
+    }
+
+    fn get(&self, cf: &Self::Cf, key: &[u8]) -> Result<Option<Vec<u8>>, StoreError> {
+        self.inner.get(cf, key)
+    }
+
+    fn get_for_update(&self, cf: &Self::Cf, key: &[u8]) -> Result<Option<Vec<u8>>, StoreError> {
+        self.inner.get_for_update(cf, key)
+    }
+
+    fn multi_get(&self, cf: &Self::Cf, keys: &[&[u8]]) -> Result<Vec<Option<Vec<u8>>>, StoreError> {
+        self.inner.multi_get(cf, keys)
+    }
+
+    fn scan_prefix<'b>(
+        &'b self,
+        cf: &Self::Cf,
+        prefix: &[u8],
+    ) -> Result<Box<dyn Iterator<Item = Result<(Vec<u8>, Vec<u8>), StoreError>> + 'b>, StoreError>
+    {
+        self.inner.scan_prefix(cf, prefix)
+    }
+
+    fn scan_prefix_rev<'b>(
+        &'b self,
+        cf: &Self::Cf,
+        prefix: &[u8],
+    ) -> Result<Box<dyn Iterator<Item = Result<(Vec<u8>, Vec<u8>), StoreError>> + 'b>, StoreError>
+    {
+        self.inner.scan_prefix_rev(cf, prefix)
+    }
+
+    fn scan_range<'b>(
+        &'b self,
+        cf: &Self::Cf,
+        start: Bound<&[u8]>,
+        end: Bound<&[u8]>,
+        reverse: bool,
+    ) -> Result<Box<dyn Iterator<Item = Result<(Vec<u8>, Vec<u8>), StoreError>> + 'b>, StoreError>
+    {
+        self.inner.scan_range(cf, start, end, reverse)
+    }
+
+    fn put(&self, cf: &Self::Cf, key: &[u8], value: &[u8]) -> Result<(), StoreError> {
+        self.inner.put(cf, key, value)
+    }
+
+    fn put_batch(&self, cf: &Self::Cf, entries: &[(&[u8], &[u8])]) -> Result<(), StoreError> {
+        self.inner.put_batch(cf, entries)
+    }
+
+    fn delete(&self, cf: &Self::Cf, key: &[u8]) -> Result<(), StoreError> {
+        self.inner.delete(cf, key)
+    }
+
+    fn delete_batch(&self, cf: &Self::Cf, keys: &[&[u8]]) -> Result<(), StoreError> {
+        self.inner.delete_batch(cf, keys)
+    }
+
+    // CF creation and removal take effect immediately on the underlying
+    // MemoryStore (they are not transactional there either), so they are
+    // logged immediately too.
+    fn create_cf(&mut self, name: &str) -> Result<(), StoreError> {
+        self.inner.check_writable()?;
+        if self.store.mem.snapshot_cf(name).is_none() {
+            self.store
+                .log_then(&[WalOp::CreateCf(name.to_string())], || Ok(()))?;
+        }
+        self.inner.create_cf(name)
+    }
+
+    fn drop_cf(&mut self, name: &str) -> Result<(), StoreError> {
+        self.inner.check_writable()?;
+        self.store
+            .log_then(&[WalOp::DropCf(name.to_string())], || Ok(()))?;
+        self.inner.drop_cf(name)
+    }
+
+    fn set_savepoint(&mut self) -> Result<(), StoreError> {
+        self.inner.set_savepoint()
+    }
+
+    fn rollback_to_savepoint(&mut self) -> Result<(), StoreError> {
+        self.inner.rollback_to_savepoint()
+    }
+
+    fn release_savepoint(&mut self) -> Result<(), StoreError> {
+        self.inner.release_savepoint()
+    }
+
+    fn commit(self) -> Result<(), StoreError> {
+        let changes = self.inner.into_changes()?;
+        if !has_writes(&changes) {
+            return Ok(());
+        }
+        self.store.commit(changes)
+    }
+
+    fn rollback(self) -> Result<(), StoreError> {
+        self.inner.rollback()
+    }
+}
@@ -1,4 +1,9 @@
+mod budget;
+mod durable;
+mod snapshot;
 mod store;
 mod transaction;
+mod wal;
 
-pub use store::MemoryStore;
+pub use durable::DurableMemoryStore;
+pub use store::{MemorySnapshot, MemoryStore};
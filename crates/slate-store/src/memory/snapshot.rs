@@ -0,0 +1,158 @@
+use std::collections::HashMap;
+use std::io::{self, Write};
+use std::sync::Arc;
+
+use crate::error::StoreError;
+
+use super::store::ColumnFamily;
+
+// ── Format ───────────────────────────────────────────────────
+//
+// [magic: 4 = "SLMS"][version: 1]
+// [cf_count: u32 LE]
+//   per CF: [name_len: u32 LE][name][entry_count: u64 LE]
+//     per entry: [key_len: u32 LE][key][value_len: u32 LE][value]
+// [crc32: u32 LE]  — over every preceding byte
+
+const MAGIC: &[u8; 4] = b"SLMS";
+const VERSION: u8 = 1;
+const CRC_SIZE: usize = 4;
+
+/// Writer adapter that feeds every byte through a CRC32 hasher.
+struct CrcWriter<W: Write> {
+    inner: W,
+    hasher: crc32fast::Hasher,
+}
+
+impl<W: Write> Write for CrcWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.hasher.update(&buf[..n]);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Write a `u32`-length-prefixed byte string.
+pub(super) fn write_bytes<W: Write>(w: &mut W, bytes: &[u8]) -> io::Result<()> {
+    w.write_all(&(bytes.len() as u32).to_le_bytes())?;
+    w.write_all(bytes)
+}
+
+/// Serialize column families to `w` in the snapshot format.
+pub(crate) fn write_snapshot<W: Write>(
+    w: W,
+    cfs: &[(String, Arc<ColumnFamily>)],
+) -> Result<(), StoreError> {
+    let mut w = CrcWriter {
+        inner: w,
+        hasher: crc32fast::Hasher::new(),
+    };
+    let io_err = |e: io::Error| StoreError::Storage(format!("snapshot write failed: {e}"));
+
+    w.write_all(MAGIC).map_err(io_err)?;
+    w.write_all(&[VERSION]).map_err(io_err)?;
+    w.write_all(&(cfs.len() as u32).to_le_bytes())
+        .map_err(io_err)?;
+    for (name, data) in cfs {
+        write_bytes(&mut w, name.as_bytes()).map_err(io_err)?;
+        w.write_all(&(data.len() as u64).to_le_bytes())
+            .map_err(io_err)?;
+        for (key, value) in data.iter() {
+            write_bytes(&mut w, key).map_err(io_err)?;
+            write_bytes(&mut w, value).map_err(io_err)?;
+        }
+    }
+
+    let crc = w.hasher.clone().finalize();
+    w.inner.write_all(&crc.to_le_bytes()).map_err(io_err)?;
+    w.inner.flush().map_err(io_err)
+}
+
+/// Bounds-checked cursor over snapshot or log bytes.
+pub(super) struct Reader<'a> {
+    pub(super) buf: &'a [u8],
+    /// What is being decoded, for error messages.
+    pub(super) what: &'static str,
+}
+
+impl<'a> Reader<'a> {
+    pub(super) fn take(&mut self, n: usize) -> Result<&'a [u8], StoreError> {
+        if self.buf.len() < n {
+            return Err(StoreError::Storage(format!(
+                "corrupt {}: unexpected end of data",
+                self.what
+            )));
+        }
+        let (head, tail) = self.buf.split_at(n);
+        self.buf = tail;
+        Ok(head)
+    }
+
+    pub(super) fn u32(&mut self) -> Result<u32, StoreError> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    pub(super) fn u64(&mut self) -> Result<u64, StoreError> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    pub(super) fn bytes(&mut self) -> Result<&'a [u8], StoreError> {
+        let len = self.u32()? as usize;
+        self.take(len)
+    }
+}
+
+fn corrupt(msg: &str) -> StoreError {
+    StoreError::Storage(format!("corrupt snapshot: {msg}"))
+}
+
+/// Decode a snapshot produced by [`write_snapshot`].
+///
+/// Verifies the magic, version, and trailing checksum before touching any
+/// entry, so a truncated or bit-flipped file is rejected as a whole.
+pub(crate) fn read_snapshot(bytes: &[u8]) -> Result<HashMap<String, ColumnFamily>, StoreError> {
+    if bytes.len() < MAGIC.len() + 1 + CRC_SIZE {
+        return Err(corrupt("too short"));
+    }
+    let (body, crc) = bytes.split_at(bytes.len() - CRC_SIZE);
+    if crc32fast::hash(body) != u32::from_le_bytes(crc.try_into().unwrap()) {
+        return Err(corrupt("checksum mismatch"));
+    }
+
+    let mut r = Reader {
+        buf: body,
+        what: "snapshot",
+    };
+    if r.take(MAGIC.len())? != MAGIC {
+        return Err(corrupt("bad magic"));
+    }
+    let version = r.take(1)?[0];
+    if version != VERSION {
+        return Err(corrupt(&format!("unsupported version {version}")));
+    }
+
+    let cf_count = r.u32()?;
+    let mut cfs = HashMap::with_capacity(cf_count as usize);
+    for _ in 0..cf_count {
+        let name = std::str::from_utf8(r.bytes()?)
+            .map_err(|_| corrupt("column family name is not UTF-8"))?
+            .to_string();
+        let entry_count = r.u64()?;
+        let mut data = ColumnFamily::new();
+        for _ in 0..entry_count {
+            let key = r.bytes()?.to_vec();
+            let value = r.bytes()?.to_vec();
+            data.insert(key, value);
+        }
+        cfs.insert(name, data);
+    }
+
+    if !r.buf.is_empty() {
+        return Err(corrupt("trailing bytes"));
+    }
+    Ok(cfs)
+}
@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::ops::{Bound, RangeBounds};
 use std::path::Path;
 use std::sync::{Arc, Mutex, MutexGuard, RwLock};
@@ -7,22 +7,60 @@ use arc_swap::ArcSwap;
 use imbl::OrdMap;
 
 use crate::error::StoreError;
-use crate::store::{BackupStore, Store};
+use crate::store::{BackupStore, ForkableStore, SnapshotStore, Store};
 
+use super::budget::CfBudget;
+use super::snapshot::{read_snapshot, write_snapshot};
 use super::transaction::MemoryTransaction;
 
 pub(crate) type ColumnFamily = OrdMap<Vec<u8>, Vec<u8>>;
 
+/// Keys a transaction wrote to one column family; `None` marks a delete.
+pub(crate) type WriteSet = OrdMap<Vec<u8>, Option<Vec<u8>>>;
+
+/// What a write transaction did to one column family, for commit-time
+/// validation.
+pub(crate) struct CfChanges {
+    /// The committed version the transaction started from.
+    pub(crate) base: Arc<ColumnFamily>,
+    /// `base` with the transaction's writes applied.
+    pub(crate) data: Arc<ColumnFamily>,
+    /// Keys read with `get` / `multi_get`.
+    pub(crate) reads: HashSet<Vec<u8>>,
+    pub(crate) writes: WriteSet,
+}
+
+/// A write transaction's changes, keyed by column family name.
+pub(crate) type TxnChanges = HashMap<String, CfChanges>;
+
+/// Whether committing `changes` would modify anything.
+pub(crate) fn has_writes(changes: &TxnChanges) -> bool {
+    changes.values().any(|cf| !cf.writes.is_empty())
+}
+
+/// In-memory store with optimistic concurrency control.
+///
+/// Any number of write transactions can run at once. Each records the keys
+/// it point-reads and writes; commit checks them against the latest
+/// committed versions and fails with [`StoreError::Conflict`] if another
+/// transaction changed any of them in the meantime. Scans aren't recorded
+/// (see [`MemoryTransaction`](super::transaction::MemoryTransaction)). Only commits (and
+/// `delete_range`) serialize, on a short internal lock.
+///
+/// By default the store grows without limit. For cache use, give column
+/// families a byte budget with [`MemoryStore::with_cf_budget`].
 pub struct MemoryStore {
     cfs: RwLock<HashMap<String, Arc<ArcSwap<ColumnFamily>>>>,
-    write_lock: Mutex<()>,
+    commit_lock: Mutex<()>,
+    budgets: HashMap<String, CfBudget>,
 }
 
 impl Default for MemoryStore {
     fn default() -> Self {
         Self {
             cfs: RwLock::new(HashMap::new()),
-            write_lock: Mutex::new(()),
+            commit_lock: Mutex::new(()),
+            budgets: HashMap::new(),
         }
     }
 }
@@ -32,11 +70,68 @@ impl MemoryStore {
         Self::default()
     }
 
-    /// Acquire the write lock. Only one write transaction can exist at a time.
-    pub(crate) fn acquire_write_lock(&self) -> Result<MutexGuard<'_, ()>, StoreError> {
-        self.write_lock
+    /// Cap column family `cf` at roughly `bytes` of keys and values.
+    ///
+    /// The store only tracks usage and access recency; it doesn't delete
+    /// anything itself. When a commit leaves the CF over budget, the engine
+    /// evicts the least recently used records (and their index entries)
+    /// until it fits again. Point reads and writes count as uses; scans
+    /// don't, so a full collection scan doesn't flush the cache.
+    pub fn with_cf_budget(mut self, cf: &str, bytes: u64) -> Self {
+        let data = self.snapshot_cf(cf);
+        self.budgets
+            .insert(cf.to_string(), CfBudget::new(bytes, data.as_deref()));
+        self
+    }
+
+    /// Load a store from a snapshot file written by [`BackupStore::backup`].
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, StoreError> {
+        let bytes = std::fs::read(path.as_ref())
+            .map_err(|e| StoreError::Storage(format!("load failed: {e}")))?;
+        Self::from_bytes(&bytes)
+    }
+
+    /// Rehydrate a store from snapshot bytes produced by [`MemoryStore::to_bytes`]
+    /// or read from a backup file.
+    ///
+    /// The checksum is verified before any data is decoded, so a truncated or
+    /// corrupted snapshot is rejected as a whole.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, StoreError> {
+        Ok(Self::from_cfs(read_snapshot(bytes)?))
+    }
+
+    /// Serialize every column family into the snapshot format.
+    ///
+    /// Same bytes as [`BackupStore::backup`] writes, for targets with no
+    /// filesystem (e.g. wasm).
+    pub fn to_bytes(&self) -> Result<Vec<u8>, StoreError> {
+        let mut buf = Vec::new();
+        write_snapshot(&mut buf, &self.sorted_snapshot())?;
+        Ok(buf)
+    }
+
+    /// Every column family, ordered by name so snapshots are deterministic.
+    ///
+    /// Each CF is loaded from its `ArcSwap` without taking the commit lock, so
+    /// this never blocks writers. Like a read transaction, each CF is a
+    /// consistent point-in-time view.
+    pub(crate) fn sorted_snapshot(&self) -> Vec<(String, Arc<ColumnFamily>)> {
+        let mut cfs: Vec<_> = {
+            let cfs = self.cfs.read().unwrap();
+            cfs.iter()
+                .map(|(name, arc_swap)| (name.clone(), arc_swap.load_full()))
+                .collect()
+        };
+        cfs.sort_by(|a, b| a.0.cmp(&b.0));
+        cfs
+    }
+
+    /// Acquire the commit lock. Validation and publication of a commit (and
+    /// any other change to the committed versions) happen under it.
+    pub(crate) fn lock_commits(&self) -> Result<MutexGuard<'_, ()>, StoreError> {
+        self.commit_lock
             .lock()
-            .map_err(|e| StoreError::Storage(format!("write lock poisoned: {e}")))
+            .map_err(|e| StoreError::Storage(format!("commit lock poisoned: {e}")))
     }
 
     /// Snapshot a single column family (lazy — called on first access).
@@ -46,15 +141,130 @@ impl MemoryStore {
         Some(arc_swap.load_full())
     }
 
-    /// Commit dirty CFs back to the store. The caller must already hold the
-    /// write lock, so no conflict detection is needed.
-    pub(crate) fn commit(&self, dirty: HashMap<String, Arc<ColumnFamily>>) {
+    /// Build a store from fully materialized column families.
+    pub(crate) fn from_cfs(data: HashMap<String, ColumnFamily>) -> Self {
+        let cfs = data
+            .into_iter()
+            .map(|(name, cf)| (name, Arc::new(ArcSwap::new(Arc::new(cf)))))
+            .collect();
+        Self {
+            cfs: RwLock::new(cfs),
+            commit_lock: Mutex::new(()),
+            budgets: HashMap::new(),
+        }
+    }
+
+    /// Every column family's current map, each behind a fresh `ArcSwap`.
+    ///
+    /// Taken under the commit lock, so the result never holds part of a
+    /// commit. The maps are shared, not copied.
+    fn share_cfs(&self) -> Result<HashMap<String, Arc<ArcSwap<ColumnFamily>>>, StoreError> {
+        let _guard = self.lock_commits()?;
+        let cfs = self.cfs.read().unwrap();
+        Ok(cfs
+            .iter()
+            .map(|(name, arc_swap)| (name.clone(), Arc::new(ArcSwap::new(arc_swap.load_full()))))
+            .collect())
+    }
+
+    /// Record point reads of `keys` for eviction ordering. Free for CFs
+    /// without a budget.
+    pub(crate) fn touch<'k>(&self, cf: &str, keys: impl IntoIterator<Item = &'k [u8]>) {
+        if let Some(budget) = self.budgets.get(cf) {
+            budget.touch(keys);
+        }
+    }
+
+    /// Check a transaction's read and write sets against the latest committed
+    /// versions. The caller must hold the commit lock.
+    ///
+    /// A key conflicts if its committed value differs from the one the
+    /// transaction started from. CFs dropped since are skipped; `publish`
+    /// ignores them too.
+    pub(crate) fn validate(&self, changes: &TxnChanges) -> Result<(), StoreError> {
+        for (name, cf) in changes {
+            let Some(current) = self.snapshot_cf(name) else {
+                continue;
+            };
+            if Arc::ptr_eq(&current, &cf.base) {
+                continue;
+            }
+            let conflict = cf
+                .reads
+                .iter()
+                .chain(cf.writes.keys())
+                .any(|key| current.get(key) != cf.base.get(key));
+            if conflict {
+                return Err(StoreError::Conflict(format!(
+                    "column family {name} was modified by a concurrent transaction"
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Apply a validated transaction's writes to the latest committed
+    /// versions. The caller must hold the commit lock.
+    ///
+    /// If nothing else committed to a CF since the transaction loaded it, the
+    /// transaction's copy is published as is; otherwise its write set is
+    /// replayed on top of the current version, so writers to disjoint keys
+    /// both land.
+    pub(crate) fn publish(&self, changes: TxnChanges) {
         let cfs = self.cfs.read().unwrap();
-        for (name, data) in dirty {
-            if let Some(arc_swap) = cfs.get(&name) {
-                arc_swap.store(data);
+        for (name, cf) in changes {
+            if cf.writes.is_empty() {
+                continue;
             }
+            let Some(arc_swap) = cfs.get(&name) else {
+                continue;
+            };
+            let current = arc_swap.load_full();
+            if let Some(budget) = self.budgets.get(&name) {
+                budget.apply(&current, &cf.writes);
+            }
+            if Arc::ptr_eq(&current, &cf.base) {
+                arc_swap.store(cf.data);
+                continue;
+            }
+            let mut data = (*current).clone();
+            for (key, value) in cf.writes {
+                match value {
+                    Some(value) => data.insert(key, value),
+                    None => data.remove(&key),
+                };
+            }
+            arc_swap.store(Arc::new(data));
+        }
+    }
+
+    /// [`Store::delete_range`] for a caller that already holds the commit lock.
+    pub(crate) fn delete_range_locked(
+        &self,
+        cf: &str,
+        range: impl RangeBounds<Vec<u8>>,
+    ) -> Result<(), StoreError> {
+        let cfs = self.cfs.read().unwrap();
+        let arc = cfs
+            .get(cf)
+            .ok_or_else(|| StoreError::Storage(format!("column family not found: {cf}")))?;
+
+        let mut data = (**arc.load()).clone();
+
+        let keys_to_delete: Vec<Vec<u8>> = data
+            .range(range_to_ord_bounds(&range))
+            .map(|(k, _)| k.clone())
+            .collect();
+
+        if let Some(budget) = self.budgets.get(cf) {
+            budget.remove(data.range(range_to_ord_bounds(&range)));
+        }
+        for key in keys_to_delete {
+            data.remove(&key);
         }
+
+        arc.store(Arc::new(data));
+        Ok(())
     }
 }
 
@@ -65,8 +275,7 @@ impl Store for MemoryStore {
         if read_only {
             Ok(MemoryTransaction::new_read_only(self))
         } else {
-            let guard = self.acquire_write_lock()?;
-            Ok(MemoryTransaction::new_writable(self, guard))
+            Ok(MemoryTransaction::new_writable(self))
         }
     }
 
@@ -80,40 +289,143 @@ impl Store for MemoryStore {
     fn drop_cf(&self, name: &str) -> Result<(), StoreError> {
         let mut cfs = self.cfs.write().unwrap();
         cfs.remove(name);
+        if let Some(budget) = self.budgets.get(name) {
+            budget.clear();
+        }
         Ok(())
     }
 
+    /// Takes effect immediately. Open transactions that read or wrote any of
+    /// the deleted keys fail to commit with [`StoreError::Conflict`].
     fn delete_range(&self, cf: &str, range: impl RangeBounds<Vec<u8>>) -> Result<(), StoreError> {
-        let cfs = self.cfs.read().unwrap();
-        let arc = cfs
-            .get(cf)
-            .ok_or_else(|| StoreError::Storage(format!("column family not found: {cf}")))?;
-
-        let mut data = (**arc.load()).clone();
+        let _guard = self.lock_commits()?;
+        self.delete_range_locked(cf, range)
+    }
 
-        let keys_to_delete: Vec<Vec<u8>> = data
-            .range(range_to_ord_bounds(&range))
-            .map(|(k, _)| k.clone())
-            .collect();
+    /// No-op: deleted keys are dropped from the map as soon as no snapshot
+    /// or transaction still holds the version that contained them.
+    fn compact_range(&self, _cf: &str, _range: impl RangeBounds<Vec<u8>>) -> Result<(), StoreError> {
+        Ok(())
+    }
 
-        for key in keys_to_delete {
-            data.remove(&key);
-        }
+    fn budget_overruns(&self) -> Vec<(String, u64)> {
+        self.budgets
+            .iter()
+            .map(|(name, budget)| (name.clone(), budget.overrun()))
+            .filter(|(_, over)| *over > 0)
+            .collect()
+    }
 
-        arc.store(Arc::new(data));
-        Ok(())
+    fn eviction_candidates(
+        &self,
+        cf: &str,
+        prefix: &[u8],
+        limit: usize,
+    ) -> Result<Vec<Vec<u8>>, StoreError> {
+        let (Some(budget), Some(data)) = (self.budgets.get(cf), self.snapshot_cf(cf)) else {
+            return Ok(Vec::new());
+        };
+        Ok(budget.candidates(&data, prefix, limit))
     }
 }
 
 impl BackupStore for MemoryStore {
-    fn backup(&self, _dest: &Path) -> Result<(), StoreError> {
-        Err(StoreError::Storage(
-            "backup is not supported for in-memory stores".into(),
-        ))
+    /// Write a checksummed snapshot of every column family to `dest`.
+    ///
+    /// Restore with [`MemoryStore::load`].
+    fn backup(&self, dest: &Path) -> Result<(), StoreError> {
+        let file = std::fs::File::create(dest)
+            .map_err(|e| StoreError::Storage(format!("backup failed: {e}")))?;
+        write_snapshot(std::io::BufWriter::new(&file), &self.sorted_snapshot())?;
+        file.sync_all()
+            .map_err(|e| StoreError::Storage(format!("backup failed: {e}")))
     }
 }
 
 /// Convert RangeBounds<Vec<u8>> to concrete bounds for OrdMap::range.
+impl SnapshotStore for MemoryStore {
+    type Snapshot = MemorySnapshot;
+
+    /// Captures every column family under the commit lock, so a snapshot
+    /// never sees part of a commit. The CF maps are shared, not copied.
+    fn snapshot(&self) -> Result<MemorySnapshot, StoreError> {
+        Ok(MemorySnapshot {
+            store: MemoryStore {
+                cfs: RwLock::new(self.share_cfs()?),
+                commit_lock: Mutex::new(()),
+                budgets: HashMap::new(),
+            },
+        })
+    }
+}
+
+impl ForkableStore for MemoryStore {
+    /// Shares every column family's current map with the fork, so the cost
+    /// is one `Arc` clone per CF. Either side copies nodes only as it
+    /// writes them. Memory budgets carry over; access recency doesn't.
+    fn fork(&self) -> Result<MemoryStore, StoreError> {
+        let cfs = self.share_cfs()?;
+        let budgets = self
+            .budgets
+            .iter()
+            .map(|(name, budget)| {
+                let data = cfs.get(name).map(|data| data.load_full());
+                (name.clone(), budget.fork(data.as_deref()))
+            })
+            .collect();
+        Ok(MemoryStore {
+            cfs: RwLock::new(cfs),
+            commit_lock: Mutex::new(()),
+            budgets,
+        })
+    }
+}
+
+/// Read-only, point-in-time view of a [`MemoryStore`] or
+/// [`DurableMemoryStore`](super::DurableMemoryStore), taken with
+/// [`SnapshotStore::snapshot`].
+///
+/// Holds `Arc` clones of each column family as of the snapshot; later
+/// commits build new versions and leave these untouched.
+pub struct MemorySnapshot {
+    store: MemoryStore,
+}
+
+impl Store for MemorySnapshot {
+    type Txn<'a> = MemoryTransaction<'a>;
+
+    fn begin(&self, read_only: bool) -> Result<Self::Txn<'_>, StoreError> {
+        if !read_only {
+            return Err(StoreError::ReadOnly);
+        }
+        self.store.begin(true)
+    }
+
+    fn is_read_only(&self) -> bool {
+        true
+    }
+
+    fn create_cf(&self, _name: &str) -> Result<(), StoreError> {
+        Err(StoreError::ReadOnly)
+    }
+
+    fn drop_cf(&self, _name: &str) -> Result<(), StoreError> {
+        Err(StoreError::ReadOnly)
+    }
+
+    fn delete_range(&self, _cf: &str, _range: impl RangeBounds<Vec<u8>>) -> Result<(), StoreError> {
+        Err(StoreError::ReadOnly)
+    }
+
+    fn compact_range(
+        &self,
+        _cf: &str,
+        _range: impl RangeBounds<Vec<u8>>,
+    ) -> Result<(), StoreError> {
+        Err(StoreError::ReadOnly)
+    }
+}
+
 fn range_to_ord_bounds(range: &impl RangeBounds<Vec<u8>>) -> (Bound<Vec<u8>>, Bound<Vec<u8>>) {
     let start = match range.start_bound() {
         Bound::Included(b) => Bound::Included(b.clone()),
@@ -1,29 +1,33 @@
 use std::cell::RefCell;
 use std::collections::{HashMap, HashSet};
-use std::sync::{Arc, MutexGuard};
+use std::ops::Bound;
+use std::sync::Arc;
 
 use imbl::ordmap::RangedIter;
 
 use crate::error::StoreError;
-use crate::store::{increment_prefix, Store, Transaction};
+use crate::store::{increment_prefix, is_empty_range, Store, Transaction};
 
-use super::store::{ColumnFamily, MemoryStore};
+use super::store::{has_writes, CfChanges, ColumnFamily, MemoryStore, TxnChanges, WriteSet};
 
-// ── Lazy prefix iterator ─────────────────────────────────────
+// ── Lazy range iterator ──────────────────────────────────────
 
-/// Lazily iterates over an `OrdMap` prefix range without collecting into a Vec.
+/// Lazily iterates over an `OrdMap` range without collecting into a Vec.
 ///
 /// Owns the `Arc<ColumnFamily>` to keep the map alive while iterating.
 /// Uses `unsafe` to extend the `RangedIter` lifetime — safe because the OrdMap
 /// is heap-allocated behind the Arc and won't be dropped while this struct exists.
-struct PrefixIter {
+///
+/// Every yielded key must start with `prefix`; bounded range scans use an
+/// empty prefix so the check always passes.
+struct RangeIter {
     _data: Arc<ColumnFamily>,
     iter: RangedIter<'static, Vec<u8>, Vec<u8>>,
     prefix: Vec<u8>,
     reverse: bool,
 }
 
-impl PrefixIter {
+impl RangeIter {
     fn forward(data: Arc<ColumnFamily>, prefix: Vec<u8>) -> Self {
         // SAFETY: `data` is heap-allocated via Arc and won't be dropped or moved
         // while this struct exists. The RangedIter borrows from the OrdMap inside
@@ -42,10 +46,18 @@ impl PrefixIter {
         // SAFETY: same as forward — Arc keeps OrdMap alive for the struct's lifetime.
         let iter: RangedIter<'static, Vec<u8>, Vec<u8>> =
             if let Some(upper) = increment_prefix(&prefix) {
-                unsafe { std::mem::transmute(data.range(prefix.clone()..upper)) }
+                unsafe {
+                    std::mem::transmute::<RangedIter<'_, _, _>, RangedIter<'static, _, _>>(
+                        data.range(prefix.clone()..upper),
+                    )
+                }
             } else {
                 // All 0xFF — no upper bound; use open-ended range (starts_with filter applies).
-                unsafe { std::mem::transmute(data.range(prefix.clone()..)) }
+                unsafe {
+                    std::mem::transmute::<RangedIter<'_, _, _>, RangedIter<'static, _, _>>(
+                        data.range(prefix.clone()..),
+                    )
+                }
             };
         Self {
             _data: data,
@@ -54,9 +66,26 @@ impl PrefixIter {
             reverse: true,
         }
     }
+
+    fn bounded(
+        data: Arc<ColumnFamily>,
+        start: Bound<Vec<u8>>,
+        end: Bound<Vec<u8>>,
+        reverse: bool,
+    ) -> Self {
+        // SAFETY: same as forward — Arc keeps OrdMap alive for the struct's lifetime.
+        let iter: RangedIter<'static, Vec<u8>, Vec<u8>> =
+            unsafe { std::mem::transmute(data.range((start, end))) };
+        Self {
+            _data: data,
+            iter,
+            prefix: Vec::new(),
+            reverse,
+        }
+    }
 }
 
-impl Iterator for PrefixIter {
+impl Iterator for RangeIter {
     type Item = Result<(Vec<u8>, Vec<u8>), StoreError>;
 
     fn next(&mut self) -> Option<Self::Item> {
@@ -81,9 +110,40 @@ pub struct MemoryCf {
     pub(crate) name: String,
 }
 
+/// One column family as seen by a transaction.
+#[derive(Clone)]
+struct CfState {
+    /// Committed version when the transaction first touched the CF.
+    base: Arc<ColumnFamily>,
+    /// `base` plus this transaction's writes; all reads go here.
+    data: Arc<ColumnFamily>,
+    /// Keys written so far, replayed onto the latest version at commit.
+    writes: WriteSet,
+}
+
+impl CfState {
+    fn new(base: Arc<ColumnFamily>) -> Self {
+        Self {
+            data: Arc::clone(&base),
+            base,
+            writes: WriteSet::new(),
+        }
+    }
+
+    fn put(&mut self, key: &[u8], value: &[u8]) {
+        Arc::make_mut(&mut self.data).insert(key.to_vec(), value.to_vec());
+        self.writes.insert(key.to_vec(), Some(value.to_vec()));
+    }
+
+    fn delete(&mut self, key: &[u8]) {
+        Arc::make_mut(&mut self.data).remove(key);
+        self.writes.insert(key.to_vec(), None);
+    }
+}
+
 /// Lazily-loaded snapshot of column families.
 struct Snapshot {
-    data: HashMap<String, Arc<ColumnFamily>>,
+    data: HashMap<String, CfState>,
 }
 
 impl Snapshot {
@@ -98,7 +158,7 @@ impl Snapshot {
         if !self.data.contains_key(cf) {
             match store.snapshot_cf(cf) {
                 Some(data) => {
-                    self.data.insert(cf.to_string(), data);
+                    self.data.insert(cf.to_string(), CfState::new(data));
                 }
                 None => {
                     return Err(StoreError::Storage(format!(
@@ -113,55 +173,121 @@ impl Snapshot {
     fn get_cf(&self, cf: &str) -> Result<&Arc<ColumnFamily>, StoreError> {
         self.data
             .get(cf)
+            .map(|state| &state.data)
             .ok_or_else(|| StoreError::Storage(format!("column family not found: {cf}")))
     }
 
-    fn get_cf_mut(&mut self, cf: &str) -> Result<&mut ColumnFamily, StoreError> {
-        let arc = self
-            .data
+    fn get_cf_mut(&mut self, cf: &str) -> Result<&mut CfState, StoreError> {
+        self.data
             .get_mut(cf)
-            .ok_or_else(|| StoreError::Storage(format!("column family not found: {cf}")))?;
-        Ok(Arc::make_mut(arc))
+            .ok_or_else(|| StoreError::Storage(format!("column family not found: {cf}")))
     }
 }
 
+/// Transaction state captured by `set_savepoint`. Cloning the CF maps is
+/// cheap — `OrdMap` shares structure until one side is written.
+struct Savepoint {
+    data: HashMap<String, CfState>,
+}
+
+/// Transaction over a [`MemoryStore`].
+///
+/// Reads see the committed version of each CF as of its first access, plus
+/// this transaction's own writes. Write transactions also record the keys
+/// they point-read (`get` / `multi_get`) and the keys they write; commit
+/// fails with [`StoreError::Conflict`] if the committed value of any of them
+/// differs from the one this transaction started from. Scans (`scan_prefix`,
+/// `scan_range`) are not validated, so a key another transaction inserts
+/// into a scanned range doesn't conflict.
+///
+/// Validating point reads is stricter than RocksDB's optimistic
+/// transactions, which only check the keys a transaction writes. Code that
+/// must conflict on every backend has to write the key it depends on.
 pub struct MemoryTransaction<'a> {
     snapshot: RefCell<Option<Snapshot>>,
-    /// CFs that have been written to.
-    dirty: RefCell<HashSet<String>>,
+    /// Keys read with `get` / `multi_get`, per CF. Empty for read-only
+    /// transactions.
+    reads: RefCell<HashMap<String, HashSet<Vec<u8>>>>,
+    savepoints: Vec<Savepoint>,
     store: &'a MemoryStore,
     read_only: bool,
-    /// Write lock held for the duration of a write transaction.
-    _write_guard: Option<MutexGuard<'a, ()>>,
 }
 
 impl<'a> MemoryTransaction<'a> {
     pub(crate) fn new_read_only(store: &'a MemoryStore) -> Self {
         Self {
             snapshot: RefCell::new(Some(Snapshot::new())),
-            dirty: RefCell::new(HashSet::new()),
+            reads: RefCell::new(HashMap::new()),
+            savepoints: Vec::new(),
             store,
             read_only: true,
-            _write_guard: None,
         }
     }
 
-    pub(crate) fn new_writable(store: &'a MemoryStore, guard: MutexGuard<'a, ()>) -> Self {
+    pub(crate) fn new_writable(store: &'a MemoryStore) -> Self {
         Self {
             snapshot: RefCell::new(Some(Snapshot::new())),
-            dirty: RefCell::new(HashSet::new()),
+            reads: RefCell::new(HashMap::new()),
+            savepoints: Vec::new(),
             store,
             read_only: false,
-            _write_guard: Some(guard),
         }
     }
 
-    fn check_writable(&self) -> Result<(), StoreError> {
+    pub(crate) fn check_writable(&self) -> Result<(), StoreError> {
         if self.read_only {
             return Err(StoreError::ReadOnly);
         }
         Ok(())
     }
+
+    fn record_reads<'k>(&self, cf: &str, keys: impl IntoIterator<Item = &'k [u8]>) {
+        if self.read_only {
+            return;
+        }
+        let mut reads = self.reads.borrow_mut();
+        let set = reads.entry(cf.to_string()).or_default();
+        for key in keys {
+            if !set.contains(key) {
+                set.insert(key.to_vec());
+            }
+        }
+    }
+
+    /// Consume the transaction, returning the read and write sets of every
+    /// CF it touched.
+    ///
+    /// Wrappers use this to persist the writes between
+    /// [`MemoryStore::validate`] and [`MemoryStore::publish`].
+    pub(crate) fn into_changes(self) -> Result<TxnChanges, StoreError> {
+        let snapshot = self
+            .snapshot
+            .into_inner()
+            .ok_or(StoreError::TransactionConsumed)?;
+
+        if self.read_only {
+            return Err(StoreError::ReadOnly);
+        }
+
+        let mut reads = self.reads.into_inner();
+        Ok(snapshot
+            .data
+            .into_iter()
+            .filter_map(|(name, state)| {
+                let reads = reads.remove(&name).unwrap_or_default();
+                if reads.is_empty() && state.writes.is_empty() {
+                    return None;
+                }
+                let changes = CfChanges {
+                    base: state.base,
+                    data: state.data,
+                    reads,
+                    writes: state.writes,
+                };
+                Some((name, changes))
+            })
+            .collect())
+    }
 }
 
 impl<'a> Transaction for MemoryTransaction<'a> {
@@ -180,6 +306,8 @@ impl<'a> Transaction for MemoryTransaction<'a> {
         let snap = self.snapshot.borrow();
         let snap = snap.as_ref().ok_or(StoreError::TransactionConsumed)?;
         let data = snap.get_cf(&cf.name)?;
+        self.record_reads(&cf.name, [key]);
+        self.store.touch(&cf.name, [key]);
         Ok(data.get(key).cloned())
     }
 
@@ -187,6 +315,8 @@ impl<'a> Transaction for MemoryTransaction<'a> {
         let snap = self.snapshot.borrow();
         let snap = snap.as_ref().ok_or(StoreError::TransactionConsumed)?;
         let data = snap.get_cf(&cf.name)?;
+        self.record_reads(&cf.name, keys.iter().copied());
+        self.store.touch(&cf.name, keys.iter().copied());
         Ok(keys.iter().map(|k| data.get(*k).cloned()).collect())
     }
 
@@ -199,7 +329,7 @@ impl<'a> Transaction for MemoryTransaction<'a> {
         let snap = self.snapshot.borrow();
         let snap_ref = snap.as_ref().ok_or(StoreError::TransactionConsumed)?;
         let data = Arc::clone(snap_ref.get_cf(&cf.name)?);
-        Ok(Box::new(PrefixIter::forward(data, prefix.to_vec())))
+        Ok(Box::new(RangeIter::forward(data, prefix.to_vec())))
     }
 
     fn scan_prefix_rev<'b>(
@@ -211,66 +341,78 @@ impl<'a> Transaction for MemoryTransaction<'a> {
         let snap = self.snapshot.borrow();
         let snap_ref = snap.as_ref().ok_or(StoreError::TransactionConsumed)?;
         let data = Arc::clone(snap_ref.get_cf(&cf.name)?);
-        Ok(Box::new(PrefixIter::reverse(data, prefix.to_vec())))
+        Ok(Box::new(RangeIter::reverse(data, prefix.to_vec())))
+    }
+
+    fn scan_range<'b>(
+        &'b self,
+        cf: &Self::Cf,
+        start: Bound<&[u8]>,
+        end: Bound<&[u8]>,
+        reverse: bool,
+    ) -> Result<Box<dyn Iterator<Item = Result<(Vec<u8>, Vec<u8>), StoreError>> + 'b>, StoreError>
+    {
+        let snap = self.snapshot.borrow();
+        let snap_ref = snap.as_ref().ok_or(StoreError::TransactionConsumed)?;
+        let data = Arc::clone(snap_ref.get_cf(&cf.name)?);
+        if is_empty_range(start, end) {
+            return Ok(Box::new(std::iter::empty()));
+        }
+        Ok(Box::new(RangeIter::bounded(
+            data,
+            start.map(<[u8]>::to_vec),
+            end.map(<[u8]>::to_vec),
+            reverse,
+        )))
     }
 
     fn put(&self, cf: &Self::Cf, key: &[u8], value: &[u8]) -> Result<(), StoreError> {
         self.check_writable()?;
-        self.dirty.borrow_mut().insert(cf.name.clone());
         let mut snap = self.snapshot.borrow_mut();
         let snap = snap.as_mut().ok_or(StoreError::TransactionConsumed)?;
-        let data = snap.get_cf_mut(&cf.name)?;
-        data.insert(key.to_vec(), value.to_vec());
+        snap.get_cf_mut(&cf.name)?.put(key, value);
         Ok(())
     }
 
     fn put_batch(&self, cf: &Self::Cf, entries: &[(&[u8], &[u8])]) -> Result<(), StoreError> {
         self.check_writable()?;
-        self.dirty.borrow_mut().insert(cf.name.clone());
         let mut snap = self.snapshot.borrow_mut();
         let snap = snap.as_mut().ok_or(StoreError::TransactionConsumed)?;
-        let data = snap.get_cf_mut(&cf.name)?;
+        let state = snap.get_cf_mut(&cf.name)?;
         for (key, value) in entries {
-            data.insert(key.to_vec(), value.to_vec());
+            state.put(key, value);
         }
         Ok(())
     }
 
     fn delete(&self, cf: &Self::Cf, key: &[u8]) -> Result<(), StoreError> {
         self.check_writable()?;
-        self.dirty.borrow_mut().insert(cf.name.clone());
         let mut snap = self.snapshot.borrow_mut();
         let snap = snap.as_mut().ok_or(StoreError::TransactionConsumed)?;
-        let data = snap.get_cf_mut(&cf.name)?;
-        data.remove(key);
+        snap.get_cf_mut(&cf.name)?.delete(key);
         Ok(())
     }
 
     fn delete_batch(&self, cf: &Self::Cf, keys: &[&[u8]]) -> Result<(), StoreError> {
         self.check_writable()?;
-        self.dirty.borrow_mut().insert(cf.name.clone());
         let mut snap = self.snapshot.borrow_mut();
         let snap = snap.as_mut().ok_or(StoreError::TransactionConsumed)?;
-        let data = snap.get_cf_mut(&cf.name)?;
+        let state = snap.get_cf_mut(&cf.name)?;
         for key in keys {
-            data.remove(*key);
+            state.delete(key);
         }
         Ok(())
     }
 
     fn create_cf(&mut self, name: &str) -> Result<(), StoreError> {
         self.check_writable()?;
-        let _ = self.store.create_cf(name);
+        self.store.create_cf(name)?;
         let snap = self
             .snapshot
             .get_mut()
             .as_mut()
             .ok_or(StoreError::TransactionConsumed)?;
-        snap.data
-            .entry(name.to_string())
-            .or_insert_with(|| Arc::new(ColumnFamily::new()));
-        self.dirty.get_mut().insert(name.to_string());
-        Ok(())
+        snap.ensure(self.store, name)
     }
 
     fn drop_cf(&mut self, name: &str) -> Result<(), StoreError> {
@@ -282,32 +424,62 @@ impl<'a> Transaction for MemoryTransaction<'a> {
             .as_mut()
             .ok_or(StoreError::TransactionConsumed)?;
         snap.data.remove(name);
-        self.dirty.get_mut().remove(name);
+        self.reads.get_mut().remove(name);
         Ok(())
     }
 
-    fn commit(self) -> Result<(), StoreError> {
-        let snapshot = self
+    fn set_savepoint(&mut self) -> Result<(), StoreError> {
+        self.check_writable()?;
+        let snap = self
             .snapshot
-            .into_inner()
+            .get_mut()
+            .as_ref()
             .ok_or(StoreError::TransactionConsumed)?;
+        self.savepoints.push(Savepoint {
+            data: snap.data.clone(),
+        });
+        Ok(())
+    }
 
-        if self.read_only {
-            return Err(StoreError::ReadOnly);
+    fn rollback_to_savepoint(&mut self) -> Result<(), StoreError> {
+        let savepoint = self.savepoints.pop().ok_or(StoreError::NoSavepoint)?;
+        let snap = self
+            .snapshot
+            .get_mut()
+            .as_mut()
+            .ok_or(StoreError::TransactionConsumed)?;
+        let mut data = savepoint.data;
+        // CFs first touched after the savepoint keep their base version (the
+        // read set still refers to it) but lose their writes.
+        for (name, state) in snap.data.drain() {
+            data.entry(name).or_insert_with(|| CfState::new(state.base));
         }
+        // CFs dropped since the savepoint are gone from the store; don't
+        // resurrect them on commit.
+        let store = self.store;
+        data.retain(|name, _| store.snapshot_cf(name).is_some());
+        snap.data = data;
+        Ok(())
+    }
 
-        let dirty_set = self.dirty.into_inner();
-        let dirty: HashMap<String, Arc<ColumnFamily>> = snapshot
-            .data
-            .into_iter()
-            .filter(|(name, _)| dirty_set.contains(name))
-            .collect();
+    fn release_savepoint(&mut self) -> Result<(), StoreError> {
+        self.savepoints.pop().ok_or(StoreError::NoSavepoint)?;
+        Ok(())
+    }
+
+    fn commit(self) -> Result<(), StoreError> {
+        let store = self.store;
+        let changes = self.into_changes()?;
 
-        if dirty.is_empty() {
+        // A transaction that only read has nothing to publish, and its
+        // snapshot was consistent when it was taken.
+        if !has_writes(&changes) {
             return Ok(());
         }
 
-        self.store.commit(dirty);
+        let _guard = store.lock_commits()?;
+        store.validate(&changes)?;
+        store.publish(changes);
         Ok(())
     }
 
@@ -0,0 +1,244 @@
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+use crate::error::StoreError;
+
+use super::snapshot::{Reader, write_bytes};
+use super::store::ColumnFamily;
+
+// ── Format ───────────────────────────────────────────────────
+//
+// The log is a sequence of records, one per committed batch:
+//
+// [payload_len: u32 LE][crc32(payload): u32 LE][payload]
+//
+// payload = [op_count: u32 LE] followed by ops:
+//   0x01 CreateCf  [name]
+//   0x02 DropCf    [name]
+//   0x03 Put       [cf][key][value]
+//   0x04 Delete    [cf][key]
+// where every [field] is a u32-LE-length-prefixed byte string.
+//
+// Ops carry absolute values, so replaying a record twice is harmless. That
+// lets compaction write the snapshot before truncating the log.
+
+const OP_CREATE_CF: u8 = 0x01;
+const OP_DROP_CF: u8 = 0x02;
+const OP_PUT: u8 = 0x03;
+const OP_DELETE: u8 = 0x04;
+const HEADER_SIZE: usize = 8;
+
+/// A single logged mutation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum WalOp {
+    CreateCf(String),
+    DropCf(String),
+    Put {
+        cf: String,
+        key: Vec<u8>,
+        value: Vec<u8>,
+    },
+    Delete {
+        cf: String,
+        key: Vec<u8>,
+    },
+}
+
+impl WalOp {
+    fn encode(&self, buf: &mut Vec<u8>) {
+        // Writes into a Vec cannot fail.
+        match self {
+            WalOp::CreateCf(name) => {
+                buf.push(OP_CREATE_CF);
+                write_bytes(buf, name.as_bytes()).unwrap();
+            }
+            WalOp::DropCf(name) => {
+                buf.push(OP_DROP_CF);
+                write_bytes(buf, name.as_bytes()).unwrap();
+            }
+            WalOp::Put { cf, key, value } => {
+                buf.push(OP_PUT);
+                write_bytes(buf, cf.as_bytes()).unwrap();
+                write_bytes(buf, key).unwrap();
+                write_bytes(buf, value).unwrap();
+            }
+            WalOp::Delete { cf, key } => {
+                buf.push(OP_DELETE);
+                write_bytes(buf, cf.as_bytes()).unwrap();
+                write_bytes(buf, key).unwrap();
+            }
+        }
+    }
+
+    fn decode(r: &mut Reader<'_>) -> Result<Self, StoreError> {
+        let name = |r: &mut Reader<'_>| -> Result<String, StoreError> {
+            std::str::from_utf8(r.bytes()?)
+                .map(str::to_string)
+                .map_err(|_| StoreError::Storage("corrupt log: CF name is not UTF-8".into()))
+        };
+        match r.take(1)?[0] {
+            OP_CREATE_CF => Ok(WalOp::CreateCf(name(r)?)),
+            OP_DROP_CF => Ok(WalOp::DropCf(name(r)?)),
+            OP_PUT => Ok(WalOp::Put {
+                cf: name(r)?,
+                key: r.bytes()?.to_vec(),
+                value: r.bytes()?.to_vec(),
+            }),
+            OP_DELETE => Ok(WalOp::Delete {
+                cf: name(r)?,
+                key: r.bytes()?.to_vec(),
+            }),
+            tag => Err(StoreError::Storage(format!(
+                "corrupt log: unknown op tag {tag:#x}"
+            ))),
+        }
+    }
+
+    /// Apply this op to materialized column families during replay.
+    ///
+    /// Writes to a CF that no longer exists are dropped, matching what
+    /// `MemoryStore::commit` does for a CF dropped mid-transaction.
+    pub(crate) fn apply(self, cfs: &mut HashMap<String, ColumnFamily>) {
+        match self {
+            WalOp::CreateCf(name) => {
+                cfs.entry(name).or_default();
+            }
+            WalOp::DropCf(name) => {
+                cfs.remove(&name);
+            }
+            WalOp::Put { cf, key, value } => {
+                if let Some(data) = cfs.get_mut(&cf) {
+                    data.insert(key, value);
+                }
+            }
+            WalOp::Delete { cf, key } => {
+                if let Some(data) = cfs.get_mut(&cf) {
+                    data.remove(&key);
+                }
+            }
+        }
+    }
+}
+
+/// Append-only, fsync'd operation log.
+pub(crate) struct Wal {
+    file: File,
+    len: u64,
+}
+
+fn io_err(e: std::io::Error) -> StoreError {
+    StoreError::Storage(format!("write-ahead log: {e}"))
+}
+
+impl Wal {
+    /// Open (or create) the log at `path` and read back every intact record.
+    ///
+    /// A record cut short by a crash — or one whose checksum does not match —
+    /// marks the end of the log; it and anything after it are truncated away
+    /// so new appends start from a clean boundary.
+    pub(crate) fn open(path: &Path) -> Result<(Self, Vec<Vec<WalOp>>), StoreError> {
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)
+            .map_err(io_err)?;
+        let mut bytes = Vec::new();
+        file.read_to_end(&mut bytes).map_err(io_err)?;
+
+        let mut batches = Vec::new();
+        let mut offset = 0;
+        while let Some((ops, next)) = Self::read_record(&bytes, offset)? {
+            batches.push(ops);
+            offset = next;
+        }
+
+        if offset < bytes.len() {
+            file.set_len(offset as u64).map_err(io_err)?;
+            file.sync_data().map_err(io_err)?;
+        }
+        file.seek(SeekFrom::Start(offset as u64)).map_err(io_err)?;
+
+        Ok((
+            Self {
+                file,
+                len: offset as u64,
+            },
+            batches,
+        ))
+    }
+
+    /// Decode the record at `offset`, returning its ops and the next offset.
+    /// Returns `None` at the end of the log or at a torn/corrupt record.
+    fn read_record(bytes: &[u8], offset: usize) -> Result<Option<(Vec<WalOp>, usize)>, StoreError> {
+        let Some(header) = bytes.get(offset..offset + HEADER_SIZE) else {
+            return Ok(None);
+        };
+        let len = u32::from_le_bytes(header[..4].try_into().unwrap()) as usize;
+        let crc = u32::from_le_bytes(header[4..].try_into().unwrap());
+        let start = offset + HEADER_SIZE;
+        let Some(payload) = bytes.get(start..start + len) else {
+            return Ok(None);
+        };
+        if crc32fast::hash(payload) != crc {
+            return Ok(None);
+        }
+
+        let mut r = Reader {
+            buf: payload,
+            what: "log",
+        };
+        let count = r.u32()?;
+        let ops = (0..count)
+            .map(|_| WalOp::decode(&mut r))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Some((ops, start + len)))
+    }
+
+    /// Append one batch as a single record and fsync it.
+    ///
+    /// The batch is durable once this returns; a crash mid-write leaves a
+    /// torn record that the next `open` discards.
+    pub(crate) fn append(&mut self, ops: &[WalOp]) -> Result<(), StoreError> {
+        let mut payload = Vec::new();
+        payload.extend_from_slice(&(ops.len() as u32).to_le_bytes());
+        for op in ops {
+            op.encode(&mut payload);
+        }
+
+        let mut record = Vec::with_capacity(HEADER_SIZE + payload.len());
+        record.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+        record.extend_from_slice(&crc32fast::hash(&payload).to_le_bytes());
+        record.extend_from_slice(&payload);
+
+        let written = self
+            .file
+            .write_all(&record)
+            .and_then(|()| self.file.sync_data());
+        if let Err(e) = written {
+            // Cut off any partial record so later appends stay readable.
+            let _ = self.file.set_len(self.len);
+            let _ = self.file.seek(SeekFrom::Start(self.len));
+            return Err(io_err(e));
+        }
+        self.len += record.len() as u64;
+        Ok(())
+    }
+
+    /// Discard every record (after they have been folded into a snapshot).
+    pub(crate) fn truncate(&mut self) -> Result<(), StoreError> {
+        self.file.set_len(0).map_err(io_err)?;
+        self.file.seek(SeekFrom::Start(0)).map_err(io_err)?;
+        self.file.sync_data().map_err(io_err)?;
+        self.len = 0;
+        Ok(())
+    }
+
+    /// Current size of the log in bytes.
+    pub(crate) fn len(&self) -> u64 {
+        self.len
+    }
+}
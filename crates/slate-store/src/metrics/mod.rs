@@ -0,0 +1,6 @@
+mod registry;
+mod store;
+mod transaction;
+
+pub use registry::{LatencyHistogram, MetricsSnapshot, OpMetrics, StoreOp};
+pub use store::{InstrumentedStore, MetricsStore};
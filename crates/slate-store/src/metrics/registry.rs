@@ -0,0 +1,326 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+// ── Operations ───────────────────────────────────────────────
+
+/// A store operation tracked by [`MetricsStore`](super::MetricsStore).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum StoreOp {
+    Get,
+    MultiGet,
+    ScanPrefix,
+    ScanPrefixRev,
+    ScanRange,
+    Put,
+    PutBatch,
+    Delete,
+    DeleteBatch,
+    DeleteRange,
+    Commit,
+    Rollback,
+}
+
+impl StoreOp {
+    /// Operations recorded per column family.
+    const CF_OPS: [StoreOp; 10] = [
+        StoreOp::Get,
+        StoreOp::MultiGet,
+        StoreOp::ScanPrefix,
+        StoreOp::ScanPrefixRev,
+        StoreOp::ScanRange,
+        StoreOp::Put,
+        StoreOp::PutBatch,
+        StoreOp::Delete,
+        StoreOp::DeleteBatch,
+        StoreOp::DeleteRange,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StoreOp::Get => "get",
+            StoreOp::MultiGet => "multi_get",
+            StoreOp::ScanPrefix => "scan_prefix",
+            StoreOp::ScanPrefixRev => "scan_prefix_rev",
+            StoreOp::ScanRange => "scan_range",
+            StoreOp::Put => "put",
+            StoreOp::PutBatch => "put_batch",
+            StoreOp::Delete => "delete",
+            StoreOp::DeleteBatch => "delete_batch",
+            StoreOp::DeleteRange => "delete_range",
+            StoreOp::Commit => "commit",
+            StoreOp::Rollback => "rollback",
+        }
+    }
+
+    fn cf_index(self) -> usize {
+        Self::CF_OPS
+            .iter()
+            .position(|op| *op == self)
+            .expect("not a per-CF operation")
+    }
+}
+
+impl fmt::Display for StoreOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+// ── Latency histogram ────────────────────────────────────────
+
+/// Number of latency buckets. Bucket `i` counts samples below `2^i` ns
+/// (and at least `2^(i-1)` ns); the last bucket is open-ended (~9 minutes+).
+const BUCKETS: usize = 40;
+
+fn bucket_for(latency: Duration) -> usize {
+    let nanos = latency.as_nanos().min(u64::MAX as u128) as u64;
+    ((u64::BITS - nanos.leading_zeros()) as usize).min(BUCKETS - 1)
+}
+
+/// Log2-bucketed latency distribution.
+///
+/// Percentiles are approximate: they report the upper bound of the bucket
+/// the percentile falls into, so they are accurate to within a factor of two.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LatencyHistogram {
+    buckets: [u64; BUCKETS],
+    total: Duration,
+}
+
+impl Default for LatencyHistogram {
+    fn default() -> Self {
+        Self {
+            buckets: [0; BUCKETS],
+            total: Duration::ZERO,
+        }
+    }
+}
+
+impl LatencyHistogram {
+    /// Number of recorded samples.
+    pub fn count(&self) -> u64 {
+        self.buckets.iter().sum()
+    }
+
+    /// Sum of all recorded latencies.
+    pub fn total(&self) -> Duration {
+        self.total
+    }
+
+    pub fn mean(&self) -> Duration {
+        match self.count() {
+            0 => Duration::ZERO,
+            n => Duration::from_nanos((self.total.as_nanos() / n as u128) as u64),
+        }
+    }
+
+    /// Approximate latency at percentile `p` (0.0–100.0).
+    pub fn percentile(&self, p: f64) -> Duration {
+        let count = self.count();
+        if count == 0 {
+            return Duration::ZERO;
+        }
+        let rank = ((p.clamp(0.0, 100.0) / 100.0) * count as f64)
+            .ceil()
+            .max(1.0) as u64;
+        let mut seen = 0;
+        for (i, n) in self.buckets.iter().enumerate() {
+            seen += n;
+            if seen >= rank {
+                return Self::upper_bound(i);
+            }
+        }
+        Self::upper_bound(BUCKETS - 1)
+    }
+
+    /// Non-empty buckets as `(upper bound, count)` pairs, in ascending order.
+    pub fn buckets(&self) -> impl Iterator<Item = (Duration, u64)> + '_ {
+        self.buckets
+            .iter()
+            .enumerate()
+            .filter(|(_, n)| **n > 0)
+            .map(|(i, n)| (Self::upper_bound(i), *n))
+    }
+
+    fn upper_bound(bucket: usize) -> Duration {
+        Duration::from_nanos(1u64 << bucket)
+    }
+}
+
+// ── Snapshots ────────────────────────────────────────────────
+
+/// Point-in-time counters for one operation.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OpMetrics {
+    pub calls: u64,
+    /// Key + value bytes returned to the caller.
+    pub bytes_read: u64,
+    /// Key + value bytes handed to the store.
+    pub bytes_written: u64,
+    /// Entries yielded by scans (or keys requested by point reads).
+    pub keys_scanned: u64,
+    pub latency: LatencyHistogram,
+}
+
+/// Point-in-time view of every counter in a [`MetricsStore`](super::MetricsStore).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MetricsSnapshot {
+    /// Per column family, per operation. Operations never called (and CFs
+    /// with no calls at all) are omitted.
+    pub column_families: BTreeMap<String, BTreeMap<StoreOp, OpMetrics>>,
+    pub commit: OpMetrics,
+    pub rollback: OpMetrics,
+}
+
+impl MetricsSnapshot {
+    /// Counters for one operation on one column family, if it was ever called.
+    pub fn op(&self, cf: &str, op: StoreOp) -> Option<&OpMetrics> {
+        self.column_families.get(cf)?.get(&op)
+    }
+}
+
+// ── Live counters ────────────────────────────────────────────
+
+pub(crate) struct OpCounters {
+    calls: AtomicU64,
+    bytes_read: AtomicU64,
+    bytes_written: AtomicU64,
+    keys_scanned: AtomicU64,
+    total_nanos: AtomicU64,
+    buckets: [AtomicU64; BUCKETS],
+}
+
+impl Default for OpCounters {
+    fn default() -> Self {
+        Self {
+            calls: AtomicU64::new(0),
+            bytes_read: AtomicU64::new(0),
+            bytes_written: AtomicU64::new(0),
+            keys_scanned: AtomicU64::new(0),
+            total_nanos: AtomicU64::new(0),
+            buckets: [const { AtomicU64::new(0) }; BUCKETS],
+        }
+    }
+}
+
+/// What one call did, recorded in a single step.
+#[derive(Default)]
+pub(crate) struct Sample {
+    pub bytes_read: u64,
+    pub bytes_written: u64,
+    pub keys_scanned: u64,
+}
+
+impl OpCounters {
+    pub(crate) fn record(&self, latency: Duration, sample: Sample) {
+        self.calls.fetch_add(1, Ordering::Relaxed);
+        self.bytes_read
+            .fetch_add(sample.bytes_read, Ordering::Relaxed);
+        self.bytes_written
+            .fetch_add(sample.bytes_written, Ordering::Relaxed);
+        self.keys_scanned
+            .fetch_add(sample.keys_scanned, Ordering::Relaxed);
+        self.total_nanos
+            .fetch_add(latency.as_nanos() as u64, Ordering::Relaxed);
+        self.buckets[bucket_for(latency)].fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn snapshot(&self) -> OpMetrics {
+        let mut latency = LatencyHistogram {
+            total: Duration::from_nanos(self.total_nanos.load(Ordering::Relaxed)),
+            ..Default::default()
+        };
+        for (dst, src) in latency.buckets.iter_mut().zip(&self.buckets) {
+            *dst = src.load(Ordering::Relaxed);
+        }
+        OpMetrics {
+            calls: self.calls.load(Ordering::Relaxed),
+            bytes_read: self.bytes_read.load(Ordering::Relaxed),
+            bytes_written: self.bytes_written.load(Ordering::Relaxed),
+            keys_scanned: self.keys_scanned.load(Ordering::Relaxed),
+            latency,
+        }
+    }
+}
+
+/// Counters for every per-CF operation on one column family.
+#[derive(Default)]
+pub(crate) struct CfCounters {
+    ops: [OpCounters; StoreOp::CF_OPS.len()],
+}
+
+impl CfCounters {
+    pub(crate) fn op(&self, op: StoreOp) -> &OpCounters {
+        &self.ops[op.cf_index()]
+    }
+}
+
+/// All counters for a store. Column families are registered the first time a
+/// transaction resolves them; after that, recording is lock-free.
+#[derive(Default)]
+pub(crate) struct Registry {
+    cfs: RwLock<HashMap<String, Arc<CfCounters>>>,
+    pub(crate) commit: OpCounters,
+    pub(crate) rollback: OpCounters,
+}
+
+impl Registry {
+    pub(crate) fn cf(&self, name: &str) -> Arc<CfCounters> {
+        if let Some(counters) = self.cfs.read().unwrap().get(name) {
+            return Arc::clone(counters);
+        }
+        let mut cfs = self.cfs.write().unwrap();
+        Arc::clone(cfs.entry(name.to_string()).or_default())
+    }
+
+    pub(crate) fn snapshot(&self) -> MetricsSnapshot {
+        let cfs = self.cfs.read().unwrap();
+        let column_families = cfs
+            .iter()
+            .map(|(name, counters)| {
+                let ops = StoreOp::CF_OPS
+                    .iter()
+                    .map(|op| (*op, counters.op(*op).snapshot()))
+                    .filter(|(_, m)| m.calls > 0)
+                    .collect();
+                (name.clone(), ops)
+            })
+            .filter(|(_, ops): &(String, BTreeMap<StoreOp, OpMetrics>)| !ops.is_empty())
+            .collect();
+        MetricsSnapshot {
+            column_families,
+            commit: self.commit.snapshot(),
+            rollback: self.rollback.snapshot(),
+        }
+    }
+
+    /// Zero every counter in place, so handles already resolved by open
+    /// transactions keep reporting.
+    pub(crate) fn reset(&self) {
+        for counters in self.cfs.read().unwrap().values() {
+            counters.ops.iter().for_each(OpCounters::reset);
+        }
+        self.commit.reset();
+        self.rollback.reset();
+    }
+}
+
+impl OpCounters {
+    fn reset(&self) {
+        for counter in [
+            &self.calls,
+            &self.bytes_read,
+            &self.bytes_written,
+            &self.keys_scanned,
+            &self.total_nanos,
+        ]
+        .into_iter()
+        .chain(&self.buckets)
+        {
+            counter.store(0, Ordering::Relaxed);
+        }
+    }
+}
@@ -0,0 +1,179 @@
+use std::ops::RangeBounds;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Instant;
+
+use crate::error::StoreError;
+use crate::store::{
+    ApproximateSize, BackupInfo, BackupPolicy, BackupStore, ForkableStore, IncrementalBackupStore,
+    SnapshotStore, Store,
+};
+
+use super::registry::{MetricsSnapshot, Registry, Sample, StoreOp};
+use super::transaction::MetricsTransaction;
+
+/// Wraps any [`Store`] and records per-column-family I/O metrics.
+///
+/// Counts calls, bytes read and written, keys scanned, and a latency
+/// histogram for every operation, keyed by column family. Scans are recorded
+/// once, when their iterator is dropped, and their latency covers both
+/// opening the scan and every `next()` call — time the caller spends between
+/// items is not included.
+///
+/// Cloning is cheap and shares both the store and the counters, so a clone
+/// kept outside a `Database` sees everything the database does.
+pub struct MetricsStore<S> {
+    store: Arc<S>,
+    registry: Arc<Registry>,
+}
+
+impl<S> Clone for MetricsStore<S> {
+    fn clone(&self) -> Self {
+        Self {
+            store: Arc::clone(&self.store),
+            registry: Arc::clone(&self.registry),
+        }
+    }
+}
+
+impl<S: Store> MetricsStore<S> {
+    pub fn new(store: S) -> Self {
+        Self {
+            store: Arc::new(store),
+            registry: Arc::new(Registry::default()),
+        }
+    }
+}
+
+/// A store that can report the I/O metrics it has collected.
+pub trait InstrumentedStore: Store {
+    /// Point-in-time copy of every counter.
+    fn metrics(&self) -> MetricsSnapshot;
+
+    /// Zero every counter.
+    fn reset_metrics(&self);
+}
+
+impl<S: Store> InstrumentedStore for MetricsStore<S> {
+    fn metrics(&self) -> MetricsSnapshot {
+        self.registry.snapshot()
+    }
+
+    fn reset_metrics(&self) {
+        self.registry.reset()
+    }
+}
+
+impl<S: Store> Store for MetricsStore<S> {
+    type Txn<'a>
+        = MetricsTransaction<'a, S::Txn<'a>>
+    where
+        Self: 'a;
+
+    fn begin(&self, read_only: bool) -> Result<Self::Txn<'_>, StoreError> {
+        Ok(MetricsTransaction {
+            inner: self.store.begin(read_only)?,
+            registry: &self.registry,
+        })
+    }
+
+    fn is_read_only(&self) -> bool {
+        self.store.is_read_only()
+    }
+
+    fn create_cf(&self, name: &str) -> Result<(), StoreError> {
+        self.store.create_cf(name)
+    }
+
+    fn drop_cf(&self, name: &str) -> Result<(), StoreError> {
+        self.store.drop_cf(name)
+    }
+
+    fn delete_range(&self, cf: &str, range: impl RangeBounds<Vec<u8>>) -> Result<(), StoreError> {
+        let start = Instant::now();
+        let result = self.store.delete_range(cf, range);
+        self.registry
+            .cf(cf)
+            .op(StoreOp::DeleteRange)
+            .record(start.elapsed(), Sample::default());
+        result
+    }
+
+    fn approximate_size(
+        &self,
+        cf: &str,
+        range: impl RangeBounds<Vec<u8>>,
+    ) -> Result<ApproximateSize, StoreError> {
+        self.store.approximate_size(cf, range)
+    }
+
+    fn compact_range(&self, cf: &str, range: impl RangeBounds<Vec<u8>>) -> Result<(), StoreError> {
+        self.store.compact_range(cf, range)
+    }
+
+    fn budget_overruns(&self) -> Vec<(String, u64)> {
+        self.store.budget_overruns()
+    }
+
+    fn eviction_candidates(
+        &self,
+        cf: &str,
+        prefix: &[u8],
+        limit: usize,
+    ) -> Result<Vec<Vec<u8>>, StoreError> {
+        self.store.eviction_candidates(cf, prefix, limit)
+    }
+}
+
+impl<S: BackupStore> BackupStore for MetricsStore<S> {
+    fn backup(&self, dest: &Path) -> Result<(), StoreError> {
+        self.store.backup(dest)
+    }
+}
+
+impl<S: IncrementalBackupStore> IncrementalBackupStore for MetricsStore<S> {
+    fn create_backup(
+        &self,
+        backup_dir: &Path,
+        policy: &BackupPolicy,
+    ) -> Result<BackupInfo, StoreError> {
+        self.store.create_backup(backup_dir, policy)
+    }
+
+    fn list_backups(backup_dir: &Path) -> Result<Vec<BackupInfo>, StoreError> {
+        S::list_backups(backup_dir)
+    }
+
+    fn verify_backup(backup_dir: &Path, backup_id: u32) -> Result<(), StoreError> {
+        S::verify_backup(backup_dir, backup_id)
+    }
+
+    fn restore(backup_dir: &Path, backup_id: u32, dest: &Path) -> Result<(), StoreError> {
+        S::restore(backup_dir, backup_id, dest)
+    }
+}
+
+/// Reads on the snapshot are recorded in the same counters.
+impl<S: ForkableStore> ForkableStore for MetricsStore<S> {
+    /// The fork counts its own metrics from zero.
+    fn fork(&self) -> Result<Self, StoreError> {
+        Ok(MetricsStore {
+            store: Arc::new(self.store.fork()?),
+            registry: Arc::new(Registry::default()),
+        })
+    }
+}
+
+impl<S: SnapshotStore> SnapshotStore for MetricsStore<S>
+where
+    S::Snapshot: Sync,
+{
+    type Snapshot = MetricsStore<S::Snapshot>;
+
+    fn snapshot(&self) -> Result<Self::Snapshot, StoreError> {
+        Ok(MetricsStore {
+            store: Arc::new(self.store.snapshot()?),
+            registry: Arc::clone(&self.registry),
+        })
+    }
+}
@@ -0,0 +1,274 @@
+use std::ops::Bound;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::error::StoreError;
+use crate::store::Transaction;
+
+use super::registry::{CfCounters, OpCounters, Registry, Sample, StoreOp};
+
+/// Column family handle for a [`MetricsTransaction`].
+///
+/// Resolves the CF's counters once, so recording an operation never touches
+/// the registry lock.
+#[derive(Clone)]
+pub struct MetricsCf<C> {
+    inner: C,
+    counters: Arc<CfCounters>,
+}
+
+/// Transaction over a [`MetricsStore`](super::MetricsStore).
+///
+/// Every call is forwarded to the wrapped transaction and timed; results and
+/// errors pass through untouched.
+pub struct MetricsTransaction<'a, T> {
+    pub(crate) inner: T,
+    pub(crate) registry: &'a Registry,
+}
+
+type EntryIter<'b> = Box<dyn Iterator<Item = Result<(Vec<u8>, Vec<u8>), StoreError>> + 'b>;
+
+fn entry_len((key, value): &(Vec<u8>, Vec<u8>)) -> u64 {
+    (key.len() + value.len()) as u64
+}
+
+/// Time `f` and record it against `counters`, with the sample derived from
+/// the successful result.
+fn timed<R>(
+    counters: &OpCounters,
+    f: impl FnOnce() -> Result<R, StoreError>,
+    sample: impl FnOnce(&R) -> Sample,
+) -> Result<R, StoreError> {
+    let start = Instant::now();
+    let result = f();
+    let elapsed = start.elapsed();
+    let sample = result.as_ref().map(sample).unwrap_or_default();
+    counters.record(elapsed, sample);
+    result
+}
+
+/// Scan iterator that accumulates time spent inside the backend iterator and
+/// records the whole scan as one call when dropped.
+struct ScanIter<'b> {
+    inner: EntryIter<'b>,
+    counters: Arc<CfCounters>,
+    op: StoreOp,
+    elapsed: Duration,
+    sample: Sample,
+}
+
+impl Iterator for ScanIter<'_> {
+    type Item = Result<(Vec<u8>, Vec<u8>), StoreError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let start = Instant::now();
+        let item = self.inner.next();
+        self.elapsed += start.elapsed();
+        if let Some(Ok(entry)) = &item {
+            self.sample.keys_scanned += 1;
+            self.sample.bytes_read += entry_len(entry);
+        }
+        item
+    }
+}
+
+impl Drop for ScanIter<'_> {
+    fn drop(&mut self) {
+        self.counters
+            .op(self.op)
+            .record(self.elapsed, std::mem::take(&mut self.sample));
+    }
+}
+
+impl<'a, T: Transaction> MetricsTransaction<'a, T> {
+    fn scan<'b>(
+        &'b self,
+        cf: &MetricsCf<T::Cf>,
+        op: StoreOp,
+        open: impl FnOnce() -> Result<EntryIter<'b>, StoreError>,
+    ) -> Result<EntryIter<'b>, StoreError> {
+        let start = Instant::now();
+        let inner = match open() {
+            Ok(inner) => inner,
+            Err(e) => {
+                cf.counters
+                    .op(op)
+                    .record(start.elapsed(), Sample::default());
+                return Err(e);
+            }
+        };
+        Ok(Box::new(ScanIter {
+            inner,
+            counters: Arc::clone(&cf.counters),
+            op,
+            elapsed: start.elapsed(),
+            sample: Sample::default(),
+        }))
+    }
+}
+
+#[allow(clippy::type_complexity)]
+impl<'a, T: Transaction> Transaction for MetricsTransaction<'a, T> {
+    type Cf = MetricsCf<T::Cf>;
+
+    fn cf(&self, name: &str) -> Result<Self::Cf, StoreError> {
+        Ok(MetricsCf {
+            inner: self.inner.cf(name)?,
+            counters: self.registry.cf(name),
+        })
+    }
+
+    fn get(&self, cf: &Self::Cf, key: &[u8]) -> Result<Option<Vec<u8>>, StoreError> {
+        timed(
+            cf.counters.op(StoreOp::Get),
+            || self.inner.get(&cf.inner, key),
+            |value| Sample {
+                bytes_read: value.as_ref().map_or(0, |v| (key.len() + v.len()) as u64),
+                keys_scanned: 1,
+                ..Default::default()
+            },
+        )
+    }
+
+    fn get_for_update(&self, cf: &Self::Cf, key: &[u8]) -> Result<Option<Vec<u8>>, StoreError> {
+        timed(
+            cf.counters.op(StoreOp::Get),
+            || self.inner.get_for_update(&cf.inner, key),
+            |value| Sample {
+                bytes_read: value.as_ref().map_or(0, |v| (key.len() + v.len()) as u64),
+                keys_scanned: 1,
+                ..Default::default()
+            },
+        )
+    }
+
+    fn multi_get(&self, cf: &Self::Cf, keys: &[&[u8]]) -> Result<Vec<Option<Vec<u8>>>, StoreError> {
+        timed(
+            cf.counters.op(StoreOp::MultiGet),
+            || self.inner.multi_get(&cf.inner, keys),
+            |values| Sample {
+                bytes_read: values
+                    .iter()
+                    .zip(keys)
+                    .filter_map(|(v, k)| v.as_ref().map(|v| (k.len() + v.len()) as u64))
+                    .sum(),
+                keys_scanned: keys.len() as u64,
+                ..Default::default()
+            },
+        )
+    }
+
+    fn scan_prefix<'b>(
+        &'b self,
+        cf: &Self::Cf,
+        prefix: &[u8],
+    ) -> Result<Box<dyn Iterator<Item = Result<(Vec<u8>, Vec<u8>), StoreError>> + 'b>, StoreError>
+    {
+        self.scan(cf, StoreOp::ScanPrefix, || {
+            self.inner.scan_prefix(&cf.inner, prefix)
+        })
+    }
+
+    fn scan_prefix_rev<'b>(
+        &'b self,
+        cf: &Self::Cf,
+        prefix: &[u8],
+    ) -> Result<Box<dyn Iterator<Item = Result<(Vec<u8>, Vec<u8>), StoreError>> + 'b>, StoreError>
+    {
+        self.scan(cf, StoreOp::ScanPrefixRev, || {
+            self.inner.scan_prefix_rev(&cf.inner, prefix)
+        })
+    }
+
+    fn scan_range<'b>(
+        &'b self,
+        cf: &Self::Cf,
+        start: Bound<&[u8]>,
+        end: Bound<&[u8]>,
+        reverse: bool,
+    ) -> Result<Box<dyn Iterator<Item = Result<(Vec<u8>, Vec<u8>), StoreError>> + 'b>, StoreError>
+    {
+        self.scan(cf, StoreOp::ScanRange, || {
+            self.inner.scan_range(&cf.inner, start, end, reverse)
+        })
+    }
+
+    fn put(&self, cf: &Self::Cf, key: &[u8], value: &[u8]) -> Result<(), StoreError> {
+        timed(
+            cf.counters.op(StoreOp::Put),
+            || self.inner.put(&cf.inner, key, value),
+            |_| Sample {
+                bytes_written: (key.len() + value.len()) as u64,
+                ..Default::default()
+            },
+        )
+    }
+
+    fn put_batch(&self, cf: &Self::Cf, entries: &[(&[u8], &[u8])]) -> Result<(), StoreError> {
+        timed(
+            cf.counters.op(StoreOp::PutBatch),
+            || self.inner.put_batch(&cf.inner, entries),
+            |_| Sample {
+                bytes_written: entries
+                    .iter()
+                    .map(|(k, v)| (k.len() + v.len()) as u64)
+                    .sum(),
+                ..Default::default()
+            },
+        )
+    }
+
+    fn delete(&self, cf: &Self::Cf, key: &[u8]) -> Result<(), StoreError> {
+        timed(
+            cf.counters.op(StoreOp::Delete),
+            || self.inner.delete(&cf.inner, key),
+            |_| Sample::default(),
+        )
+    }
+
+    fn delete_batch(&self, cf: &Self::Cf, keys: &[&[u8]]) -> Result<(), StoreError> {
+        timed(
+            cf.counters.op(StoreOp::DeleteBatch),
+            || self.inner.delete_batch(&cf.inner, keys),
+            |_| Sample::default(),
+        )
+    }
+
+    fn create_cf(&mut self, name: &str) -> Result<(), StoreError> {
+        self.inner.create_cf(name)
+    }
+
+    fn drop_cf(&mut self, name: &str) -> Result<(), StoreError> {
+        self.inner.drop_cf(name)
+    }
+
+    fn set_savepoint(&mut self) -> Result<(), StoreError> {
+        self.inner.set_savepoint()
+    }
+
+    fn rollback_to_savepoint(&mut self) -> Result<(), StoreError> {
+        self.inner.rollback_to_savepoint()
+    }
+
+    fn release_savepoint(&mut self) -> Result<(), StoreError> {
+        self.inner.release_savepoint()
+    }
+
+    fn commit(self) -> Result<(), StoreError> {
+        let inner = self.inner;
+        timed(
+            &self.registry.commit,
+            || inner.commit(),
+            |_| Sample::default(),
+        )
+    }
+
+    fn rollback(self) -> Result<(), StoreError> {
+        let inner = self.inner;
+        timed(
+            &self.registry.rollback,
+            || inner.rollback(),
+            |_| Sample::default(),
+        )
+    }
+}
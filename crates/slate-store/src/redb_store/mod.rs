@@ -1,4 +1,4 @@
 mod store;
 mod transaction;
 
-pub use store::RedbStore;
+pub use store::{RedbSnapshot, RedbStore};
@@ -1,42 +1,99 @@
 use std::ops::{Bound, RangeBounds};
 use std::path::{Path, PathBuf};
+use std::sync::RwLock;
 
-use redb::{Database, ReadableTable, TableDefinition};
+use redb::{CompactionError, Database, ReadableTable, TableDefinition};
 
 use crate::error::StoreError;
-use crate::store::{BackupStore, Store};
+use crate::store::{BackupStore, SnapshotStore, Store};
 
 use super::transaction::RedbTransaction;
 
 pub struct RedbStore {
-    db: Database,
+    /// redb transactions don't borrow the database, so the lock is only
+    /// held while one begins. Compaction takes it exclusively.
+    db: RwLock<Database>,
     path: PathBuf,
+    read_only: bool,
 }
 
 impl RedbStore {
     pub fn open(path: &Path) -> Result<Self, StoreError> {
         let db = Database::create(path).map_err(|e| StoreError::Storage(e.to_string()))?;
         Ok(Self {
-            db,
+            db: RwLock::new(db),
             path: path.to_path_buf(),
+            read_only: false,
         })
     }
+
+    /// Open an existing database without write access.
+    ///
+    /// Write transactions, schema changes and compaction fail with
+    /// [`StoreError::ReadOnly`]. redb 2 still takes an exclusive lock on the
+    /// file, so a read-only store cannot share it with a live writer in
+    /// another process; use it for inspection tools and offline readers.
+    pub fn open_read_only(path: &Path) -> Result<Self, StoreError> {
+        let db = Database::open(path).map_err(|e| StoreError::Storage(e.to_string()))?;
+        Ok(Self {
+            db: RwLock::new(db),
+            path: path.to_path_buf(),
+            read_only: true,
+        })
+    }
+
+    /// Shrink the file by moving live pages down and truncating the tail.
+    ///
+    /// redb only compacts with exclusive access: this waits for
+    /// transactions to finish beginning, then fails with
+    /// [`StoreError::Conflict`] if one, or a snapshot, is still alive.
+    /// Returns `true` if any space was reclaimed.
+    pub fn compact(&self) -> Result<bool, StoreError> {
+        self.check_writable()?;
+        let mut db = self.db.write().unwrap();
+        db.compact().map_err(|e| match e {
+            CompactionError::TransactionInProgress => {
+                StoreError::Conflict("compaction needs every transaction to finish".into())
+            }
+            e => StoreError::Storage(format!("compaction failed: {e}")),
+        })
+    }
+
+    fn begin_write(&self) -> Result<redb::WriteTransaction, StoreError> {
+        self.db
+            .read()
+            .unwrap()
+            .begin_write()
+            .map_err(|e| StoreError::Storage(e.to_string()))
+    }
+
+    fn check_writable(&self) -> Result<(), StoreError> {
+        if self.read_only {
+            return Err(StoreError::ReadOnly);
+        }
+        Ok(())
+    }
 }
 
 impl Store for RedbStore {
     type Txn<'a> = RedbTransaction<'a>;
 
     fn begin(&self, read_only: bool) -> Result<Self::Txn<'_>, StoreError> {
-        RedbTransaction::new(&self.db, read_only)
+        if self.read_only && !read_only {
+            return Err(StoreError::ReadOnly);
+        }
+        RedbTransaction::new(&self.db.read().unwrap(), read_only)
+    }
+
+    fn is_read_only(&self) -> bool {
+        self.read_only
     }
 
     fn create_cf(&self, name: &str) -> Result<(), StoreError> {
+        self.check_writable()?;
         let name = name.to_string();
         let def: TableDefinition<'_, &[u8], &[u8]> = TableDefinition::new(&name);
-        let txn = self
-            .db
-            .begin_write()
-            .map_err(|e| StoreError::Storage(e.to_string()))?;
+        let txn = self.begin_write()?;
         txn.open_table(def)
             .map_err(|e| StoreError::Storage(e.to_string()))?;
         txn.commit()
@@ -45,12 +102,10 @@ impl Store for RedbStore {
     }
 
     fn drop_cf(&self, name: &str) -> Result<(), StoreError> {
+        self.check_writable()?;
         let name = name.to_string();
         let def: TableDefinition<'_, &[u8], &[u8]> = TableDefinition::new(&name);
-        let txn = self
-            .db
-            .begin_write()
-            .map_err(|e| StoreError::Storage(e.to_string()))?;
+        let txn = self.begin_write()?;
         txn.delete_table(def)
             .map_err(|e| StoreError::Storage(e.to_string()))?;
         txn.commit()
@@ -59,12 +114,10 @@ impl Store for RedbStore {
     }
 
     fn delete_range(&self, cf: &str, range: impl RangeBounds<Vec<u8>>) -> Result<(), StoreError> {
+        self.check_writable()?;
         let cf = cf.to_string();
         let def: TableDefinition<'_, &[u8], &[u8]> = TableDefinition::new(&cf);
-        let txn = self
-            .db
-            .begin_write()
-            .map_err(|e| StoreError::Storage(e.to_string()))?;
+        let txn = self.begin_write()?;
         {
             let mut table = txn
                 .open_table(def)
@@ -104,6 +157,12 @@ impl Store for RedbStore {
             .map_err(|e| StoreError::Storage(e.to_string()))?;
         Ok(())
     }
+
+    /// Compacts the whole file with [`RedbStore::compact`]; redb has no
+    /// per-table compaction, so the column family and range are ignored.
+    fn compact_range(&self, _cf: &str, _range: impl RangeBounds<Vec<u8>>) -> Result<(), StoreError> {
+        self.compact().map(|_| ())
+    }
 }
 
 impl BackupStore for RedbStore {
@@ -113,3 +172,62 @@ impl BackupStore for RedbStore {
         Ok(())
     }
 }
+
+impl SnapshotStore for RedbStore {
+    type Snapshot = RedbSnapshot;
+
+    fn snapshot(&self) -> Result<RedbSnapshot, StoreError> {
+        let txn = self
+            .db
+            .read()
+            .unwrap()
+            .begin_read()
+            .map_err(|e| StoreError::Storage(e.to_string()))?;
+        Ok(RedbSnapshot { txn })
+    }
+}
+
+/// Read-only, point-in-time view of a [`RedbStore`], taken with
+/// [`SnapshotStore::snapshot`].
+///
+/// Wraps one long-lived redb read transaction. Every transaction begun on the
+/// snapshot reads through it. While the snapshot is alive, redb cannot
+/// reclaim pages freed by later commits, so the file may grow.
+pub struct RedbSnapshot {
+    txn: redb::ReadTransaction,
+}
+
+impl Store for RedbSnapshot {
+    type Txn<'a> = RedbTransaction<'a>;
+
+    fn begin(&self, read_only: bool) -> Result<Self::Txn<'_>, StoreError> {
+        if !read_only {
+            return Err(StoreError::ReadOnly);
+        }
+        Ok(RedbTransaction::from_snapshot(&self.txn))
+    }
+
+    fn is_read_only(&self) -> bool {
+        true
+    }
+
+    fn create_cf(&self, _name: &str) -> Result<(), StoreError> {
+        Err(StoreError::ReadOnly)
+    }
+
+    fn drop_cf(&self, _name: &str) -> Result<(), StoreError> {
+        Err(StoreError::ReadOnly)
+    }
+
+    fn delete_range(&self, _cf: &str, _range: impl RangeBounds<Vec<u8>>) -> Result<(), StoreError> {
+        Err(StoreError::ReadOnly)
+    }
+
+    fn compact_range(
+        &self,
+        _cf: &str,
+        _range: impl RangeBounds<Vec<u8>>,
+    ) -> Result<(), StoreError> {
+        Err(StoreError::ReadOnly)
+    }
+}
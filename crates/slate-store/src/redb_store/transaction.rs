@@ -1,28 +1,65 @@
+use std::cell::RefCell;
+use std::ops::{Bound, Deref};
+
 use redb::{Database, ReadableTable, TableDefinition};
 
 use crate::error::StoreError;
-use crate::store::{increment_prefix, Transaction};
+use crate::store::{increment_prefix, is_empty_range, Transaction};
+
+/// Key-value pairs collected eagerly from a write transaction's table.
+type Entries = Vec<(Vec<u8>, Vec<u8>)>;
 
-enum Inner {
-    Read(redb::ReadTransaction),
+#[allow(clippy::large_enum_variant)]
+enum Inner<'db> {
+    Read(ReadTxn<'db>),
     Write(redb::WriteTransaction),
     Consumed,
 }
 
+/// A read transaction of our own, or one shared with a [`RedbSnapshot`].
+///
+/// [`RedbSnapshot`]: super::RedbSnapshot
+enum ReadTxn<'db> {
+    Owned(redb::ReadTransaction),
+    Snapshot(&'db redb::ReadTransaction),
+}
+
+impl Deref for ReadTxn<'_> {
+    type Target = redb::ReadTransaction;
+
+    fn deref(&self) -> &Self::Target {
+        match self {
+            ReadTxn::Owned(txn) => txn,
+            ReadTxn::Snapshot(txn) => txn,
+        }
+    }
+}
+
+/// Prior state of one key, recorded while a savepoint is set.
+struct Undo {
+    cf: String,
+    key: Vec<u8>,
+    old: Option<Vec<u8>>,
+}
+
 pub struct RedbTransaction<'db> {
-    inner: Inner,
-    #[allow(dead_code)]
-    db: &'db Database,
+    inner: Inner<'db>,
     read_only: bool,
+    /// redb only allows native savepoints before any table is opened, so
+    /// savepoints are emulated with an undo log: while one is set, every
+    /// write records the value it replaced. Each savepoint is an offset
+    /// into the log.
+    undo: RefCell<Vec<Undo>>,
+    savepoints: Vec<usize>,
 }
 
 impl<'db> RedbTransaction<'db> {
-    pub fn new(db: &'db Database, read_only: bool) -> Result<Self, StoreError> {
+    pub fn new(db: &Database, read_only: bool) -> Result<Self, StoreError> {
         let inner = if read_only {
-            Inner::Read(
+            Inner::Read(ReadTxn::Owned(
                 db.begin_read()
                     .map_err(|e| StoreError::Storage(e.to_string()))?,
-            )
+            ))
         } else {
             Inner::Write(
                 db.begin_write()
@@ -31,11 +68,33 @@ impl<'db> RedbTransaction<'db> {
         };
         Ok(Self {
             inner,
-            db,
             read_only,
+            undo: RefCell::new(Vec::new()),
+            savepoints: Vec::new(),
         })
     }
 
+    /// A read-only transaction over a snapshot's pinned read transaction.
+    pub(crate) fn from_snapshot(txn: &'db redb::ReadTransaction) -> Self {
+        Self {
+            inner: Inner::Read(ReadTxn::Snapshot(txn)),
+            read_only: true,
+            undo: RefCell::new(Vec::new()),
+            savepoints: Vec::new(),
+        }
+    }
+
+    /// Record the value a write replaced, if a savepoint needs it.
+    fn record_undo(&self, cf: &str, key: &[u8], old: Option<redb::AccessGuard<'_, &[u8]>>) {
+        if !self.savepoints.is_empty() {
+            self.undo.borrow_mut().push(Undo {
+                cf: cf.to_string(),
+                key: key.to_vec(),
+                old: old.map(|v| v.value().to_vec()),
+            });
+        }
+    }
+
     fn check_writable(&self) -> Result<(), StoreError> {
         if self.read_only {
             return Err(StoreError::ReadOnly);
@@ -50,15 +109,37 @@ impl<'db> RedbTransaction<'db> {
         cf: &str,
         prefix: &[u8],
         reverse: bool,
-    ) -> Result<Vec<(Vec<u8>, Vec<u8>)>, StoreError> {
+    ) -> Result<Entries, StoreError> {
         let cf = cf.to_string();
         let def: TableDefinition<'_, &[u8], &[u8]> = TableDefinition::new(&cf);
         let upper = increment_prefix(prefix);
 
+        let end = match upper.as_deref() {
+            Some(upper) => Bound::Excluded(upper),
+            None => Bound::Unbounded,
+        };
+
         let table = txn
             .open_table(def)
             .map_err(|e| StoreError::Storage(e.to_string()))?;
-        collect_from_readable(&table, prefix, upper.as_deref(), reverse)
+        collect_from_readable(&table, Bound::Included(prefix), end, reverse)
+    }
+
+    /// Eagerly collect a bounded range for write transactions.
+    fn collect_range_write(
+        txn: &redb::WriteTransaction,
+        cf: &str,
+        start: Bound<&[u8]>,
+        end: Bound<&[u8]>,
+        reverse: bool,
+    ) -> Result<Entries, StoreError> {
+        let cf = cf.to_string();
+        let def: TableDefinition<'_, &[u8], &[u8]> = TableDefinition::new(&cf);
+
+        let table = txn
+            .open_table(def)
+            .map_err(|e| StoreError::Storage(e.to_string()))?;
+        collect_from_readable(&table, start, end, reverse)
     }
 }
 
@@ -212,6 +293,48 @@ impl<'db> Transaction for RedbTransaction<'db> {
         }
     }
 
+    fn scan_range<'a>(
+        &'a self,
+        cf: &Self::Cf,
+        start: Bound<&[u8]>,
+        end: Bound<&[u8]>,
+        reverse: bool,
+    ) -> Result<Box<dyn Iterator<Item = Result<(Vec<u8>, Vec<u8>), StoreError>> + 'a>, StoreError>
+    {
+        if matches!(self.inner, Inner::Consumed) {
+            return Err(StoreError::TransactionConsumed);
+        }
+        if is_empty_range(start, end) {
+            return Ok(Box::new(std::iter::empty()));
+        }
+        match &self.inner {
+            Inner::Read(txn) => {
+                let cf_str = cf.to_string();
+                let def: TableDefinition<'_, &[u8], &[u8]> = TableDefinition::new(&cf_str);
+                let table = txn
+                    .open_table(def)
+                    .map_err(|e| StoreError::Storage(e.to_string()))?;
+                let range = table
+                    .range::<&[u8]>((start, end))
+                    .map_err(|e| StoreError::Storage(e.to_string()))?;
+                let range: Box<dyn Iterator<Item = _>> = if reverse {
+                    Box::new(range.rev())
+                } else {
+                    Box::new(range)
+                };
+                Ok(Box::new(range.map(|entry| {
+                    let (k, v) = entry.map_err(|e| StoreError::Storage(e.to_string()))?;
+                    Ok((k.value().to_vec(), v.value().to_vec()))
+                })))
+            }
+            Inner::Write(txn) => {
+                let entries = Self::collect_range_write(txn, cf, start, end, reverse)?;
+                Ok(Box::new(entries.into_iter().map(Ok)))
+            }
+            Inner::Consumed => Err(StoreError::TransactionConsumed),
+        }
+    }
+
     fn put(&self, cf: &Self::Cf, key: &[u8], value: &[u8]) -> Result<(), StoreError> {
         self.check_writable()?;
         let def: TableDefinition<'_, &[u8], &[u8]> = TableDefinition::new(cf);
@@ -220,9 +343,10 @@ impl<'db> Transaction for RedbTransaction<'db> {
                 let mut table = txn
                     .open_table(def)
                     .map_err(|e| StoreError::Storage(e.to_string()))?;
-                table
+                let old = table
                     .insert(key, value)
                     .map_err(|e| StoreError::Storage(e.to_string()))?;
+                self.record_undo(cf, key, old);
                 Ok(())
             }
             Inner::Consumed => Err(StoreError::TransactionConsumed),
@@ -239,9 +363,10 @@ impl<'db> Transaction for RedbTransaction<'db> {
                     .open_table(def)
                     .map_err(|e| StoreError::Storage(e.to_string()))?;
                 for (key, value) in entries {
-                    table
+                    let old = table
                         .insert(*key, *value)
                         .map_err(|e| StoreError::Storage(e.to_string()))?;
+                    self.record_undo(cf, key, old);
                 }
                 Ok(())
             }
@@ -258,9 +383,10 @@ impl<'db> Transaction for RedbTransaction<'db> {
                 let mut table = txn
                     .open_table(def)
                     .map_err(|e| StoreError::Storage(e.to_string()))?;
-                table
+                let old = table
                     .remove(key)
                     .map_err(|e| StoreError::Storage(e.to_string()))?;
+                self.record_undo(cf, key, old);
                 Ok(())
             }
             Inner::Consumed => Err(StoreError::TransactionConsumed),
@@ -277,9 +403,10 @@ impl<'db> Transaction for RedbTransaction<'db> {
                     .open_table(def)
                     .map_err(|e| StoreError::Storage(e.to_string()))?;
                 for key in keys {
-                    table
+                    let old = table
                         .remove(*key)
                         .map_err(|e| StoreError::Storage(e.to_string()))?;
+                    self.record_undo(cf, key, old);
                 }
                 Ok(())
             }
@@ -318,6 +445,46 @@ impl<'db> Transaction for RedbTransaction<'db> {
         }
     }
 
+    fn set_savepoint(&mut self) -> Result<(), StoreError> {
+        self.check_writable()?;
+        if matches!(self.inner, Inner::Consumed) {
+            return Err(StoreError::TransactionConsumed);
+        }
+        self.savepoints.push(self.undo.get_mut().len());
+        Ok(())
+    }
+
+    fn rollback_to_savepoint(&mut self) -> Result<(), StoreError> {
+        let mark = self.savepoints.pop().ok_or(StoreError::NoSavepoint)?;
+        let txn = match &self.inner {
+            Inner::Write(txn) => txn,
+            Inner::Consumed => return Err(StoreError::TransactionConsumed),
+            _ => unreachable!(),
+        };
+        let undo = self.undo.get_mut();
+        for Undo { cf, key, old } in undo.drain(mark..).rev() {
+            let def: TableDefinition<'_, &[u8], &[u8]> = TableDefinition::new(&cf);
+            let mut table = txn
+                .open_table(def)
+                .map_err(|e| StoreError::Storage(e.to_string()))?;
+            match old {
+                Some(old) => table.insert(key.as_slice(), old.as_slice()),
+                None => table.remove(key.as_slice()),
+            }
+            .map_err(|e| StoreError::Storage(e.to_string()))?;
+        }
+        Ok(())
+    }
+
+    fn release_savepoint(&mut self) -> Result<(), StoreError> {
+        self.savepoints.pop().ok_or(StoreError::NoSavepoint)?;
+        // Outer savepoints still need the entries; with none left they're dead.
+        if self.savepoints.is_empty() {
+            self.undo.get_mut().clear();
+        }
+        Ok(())
+    }
+
     fn commit(mut self) -> Result<(), StoreError> {
         let inner = std::mem::replace(&mut self.inner, Inner::Consumed);
         match inner {
@@ -343,18 +510,15 @@ impl<'db> Transaction for RedbTransaction<'db> {
 
 fn collect_from_readable<T: ReadableTable<&'static [u8], &'static [u8]>>(
     table: &T,
-    prefix: &[u8],
-    upper: Option<&[u8]>,
+    start: Bound<&[u8]>,
+    end: Bound<&[u8]>,
     reverse: bool,
-) -> Result<Vec<(Vec<u8>, Vec<u8>)>, StoreError> {
-    let range = if let Some(upper) = upper {
-        table.range::<&[u8]>(prefix..upper)
-    } else {
-        table.range::<&[u8]>(prefix..)
-    }
-    .map_err(|e| StoreError::Storage(e.to_string()))?;
+) -> Result<Entries, StoreError> {
+    let range = table
+        .range::<&[u8]>((start, end))
+        .map_err(|e| StoreError::Storage(e.to_string()))?;
 
-    let entries: Vec<(Vec<u8>, Vec<u8>)> = if reverse {
+    let entries: Entries = if reverse {
         range
             .rev()
             .map(|entry| {
@@ -1,4 +1,6 @@
+mod read_only;
 mod store;
 mod transaction;
 
-pub use store::RocksStore;
+pub use read_only::RocksReadOnlyStore;
+pub use store::{RocksSnapshot, RocksStore};
@@ -0,0 +1,264 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ops::{Bound, RangeBounds};
+use std::path::Path;
+use std::sync::Arc;
+
+use rocksdb::{
+    BoundColumnFamily, DBWithThreadMode, Direction, IteratorMode, MultiThreaded, Options,
+    ReadOptions, SnapshotWithThreadMode,
+};
+
+use crate::error::StoreError;
+use crate::store::{Store, Transaction, increment_prefix, is_empty_range};
+
+use super::store::RocksStore;
+use super::transaction::{KvIter, RangeScan, RocksCf, prefix_rev_mode, within_prefix};
+
+type ReadDB = DBWithThreadMode<MultiThreaded>;
+
+impl RocksStore {
+    /// Open an existing database without write access.
+    ///
+    /// The instance sees the data as of the moment it was opened; writes made
+    /// later by another process are not visible. Several read-only instances
+    /// may share the files with a running primary.
+    pub fn open_read_only(path: &Path) -> Result<RocksReadOnlyStore, StoreError> {
+        let opts = Options::default();
+        let cfs = list_cfs(&opts, path)?;
+        let db = ReadDB::open_cf_for_read_only(&opts, path, &cfs, false)
+            .map_err(|e| StoreError::Storage(e.to_string()))?;
+        Ok(RocksReadOnlyStore { db })
+    }
+
+    /// Open a secondary instance that follows the primary at `primary_path`.
+    ///
+    /// The secondary keeps its own info log in `secondary_path` and reads the
+    /// primary's files in place. Call
+    /// [`RocksReadOnlyStore::try_catch_up_with_primary`] to see newer writes.
+    /// Column families the primary creates afterwards need a reopen.
+    pub fn open_as_secondary(
+        primary_path: &Path,
+        secondary_path: &Path,
+    ) -> Result<RocksReadOnlyStore, StoreError> {
+        let mut opts = Options::default();
+        // Secondaries must keep every table file open to follow the primary.
+        opts.set_max_open_files(-1);
+        let cfs = list_cfs(&opts, primary_path)?;
+        let db = ReadDB::open_cf_as_secondary(&opts, primary_path, secondary_path, &cfs)
+            .map_err(|e| StoreError::Storage(e.to_string()))?;
+        Ok(RocksReadOnlyStore { db })
+    }
+}
+
+fn list_cfs(opts: &Options, path: &Path) -> Result<Vec<String>, StoreError> {
+    ReadDB::list_cf(opts, path).map_err(|e| StoreError::Storage(e.to_string()))
+}
+
+/// A RocksDB database opened read-only or as a secondary, from
+/// [`RocksStore::open_read_only`] or [`RocksStore::open_as_secondary`].
+///
+/// Read transactions each take a native snapshot. Write transactions, schema
+/// changes and range deletes fail with [`StoreError::ReadOnly`].
+pub struct RocksReadOnlyStore {
+    db: ReadDB,
+}
+
+impl RocksReadOnlyStore {
+    /// Replay the primary's newest writes. Only valid for secondaries; a
+    /// plain read-only instance returns an error.
+    ///
+    /// Transactions that are already open keep reading their own snapshot.
+    pub fn try_catch_up_with_primary(&self) -> Result<(), StoreError> {
+        self.db
+            .try_catch_up_with_primary()
+            .map_err(|e| StoreError::Storage(e.to_string()))
+    }
+}
+
+impl Store for RocksReadOnlyStore {
+    type Txn<'a> = RocksReadTransaction<'a>;
+
+    fn begin(&self, read_only: bool) -> Result<Self::Txn<'_>, StoreError> {
+        if !read_only {
+            return Err(StoreError::ReadOnly);
+        }
+        Ok(RocksReadTransaction {
+            db: &self.db,
+            snapshot: Some(self.db.snapshot()),
+            cf_cache: RefCell::new(HashMap::new()),
+        })
+    }
+
+    fn is_read_only(&self) -> bool {
+        true
+    }
+
+    fn create_cf(&self, _name: &str) -> Result<(), StoreError> {
+        Err(StoreError::ReadOnly)
+    }
+
+    fn drop_cf(&self, _name: &str) -> Result<(), StoreError> {
+        Err(StoreError::ReadOnly)
+    }
+
+    fn delete_range(&self, _cf: &str, _range: impl RangeBounds<Vec<u8>>) -> Result<(), StoreError> {
+        Err(StoreError::ReadOnly)
+    }
+
+    fn compact_range(
+        &self,
+        _cf: &str,
+        _range: impl RangeBounds<Vec<u8>>,
+    ) -> Result<(), StoreError> {
+        Err(StoreError::ReadOnly)
+    }
+}
+
+/// Read transaction over a [`RocksReadOnlyStore`], pinned to the snapshot
+/// taken when it began.
+pub struct RocksReadTransaction<'db> {
+    db: &'db ReadDB,
+    snapshot: Option<SnapshotWithThreadMode<'db, ReadDB>>,
+    cf_cache: RefCell<HashMap<String, Arc<BoundColumnFamily<'db>>>>,
+}
+
+impl<'db> RocksReadTransaction<'db> {
+    fn read_opts(&self) -> Result<ReadOptions, StoreError> {
+        let snapshot = self
+            .snapshot
+            .as_ref()
+            .ok_or(StoreError::TransactionConsumed)?;
+        let mut opts = ReadOptions::default();
+        opts.set_snapshot(snapshot);
+        Ok(opts)
+    }
+
+    fn cf_handle(&self, cf: &str) -> Result<Arc<BoundColumnFamily<'db>>, StoreError> {
+        if let Some(handle) = self.cf_cache.borrow().get(cf) {
+            return Ok(Arc::clone(handle));
+        }
+        let handle = self
+            .db
+            .cf_handle(cf)
+            .ok_or_else(|| StoreError::Storage(format!("column family not found: {cf}")))?;
+        self.cf_cache
+            .borrow_mut()
+            .insert(cf.to_string(), Arc::clone(&handle));
+        Ok(handle)
+    }
+
+    fn finish(mut self) -> Result<(), StoreError> {
+        self.snapshot
+            .take()
+            .map(drop)
+            .ok_or(StoreError::TransactionConsumed)
+    }
+}
+
+impl<'db> Transaction for RocksReadTransaction<'db> {
+    type Cf = RocksCf<'db>;
+
+    fn cf(&self, name: &str) -> Result<Self::Cf, StoreError> {
+        let handle = self.cf_handle(name)?;
+        Ok(RocksCf { handle })
+    }
+
+    fn get(&self, cf: &Self::Cf, key: &[u8]) -> Result<Option<Vec<u8>>, StoreError> {
+        self.db
+            .get_cf_opt(&cf.handle, key, &self.read_opts()?)
+            .map_err(|e| StoreError::Storage(e.to_string()))
+    }
+
+    fn multi_get(&self, cf: &Self::Cf, keys: &[&[u8]]) -> Result<Vec<Option<Vec<u8>>>, StoreError> {
+        let cf_keys: Vec<_> = keys.iter().map(|k| (&cf.handle, *k)).collect();
+        self.db
+            .multi_get_cf_opt(cf_keys, &self.read_opts()?)
+            .into_iter()
+            .map(|r| r.map_err(|e| StoreError::Storage(e.to_string())))
+            .collect()
+    }
+
+    fn scan_prefix<'a>(&'a self, cf: &Self::Cf, prefix: &[u8]) -> Result<KvIter<'a>, StoreError> {
+        let iter = self.db.iterator_cf_opt(
+            &cf.handle,
+            self.read_opts()?,
+            IteratorMode::From(prefix, Direction::Forward),
+        );
+        Ok(within_prefix(iter, prefix))
+    }
+
+    fn scan_prefix_rev<'a>(
+        &'a self,
+        cf: &Self::Cf,
+        prefix: &[u8],
+    ) -> Result<KvIter<'a>, StoreError> {
+        let upper = increment_prefix(prefix);
+        let iter = self.db.iterator_cf_opt(
+            &cf.handle,
+            self.read_opts()?,
+            prefix_rev_mode(upper.as_deref()),
+        );
+        Ok(within_prefix(iter, prefix))
+    }
+
+    fn scan_range<'a>(
+        &'a self,
+        cf: &Self::Cf,
+        start: Bound<&[u8]>,
+        end: Bound<&[u8]>,
+        reverse: bool,
+    ) -> Result<KvIter<'a>, StoreError> {
+        let opts = self.read_opts()?;
+        if is_empty_range(start, end) {
+            return Ok(Box::new(std::iter::empty()));
+        }
+        let scan = RangeScan::new(start, end, reverse);
+        let iter = self.db.iterator_cf_opt(&cf.handle, opts, scan.mode);
+        Ok(scan.bound(iter))
+    }
+
+    fn put(&self, _cf: &Self::Cf, _key: &[u8], _value: &[u8]) -> Result<(), StoreError> {
+        Err(StoreError::ReadOnly)
+    }
+
+    fn put_batch(&self, _cf: &Self::Cf, _entries: &[(&[u8], &[u8])]) -> Result<(), StoreError> {
+        Err(StoreError::ReadOnly)
+    }
+
+    fn delete(&self, _cf: &Self::Cf, _key: &[u8]) -> Result<(), StoreError> {
+        Err(StoreError::ReadOnly)
+    }
+
+    fn delete_batch(&self, _cf: &Self::Cf, _keys: &[&[u8]]) -> Result<(), StoreError> {
+        Err(StoreError::ReadOnly)
+    }
+
+    fn create_cf(&mut self, _name: &str) -> Result<(), StoreError> {
+        Err(StoreError::ReadOnly)
+    }
+
+    fn drop_cf(&mut self, _name: &str) -> Result<(), StoreError> {
+        Err(StoreError::ReadOnly)
+    }
+
+    fn set_savepoint(&mut self) -> Result<(), StoreError> {
+        Err(StoreError::ReadOnly)
+    }
+
+    fn rollback_to_savepoint(&mut self) -> Result<(), StoreError> {
+        Err(StoreError::NoSavepoint)
+    }
+
+    fn release_savepoint(&mut self) -> Result<(), StoreError> {
+        Err(StoreError::NoSavepoint)
+    }
+
+    fn commit(self) -> Result<(), StoreError> {
+        self.finish()
+    }
+
+    fn rollback(self) -> Result<(), StoreError> {
+        self.finish()
+    }
+}
@@ -1,17 +1,28 @@
 use std::ops::{Bound, RangeBounds};
 use std::path::Path;
+use std::sync::{Arc, Mutex};
 
-use rocksdb::{MultiThreaded, OptimisticTransactionDB, Options, checkpoint::Checkpoint};
+use rocksdb::backup::{BackupEngine, BackupEngineOptions, RestoreOptions};
+use rocksdb::{
+    BoundColumnFamily, Env, MultiThreaded, OptimisticTransactionDB, Options, Range,
+    SnapshotWithThreadMode, checkpoint::Checkpoint, properties,
+};
 
 use crate::error::StoreError;
-use crate::store::{BackupStore, Store};
+use crate::store::{
+    ApproximateSize, BackupInfo, BackupPolicy, BackupStore, IncrementalBackupStore, SnapshotStore,
+    Store,
+};
 
 use super::transaction::RocksTransaction;
 
 type DB = OptimisticTransactionDB<MultiThreaded>;
 
 pub struct RocksStore {
-    db: DB,
+    db: Arc<DB>,
+    /// Serializes incremental backups; a `BackupEngine` directory supports
+    /// only one writer at a time.
+    backup_lock: Mutex<()>,
 }
 
 impl RocksStore {
@@ -29,12 +40,21 @@ impl RocksStore {
         }
         .map_err(|e| StoreError::Storage(e.to_string()))?;
 
-        Ok(Self { db })
+        Ok(Self {
+            db: Arc::new(db),
+            backup_lock: Mutex::new(()),
+        })
     }
 
     pub fn db(&self) -> &DB {
         &self.db
     }
+
+    fn cf_handle(&self, cf: &str) -> Result<Arc<BoundColumnFamily<'_>>, StoreError> {
+        self.db
+            .cf_handle(cf)
+            .ok_or_else(|| StoreError::Storage(format!("column family not found: {cf}")))
+    }
 }
 
 impl Store for RocksStore {
@@ -61,42 +81,223 @@ impl Store for RocksStore {
     }
 
     fn delete_range(&self, cf: &str, range: impl RangeBounds<Vec<u8>>) -> Result<(), StoreError> {
-        let cf_handle = self
+        let cf_handle = self.cf_handle(cf)?;
+        let (from, to) = key_span(&range);
+        self.db
+            .delete_range_cf(&cf_handle, &from, &to)
+            .map_err(|e| StoreError::Storage(e.to_string()))
+    }
+
+    /// Uses RocksDB's size estimate for the SST files overlapping the range;
+    /// writes still in the memtable are not counted until they are flushed.
+    /// The key count scales the column family's estimated key count by the
+    /// range's share of its file size.
+    fn approximate_size(
+        &self,
+        cf: &str,
+        range: impl RangeBounds<Vec<u8>>,
+    ) -> Result<ApproximateSize, StoreError> {
+        let cf_handle = self.cf_handle(cf)?;
+        let (from, to) = key_span(&range);
+        let bytes = self
             .db
-            .cf_handle(cf)
-            .ok_or_else(|| StoreError::Storage(format!("column family not found: {cf}")))?;
-
-        let from = match range.start_bound() {
-            Bound::Included(b) => b.clone(),
-            Bound::Excluded(b) => {
-                let mut v = b.clone();
-                v.push(0);
-                v
-            }
-            Bound::Unbounded => vec![],
-        };
+            .get_approximate_sizes_cf(&cf_handle, &[Range::new(&from, &to)])[0];
 
-        let to = match range.end_bound() {
-            Bound::Included(b) => {
-                let mut v = b.clone();
-                v.push(0);
-                v
-            }
-            Bound::Excluded(b) => b.clone(),
-            Bound::Unbounded => vec![0xFF],
+        let property = |name: &properties::PropName| {
+            self.db
+                .property_int_value_cf(&cf_handle, name.as_c_str())
+                .map_err(|e| StoreError::Storage(e.to_string()))
+                .map(Option::unwrap_or_default)
+        };
+        let total_keys = property(properties::ESTIMATE_NUM_KEYS)?;
+        let total_bytes = property(properties::TOTAL_SST_FILES_SIZE)?;
+        let keys = match total_bytes {
+            0 => 0,
+            total => ((total_keys as u128 * bytes as u128) / total as u128) as u64,
         };
+        Ok(ApproximateSize { bytes, keys })
+    }
 
-        self.db
-            .delete_range_cf(&cf_handle, &from, &to)
-            .map_err(|e| StoreError::Storage(e.to_string()))
+    fn compact_range(&self, cf: &str, range: impl RangeBounds<Vec<u8>>) -> Result<(), StoreError> {
+        let cf_handle = self.cf_handle(cf)?;
+        let (from, to) = key_span(&range);
+        let start = match range.start_bound() {
+            Bound::Unbounded => None,
+            _ => Some(from),
+        };
+        let end = match range.end_bound() {
+            Bound::Unbounded => None,
+            _ => Some(to),
+        };
+        self.db.compact_range_cf(&cf_handle, start, end);
+        Ok(())
     }
 }
 
+/// Convert a range into the half-open `[from, to)` keys RocksDB expects.
+fn key_span(range: &impl RangeBounds<Vec<u8>>) -> (Vec<u8>, Vec<u8>) {
+    let from = match range.start_bound() {
+        Bound::Included(b) => b.clone(),
+        Bound::Excluded(b) => {
+            let mut v = b.clone();
+            v.push(0);
+            v
+        }
+        Bound::Unbounded => vec![],
+    };
+
+    let to = match range.end_bound() {
+        Bound::Included(b) => {
+            let mut v = b.clone();
+            v.push(0);
+            v
+        }
+        Bound::Excluded(b) => b.clone(),
+        Bound::Unbounded => vec![0xFF],
+    };
+    (from, to)
+}
+
 impl BackupStore for RocksStore {
     fn backup(&self, dest: &Path) -> Result<(), StoreError> {
-        let cp = Checkpoint::new(&self.db)
+        let cp = Checkpoint::new(self.db())
             .map_err(|e| StoreError::Storage(e.to_string()))?;
         cp.create_checkpoint(dest)
             .map_err(|e| StoreError::Storage(e.to_string()))
     }
 }
+
+impl IncrementalBackupStore for RocksStore {
+    /// Flushes memtables and adds a backup with RocksDB's `BackupEngine`,
+    /// which shares SST files with earlier backups instead of copying them
+    /// again.
+    fn create_backup(
+        &self,
+        backup_dir: &Path,
+        policy: &BackupPolicy,
+    ) -> Result<BackupInfo, StoreError> {
+        let _guard = self
+            .backup_lock
+            .lock()
+            .map_err(|e| StoreError::Storage(format!("backup lock poisoned: {e}")))?;
+        let mut engine = open_backup_engine(backup_dir)?;
+        engine
+            .create_new_backup_flush(self.db(), true)
+            .map_err(backup_err)?;
+        let info = list(&engine)
+            .pop()
+            .ok_or_else(|| StoreError::Storage("backup failed: no backup recorded".into()))?;
+        if policy.verify {
+            engine.verify_backup(info.id).map_err(backup_err)?;
+        }
+        if let Some(keep) = policy.keep_last {
+            engine.purge_old_backups(keep.max(1)).map_err(backup_err)?;
+        }
+        Ok(info)
+    }
+
+    fn list_backups(backup_dir: &Path) -> Result<Vec<BackupInfo>, StoreError> {
+        Ok(list(&open_backup_engine(backup_dir)?))
+    }
+
+    /// Checks file presence and sizes; RocksDB does not re-read checksums here.
+    fn verify_backup(backup_dir: &Path, backup_id: u32) -> Result<(), StoreError> {
+        open_backup_engine(backup_dir)?
+            .verify_backup(backup_id)
+            .map_err(backup_err)
+    }
+
+    fn restore(backup_dir: &Path, backup_id: u32, dest: &Path) -> Result<(), StoreError> {
+        open_backup_engine(backup_dir)?
+            .restore_from_backup(dest, dest, &RestoreOptions::default(), backup_id)
+            .map_err(backup_err)
+    }
+}
+
+fn open_backup_engine(backup_dir: &Path) -> Result<BackupEngine, StoreError> {
+    let opts = BackupEngineOptions::new(backup_dir).map_err(backup_err)?;
+    let env = Env::new().map_err(backup_err)?;
+    BackupEngine::open(&opts, &env).map_err(backup_err)
+}
+
+fn list(engine: &BackupEngine) -> Vec<BackupInfo> {
+    let mut backups: Vec<BackupInfo> = engine
+        .get_backup_info()
+        .into_iter()
+        .map(|info| BackupInfo {
+            id: info.backup_id,
+            timestamp: info.timestamp,
+            size: info.size,
+            num_files: info.num_files,
+        })
+        .collect();
+    backups.sort_by_key(|info| info.id);
+    backups
+}
+
+fn backup_err(e: rocksdb::Error) -> StoreError {
+    StoreError::Storage(format!("backup failed: {e}"))
+}
+
+impl SnapshotStore for RocksStore {
+    type Snapshot = RocksSnapshot;
+
+    fn snapshot(&self) -> Result<RocksSnapshot, StoreError> {
+        let db = Arc::clone(&self.db);
+        // SAFETY: the snapshot borrows the DB behind the Arc, which
+        // `RocksSnapshot` keeps alive and drops after the snapshot.
+        let snapshot = unsafe {
+            std::mem::transmute::<SnapshotWithThreadMode<'_, DB>, SnapshotWithThreadMode<'static, DB>>(
+                db.snapshot(),
+            )
+        };
+        Ok(RocksSnapshot { snapshot, db })
+    }
+}
+
+/// Read-only, point-in-time view of a [`RocksStore`], taken with
+/// [`SnapshotStore::snapshot`].
+///
+/// Holds a native RocksDB snapshot. Every transaction begun on it reads at
+/// the snapshot's sequence number. Compaction keeps the versions it needs
+/// until the snapshot is dropped.
+pub struct RocksSnapshot {
+    // Field order matters: the snapshot must be released before the DB.
+    snapshot: SnapshotWithThreadMode<'static, DB>,
+    db: Arc<DB>,
+}
+
+impl Store for RocksSnapshot {
+    type Txn<'a> = RocksTransaction<'a>;
+
+    fn begin(&self, read_only: bool) -> Result<Self::Txn<'_>, StoreError> {
+        if !read_only {
+            return Err(StoreError::ReadOnly);
+        }
+        Ok(RocksTransaction::from_snapshot(&self.db, &self.snapshot))
+    }
+
+    fn is_read_only(&self) -> bool {
+        true
+    }
+
+    fn create_cf(&self, _name: &str) -> Result<(), StoreError> {
+        Err(StoreError::ReadOnly)
+    }
+
+    fn drop_cf(&self, _name: &str) -> Result<(), StoreError> {
+        Err(StoreError::ReadOnly)
+    }
+
+    fn delete_range(&self, _cf: &str, _range: impl RangeBounds<Vec<u8>>) -> Result<(), StoreError> {
+        Err(StoreError::ReadOnly)
+    }
+
+    fn compact_range(
+        &self,
+        _cf: &str,
+        _range: impl RangeBounds<Vec<u8>>,
+    ) -> Result<(), StoreError> {
+        Err(StoreError::ReadOnly)
+    }
+}
@@ -1,44 +1,88 @@
 use std::cell::RefCell;
 use std::collections::HashMap;
+use std::ops::Bound;
 use std::sync::Arc;
 
 use rocksdb::{
-    BoundColumnFamily, Direction, IteratorMode, MultiThreaded, OptimisticTransactionDB, Options,
+    BoundColumnFamily, Direction, ErrorKind, IteratorMode, MultiThreaded, OptimisticTransactionDB,
+    OptimisticTransactionOptions, Options, ReadOptions, SnapshotWithThreadMode, WriteOptions,
 };
 
 use crate::error::StoreError;
-use crate::store::{increment_prefix, Transaction};
+use crate::store::{Transaction, increment_prefix, is_empty_range};
 
 type DB = OptimisticTransactionDB<MultiThreaded>;
 
 /// Pre-resolved column family handle for reads.
 #[derive(Clone)]
 pub struct RocksCf<'db> {
-    handle: Arc<BoundColumnFamily<'db>>,
+    pub(super) handle: Arc<BoundColumnFamily<'db>>,
 }
 
 pub struct RocksTransaction<'db> {
     txn: Option<rocksdb::Transaction<'db, DB>>,
     db: &'db DB,
+    /// Snapshot owned by a [`RocksSnapshot`](super::RocksSnapshot); reads use
+    /// it instead of the transaction's own.
+    pinned: Option<&'db SnapshotWithThreadMode<'db, DB>>,
     read_only: bool,
     cf_cache: RefCell<HashMap<String, Arc<BoundColumnFamily<'db>>>>,
+    /// Live savepoints, each with the number of native savepoints a
+    /// rollback to it must unwind. The binding can't pop a native savepoint
+    /// without rolling back to it, so releasing one folds its count into the
+    /// live savepoint beneath. Released savepoints therefore cost RocksDB
+    /// memory until the transaction ends, and one extra native rollback each
+    /// if an outer savepoint is rolled back.
+    savepoints: Vec<usize>,
 }
 
 impl<'db> RocksTransaction<'db> {
     pub fn new(db: &'db DB, read_only: bool) -> Result<Self, StoreError> {
-        let txn = db.transaction();
+        // Take the snapshot at begin: reads see a single point in time, and
+        // commit-time conflict checks cover writes since then.
+        let mut opts = OptimisticTransactionOptions::default();
+        opts.set_snapshot(true);
+        let txn = db.transaction_opt(&WriteOptions::default(), &opts);
         Ok(Self {
             txn: Some(txn),
             db,
+            pinned: None,
             read_only,
             cf_cache: RefCell::new(HashMap::new()),
+            savepoints: Vec::new(),
         })
     }
 
+    /// A read-only transaction that reads at an existing snapshot.
+    pub(crate) fn from_snapshot(
+        db: &'db DB,
+        snapshot: &'db SnapshotWithThreadMode<'db, DB>,
+    ) -> Self {
+        Self {
+            txn: Some(db.transaction()),
+            db,
+            pinned: Some(snapshot),
+            read_only: true,
+            cf_cache: RefCell::new(HashMap::new()),
+            savepoints: Vec::new(),
+        }
+    }
+
     fn txn(&self) -> Result<&rocksdb::Transaction<'db, DB>, StoreError> {
         self.txn.as_ref().ok_or(StoreError::TransactionConsumed)
     }
 
+    /// Read options pinned to the transaction's snapshot. Reads still see the
+    /// transaction's own writes.
+    fn read_opts(&self) -> Result<ReadOptions, StoreError> {
+        let mut opts = ReadOptions::default();
+        match self.pinned {
+            Some(snapshot) => opts.set_snapshot(snapshot),
+            None => opts.set_snapshot(&self.txn()?.snapshot()),
+        }
+        Ok(opts)
+    }
+
     fn check_writable(&self) -> Result<(), StoreError> {
         if self.read_only {
             return Err(StoreError::ReadOnly);
@@ -62,6 +106,106 @@ impl<'db> RocksTransaction<'db> {
     }
 }
 
+// ── Scan helpers ────────────────────────────────────────────────
+//
+// Shared with `RocksReadTransaction`, whose iterators come from a plain DB
+// rather than a transaction but yield the same items.
+
+pub(super) type KvIter<'a> = Box<dyn Iterator<Item = Result<(Vec<u8>, Vec<u8>), StoreError>> + 'a>;
+
+/// What RocksDB iterators yield.
+type RawEntry = Result<(Box<[u8]>, Box<[u8]>), rocksdb::Error>;
+
+/// Where a reverse prefix scan starts: just below the prefix's upper bound,
+/// or at the end when there is none.
+pub(super) fn prefix_rev_mode(upper: Option<&[u8]>) -> IteratorMode<'_> {
+    match upper {
+        Some(u) => IteratorMode::From(u, Direction::Reverse),
+        None => IteratorMode::End,
+    }
+}
+
+/// Stop `iter` at the first key outside `prefix` and convert its items.
+pub(super) fn within_prefix<'a>(
+    iter: impl Iterator<Item = RawEntry> + 'a,
+    prefix: &[u8],
+) -> KvIter<'a> {
+    let prefix_owned = prefix.to_vec();
+    Box::new(
+        iter.take_while(move |item| match item {
+            Ok((key, _)) => key.starts_with(&prefix_owned),
+            Err(_) => true,
+        })
+        .map(into_entry),
+    )
+}
+
+/// A range scan: the iterator mode to open with, and the bounds to apply
+/// to what it yields.
+pub(super) struct RangeScan<'k> {
+    pub(super) mode: IteratorMode<'k>,
+    skip: Option<Vec<u8>>,
+    far: Bound<Vec<u8>>,
+    reverse: bool,
+}
+
+impl<'k> RangeScan<'k> {
+    /// Seek to the bound we start from; the far bound ends iteration and an
+    /// excluded near bound is skipped if the seek lands exactly on it.
+    pub(super) fn new(start: Bound<&'k [u8]>, end: Bound<&'k [u8]>, reverse: bool) -> Self {
+        let (near, far) = if reverse { (end, start) } else { (start, end) };
+        let direction = if reverse {
+            Direction::Reverse
+        } else {
+            Direction::Forward
+        };
+        let mode = match near {
+            Bound::Included(key) | Bound::Excluded(key) => IteratorMode::From(key, direction),
+            Bound::Unbounded if reverse => IteratorMode::End,
+            Bound::Unbounded => IteratorMode::Start,
+        };
+        let skip = match near {
+            Bound::Excluded(key) => Some(key.to_vec()),
+            _ => None,
+        };
+        Self {
+            mode,
+            skip,
+            far: far.map(<[u8]>::to_vec),
+            reverse,
+        }
+    }
+
+    /// Apply the bounds to an iterator opened with `self.mode`.
+    pub(super) fn bound<'a>(self, iter: impl Iterator<Item = RawEntry> + 'a) -> KvIter<'a> {
+        let Self {
+            skip, far, reverse, ..
+        } = self;
+        Box::new(
+            iter.skip_while(move |item| match (item, &skip) {
+                (Ok((key, _)), Some(skip)) => key[..] == skip[..],
+                _ => false,
+            })
+            .take_while(move |item| match item {
+                Ok((key, _)) => match &far {
+                    Bound::Included(f) if reverse => key[..] >= f[..],
+                    Bound::Excluded(f) if reverse => key[..] > f[..],
+                    Bound::Included(f) => key[..] <= f[..],
+                    Bound::Excluded(f) => key[..] < f[..],
+                    Bound::Unbounded => true,
+                },
+                Err(_) => true,
+            })
+            .map(into_entry),
+        )
+    }
+}
+
+fn into_entry(item: RawEntry) -> Result<(Vec<u8>, Vec<u8>), StoreError> {
+    item.map(|(k, v)| (k.into_vec(), v.into_vec()))
+        .map_err(|e| StoreError::Storage(e.to_string()))
+}
+
 impl<'db> Transaction for RocksTransaction<'db> {
     type Cf = RocksCf<'db>;
 
@@ -73,7 +217,20 @@ impl<'db> Transaction for RocksTransaction<'db> {
     fn get(&self, cf: &Self::Cf, key: &[u8]) -> Result<Option<Vec<u8>>, StoreError> {
         let data = self
             .txn()?
-            .get_cf(&cf.handle, key)
+            .get_cf_opt(&cf.handle, key, &self.read_opts()?)
+            .map_err(|e| StoreError::Storage(e.to_string()))?;
+        Ok(data)
+    }
+
+    fn get_for_update(&self, cf: &Self::Cf, key: &[u8]) -> Result<Option<Vec<u8>>, StoreError> {
+        // A snapshot transaction never commits writes, so there is nothing
+        // to validate.
+        if self.read_only || self.pinned.is_some() {
+            return self.get(cf, key);
+        }
+        let data = self
+            .txn()?
+            .get_for_update_cf_opt(&cf.handle, key, true, &self.read_opts()?)
             .map_err(|e| StoreError::Storage(e.to_string()))?;
         Ok(data)
     }
@@ -81,7 +238,7 @@ impl<'db> Transaction for RocksTransaction<'db> {
     fn multi_get(&self, cf: &Self::Cf, keys: &[&[u8]]) -> Result<Vec<Option<Vec<u8>>>, StoreError> {
         let txn = self.txn()?;
         let cf_keys: Vec<_> = keys.iter().map(|k| (&cf.handle, *k)).collect();
-        let results = txn.multi_get_cf(cf_keys);
+        let results = txn.multi_get_cf_opt(cf_keys, &self.read_opts()?);
         results
             .into_iter()
             .map(|r| r.map_err(|e| StoreError::Storage(e.to_string())))
@@ -94,20 +251,12 @@ impl<'db> Transaction for RocksTransaction<'db> {
         prefix: &[u8],
     ) -> Result<Box<dyn Iterator<Item = Result<(Vec<u8>, Vec<u8>), StoreError>> + 'a>, StoreError>
     {
-        let prefix_owned = prefix.to_vec();
-        let iter = self
-            .txn()?
-            .iterator_cf(&cf.handle, IteratorMode::From(prefix, Direction::Forward));
-        Ok(Box::new(
-            iter.take_while(move |item| match item {
-                Ok((key, _)) => key.starts_with(&prefix_owned),
-                Err(_) => true,
-            })
-            .map(|item| {
-                item.map(|(k, v)| (k.into_vec(), v.into_vec()))
-                    .map_err(|e| StoreError::Storage(e.to_string()))
-            }),
-        ))
+        let iter = self.txn()?.iterator_cf_opt(
+            &cf.handle,
+            self.read_opts()?,
+            IteratorMode::From(prefix, Direction::Forward),
+        );
+        Ok(within_prefix(iter, prefix))
     }
 
     fn scan_prefix_rev<'a>(
@@ -116,25 +265,30 @@ impl<'db> Transaction for RocksTransaction<'db> {
         prefix: &[u8],
     ) -> Result<Box<dyn Iterator<Item = Result<(Vec<u8>, Vec<u8>), StoreError>> + 'a>, StoreError>
     {
-        let prefix_owned = prefix.to_vec();
         let upper = increment_prefix(prefix);
-        let mode = match upper.as_deref() {
-            Some(u) => IteratorMode::From(u, Direction::Reverse),
-            None => IteratorMode::End,
-        };
-        let iter = self
-            .txn()?
-            .iterator_cf(&cf.handle, mode);
-        Ok(Box::new(
-            iter.take_while(move |item| match item {
-                Ok((key, _)) => key.starts_with(&prefix_owned),
-                Err(_) => true,
-            })
-            .map(|item| {
-                item.map(|(k, v)| (k.into_vec(), v.into_vec()))
-                    .map_err(|e| StoreError::Storage(e.to_string()))
-            }),
-        ))
+        let iter = self.txn()?.iterator_cf_opt(
+            &cf.handle,
+            self.read_opts()?,
+            prefix_rev_mode(upper.as_deref()),
+        );
+        Ok(within_prefix(iter, prefix))
+    }
+
+    fn scan_range<'a>(
+        &'a self,
+        cf: &Self::Cf,
+        start: Bound<&[u8]>,
+        end: Bound<&[u8]>,
+        reverse: bool,
+    ) -> Result<Box<dyn Iterator<Item = Result<(Vec<u8>, Vec<u8>), StoreError>> + 'a>, StoreError>
+    {
+        let txn = self.txn()?;
+        if is_empty_range(start, end) {
+            return Ok(Box::new(std::iter::empty()));
+        }
+        let scan = RangeScan::new(start, end, reverse);
+        let iter = txn.iterator_cf_opt(&cf.handle, self.read_opts()?, scan.mode);
+        Ok(scan.bound(iter))
     }
 
     fn put(&self, cf: &Self::Cf, key: &[u8], value: &[u8]) -> Result<(), StoreError> {
@@ -196,10 +350,42 @@ impl<'db> Transaction for RocksTransaction<'db> {
             .map_err(|e| StoreError::Storage(e.to_string()))
     }
 
+    fn set_savepoint(&mut self) -> Result<(), StoreError> {
+        self.check_writable()?;
+        self.txn()?.set_savepoint();
+        self.savepoints.push(1);
+        Ok(())
+    }
+
+    fn rollback_to_savepoint(&mut self) -> Result<(), StoreError> {
+        let native = self.savepoints.pop().ok_or(StoreError::NoSavepoint)?;
+        let txn = self.txn.as_ref().ok_or(StoreError::TransactionConsumed)?;
+        // Unwind released savepoints above the live one, then the live one.
+        for _ in 0..native {
+            txn.rollback_to_savepoint()
+                .map_err(|e| StoreError::Storage(e.to_string()))?;
+        }
+        Ok(())
+    }
+
+    fn release_savepoint(&mut self) -> Result<(), StoreError> {
+        let native = self.savepoints.pop().ok_or(StoreError::NoSavepoint)?;
+        // Without a live savepoint beneath, nothing will roll back past
+        // these; they go away with the transaction.
+        if let Some(outer) = self.savepoints.last_mut() {
+            *outer += native;
+        }
+        Ok(())
+    }
+
     fn commit(mut self) -> Result<(), StoreError> {
         let txn = self.txn.take().ok_or(StoreError::TransactionConsumed)?;
-        txn.commit()
-            .map_err(|e| StoreError::Storage(e.to_string()))?;
+        txn.commit().map_err(|e| match e.kind() {
+            // Optimistic validation failed: a concurrent commit touched one of
+            // our keys (Busy), or the memtable history was too short to tell (TryAgain).
+            ErrorKind::Busy | ErrorKind::TryAgain => StoreError::Conflict(e.to_string()),
+            _ => StoreError::Storage(e.to_string()),
+        })?;
         Ok(())
     }
 
@@ -0,0 +1,4 @@
+mod store;
+mod transaction;
+
+pub use store::{SqliteSnapshot, SqliteStore};
@@ -0,0 +1,321 @@
+use std::ops::{Bound, RangeBounds};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use rusqlite::{Connection, ErrorCode, MAIN_DB};
+
+use crate::error::StoreError;
+use crate::store::{ApproximateSize, BackupStore, SnapshotStore, Store};
+
+use super::transaction::SqliteTransaction;
+
+/// How long a connection waits on SQLite's file lock before giving up.
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Store backed by a single SQLite database file.
+///
+/// Each column family is a `WITHOUT ROWID` table `cf_<name>(key BLOB PRIMARY
+/// KEY, value BLOB)`, so keys are stored in byte order and the file can be
+/// inspected with the `sqlite3` shell.
+///
+/// The database runs in WAL mode: readers see a snapshot and never block the
+/// writer. SQLite allows one writer at a time, so write transactions begin
+/// with `BEGIN IMMEDIATE` and queue on the file lock. Connections are pooled
+/// and reused across transactions.
+pub struct SqliteStore {
+    path: PathBuf,
+    pool: Mutex<Vec<Connection>>,
+}
+
+impl SqliteStore {
+    pub fn open(path: &Path) -> Result<Self, StoreError> {
+        let store = Self {
+            path: path.to_path_buf(),
+            pool: Mutex::new(Vec::new()),
+        };
+        let conn = store.connect()?;
+        conn.pragma_update(None, "journal_mode", "WAL")
+            .map_err(sqlite_err)?;
+        store.release(conn);
+        Ok(store)
+    }
+
+    fn connect(&self) -> Result<Connection, StoreError> {
+        let conn = Connection::open(&self.path).map_err(sqlite_err)?;
+        conn.busy_timeout(BUSY_TIMEOUT).map_err(sqlite_err)?;
+        Ok(conn)
+    }
+
+    /// Take an idle connection from the pool, or open a new one.
+    pub(crate) fn acquire(&self) -> Result<Connection, StoreError> {
+        let pooled = self.pool.lock().unwrap().pop();
+        match pooled {
+            Some(conn) => Ok(conn),
+            None => self.connect(),
+        }
+    }
+
+    /// Return a connection to the pool. It must not be inside a transaction.
+    pub(crate) fn release(&self, conn: Connection) {
+        if conn.is_autocommit() {
+            self.pool.lock().unwrap().push(conn);
+        }
+    }
+
+    /// Run `f` in its own write transaction on a pooled connection.
+    fn write<T>(&self, f: impl FnOnce(&Connection) -> Result<T, StoreError>) -> Result<T, StoreError> {
+        let conn = self.acquire()?;
+        conn.execute_batch("BEGIN IMMEDIATE").map_err(sqlite_err)?;
+        let result = f(&conn).and_then(|value| {
+            conn.execute_batch("COMMIT").map_err(sqlite_err)?;
+            Ok(value)
+        });
+        if result.is_err() {
+            let _ = conn.execute_batch("ROLLBACK");
+        }
+        self.release(conn);
+        result
+    }
+}
+
+impl Store for SqliteStore {
+    type Txn<'a> = SqliteTransaction<'a>;
+
+    fn begin(&self, read_only: bool) -> Result<Self::Txn<'_>, StoreError> {
+        SqliteTransaction::new(self, read_only)
+    }
+
+    fn create_cf(&self, name: &str) -> Result<(), StoreError> {
+        self.write(|conn| create_table(conn, name))
+    }
+
+    fn drop_cf(&self, name: &str) -> Result<(), StoreError> {
+        self.write(|conn| drop_table(conn, name))
+    }
+
+    fn delete_range(&self, cf: &str, range: impl RangeBounds<Vec<u8>>) -> Result<(), StoreError> {
+        self.write(|conn| {
+            if !table_exists(conn, cf)? {
+                return Err(StoreError::Storage(format!("column family not found: {cf}")));
+            }
+            let (clause, params) = range_clause(
+                range.start_bound().map(Vec::as_slice),
+                range.end_bound().map(Vec::as_slice),
+            );
+            let sql = format!("DELETE FROM {}{clause}", table_name(cf));
+            conn.execute(&sql, rusqlite::params_from_iter(params))
+                .map_err(sqlite_err)?;
+            Ok(())
+        })
+    }
+
+    /// Sums key and value lengths with a single aggregate query.
+    fn approximate_size(
+        &self,
+        cf: &str,
+        range: impl RangeBounds<Vec<u8>>,
+    ) -> Result<ApproximateSize, StoreError> {
+        let conn = self.acquire()?;
+        let result = (|| {
+            if !table_exists(&conn, cf)? {
+                return Err(StoreError::Storage(format!("column family not found: {cf}")));
+            }
+            let (clause, params) = range_clause(
+                range.start_bound().map(Vec::as_slice),
+                range.end_bound().map(Vec::as_slice),
+            );
+            let sql = format!(
+                "SELECT COUNT(*), COALESCE(SUM(LENGTH(key) + LENGTH(value)), 0) FROM {}{clause}",
+                table_name(cf)
+            );
+            conn.query_row(&sql, rusqlite::params_from_iter(params), |row| {
+                Ok(ApproximateSize {
+                    keys: row.get::<_, i64>(0)? as u64,
+                    bytes: row.get::<_, i64>(1)? as u64,
+                })
+            })
+            .map_err(sqlite_err)
+        })();
+        self.release(conn);
+        result
+    }
+
+    /// Rebuilds the whole database file with `VACUUM`, then checkpoints the
+    /// log and truncates it, so the space is returned to the file system.
+    /// SQLite has no per-table compaction, so the column family and range
+    /// are ignored. A checkpoint held back by an open reader, such as a
+    /// snapshot, fails with [`StoreError::Conflict`].
+    fn compact_range(&self, _cf: &str, _range: impl RangeBounds<Vec<u8>>) -> Result<(), StoreError> {
+        let conn = self.acquire()?;
+        let result = (|| {
+            conn.execute_batch("VACUUM")
+                .map_err(|e| StoreError::Storage(format!("compaction failed: {e}")))?;
+            let busy: i64 = conn
+                .query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |row| row.get(0))
+                .map_err(sqlite_err)?;
+            if busy != 0 {
+                return Err(StoreError::Conflict(
+                    "compaction checkpoint blocked by an open reader".into(),
+                ));
+            }
+            Ok(())
+        })();
+        self.release(conn);
+        result
+    }
+}
+
+impl BackupStore for SqliteStore {
+    /// Copy the database to `dest` with SQLite's online backup API.
+    ///
+    /// The copy is a consistent snapshot and can be opened with
+    /// [`SqliteStore::open`].
+    fn backup(&self, dest: &Path) -> Result<(), StoreError> {
+        let conn = self.acquire()?;
+        let result = conn
+            .backup(MAIN_DB, dest, None)
+            .map_err(|e| StoreError::Storage(format!("backup failed: {e}")));
+        self.release(conn);
+        result
+    }
+}
+
+/// Map a SQLite error. Lock contention that outlasted the busy timeout is
+/// reported as a conflict, since retrying the transaction may succeed.
+pub(crate) fn sqlite_err(e: rusqlite::Error) -> StoreError {
+    match e.sqlite_error_code() {
+        Some(ErrorCode::DatabaseBusy | ErrorCode::DatabaseLocked) => {
+            StoreError::Conflict(e.to_string())
+        }
+        _ => StoreError::Storage(e.to_string()),
+    }
+}
+
+/// Quoted table name for a column family.
+pub(crate) fn table_name(cf: &str) -> String {
+    format!("\"cf_{}\"", cf.replace('"', "\"\""))
+}
+
+pub(crate) fn table_exists(conn: &Connection, cf: &str) -> Result<bool, StoreError> {
+    let mut stmt = conn
+        .prepare_cached("SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = ?1")
+        .map_err(sqlite_err)?;
+    stmt.exists([format!("cf_{cf}")]).map_err(sqlite_err)
+}
+
+pub(crate) fn create_table(conn: &Connection, cf: &str) -> Result<(), StoreError> {
+    let sql = format!(
+        "CREATE TABLE IF NOT EXISTS {} (key BLOB PRIMARY KEY, value BLOB) WITHOUT ROWID",
+        table_name(cf)
+    );
+    conn.execute_batch(&sql).map_err(sqlite_err)
+}
+
+pub(crate) fn drop_table(conn: &Connection, cf: &str) -> Result<(), StoreError> {
+    let sql = format!("DROP TABLE IF EXISTS {}", table_name(cf));
+    conn.execute_batch(&sql).map_err(sqlite_err)
+}
+
+/// ` WHERE ...` clause (or nothing) restricting `key` to `start..end`, with
+/// its positional parameters.
+pub(crate) fn range_clause<'k>(
+    start: Bound<&'k [u8]>,
+    end: Bound<&'k [u8]>,
+) -> (String, Vec<&'k [u8]>) {
+    let mut conditions = Vec::new();
+    let mut params = Vec::new();
+    match start {
+        Bound::Included(key) => {
+            params.push(key);
+            conditions.push(format!("key >= ?{}", params.len()));
+        }
+        Bound::Excluded(key) => {
+            params.push(key);
+            conditions.push(format!("key > ?{}", params.len()));
+        }
+        Bound::Unbounded => {}
+    }
+    match end {
+        Bound::Included(key) => {
+            params.push(key);
+            conditions.push(format!("key <= ?{}", params.len()));
+        }
+        Bound::Excluded(key) => {
+            params.push(key);
+            conditions.push(format!("key < ?{}", params.len()));
+        }
+        Bound::Unbounded => {}
+    }
+    if conditions.is_empty() {
+        (String::new(), params)
+    } else {
+        (format!(" WHERE {}", conditions.join(" AND ")), params)
+    }
+}
+
+impl SnapshotStore for SqliteStore {
+    type Snapshot = SqliteSnapshot;
+
+    /// Opens a read transaction on a dedicated connection and pins it with a
+    /// first read; in WAL mode that fixes the snapshot the connection sees.
+    fn snapshot(&self) -> Result<SqliteSnapshot, StoreError> {
+        let conn = self.acquire()?;
+        let pinned = conn.execute_batch("BEGIN DEFERRED").and_then(|()| {
+            conn.query_row("SELECT COUNT(*) FROM sqlite_master", [], |_| Ok(()))
+        });
+        if let Err(e) = pinned {
+            if !conn.is_autocommit() {
+                let _ = conn.execute_batch("ROLLBACK");
+            }
+            self.release(conn);
+            return Err(sqlite_err(e));
+        }
+        Ok(SqliteSnapshot { conn })
+    }
+}
+
+/// Read-only, point-in-time view of a [`SqliteStore`], taken with
+/// [`SnapshotStore::snapshot`].
+///
+/// Owns a connection held inside one read transaction for the snapshot's
+/// lifetime. Transactions begun on it share that connection. While it is
+/// open, WAL checkpoints cannot move past the snapshot, so the log may grow.
+pub struct SqliteSnapshot {
+    conn: Connection,
+}
+
+impl Store for SqliteSnapshot {
+    type Txn<'a> = SqliteTransaction<'a>;
+
+    fn begin(&self, read_only: bool) -> Result<Self::Txn<'_>, StoreError> {
+        if !read_only {
+            return Err(StoreError::ReadOnly);
+        }
+        Ok(SqliteTransaction::from_snapshot(&self.conn))
+    }
+
+    fn is_read_only(&self) -> bool {
+        true
+    }
+
+    fn create_cf(&self, _name: &str) -> Result<(), StoreError> {
+        Err(StoreError::ReadOnly)
+    }
+
+    fn drop_cf(&self, _name: &str) -> Result<(), StoreError> {
+        Err(StoreError::ReadOnly)
+    }
+
+    fn delete_range(&self, _cf: &str, _range: impl RangeBounds<Vec<u8>>) -> Result<(), StoreError> {
+        Err(StoreError::ReadOnly)
+    }
+
+    fn compact_range(
+        &self,
+        _cf: &str,
+        _range: impl RangeBounds<Vec<u8>>,
+    ) -> Result<(), StoreError> {
+        Err(StoreError::ReadOnly)
+    }
+}
@@ -0,0 +1,429 @@
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::ops::{Bound, Deref};
+use std::rc::{Rc, Weak};
+use std::sync::Arc;
+
+use rusqlite::{Connection, OptionalExtension};
+
+use crate::error::StoreError;
+use crate::store::{increment_prefix, is_empty_range, Transaction};
+
+use super::store::{
+    create_table, drop_table, range_clause, sqlite_err, table_exists, table_name, SqliteStore,
+};
+
+/// Key-value pairs collected eagerly from a query.
+type Entries = Vec<(Vec<u8>, Vec<u8>)>;
+
+type EntryIter<'b> = Box<dyn Iterator<Item = Result<(Vec<u8>, Vec<u8>), StoreError>> + 'b>;
+
+/// Rows fetched by a scan's first page; later pages double up to
+/// [`MAX_PAGE`], so a short scan reads little and a long one few queries.
+const FIRST_PAGE: usize = 16;
+const MAX_PAGE: usize = 1024;
+
+/// Column family handle for the SQLite backend: the CF's quoted table name.
+#[derive(Clone)]
+pub struct SqliteCf {
+    table: Arc<str>,
+}
+
+/// The connection a transaction runs on.
+enum Conn<'a> {
+    /// A pooled connection inside this transaction's own `BEGIN`.
+    Pooled(Connection, &'a SqliteStore),
+    /// A [`SqliteSnapshot`]'s connection, already inside its read transaction.
+    ///
+    /// [`SqliteSnapshot`]: super::SqliteSnapshot
+    Snapshot(&'a Connection),
+}
+
+impl Deref for Conn<'_> {
+    type Target = Connection;
+
+    fn deref(&self) -> &Connection {
+        match self {
+            Conn::Pooled(conn, _) => conn,
+            Conn::Snapshot(conn) => conn,
+        }
+    }
+}
+
+/// Transaction over a [`SqliteStore`], holding one pooled connection inside
+/// `BEGIN` / `BEGIN IMMEDIATE`.
+///
+/// Savepoints map directly onto SQLite's `SAVEPOINT`. Scans read in pages,
+/// each a query that resumes after the last key returned, since a live
+/// cursor would borrow the connection for the iterator's lifetime. A write
+/// first fetches the rest of every open scan, so a scan returns the rows as
+/// they were when it began, like the other backends, and a caller writing
+/// while it iterates never meets its own writes.
+pub struct SqliteTransaction<'a> {
+    conn: Option<Conn<'a>>,
+    read_only: bool,
+    savepoints: usize,
+    /// Scans that may still have pages to fetch.
+    scans: RefCell<Vec<Weak<RefCell<ScanState>>>>,
+}
+
+impl<'a> SqliteTransaction<'a> {
+    pub(crate) fn new(store: &'a SqliteStore, read_only: bool) -> Result<Self, StoreError> {
+        let conn = store.acquire()?;
+        let begin = if read_only {
+            "BEGIN DEFERRED"
+        } else {
+            "BEGIN IMMEDIATE"
+        };
+        if let Err(e) = conn.execute_batch(begin) {
+            store.release(conn);
+            return Err(sqlite_err(e));
+        }
+        Ok(Self {
+            conn: Some(Conn::Pooled(conn, store)),
+            read_only,
+            savepoints: 0,
+            scans: RefCell::new(Vec::new()),
+        })
+    }
+
+    /// A read-only transaction on a snapshot's connection.
+    pub(crate) fn from_snapshot(conn: &'a Connection) -> Self {
+        Self {
+            conn: Some(Conn::Snapshot(conn)),
+            read_only: true,
+            savepoints: 0,
+            scans: RefCell::new(Vec::new()),
+        }
+    }
+
+    fn conn(&self) -> Result<&Connection, StoreError> {
+        self.conn.as_deref().ok_or(StoreError::TransactionConsumed)
+    }
+
+    fn check_writable(&self) -> Result<(), StoreError> {
+        if self.read_only {
+            return Err(StoreError::ReadOnly);
+        }
+        Ok(())
+    }
+
+    /// Check the transaction may write, then fetch the rest of every open
+    /// scan so the write can't show up in it.
+    fn prepare_write(&self) -> Result<(), StoreError> {
+        self.check_writable()?;
+        let scans = std::mem::take(&mut *self.scans.borrow_mut());
+        for scan in scans.iter().filter_map(Weak::upgrade) {
+            scan.borrow_mut().fetch(self.conn()?, None)?;
+        }
+        Ok(())
+    }
+
+    fn scan<'b>(
+        &'b self,
+        cf: &SqliteCf,
+        start: Bound<&[u8]>,
+        end: Bound<&[u8]>,
+        reverse: bool,
+    ) -> Result<EntryIter<'b>, StoreError> {
+        let mut state = ScanState {
+            table: Arc::clone(&cf.table),
+            start: start.map(<[u8]>::to_vec),
+            end: end.map(<[u8]>::to_vec),
+            reverse,
+            rows: VecDeque::new(),
+            page: FIRST_PAGE,
+            done: false,
+        };
+        // The first page is read now, so a bad query fails here.
+        state.fetch(self.conn()?, Some(FIRST_PAGE))?;
+        if state.done {
+            return Ok(Box::new(state.rows.into_iter().map(Ok)));
+        }
+        let state = Rc::new(RefCell::new(state));
+        let mut scans = self.scans.borrow_mut();
+        scans.retain(|scan| scan.strong_count() > 0);
+        scans.push(Rc::downgrade(&state));
+        Ok(Box::new(Pages { txn: self, state }))
+    }
+
+    fn prefix_scan<'b>(
+        &'b self,
+        cf: &SqliteCf,
+        prefix: &[u8],
+        reverse: bool,
+    ) -> Result<EntryIter<'b>, StoreError> {
+        let upper = increment_prefix(prefix);
+        let end = match upper.as_deref() {
+            Some(upper) => Bound::Excluded(upper),
+            None => Bound::Unbounded,
+        };
+        self.scan(cf, Bound::Included(prefix), end, reverse)
+    }
+
+    fn execute(&self, sql: &str, params: &[&[u8]]) -> Result<(), StoreError> {
+        let mut stmt = self.conn()?.prepare_cached(sql).map_err(sqlite_err)?;
+        stmt.execute(rusqlite::params_from_iter(params))
+            .map_err(sqlite_err)?;
+        Ok(())
+    }
+
+    /// End the transaction with `sql` and hand the connection back to the pool.
+    fn finish(&mut self, sql: &str) -> Result<(), StoreError> {
+        let conn = self.conn.take().ok_or(StoreError::TransactionConsumed)?;
+        let Conn::Pooled(conn, store) = conn else {
+            // The snapshot's own read transaction stays open.
+            return Ok(());
+        };
+        let result = conn.execute_batch(sql).map_err(sqlite_err);
+        if result.is_err() && !conn.is_autocommit() {
+            let _ = conn.execute_batch("ROLLBACK");
+        }
+        store.release(conn);
+        result
+    }
+}
+
+/// Where a paged scan resumes, and the rows it fetched but hasn't returned.
+struct ScanState {
+    table: Arc<str>,
+    /// Range still to fetch: the bound on the scan's side moves past each
+    /// page's last key.
+    start: Bound<Vec<u8>>,
+    end: Bound<Vec<u8>>,
+    reverse: bool,
+    rows: VecDeque<(Vec<u8>, Vec<u8>)>,
+    /// Size of the next page.
+    page: usize,
+    done: bool,
+}
+
+impl ScanState {
+    /// Fetch up to `limit` more rows, or all that remain.
+    fn fetch(&mut self, conn: &Connection, limit: Option<usize>) -> Result<(), StoreError> {
+        if self.done {
+            return Ok(());
+        }
+        let (clause, params) = range_clause(
+            self.start.as_ref().map(Vec::as_slice),
+            self.end.as_ref().map(Vec::as_slice),
+        );
+        let order = if self.reverse { "DESC" } else { "ASC" };
+        let limit_clause = limit.map(|n| format!(" LIMIT {n}")).unwrap_or_default();
+        let sql = format!(
+            "SELECT key, value FROM {}{clause} ORDER BY key {order}{limit_clause}",
+            self.table
+        );
+        let mut stmt = conn.prepare_cached(&sql).map_err(sqlite_err)?;
+        let rows: Entries = stmt
+            .query_map(rusqlite::params_from_iter(params), |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })
+            .map_err(sqlite_err)?
+            .collect::<Result<_, _>>()
+            .map_err(sqlite_err)?;
+
+        self.done = limit.is_none_or(|n| rows.len() < n);
+        if let Some((last, _)) = rows.last() {
+            let after = Bound::Excluded(last.clone());
+            if self.reverse {
+                self.end = after;
+            } else {
+                self.start = after;
+            }
+        }
+        self.rows.extend(rows);
+        self.page = (self.page * 2).min(MAX_PAGE);
+        Ok(())
+    }
+}
+
+/// Iterator over a paged scan, fetching the next page when the buffered
+/// rows run out.
+struct Pages<'b, 'a> {
+    txn: &'b SqliteTransaction<'a>,
+    state: Rc<RefCell<ScanState>>,
+}
+
+impl Iterator for Pages<'_, '_> {
+    type Item = Result<(Vec<u8>, Vec<u8>), StoreError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut state = self.state.borrow_mut();
+        if state.rows.is_empty() && !state.done {
+            let page = state.page;
+            let fetched = self
+                .txn
+                .conn()
+                .and_then(|conn| state.fetch(conn, Some(page)));
+            if let Err(e) = fetched {
+                state.done = true;
+                return Some(Err(e));
+            }
+        }
+        state.rows.pop_front().map(Ok)
+    }
+}
+
+impl Drop for SqliteTransaction<'_> {
+    fn drop(&mut self) {
+        if self.conn.is_some() {
+            let _ = self.finish("ROLLBACK");
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+impl<'a> Transaction for SqliteTransaction<'a> {
+    type Cf = SqliteCf;
+
+    fn cf(&self, name: &str) -> Result<Self::Cf, StoreError> {
+        if !table_exists(self.conn()?, name)? {
+            return Err(StoreError::Storage(format!(
+                "column family not found: {name}"
+            )));
+        }
+        Ok(SqliteCf {
+            table: table_name(name).into(),
+        })
+    }
+
+    fn get(&self, cf: &Self::Cf, key: &[u8]) -> Result<Option<Vec<u8>>, StoreError> {
+        let sql = format!("SELECT value FROM {} WHERE key = ?1", cf.table);
+        let mut stmt = self.conn()?.prepare_cached(&sql).map_err(sqlite_err)?;
+        stmt.query_row([key], |row| row.get(0))
+            .optional()
+            .map_err(sqlite_err)
+    }
+
+    fn multi_get(&self, cf: &Self::Cf, keys: &[&[u8]]) -> Result<Vec<Option<Vec<u8>>>, StoreError> {
+        let sql = format!("SELECT value FROM {} WHERE key = ?1", cf.table);
+        let mut stmt = self.conn()?.prepare_cached(&sql).map_err(sqlite_err)?;
+        keys.iter()
+            .map(|key| {
+                stmt.query_row([key], |row| row.get(0))
+                    .optional()
+                    .map_err(sqlite_err)
+            })
+            .collect()
+    }
+
+    fn scan_prefix<'b>(
+        &'b self,
+        cf: &Self::Cf,
+        prefix: &[u8],
+    ) -> Result<Box<dyn Iterator<Item = Result<(Vec<u8>, Vec<u8>), StoreError>> + 'b>, StoreError>
+    {
+        self.prefix_scan(cf, prefix, false)
+    }
+
+    fn scan_prefix_rev<'b>(
+        &'b self,
+        cf: &Self::Cf,
+        prefix: &[u8],
+    ) -> Result<Box<dyn Iterator<Item = Result<(Vec<u8>, Vec<u8>), StoreError>> + 'b>, StoreError>
+    {
+        self.prefix_scan(cf, prefix, true)
+    }
+
+    fn scan_range<'b>(
+        &'b self,
+        cf: &Self::Cf,
+        start: Bound<&[u8]>,
+        end: Bound<&[u8]>,
+        reverse: bool,
+    ) -> Result<Box<dyn Iterator<Item = Result<(Vec<u8>, Vec<u8>), StoreError>> + 'b>, StoreError>
+    {
+        self.conn()?;
+        if is_empty_range(start, end) {
+            return Ok(Box::new(std::iter::empty()));
+        }
+        self.scan(cf, start, end, reverse)
+    }
+
+    fn put(&self, cf: &Self::Cf, key: &[u8], value: &[u8]) -> Result<(), StoreError> {
+        self.prepare_write()?;
+        let sql = format!(
+            "INSERT OR REPLACE INTO {} (key, value) VALUES (?1, ?2)",
+            cf.table
+        );
+        self.execute(&sql, &[key, value])
+    }
+
+    fn put_batch(&self, cf: &Self::Cf, entries: &[(&[u8], &[u8])]) -> Result<(), StoreError> {
+        self.prepare_write()?;
+        let sql = format!(
+            "INSERT OR REPLACE INTO {} (key, value) VALUES (?1, ?2)",
+            cf.table
+        );
+        for (key, value) in entries {
+            self.execute(&sql, &[key, value])?;
+        }
+        Ok(())
+    }
+
+    fn delete(&self, cf: &Self::Cf, key: &[u8]) -> Result<(), StoreError> {
+        self.prepare_write()?;
+        let sql = format!("DELETE FROM {} WHERE key = ?1", cf.table);
+        self.execute(&sql, &[key])
+    }
+
+    fn delete_batch(&self, cf: &Self::Cf, keys: &[&[u8]]) -> Result<(), StoreError> {
+        self.prepare_write()?;
+        let sql = format!("DELETE FROM {} WHERE key = ?1", cf.table);
+        for key in keys {
+            self.execute(&sql, &[key])?;
+        }
+        Ok(())
+    }
+
+    // DDL is transactional in SQLite, so CF creation and removal commit or
+    // roll back with the rest of the transaction.
+    fn create_cf(&mut self, name: &str) -> Result<(), StoreError> {
+        self.check_writable()?;
+        create_table(self.conn()?, name)
+    }
+
+    fn drop_cf(&mut self, name: &str) -> Result<(), StoreError> {
+        self.check_writable()?;
+        drop_table(self.conn()?, name)
+    }
+
+    fn set_savepoint(&mut self) -> Result<(), StoreError> {
+        self.check_writable()?;
+        let sql = format!("SAVEPOINT sp{}", self.savepoints);
+        self.conn()?.execute_batch(&sql).map_err(sqlite_err)?;
+        self.savepoints += 1;
+        Ok(())
+    }
+
+    fn rollback_to_savepoint(&mut self) -> Result<(), StoreError> {
+        if self.savepoints == 0 {
+            return Err(StoreError::NoSavepoint);
+        }
+        // ROLLBACK TO keeps the savepoint on SQLite's stack; RELEASE pops it.
+        let name = format!("sp{}", self.savepoints - 1);
+        let sql = format!("ROLLBACK TO {name}; RELEASE {name}");
+        self.conn()?.execute_batch(&sql).map_err(sqlite_err)?;
+        self.savepoints -= 1;
+        Ok(())
+    }
+
+    fn release_savepoint(&mut self) -> Result<(), StoreError> {
+        if self.savepoints == 0 {
+            return Err(StoreError::NoSavepoint);
+        }
+        let sql = format!("RELEASE sp{}", self.savepoints - 1);
+        self.conn()?.execute_batch(&sql).map_err(sqlite_err)?;
+        self.savepoints -= 1;
+        Ok(())
+    }
+
+    fn commit(mut self) -> Result<(), StoreError> {
+        self.finish("COMMIT")
+    }
+
+    fn rollback(mut self) -> Result<(), StoreError> {
+        self.finish("ROLLBACK")
+    }
+}
@@ -1,31 +1,50 @@
-use std::ops::RangeBounds;
+use std::ops::{Bound, RangeBounds};
 use std::path::Path;
 
 use crate::error::StoreError;
 
-/// Increment a prefix byte-string to produce an exclusive upper bound.
+/// Increment a prefix byte-string to produce an exclusive upper bound: the
+/// smallest key greater than every key starting with `prefix`.
 ///
 /// Returns `None` when the entire prefix is `0xFF` (no upper bound exists).
-pub(crate) fn increment_prefix(prefix: &[u8]) -> Option<Vec<u8>> {
+pub fn increment_prefix(prefix: &[u8]) -> Option<Vec<u8>> {
+    // Trailing 0xFF bytes are dropped, not carried: `a\xFF` becomes `b`.
     let mut upper = prefix.to_vec();
-    for byte in upper.iter_mut().rev() {
-        match byte.checked_add(1) {
-            Some(incremented) => {
-                *byte = incremented;
-                return Some(upper);
-            }
-            None => *byte = 0x00, // carry
+    while let Some(byte) = upper.pop() {
+        if let Some(incremented) = byte.checked_add(1) {
+            upper.push(incremented);
+            return Some(upper);
         }
     }
     None // all 0xFF — no upper bound exists
 }
 
+/// Returns `true` when `start..end` cannot contain any key.
+///
+/// Backends check this before building a native range so that inverted
+/// bounds yield an empty scan instead of panicking inside the B-tree.
+pub(crate) fn is_empty_range(start: Bound<&[u8]>, end: Bound<&[u8]>) -> bool {
+    match (start, end) {
+        (Bound::Included(s), Bound::Included(e)) => s > e,
+        (Bound::Included(s), Bound::Excluded(e))
+        | (Bound::Excluded(s), Bound::Included(e))
+        | (Bound::Excluded(s), Bound::Excluded(e)) => s >= e,
+        _ => false,
+    }
+}
+
 pub trait Store {
     type Txn<'a>: Transaction
     where
         Self: 'a;
 
     fn begin(&self, read_only: bool) -> Result<Self::Txn<'_>, StoreError>;
+    /// `true` if the store was opened without write access. Write
+    /// transactions, schema changes and range deletes then fail with
+    /// [`StoreError::ReadOnly`].
+    fn is_read_only(&self) -> bool {
+        false
+    }
     fn create_cf(&self, name: &str) -> Result<(), StoreError>;
     fn drop_cf(&self, name: &str) -> Result<(), StoreError>;
     /// Deletes all keys in the given range within a column family.
@@ -37,6 +56,76 @@ pub trait Store {
     /// Best used for user-level pruning (e.g. clearing a single user's cache),
     /// not global operations while transactions are in flight.
     fn delete_range(&self, cf: &str, range: impl RangeBounds<Vec<u8>>) -> Result<(), StoreError>;
+
+    /// Estimates the bytes and number of keys stored in the given range.
+    ///
+    /// Backends with native size estimates override this; the default walks
+    /// the range in a read transaction and sums key and value lengths, which
+    /// is exact but linear in the size of the range.
+    fn approximate_size(
+        &self,
+        cf: &str,
+        range: impl RangeBounds<Vec<u8>>,
+    ) -> Result<ApproximateSize, StoreError> {
+        let txn = self.begin(true)?;
+        let handle = txn.cf(cf)?;
+        let mut size = ApproximateSize::default();
+        {
+            let iter = txn.scan_range(
+                &handle,
+                range.start_bound().map(Vec::as_slice),
+                range.end_bound().map(Vec::as_slice),
+                false,
+            )?;
+            for entry in iter {
+                let (key, value) = entry?;
+                size.bytes += (key.len() + value.len()) as u64;
+                size.keys += 1;
+            }
+        }
+        txn.rollback()?;
+        Ok(size)
+    }
+
+    /// Reclaims space held by deleted or overwritten keys in the given range.
+    ///
+    /// Backends that reuse freed space on their own treat this as a no-op.
+    /// Like [`delete_range`](Store::delete_range) it runs outside of
+    /// transactions and never changes what readers see.
+    fn compact_range(&self, cf: &str, range: impl RangeBounds<Vec<u8>>) -> Result<(), StoreError>;
+
+    /// Column families over their memory budget, each with the number of
+    /// bytes it is over by.
+    ///
+    /// Only bounded stores have budgets; the default reports none. The
+    /// store never evicts on its own, since it can't tell records from index
+    /// entries. The engine deletes [`eviction_candidates`](Store::eviction_candidates)
+    /// until every CF is back within budget.
+    fn budget_overruns(&self) -> Vec<(String, u64)> {
+        Vec::new()
+    }
+
+    /// Up to `limit` keys under `prefix` in `cf`, least recently used first.
+    ///
+    /// Recency is approximate and only tracked for CFs with a memory budget;
+    /// other stores return no candidates.
+    fn eviction_candidates(
+        &self,
+        _cf: &str,
+        _prefix: &[u8],
+        _limit: usize,
+    ) -> Result<Vec<Vec<u8>>, StoreError> {
+        Ok(Vec::new())
+    }
+}
+
+/// Size estimate returned by [`Store::approximate_size`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ApproximateSize {
+    /// Bytes used by keys and values, as the backend counts them.
+    pub bytes: u64,
+    /// Number of keys.
+    pub keys: u64,
 }
 
 #[allow(clippy::type_complexity)]
@@ -51,6 +140,16 @@ pub trait Transaction {
     // Reads
     fn get(&self, cf: &Self::Cf, key: &[u8]) -> Result<Option<Vec<u8>>, StoreError>;
     fn multi_get(&self, cf: &Self::Cf, keys: &[&[u8]]) -> Result<Vec<Option<Vec<u8>>>, StoreError>;
+    /// Read `key` so that commit fails with [`StoreError::Conflict`] if
+    /// another transaction writes it after this one started.
+    ///
+    /// Plain reads carry no such guarantee on every backend: RocksDB only
+    /// validates the keys a transaction writes. The default suits backends
+    /// that already validate point reads or serialize writers; wrappers
+    /// must forward it to the store they wrap.
+    fn get_for_update(&self, cf: &Self::Cf, key: &[u8]) -> Result<Option<Vec<u8>>, StoreError> {
+        self.get(cf, key)
+    }
     fn scan_prefix<'a>(
         &'a self,
         cf: &Self::Cf,
@@ -61,6 +160,17 @@ pub trait Transaction {
         cf: &Self::Cf,
         prefix: &[u8],
     ) -> Result<Box<dyn Iterator<Item = Result<(Vec<u8>, Vec<u8>), StoreError>> + 'a>, StoreError>;
+    /// Iterates keys within `start..end`, seeking directly to the first bound.
+    ///
+    /// With `reverse = true` iteration starts at the upper bound and walks
+    /// down to the lower bound. An inverted range yields no entries.
+    fn scan_range<'a>(
+        &'a self,
+        cf: &Self::Cf,
+        start: Bound<&[u8]>,
+        end: Bound<&[u8]>,
+        reverse: bool,
+    ) -> Result<Box<dyn Iterator<Item = Result<(Vec<u8>, Vec<u8>), StoreError>> + 'a>, StoreError>;
 
     // Writes
     fn put(&self, cf: &Self::Cf, key: &[u8], value: &[u8]) -> Result<(), StoreError>;
@@ -72,6 +182,18 @@ pub trait Transaction {
     fn create_cf(&mut self, name: &str) -> Result<(), StoreError>;
     fn drop_cf(&mut self, name: &str) -> Result<(), StoreError>;
 
+    // Savepoints
+    /// Mark the transaction's current state. Savepoints nest: each call
+    /// pushes onto a stack. Only valid on write transactions.
+    fn set_savepoint(&mut self) -> Result<(), StoreError>;
+    /// Undo every write since the most recent savepoint and pop it.
+    ///
+    /// CF creation and removal take effect outside the transaction on some
+    /// backends and are not undone.
+    fn rollback_to_savepoint(&mut self) -> Result<(), StoreError>;
+    /// Pop the most recent savepoint, keeping its writes.
+    fn release_savepoint(&mut self) -> Result<(), StoreError>;
+
     // Lifecycle
     fn commit(self) -> Result<(), StoreError>;
     fn rollback(self) -> Result<(), StoreError>;
@@ -85,3 +207,77 @@ pub trait Transaction {
 pub trait BackupStore: Store {
     fn backup(&self, dest: &Path) -> Result<(), StoreError>;
 }
+
+/// Retention and verification settings for
+/// [`IncrementalBackupStore::create_backup`].
+#[derive(Debug, Clone, Default)]
+pub struct BackupPolicy {
+    /// Keep only the newest `n` backups, purging older ones after each new
+    /// backup. `None` keeps every backup. The new backup is always kept.
+    pub keep_last: Option<usize>,
+    /// Check the new backup's files once it is written.
+    pub verify: bool,
+}
+
+/// One numbered backup in a backup directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BackupInfo {
+    /// Backup id; ids only increase within a backup directory.
+    pub id: u32,
+    /// Creation time, in seconds since the Unix epoch.
+    pub timestamp: i64,
+    /// Bytes referenced by this backup, including files shared with others.
+    pub size: u64,
+    /// Number of files referenced by this backup.
+    pub num_files: u32,
+}
+
+/// Optional trait for stores that keep numbered, incremental backups.
+///
+/// Backups accumulate in one backup directory. Each new backup only copies
+/// files the directory does not already hold, so repeated backups of a large,
+/// mostly unchanged store are cheap. Safe to call while the store is live.
+pub trait IncrementalBackupStore: Store {
+    /// Add a backup of the current state to `backup_dir`, then apply the
+    /// policy's verification and retention.
+    fn create_backup(
+        &self,
+        backup_dir: &Path,
+        policy: &BackupPolicy,
+    ) -> Result<BackupInfo, StoreError>;
+
+    /// List the backups in `backup_dir`, oldest first.
+    fn list_backups(backup_dir: &Path) -> Result<Vec<BackupInfo>, StoreError>;
+
+    /// Check that every file of a backup exists with the expected size.
+    fn verify_backup(backup_dir: &Path, backup_id: u32) -> Result<(), StoreError>;
+
+    /// Restore a backup into `dest`, which can then be opened as a new store.
+    fn restore(backup_dir: &Path, backup_id: u32, dest: &Path) -> Result<(), StoreError>;
+}
+
+/// Optional trait for stores that can hand out detached read snapshots.
+///
+/// A snapshot is an owned, read-only store frozen at the moment it was
+/// taken: every read transaction begun on it sees the same data across all
+/// column families, no matter what commits afterwards. Unlike a transaction
+/// it does not borrow the store, so it can be moved to another thread and
+/// kept for as long as needed.
+///
+/// Write transactions and schema changes on a snapshot fail with
+/// [`StoreError::ReadOnly`].
+pub trait SnapshotStore: Store {
+    type Snapshot: Store + Send + 'static;
+
+    fn snapshot(&self) -> Result<Self::Snapshot, StoreError>;
+}
+
+/// Optional trait for stores that can make a writable, independent copy of
+/// themselves.
+///
+/// A fork starts out with every column family exactly as committed when it
+/// was taken. From then on the two stores diverge: writes to the fork are
+/// never visible in the original, and the other way round.
+pub trait ForkableStore: Store + Sized {
+    fn fork(&self) -> Result<Self, StoreError>;
+}
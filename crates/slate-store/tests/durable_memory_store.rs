@@ -0,0 +1,233 @@
+#![cfg(feature = "memory")]
+
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::Path;
+
+use slate_store::{DurableMemoryStore, Store, StoreError, Transaction};
+
+fn temp_store() -> (DurableMemoryStore, tempfile::TempDir) {
+    let dir = tempfile::tempdir().unwrap();
+    let store = DurableMemoryStore::open(dir.path()).unwrap();
+    store.create_cf("test").unwrap();
+    (store, dir)
+}
+
+const CF: &str = "test";
+
+slate_store::conformance_tests!(|dir: &Path| DurableMemoryStore::open(dir).unwrap());
+slate_store::snapshot_conformance_tests!(|dir: &Path| DurableMemoryStore::open(dir).unwrap());
+
+fn put(store: &DurableMemoryStore, key: &[u8], value: &[u8]) {
+    let txn = store.begin(false).unwrap();
+    let cf = txn.cf(CF).unwrap();
+    txn.put(&cf, key, value).unwrap();
+    txn.commit().unwrap();
+}
+
+fn get(store: &DurableMemoryStore, key: &[u8]) -> Option<Vec<u8>> {
+    let txn = store.begin(true).unwrap();
+    let cf = txn.cf(CF).unwrap();
+    txn.get(&cf, key).unwrap()
+}
+
+#[test]
+fn committed_writes_survive_reopen() {
+    let (store, dir) = temp_store();
+    put(&store, b"key1", b"value1");
+    put(&store, b"key2", b"value2");
+    drop(store);
+
+    let store = DurableMemoryStore::open(dir.path()).unwrap();
+    assert_eq!(get(&store, b"key1").unwrap(), b"value1");
+    assert_eq!(get(&store, b"key2").unwrap(), b"value2");
+}
+
+#[test]
+fn deletes_survive_reopen() {
+    let (store, dir) = temp_store();
+    put(&store, b"key1", b"value1");
+    put(&store, b"key2", b"value2");
+
+    let txn = store.begin(false).unwrap();
+    let cf = txn.cf(CF).unwrap();
+    txn.delete(&cf, b"key1").unwrap();
+    txn.commit().unwrap();
+    drop(store);
+
+    let store = DurableMemoryStore::open(dir.path()).unwrap();
+    assert!(get(&store, b"key1").is_none());
+    assert_eq!(get(&store, b"key2").unwrap(), b"value2");
+}
+
+#[test]
+fn rolled_back_writes_are_not_persisted() {
+    let (store, dir) = temp_store();
+    let txn = store.begin(false).unwrap();
+    let cf = txn.cf(CF).unwrap();
+    txn.put(&cf, b"key1", b"value1").unwrap();
+    txn.rollback().unwrap();
+    drop(store);
+
+    let store = DurableMemoryStore::open(dir.path()).unwrap();
+    assert!(get(&store, b"key1").is_none());
+}
+
+#[test]
+fn cf_create_and_drop_survive_reopen() {
+    let (store, dir) = temp_store();
+    let mut txn = store.begin(false).unwrap();
+    txn.create_cf("other").unwrap();
+    let cf = txn.cf("other").unwrap();
+    txn.put(&cf, b"k", b"v").unwrap();
+    txn.commit().unwrap();
+    store.drop_cf(CF).unwrap();
+    drop(store);
+
+    let store = DurableMemoryStore::open(dir.path()).unwrap();
+    let txn = store.begin(true).unwrap();
+    assert!(txn.cf(CF).is_err());
+    let cf = txn.cf("other").unwrap();
+    assert_eq!(txn.get(&cf, b"k").unwrap().unwrap(), b"v");
+}
+
+#[test]
+fn delete_range_survives_reopen() {
+    let (store, dir) = temp_store();
+    put(&store, b"a1", b"1");
+    put(&store, b"a2", b"2");
+    put(&store, b"b1", b"3");
+    store
+        .delete_range(CF, b"a".to_vec()..b"b".to_vec())
+        .unwrap();
+    drop(store);
+
+    let store = DurableMemoryStore::open(dir.path()).unwrap();
+    assert!(get(&store, b"a1").is_none());
+    assert!(get(&store, b"a2").is_none());
+    assert_eq!(get(&store, b"b1").unwrap(), b"3");
+}
+
+#[test]
+fn torn_log_tail_is_discarded() {
+    let (store, dir) = temp_store();
+    put(&store, b"key1", b"value1");
+    drop(store);
+
+    // Simulate a crash in the middle of appending the next record.
+    let mut wal = OpenOptions::new()
+        .append(true)
+        .open(dir.path().join("wal"))
+        .unwrap();
+    wal.write_all(&[0x40, 0x00, 0x00, 0x00, 0xde, 0xad])
+        .unwrap();
+    drop(wal);
+
+    let store = DurableMemoryStore::open(dir.path()).unwrap();
+    assert_eq!(get(&store, b"key1").unwrap(), b"value1");
+
+    // Appends after recovery land on a clean boundary.
+    put(&store, b"key2", b"value2");
+    drop(store);
+    let store = DurableMemoryStore::open(dir.path()).unwrap();
+    assert_eq!(get(&store, b"key2").unwrap(), b"value2");
+}
+
+#[test]
+fn compaction_writes_snapshot_and_truncates_log() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = DurableMemoryStore::open(dir.path())
+        .unwrap()
+        .with_compaction_threshold(256);
+    store.create_cf(CF).unwrap();
+    for i in 0..50u32 {
+        put(&store, &i.to_be_bytes(), b"some value bytes");
+    }
+
+    let snapshot = dir.path().join("snapshot");
+    assert!(
+        snapshot.exists(),
+        "threshold should have triggered compaction"
+    );
+    let wal_len = std::fs::metadata(dir.path().join("wal")).unwrap().len();
+    assert!(
+        wal_len < 256,
+        "log should be truncated, got {wal_len} bytes"
+    );
+    drop(store);
+
+    let store = DurableMemoryStore::open(dir.path()).unwrap();
+    for i in 0..50u32 {
+        assert_eq!(get(&store, &i.to_be_bytes()).unwrap(), b"some value bytes");
+    }
+}
+
+#[test]
+fn explicit_compact_then_reopen() {
+    let (store, dir) = temp_store();
+    put(&store, b"key1", b"value1");
+    store.compact().unwrap();
+    assert_eq!(std::fs::metadata(dir.path().join("wal")).unwrap().len(), 0);
+    put(&store, b"key2", b"value2");
+    drop(store);
+
+    let store = DurableMemoryStore::open(dir.path()).unwrap();
+    assert_eq!(get(&store, b"key1").unwrap(), b"value1");
+    assert_eq!(get(&store, b"key2").unwrap(), b"value2");
+}
+
+#[test]
+fn corrupt_snapshot_is_rejected() {
+    let (store, dir) = temp_store();
+    put(&store, b"key1", b"value1");
+    store.compact().unwrap();
+    drop(store);
+
+    let path = dir.path().join("snapshot");
+    let mut bytes = std::fs::read(&path).unwrap();
+    let mid = bytes.len() / 2;
+    bytes[mid] ^= 0xFF;
+    std::fs::write(&path, bytes).unwrap();
+
+    assert!(DurableMemoryStore::open(dir.path()).is_err());
+}
+
+#[test]
+fn savepoint_rollback_is_not_persisted() {
+    let (store, dir) = temp_store();
+    let mut txn = store.begin(false).unwrap();
+    let cf = txn.cf(CF).unwrap();
+    txn.put(&cf, b"kept", b"1").unwrap();
+    txn.set_savepoint().unwrap();
+    txn.put(&cf, b"undone", b"1").unwrap();
+    txn.rollback_to_savepoint().unwrap();
+    txn.commit().unwrap();
+    drop(store);
+
+    let store = DurableMemoryStore::open(dir.path()).unwrap();
+    assert!(get(&store, b"kept").is_some());
+    assert!(get(&store, b"undone").is_none());
+}
+
+#[test]
+fn concurrent_commits_are_logged_and_conflicts_are_not() {
+    let (store, dir) = temp_store();
+    let txn1 = store.begin(false).unwrap();
+    let txn2 = store.begin(false).unwrap();
+    let txn3 = store.begin(false).unwrap();
+    let cf1 = txn1.cf(CF).unwrap();
+    let cf2 = txn2.cf(CF).unwrap();
+    let cf3 = txn3.cf(CF).unwrap();
+    txn1.put(&cf1, b"a", b"1").unwrap();
+    txn2.put(&cf2, b"b", b"2").unwrap();
+    txn3.put(&cf3, b"a", b"3").unwrap();
+    txn1.commit().unwrap();
+    txn2.commit().unwrap();
+    let result = txn3.commit();
+    assert!(matches!(result, Err(StoreError::Conflict(_))), "got {result:?}");
+    drop(store);
+
+    let store = DurableMemoryStore::open(dir.path()).unwrap();
+    assert_eq!(get(&store, b"a").unwrap(), b"1");
+    assert_eq!(get(&store, b"b").unwrap(), b"2");
+}
@@ -0,0 +1,258 @@
+#![cfg(all(feature = "encryption", feature = "memory"))]
+
+use std::ops::Bound;
+use std::sync::Arc;
+
+use slate_store::{
+    BackupStore, EncryptedStore, KeyProvider, MemoryStore, StaticKeyProvider, Store, Transaction,
+};
+
+const CF: &str = "test";
+const KEY_1: [u8; 32] = [1; 32];
+const KEY_2: [u8; 32] = [2; 32];
+
+fn encrypted_store() -> EncryptedStore<MemoryStore> {
+    let store = EncryptedStore::new(MemoryStore::new(), StaticKeyProvider::new(1, KEY_1));
+    store.create_cf(CF).unwrap();
+    store
+}
+
+fn put(store: &impl Store, key: &[u8], value: &[u8]) {
+    let txn = store.begin(false).unwrap();
+    let cf = txn.cf(CF).unwrap();
+    txn.put(&cf, key, value).unwrap();
+    txn.commit().unwrap();
+}
+
+/// Reload the wrapped store's raw contents through a backup.
+fn raw_contents(store: &EncryptedStore<MemoryStore>) -> MemoryStore {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("backup");
+    store.backup(&path).unwrap();
+    MemoryStore::load(&path).unwrap()
+}
+
+#[test]
+fn put_and_get_roundtrip() {
+    let store = encrypted_store();
+    put(&store, b"key1", b"value1");
+    put(&store, b"empty", b"");
+
+    let txn = store.begin(true).unwrap();
+    let cf = txn.cf(CF).unwrap();
+    assert_eq!(txn.get(&cf, b"key1").unwrap().unwrap(), b"value1");
+    assert_eq!(txn.get(&cf, b"empty").unwrap().unwrap(), b"");
+    assert!(txn.get(&cf, b"missing").unwrap().is_none());
+}
+
+#[test]
+fn values_are_not_stored_in_plaintext() {
+    let store = encrypted_store();
+    put(&store, b"key1", b"secret value");
+
+    let raw = raw_contents(&store);
+    let txn = raw.begin(true).unwrap();
+    let cf = txn.cf(CF).unwrap();
+    let stored = txn.get(&cf, b"key1").unwrap().unwrap();
+    assert_ne!(stored, b"secret value");
+    assert!(!stored.windows(b"secret".len()).any(|w| w == b"secret"));
+}
+
+#[test]
+fn put_batch_and_multi_get() {
+    let store = encrypted_store();
+    let txn = store.begin(false).unwrap();
+    let cf = txn.cf(CF).unwrap();
+    txn.put_batch(&cf, &[(b"a", b"1"), (b"b", b"2"), (b"c", b"3")])
+        .unwrap();
+    txn.commit().unwrap();
+
+    let txn = store.begin(true).unwrap();
+    let cf = txn.cf(CF).unwrap();
+    let keys: Vec<&[u8]> = vec![b"a", b"missing", b"c"];
+    let results = txn.multi_get(&cf, &keys).unwrap();
+    assert_eq!(results[0].as_deref(), Some(&b"1"[..]));
+    assert!(results[1].is_none());
+    assert_eq!(results[2].as_deref(), Some(&b"3"[..]));
+}
+
+#[test]
+fn scans_decrypt_in_key_order() {
+    let store = encrypted_store();
+    for (k, v) in [
+        (b"p:1", b"one"),
+        (b"p:2", b"two"),
+        (b"p:3", b"thr"),
+        (b"q:1", b"zzz"),
+    ] {
+        put(&store, k, v);
+    }
+
+    let txn = store.begin(true).unwrap();
+    let cf = txn.cf(CF).unwrap();
+
+    let forward: Vec<_> = txn
+        .scan_prefix(&cf, b"p:")
+        .unwrap()
+        .map(|r| r.unwrap())
+        .collect();
+    assert_eq!(
+        forward,
+        vec![
+            (b"p:1".to_vec(), b"one".to_vec()),
+            (b"p:2".to_vec(), b"two".to_vec()),
+            (b"p:3".to_vec(), b"thr".to_vec()),
+        ]
+    );
+
+    let reverse: Vec<_> = txn
+        .scan_prefix_rev(&cf, b"p:")
+        .unwrap()
+        .map(|r| r.unwrap().1)
+        .collect();
+    assert_eq!(
+        reverse,
+        vec![b"thr".to_vec(), b"two".to_vec(), b"one".to_vec()]
+    );
+
+    let range: Vec<_> = txn
+        .scan_range(&cf, Bound::Excluded(b"p:1"), Bound::Included(b"q:1"), false)
+        .unwrap()
+        .map(|r| r.unwrap().1)
+        .collect();
+    assert_eq!(
+        range,
+        vec![b"two".to_vec(), b"thr".to_vec(), b"zzz".to_vec()]
+    );
+}
+
+#[test]
+fn wrong_key_fails_to_decrypt() {
+    let store = encrypted_store();
+    put(&store, b"key1", b"value1");
+
+    let reopened = EncryptedStore::new(raw_contents(&store), StaticKeyProvider::new(1, KEY_2));
+    let txn = reopened.begin(true).unwrap();
+    let cf = txn.cf(CF).unwrap();
+    assert!(txn.get(&cf, b"key1").is_err());
+}
+
+#[test]
+fn value_moved_to_another_key_fails_to_decrypt() {
+    let store = encrypted_store();
+    put(&store, b"key1", b"value1");
+
+    let raw = raw_contents(&store);
+    let txn = raw.begin(false).unwrap();
+    let cf = txn.cf(CF).unwrap();
+    let sealed = txn.get(&cf, b"key1").unwrap().unwrap();
+    txn.put(&cf, b"key2", &sealed).unwrap();
+    txn.commit().unwrap();
+
+    let reopened = EncryptedStore::new(raw, StaticKeyProvider::new(1, KEY_1));
+    let txn = reopened.begin(true).unwrap();
+    let cf = txn.cf(CF).unwrap();
+    assert_eq!(txn.get(&cf, b"key1").unwrap().unwrap(), b"value1");
+    assert!(txn.get(&cf, b"key2").is_err());
+}
+
+#[test]
+fn old_values_stay_readable_after_key_change() {
+    let provider = Arc::new(StaticKeyProvider::new(1, KEY_1));
+    let store = EncryptedStore::with_provider(MemoryStore::new(), provider.clone());
+    store.create_cf(CF).unwrap();
+    put(&store, b"old", b"v1");
+
+    provider.add_key(2, KEY_2);
+    provider.set_current(2).unwrap();
+    put(&store, b"new", b"v2");
+
+    let txn = store.begin(true).unwrap();
+    let cf = txn.cf(CF).unwrap();
+    assert_eq!(txn.get(&cf, b"old").unwrap().unwrap(), b"v1");
+    assert_eq!(txn.get(&cf, b"new").unwrap().unwrap(), b"v2");
+}
+
+#[test]
+fn set_current_rejects_unknown_key() {
+    let provider = StaticKeyProvider::new(1, KEY_1);
+    assert!(provider.set_current(7).is_err());
+    assert_eq!(provider.current_key_id(), 1);
+}
+
+#[test]
+fn rotate_cf_reencrypts_with_current_key() {
+    let provider = Arc::new(StaticKeyProvider::new(1, KEY_1));
+    let store = EncryptedStore::with_provider(MemoryStore::new(), provider.clone())
+        .with_rotation_batch_size(3);
+    store.create_cf(CF).unwrap();
+    for i in 0..10u8 {
+        put(&store, &[i], &[i; 4]);
+    }
+
+    provider.add_key(2, KEY_2);
+    provider.set_current(2).unwrap();
+    put(&store, b"already-current", b"x");
+
+    assert_eq!(store.rotate_cf(CF).unwrap(), 10);
+    // Nothing left to rewrite on a second pass.
+    assert_eq!(store.rotate_cf(CF).unwrap(), 0);
+
+    // Everything now decrypts without the retired key.
+    let rotated = EncryptedStore::new(raw_contents(&store), StaticKeyProvider::new(2, KEY_2));
+    let txn = rotated.begin(true).unwrap();
+    let cf = txn.cf(CF).unwrap();
+    for i in 0..10u8 {
+        assert_eq!(txn.get(&cf, &[i]).unwrap().unwrap(), vec![i; 4]);
+    }
+    assert_eq!(txn.get(&cf, b"already-current").unwrap().unwrap(), b"x");
+}
+
+#[test]
+fn rotate_in_background_covers_all_cfs() {
+    let provider = Arc::new(StaticKeyProvider::new(1, KEY_1));
+    let store = EncryptedStore::with_provider(MemoryStore::new(), provider.clone())
+        .with_rotation_batch_size(4);
+    store.create_cf(CF).unwrap();
+    store.create_cf("other").unwrap();
+    for i in 0..20u8 {
+        put(&store, &[i], b"v");
+    }
+    let txn = store.begin(false).unwrap();
+    let other = txn.cf("other").unwrap();
+    txn.put(&other, b"k", b"v").unwrap();
+    txn.commit().unwrap();
+
+    provider.add_key(2, KEY_2);
+    provider.set_current(2).unwrap();
+
+    let handle = store.rotate_in_background(vec![CF.to_string(), "other".to_string()]);
+    assert_eq!(handle.join().unwrap(), 21);
+    assert_eq!(store.rotate_cf(CF).unwrap(), 0);
+    assert_eq!(store.rotate_cf("other").unwrap(), 0);
+}
+
+#[cfg(feature = "redb")]
+#[test]
+fn wraps_redb_across_reopen() {
+    use slate_store::RedbStore;
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("db.redb");
+    {
+        let store = EncryptedStore::new(
+            RedbStore::open(&path).unwrap(),
+            StaticKeyProvider::new(1, KEY_1),
+        );
+        store.create_cf(CF).unwrap();
+        put(&store, b"key1", b"value1");
+    }
+
+    let store = EncryptedStore::new(
+        RedbStore::open(&path).unwrap(),
+        StaticKeyProvider::new(1, KEY_1),
+    );
+    let txn = store.begin(true).unwrap();
+    let cf = txn.cf(CF).unwrap();
+    assert_eq!(txn.get(&cf, b"key1").unwrap().unwrap(), b"value1");
+}
@@ -0,0 +1,117 @@
+#![cfg(feature = "memory")]
+
+use std::panic::{AssertUnwindSafe, catch_unwind};
+
+use slate_store::{Fault, FaultPlan, FaultyStore, MemoryStore, Store, StoreOp, Transaction};
+
+const CF: &str = "test";
+
+fn faulty_store() -> FaultyStore<MemoryStore> {
+    let store = FaultyStore::new(MemoryStore::new());
+    store.create_cf(CF).unwrap();
+    store
+}
+
+fn get(store: &impl Store, key: &[u8]) -> Option<Vec<u8>> {
+    let txn = store.begin(true).unwrap();
+    let cf = txn.cf(CF).unwrap();
+    txn.get(&cf, key).unwrap()
+}
+
+#[test]
+fn passes_through_without_a_plan() {
+    let store = faulty_store();
+    let txn = store.begin(false).unwrap();
+    let cf = txn.cf(CF).unwrap();
+    txn.put(&cf, b"k", b"v").unwrap();
+    txn.commit().unwrap();
+    assert_eq!(get(&store, b"k").unwrap(), b"v");
+    assert_eq!(store.fired(), 0);
+}
+
+#[test]
+fn fails_the_nth_put() {
+    let store = faulty_store();
+    store.arm(FaultPlan::new().fail_put(2));
+
+    let txn = store.begin(false).unwrap();
+    let cf = txn.cf(CF).unwrap();
+    txn.put(&cf, b"a", b"1").unwrap();
+    assert!(txn.put(&cf, b"b", b"2").is_err());
+    txn.put(&cf, b"c", b"3").unwrap();
+    txn.commit().unwrap();
+
+    assert!(get(&store, b"a").is_some());
+    assert!(get(&store, b"b").is_none());
+    assert!(get(&store, b"c").is_some());
+    assert_eq!(store.fired(), 1);
+}
+
+#[test]
+fn partial_batch_applies_a_prefix() {
+    let store = faulty_store();
+    store.arm(FaultPlan::new().partial_batch(1, 2));
+
+    let txn = store.begin(false).unwrap();
+    let cf = txn.cf(CF).unwrap();
+    assert!(
+        txn.put_batch(&cf, &[(b"a", b"1"), (b"b", b"2"), (b"c", b"3")])
+            .is_err()
+    );
+    assert!(txn.get(&cf, b"b").unwrap().is_some());
+    assert!(txn.get(&cf, b"c").unwrap().is_none());
+}
+
+#[test]
+fn failed_commit_discards_writes() {
+    let store = faulty_store();
+    store.arm(FaultPlan::new().fail_commit(1));
+
+    let txn = store.begin(false).unwrap();
+    let cf = txn.cf(CF).unwrap();
+    txn.put(&cf, b"k", b"v").unwrap();
+    assert!(txn.commit().is_err());
+    assert!(get(&store, b"k").is_none());
+}
+
+#[test]
+fn corrupt_get_flips_bytes() {
+    let store = faulty_store();
+    let txn = store.begin(false).unwrap();
+    let cf = txn.cf(CF).unwrap();
+    txn.put(&cf, b"k", &[0x00, 0x0F]).unwrap();
+    txn.commit().unwrap();
+
+    store.arm(FaultPlan::new().corrupt_get(1));
+    assert_eq!(get(&store, b"k").unwrap(), vec![0xFF, 0xF0]);
+    // Fires once.
+    assert_eq!(get(&store, b"k").unwrap(), vec![0x00, 0x0F]);
+}
+
+#[test]
+fn rules_can_target_a_column_family() {
+    let store = faulty_store();
+    store.create_cf("other").unwrap();
+    store.arm(FaultPlan::new().on_cf("other", StoreOp::Put, 1, Fault::Error));
+
+    let txn = store.begin(false).unwrap();
+    let cf = txn.cf(CF).unwrap();
+    let other = txn.cf("other").unwrap();
+    txn.put(&cf, b"k", b"v").unwrap();
+    assert!(txn.put(&other, b"k", b"v").is_err());
+}
+
+#[test]
+fn panic_at_commit_leaves_store_unchanged() {
+    let store = faulty_store();
+    store.arm(FaultPlan::new().panic_at(StoreOp::Commit, 1));
+
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        let txn = store.begin(false).unwrap();
+        let cf = txn.cf(CF).unwrap();
+        txn.put(&cf, b"k", b"v").unwrap();
+        txn.commit()
+    }));
+    assert!(result.is_err());
+    assert!(get(&store, b"k").is_none());
+}
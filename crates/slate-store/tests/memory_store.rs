@@ -1,6 +1,11 @@
 #![cfg(feature = "memory")]
 
-use slate_store::{BackupStore, MemoryStore, Store, Transaction};
+use std::ops::Bound;
+use std::path::Path;
+
+use slate_store::{
+    ApproximateSize, BackupStore, ForkableStore, MemoryStore, Store, StoreError, Transaction,
+};
 
 fn mem_store() -> MemoryStore {
     let store = MemoryStore::new();
@@ -10,477 +15,397 @@ fn mem_store() -> MemoryStore {
 
 const CF: &str = "test";
 
-#[test]
-fn put_and_get() {
-    let store = mem_store();
-    let txn = store.begin(false).unwrap();
-    let cf = txn.cf(CF).unwrap();
-    txn.put(&cf, b"key1", b"value1").unwrap();
-    txn.commit().unwrap();
-
-    let txn = store.begin(true).unwrap();
-    let cf = txn.cf(CF).unwrap();
-    let result = txn.get(&cf, b"key1").unwrap().unwrap();
-    assert_eq!(&*result, b"value1");
-}
+slate_store::conformance_tests!(|_dir: &Path| MemoryStore::new());
+slate_store::snapshot_conformance_tests!(|_dir: &Path| MemoryStore::new());
 
 #[test]
-fn get_missing_key_returns_none() {
+fn backup_and_restore() {
     let store = mem_store();
-    let txn = store.begin(true).unwrap();
-    let cf = txn.cf(CF).unwrap();
-    let result = txn.get(&cf, b"nonexistent").unwrap();
-    assert!(result.is_none());
-}
-
-#[test]
-fn put_and_delete() {
-    let store = mem_store();
-    let txn = store.begin(false).unwrap();
-    let cf = txn.cf(CF).unwrap();
-    txn.put(&cf, b"key1", b"value1").unwrap();
-    txn.commit().unwrap();
+    store.create_cf("other").unwrap();
 
     let txn = store.begin(false).unwrap();
     let cf = txn.cf(CF).unwrap();
-    txn.delete(&cf, b"key1").unwrap();
+    txn.put(&cf, b"name", b"Alice").unwrap();
+    txn.put(&cf, b"score", b"100").unwrap();
+    let other = txn.cf("other").unwrap();
+    txn.put(&other, b"k", b"v").unwrap();
     txn.commit().unwrap();
 
-    let txn = store.begin(true).unwrap();
-    let cf = txn.cf(CF).unwrap();
-    let result = txn.get(&cf, b"key1").unwrap();
-    assert!(result.is_none());
-}
-
-#[test]
-fn put_batch() {
-    let store = mem_store();
-    let txn = store.begin(false).unwrap();
-    let cf = txn.cf(CF).unwrap();
-    txn.put_batch(
-        &cf,
-        &[
-            (b"accounts:1:email" as &[u8], b"a@test.com" as &[u8]),
-            (b"accounts:1:name", b"Alice"),
-            (b"accounts:1:status", b"active"),
-        ],
-    )
-    .unwrap();
-    txn.commit().unwrap();
+    let backup_dir = tempfile::tempdir().unwrap();
+    let backup_path = backup_dir.path().join("backup.slms");
+    store.backup(&backup_path).unwrap();
 
-    let txn = store.begin(true).unwrap();
+    let restored = MemoryStore::load(&backup_path).unwrap();
+    let txn = restored.begin(true).unwrap();
     let cf = txn.cf(CF).unwrap();
-    assert_eq!(
-        &*txn.get(&cf, b"accounts:1:email").unwrap().unwrap(),
-        b"a@test.com"
-    );
-    assert_eq!(
-        &*txn.get(&cf, b"accounts:1:name").unwrap().unwrap(),
-        b"Alice"
-    );
-    assert_eq!(
-        &*txn.get(&cf, b"accounts:1:status").unwrap().unwrap(),
-        b"active"
-    );
+    assert_eq!(&*txn.get(&cf, b"name").unwrap().unwrap(), b"Alice");
+    assert_eq!(&*txn.get(&cf, b"score").unwrap().unwrap(), b"100");
+    let other = txn.cf("other").unwrap();
+    assert_eq!(&*txn.get(&other, b"k").unwrap().unwrap(), b"v");
 }
 
 #[test]
-fn scan_prefix_returns_matching_pairs() {
+fn to_bytes_and_from_bytes_roundtrip() {
     let store = mem_store();
     let txn = store.begin(false).unwrap();
     let cf = txn.cf(CF).unwrap();
-    txn.put(&cf, b"accounts:1:email", b"a@test.com").unwrap();
-    txn.put(&cf, b"accounts:1:name", b"Alice").unwrap();
-    txn.put(&cf, b"accounts:2:email", b"b@test.com").unwrap();
-    txn.put(&cf, b"other:1:foo", b"bar").unwrap();
+    txn.put(&cf, b"a", b"1").unwrap();
+    txn.put(&cf, b"b", b"").unwrap();
     txn.commit().unwrap();
 
-    let txn = store.begin(true).unwrap();
+    let bytes = store.to_bytes().unwrap();
+    let restored = MemoryStore::from_bytes(&bytes).unwrap();
+    let txn = restored.begin(true).unwrap();
     let cf = txn.cf(CF).unwrap();
     let entries: Vec<_> = txn
-        .scan_prefix(&cf, b"accounts:1:")
+        .scan_prefix(&cf, b"")
         .unwrap()
         .map(|r| r.unwrap())
         .collect();
-    assert_eq!(entries.len(), 2);
-    assert_eq!(&*entries[0].0, b"accounts:1:email");
-    assert_eq!(&*entries[0].1, b"a@test.com");
-    assert_eq!(&*entries[1].0, b"accounts:1:name");
-    assert_eq!(&*entries[1].1, b"Alice");
+    assert_eq!(
+        entries,
+        vec![
+            (b"a".to_vec(), b"1".to_vec()),
+            (b"b".to_vec(), b"".to_vec()),
+        ]
+    );
+
+    // Deterministic: the restored store serializes to the same bytes.
+    assert_eq!(restored.to_bytes().unwrap(), bytes);
 }
 
 #[test]
-fn scan_prefix_no_matches() {
+fn from_bytes_rejects_corruption() {
     let store = mem_store();
     let txn = store.begin(false).unwrap();
     let cf = txn.cf(CF).unwrap();
-    txn.put(&cf, b"accounts:1:email", b"a@test.com").unwrap();
+    txn.put(&cf, b"key", b"value").unwrap();
     txn.commit().unwrap();
+    let bytes = store.to_bytes().unwrap();
 
-    let txn = store.begin(true).unwrap();
-    let cf = txn.cf(CF).unwrap();
-    let entries: Vec<_> = txn
-        .scan_prefix(&cf, b"contacts:")
-        .unwrap()
-        .map(|r| r.unwrap())
-        .collect();
-    assert!(entries.is_empty());
+    let mut flipped = bytes.clone();
+    flipped[bytes.len() / 2] ^= 0xFF;
+    assert!(MemoryStore::from_bytes(&flipped).is_err());
+
+    assert!(MemoryStore::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+    assert!(MemoryStore::from_bytes(b"").is_err());
 }
 
 #[test]
-fn scan_prefix_broader() {
+fn backup_does_not_block_writers() {
     let store = mem_store();
     let txn = store.begin(false).unwrap();
     let cf = txn.cf(CF).unwrap();
-    txn.put(&cf, b"accounts:1:email", b"a@test.com").unwrap();
-    txn.put(&cf, b"accounts:1:name", b"Alice").unwrap();
-    txn.put(&cf, b"accounts:2:email", b"b@test.com").unwrap();
-    txn.put(&cf, b"accounts:2:name", b"Bob").unwrap();
-    txn.put(&cf, b"other:1:foo", b"bar").unwrap();
+    txn.put(&cf, b"committed", b"1").unwrap();
     txn.commit().unwrap();
 
-    let txn = store.begin(true).unwrap();
-    let cf = txn.cf(CF).unwrap();
-    let entries: Vec<_> = txn
-        .scan_prefix(&cf, b"accounts:")
-        .unwrap()
-        .map(|r| r.unwrap())
-        .collect();
-    assert_eq!(entries.len(), 4);
-}
+    // A write transaction is open while the backup runs.
+    let writer = store.begin(false).unwrap();
+    let cf = writer.cf(CF).unwrap();
+    writer.put(&cf, b"pending", b"2").unwrap();
 
-#[test]
-fn scan_prefix_rev_returns_reverse_order() {
-    let store = mem_store();
-    let txn = store.begin(false).unwrap();
-    let cf = txn.cf(CF).unwrap();
-    txn.put(&cf, b"accounts:1:email", b"a@test.com").unwrap();
-    txn.put(&cf, b"accounts:1:name", b"Alice").unwrap();
-    txn.put(&cf, b"accounts:2:email", b"b@test.com").unwrap();
-    txn.put(&cf, b"other:1:foo", b"bar").unwrap();
-    txn.commit().unwrap();
+    let bytes = store.to_bytes().unwrap();
+    writer.commit().unwrap();
 
-    let txn = store.begin(true).unwrap();
+    let restored = MemoryStore::from_bytes(&bytes).unwrap();
+    let txn = restored.begin(true).unwrap();
     let cf = txn.cf(CF).unwrap();
-    let entries: Vec<_> = txn
-        .scan_prefix_rev(&cf, b"accounts:1:")
-        .unwrap()
-        .map(|r| r.unwrap())
-        .collect();
-    assert_eq!(entries.len(), 2);
-    // Reverse order: name before email
-    assert_eq!(&*entries[0].0, b"accounts:1:name");
-    assert_eq!(&*entries[0].1, b"Alice");
-    assert_eq!(&*entries[1].0, b"accounts:1:email");
-    assert_eq!(&*entries[1].1, b"a@test.com");
+    assert!(txn.get(&cf, b"committed").unwrap().is_some());
+    assert!(txn.get(&cf, b"pending").unwrap().is_none());
 }
 
+// ── Concurrent writers ──────────────────────────────────────────
+
 #[test]
-fn scan_prefix_rev_no_matches() {
+fn concurrent_writers_to_disjoint_keys_both_commit() {
     let store = mem_store();
-    let txn = store.begin(false).unwrap();
-    let cf = txn.cf(CF).unwrap();
-    txn.put(&cf, b"accounts:1:email", b"a@test.com").unwrap();
-    txn.commit().unwrap();
+    let txn1 = store.begin(false).unwrap();
+    let txn2 = store.begin(false).unwrap();
+    let cf1 = txn1.cf(CF).unwrap();
+    let cf2 = txn2.cf(CF).unwrap();
+    txn1.put(&cf1, b"key1", b"one").unwrap();
+    txn2.put(&cf2, b"key2", b"two").unwrap();
+    txn2.delete(&cf2, b"missing").unwrap();
+    txn1.commit().unwrap();
+    txn2.commit().unwrap();
 
     let txn = store.begin(true).unwrap();
     let cf = txn.cf(CF).unwrap();
-    let entries: Vec<_> = txn
-        .scan_prefix_rev(&cf, b"contacts:")
-        .unwrap()
-        .map(|r| r.unwrap())
-        .collect();
-    assert!(entries.is_empty());
+    assert_eq!(txn.get(&cf, b"key1").unwrap().unwrap(), b"one");
+    assert_eq!(txn.get(&cf, b"key2").unwrap().unwrap(), b"two");
 }
 
 #[test]
-fn read_only_rejects_put() {
+fn concurrent_writers_to_different_cfs_both_commit() {
     let store = mem_store();
-    let txn = store.begin(true).unwrap();
-    let cf = txn.cf(CF).unwrap();
-    let result = txn.put(&cf, b"key1", b"value1");
-    assert!(result.is_err());
-}
+    store.create_cf("other").unwrap();
+    let txn1 = store.begin(false).unwrap();
+    let txn2 = store.begin(false).unwrap();
+    let cf1 = txn1.cf(CF).unwrap();
+    let cf2 = txn2.cf("other").unwrap();
+    txn1.put(&cf1, b"key", b"one").unwrap();
+    txn2.put(&cf2, b"key", b"two").unwrap();
+    txn2.commit().unwrap();
+    txn1.commit().unwrap();
 
-#[test]
-fn read_only_rejects_put_batch() {
-    let store = mem_store();
     let txn = store.begin(true).unwrap();
-    let cf = txn.cf(CF).unwrap();
-    let result = txn.put_batch(&cf, &[(b"key1" as &[u8], b"value1" as &[u8])]);
-    assert!(result.is_err());
+    assert_eq!(txn.get(&txn.cf(CF).unwrap(), b"key").unwrap().unwrap(), b"one");
+    assert_eq!(txn.get(&txn.cf("other").unwrap(), b"key").unwrap().unwrap(), b"two");
 }
 
 #[test]
-fn read_only_rejects_delete() {
+fn concurrent_write_conflict_is_typed() {
     let store = mem_store();
+    let txn1 = store.begin(false).unwrap();
+    let txn2 = store.begin(false).unwrap();
+    let cf1 = txn1.cf(CF).unwrap();
+    let cf2 = txn2.cf(CF).unwrap();
+    txn1.put(&cf1, b"key1", b"one").unwrap();
+    txn2.put(&cf2, b"key1", b"two").unwrap();
+    txn1.commit().unwrap();
+
+    let result = txn2.commit();
+    assert!(matches!(result, Err(StoreError::Conflict(_))), "got {result:?}");
+
     let txn = store.begin(true).unwrap();
     let cf = txn.cf(CF).unwrap();
-    let result = txn.delete(&cf, b"key1");
-    assert!(result.is_err());
+    assert_eq!(txn.get(&cf, b"key1").unwrap().unwrap(), b"one");
 }
 
 #[test]
-fn read_own_writes() {
+fn read_of_concurrently_written_key_conflicts() {
     let store = mem_store();
-    let txn = store.begin(false).unwrap();
-    let cf = txn.cf(CF).unwrap();
-    txn.put(&cf, b"key1", b"value1").unwrap();
-
-    // Should see the write through the same CF handle.
-    let result = txn.get(&cf, b"key1").unwrap();
-    assert!(result.is_some(), "transaction should read its own writes");
-    assert_eq!(&*result.unwrap(), b"value1");
+    let reader = store.begin(false).unwrap();
+    let cf = reader.cf(CF).unwrap();
+    assert!(reader.get(&cf, b"balance").unwrap().is_none());
+    reader.put(&cf, b"audit", b"saw none").unwrap();
+
+    let writer = store.begin(false).unwrap();
+    let wcf = writer.cf(CF).unwrap();
+    writer.put(&wcf, b"balance", b"100").unwrap();
+    writer.commit().unwrap();
+
+    let result = reader.commit();
+    assert!(matches!(result, Err(StoreError::Conflict(_))), "got {result:?}");
 }
 
 #[test]
-fn read_own_writes_scan() {
+fn scan_of_concurrently_written_range_does_not_conflict() {
     let store = mem_store();
-    let txn = store.begin(false).unwrap();
-    let cf = txn.cf(CF).unwrap();
-    txn.put(&cf, b"prefix:a", b"1").unwrap();
-    txn.put(&cf, b"prefix:b", b"2").unwrap();
+    let reader = store.begin(false).unwrap();
+    let cf = reader.cf(CF).unwrap();
+    assert_eq!(reader.scan_prefix(&cf, b"user:").unwrap().count(), 0);
+    assert_eq!(
+        reader
+            .scan_range(&cf, Bound::Included(b"a"), Bound::Excluded(b"z"), false)
+            .unwrap()
+            .count(),
+        0
+    );
+    reader.put(&cf, b"audit", b"saw none").unwrap();
 
-    // Scan should see uncommitted writes.
-    let entries: Vec<_> = txn
-        .scan_prefix(&cf, b"prefix:")
-        .unwrap()
-        .map(|r| r.unwrap())
-        .collect();
-    assert_eq!(entries.len(), 2, "scan should see own writes");
+    let writer = store.begin(false).unwrap();
+    let wcf = writer.cf(CF).unwrap();
+    writer.put(&wcf, b"user:1", b"ada").unwrap();
+    writer.commit().unwrap();
+
+    // Only point reads and writes are validated.
+    reader.commit().unwrap();
 }
 
 #[test]
-fn overwrite_key() {
+fn rewrite_with_same_value_does_not_conflict() {
     let store = mem_store();
     let txn = store.begin(false).unwrap();
     let cf = txn.cf(CF).unwrap();
-    txn.put(&cf, b"key1", b"old").unwrap();
+    txn.put(&cf, b"key", b"v").unwrap();
     txn.commit().unwrap();
 
-    let txn = store.begin(false).unwrap();
-    let cf = txn.cf(CF).unwrap();
-    txn.put(&cf, b"key1", b"new").unwrap();
-    txn.commit().unwrap();
+    let reader = store.begin(false).unwrap();
+    let cf = reader.cf(CF).unwrap();
+    assert_eq!(reader.get(&cf, b"key").unwrap().unwrap(), b"v");
+    reader.put(&cf, b"other", b"x").unwrap();
 
-    let txn = store.begin(true).unwrap();
-    let cf = txn.cf(CF).unwrap();
-    let result = txn.get(&cf, b"key1").unwrap().unwrap();
-    assert_eq!(&*result, b"new");
+    let writer = store.begin(false).unwrap();
+    let wcf = writer.cf(CF).unwrap();
+    writer.put(&wcf, b"key", b"v").unwrap();
+    writer.commit().unwrap();
+
+    // Validation compares values, not versions.
+    reader.commit().unwrap();
 }
 
 #[test]
-fn rollback_discards_writes() {
+fn read_only_commit_ignores_concurrent_writes() {
     let store = mem_store();
-    let txn = store.begin(false).unwrap();
-    let cf = txn.cf(CF).unwrap();
-    txn.put(&cf, b"key1", b"value1").unwrap();
-    txn.rollback().unwrap();
+    let txn1 = store.begin(false).unwrap();
+    let cf1 = txn1.cf(CF).unwrap();
+    assert!(txn1.get(&cf1, b"key").unwrap().is_none());
 
-    let txn = store.begin(true).unwrap();
-    let cf = txn.cf(CF).unwrap();
-    let result = txn.get(&cf, b"key1").unwrap();
-    assert!(result.is_none());
+    let txn2 = store.begin(false).unwrap();
+    let cf2 = txn2.cf(CF).unwrap();
+    txn2.put(&cf2, b"key", b"value").unwrap();
+    txn2.commit().unwrap();
+
+    // txn1 wrote nothing, so there is nothing to validate.
+    txn1.commit().unwrap();
 }
 
 #[test]
-fn rollback_does_not_affect_committed_data() {
+fn delete_range_conflicts_with_open_writer() {
     let store = mem_store();
     let txn = store.begin(false).unwrap();
     let cf = txn.cf(CF).unwrap();
-    txn.put(&cf, b"key1", b"value1").unwrap();
+    txn.put(&cf, b"a", b"1").unwrap();
     txn.commit().unwrap();
 
     let txn = store.begin(false).unwrap();
     let cf = txn.cf(CF).unwrap();
-    txn.put(&cf, b"key2", b"value2").unwrap();
-    txn.delete(&cf, b"key1").unwrap();
-    txn.rollback().unwrap();
+    txn.put(&cf, b"a", b"2").unwrap();
+    store.delete_range(CF, b"a".to_vec()..b"b".to_vec()).unwrap();
 
-    let txn = store.begin(true).unwrap();
-    let cf = txn.cf(CF).unwrap();
-    assert!(txn.get(&cf, b"key1").unwrap().is_some());
-    assert!(txn.get(&cf, b"key2").unwrap().is_none());
+    let result = txn.commit();
+    assert!(matches!(result, Err(StoreError::Conflict(_))), "got {result:?}");
 }
 
 #[test]
-fn empty_value() {
+fn parallel_writers_on_threads_lose_no_updates() {
     let store = mem_store();
-    let txn = store.begin(false).unwrap();
-    let cf = txn.cf(CF).unwrap();
-    txn.put(&cf, b"index:key", b"").unwrap();
-    txn.commit().unwrap();
+    std::thread::scope(|s| {
+        for t in 0..4u8 {
+            let store = &store;
+            s.spawn(move || {
+                for i in 0..50u8 {
+                    let txn = store.begin(false).unwrap();
+                    let cf = txn.cf(CF).unwrap();
+                    txn.put(&cf, &[t, i], &[i]).unwrap();
+                    txn.commit().unwrap();
+                }
+            });
+        }
+    });
 
     let txn = store.begin(true).unwrap();
     let cf = txn.cf(CF).unwrap();
-    let result = txn.get(&cf, b"index:key").unwrap().unwrap();
-    assert_eq!(&*result, b"");
+    assert_eq!(txn.scan_prefix(&cf, b"").unwrap().count(), 200);
 }
 
-// --- Column family tests ---
-
 #[test]
-fn create_and_use_cf() {
-    let store = MemoryStore::new();
-    store.create_cf("accounts").unwrap();
-
+fn approximate_size_is_exact() {
+    let store = mem_store();
     let txn = store.begin(false).unwrap();
-    let cf = txn.cf("accounts").unwrap();
-    txn.put(&cf, b"key1", b"value1").unwrap();
+    let cf = txn.cf(CF).unwrap();
+    txn.put_batch(&cf, &[(b"a1", b"xx"), (b"a2", b"yyy"), (b"b1", b"z")])
+        .unwrap();
     txn.commit().unwrap();
 
-    let txn = store.begin(true).unwrap();
-    let cf = txn.cf("accounts").unwrap();
-    let result = txn.get(&cf, b"key1").unwrap().unwrap();
-    assert_eq!(&*result, b"value1");
+    let size = store
+        .approximate_size(CF, b"a".to_vec()..b"b".to_vec())
+        .unwrap();
+    assert_eq!(size, ApproximateSize { bytes: 9, keys: 2 });
+    let size = store.approximate_size(CF, ..).unwrap();
+    assert_eq!(size, ApproximateSize { bytes: 12, keys: 3 });
 }
 
-#[test]
-fn cf_isolation() {
-    let store = MemoryStore::new();
-    store.create_cf("cf_a").unwrap();
-    store.create_cf("cf_b").unwrap();
-
+fn put(store: &MemoryStore, key: &[u8], value: &[u8]) {
     let txn = store.begin(false).unwrap();
-    let cf_a = txn.cf("cf_a").unwrap();
-    let cf_b = txn.cf("cf_b").unwrap();
-    txn.put(&cf_a, b"key1", b"value_a").unwrap();
-    txn.put(&cf_b, b"key1", b"value_b").unwrap();
+    let cf = txn.cf(CF).unwrap();
+    txn.put(&cf, key, value).unwrap();
     txn.commit().unwrap();
-
-    let txn = store.begin(true).unwrap();
-    let cf_a = txn.cf("cf_a").unwrap();
-    let cf_b = txn.cf("cf_b").unwrap();
-    assert_eq!(&*txn.get(&cf_a, b"key1").unwrap().unwrap(), b"value_a");
-    assert_eq!(&*txn.get(&cf_b, b"key1").unwrap().unwrap(), b"value_b");
 }
 
 #[test]
-fn get_on_missing_cf_returns_error() {
-    let store = MemoryStore::new();
-
-    let txn = store.begin(true).unwrap();
-    let result = txn.cf("nonexistent");
-    assert!(result.is_err());
+fn budget_reports_overrun_in_bytes() {
+    let store = MemoryStore::new().with_cf_budget(CF, 10);
+    store.create_cf(CF).unwrap();
+    assert!(store.budget_overruns().is_empty());
+
+    put(&store, b"k1", b"12345678");
+    assert!(store.budget_overruns().is_empty());
+    put(&store, b"k2", b"1234");
+    assert_eq!(store.budget_overruns(), vec![(CF.to_string(), 6)]);
+
+    store.delete_range(CF, b"k2".to_vec()..).unwrap();
+    assert!(store.budget_overruns().is_empty());
 }
 
 #[test]
-fn drop_cf_removes_data() {
-    let store = MemoryStore::new();
-    store.create_cf("temp").unwrap();
-
-    let txn = store.begin(false).unwrap();
-    let cf = txn.cf("temp").unwrap();
-    txn.put(&cf, b"key1", b"value1").unwrap();
-    txn.commit().unwrap();
-
-    store.drop_cf("temp").unwrap();
+fn eviction_candidates_are_least_recently_used_first() {
+    let store = MemoryStore::new().with_cf_budget(CF, 0);
+    store.create_cf(CF).unwrap();
+    put(&store, b"r-a", b"1");
+    put(&store, b"r-b", b"2");
+    put(&store, b"r-c", b"3");
+    put(&store, b"x-index", b"");
 
     let txn = store.begin(true).unwrap();
-    let result = txn.cf("temp");
-    assert!(result.is_err()); // CF no longer exists
+    let cf = txn.cf(CF).unwrap();
+    txn.get(&cf, b"r-a").unwrap();
+    drop(txn);
+
+    let keys = store.eviction_candidates(CF, b"r-", 10).unwrap();
+    assert_eq!(keys, vec![b"r-b".to_vec(), b"r-c".to_vec(), b"r-a".to_vec()]);
+    let keys = store.eviction_candidates(CF, b"r-", 1).unwrap();
+    assert_eq!(keys, vec![b"r-b".to_vec()]);
 }
 
 #[test]
-fn delete_range_clears_matching_keys() {
-    let store = MemoryStore::new();
-    store.create_cf("data").unwrap();
+fn eviction_candidates_start_with_keys_written_before_the_budget() {
+    let store = mem_store();
+    put(&store, b"r-b", b"2");
+    put(&store, b"r-a", b"1");
+    let store = store.with_cf_budget(CF, 0);
+    put(&store, b"r-c", b"3");
 
-    let txn = store.begin(false).unwrap();
-    let cf = txn.cf("data").unwrap();
-    txn.put(&cf, b"a", b"1").unwrap();
-    txn.put(&cf, b"b", b"2").unwrap();
-    txn.put(&cf, b"c", b"3").unwrap();
-    txn.put(&cf, b"d", b"4").unwrap();
-    txn.put(&cf, b"e", b"5").unwrap();
-    txn.commit().unwrap();
+    let txn = store.begin(true).unwrap();
+    let cf = txn.cf(CF).unwrap();
+    txn.get(&cf, b"r-a").unwrap();
+    txn.get(&cf, b"r-missing").unwrap();
+    drop(txn);
 
-    // Delete range [b, d) — should remove b and c
-    store
-        .delete_range("data", b"b".to_vec()..b"d".to_vec())
-        .unwrap();
+    let keys = store.eviction_candidates(CF, b"r-", 10).unwrap();
+    assert_eq!(keys, vec![b"r-b".to_vec(), b"r-c".to_vec(), b"r-a".to_vec()]);
 
-    let txn = store.begin(true).unwrap();
-    let cf = txn.cf("data").unwrap();
-    assert!(txn.get(&cf, b"a").unwrap().is_some());
-    assert!(txn.get(&cf, b"b").unwrap().is_none());
-    assert!(txn.get(&cf, b"c").unwrap().is_none());
-    assert!(txn.get(&cf, b"d").unwrap().is_some());
-    assert!(txn.get(&cf, b"e").unwrap().is_some());
+    store.delete_range(CF, b"r-b".to_vec()..b"r-c".to_vec()).unwrap();
+    let keys = store.eviction_candidates(CF, b"r-", 10).unwrap();
+    assert_eq!(keys, vec![b"r-c".to_vec(), b"r-a".to_vec()]);
 }
 
 #[test]
-fn delete_range_unbounded_clears_all() {
-    let store = MemoryStore::new();
-    store.create_cf("cache").unwrap();
-
-    let txn = store.begin(false).unwrap();
-    let cf = txn.cf("cache").unwrap();
-    txn.put(&cf, b"x", b"1").unwrap();
-    txn.put(&cf, b"y", b"2").unwrap();
-    txn.put(&cf, b"z", b"3").unwrap();
-    txn.commit().unwrap();
-
-    store.delete_range("cache", ..).unwrap();
-
-    let txn = store.begin(true).unwrap();
-    let cf = txn.cf("cache").unwrap();
-    assert!(txn.get(&cf, b"x").unwrap().is_none());
-    assert!(txn.get(&cf, b"y").unwrap().is_none());
-    assert!(txn.get(&cf, b"z").unwrap().is_none());
+fn unbounded_store_has_no_eviction_candidates() {
+    let store = mem_store();
+    put(&store, b"k", b"v");
+    assert!(store.budget_overruns().is_empty());
+    assert!(store.eviction_candidates(CF, b"", 10).unwrap().is_empty());
 }
 
 #[test]
-fn delete_range_inclusive_end() {
-    let store = MemoryStore::new();
-    store.create_cf("data").unwrap();
+fn fork_is_isolated_both_ways() {
+    let store = mem_store();
+    put(&store, b"shared", b"1");
+    let fork = store.fork().unwrap();
 
-    let txn = store.begin(false).unwrap();
-    let cf = txn.cf("data").unwrap();
-    txn.put(&cf, b"a", b"1").unwrap();
-    txn.put(&cf, b"b", b"2").unwrap();
-    txn.put(&cf, b"c", b"3").unwrap();
-    txn.commit().unwrap();
-
-    // Delete range [a, b] inclusive — should remove a and b
-    store
-        .delete_range("data", b"a".to_vec()..=b"b".to_vec())
-        .unwrap();
+    put(&store, b"parent", b"2");
+    put(&fork, b"child", b"3");
+    store.create_cf("parent_only").unwrap();
 
     let txn = store.begin(true).unwrap();
-    let cf = txn.cf("data").unwrap();
-    assert!(txn.get(&cf, b"a").unwrap().is_none());
-    assert!(txn.get(&cf, b"b").unwrap().is_none());
-    assert!(txn.get(&cf, b"c").unwrap().is_some());
-}
-
-#[test]
-fn multi_get_returns_matching_values() {
-    let store = mem_store();
-    let txn = store.begin(false).unwrap();
     let cf = txn.cf(CF).unwrap();
-    txn.put(&cf, b"k1", b"v1").unwrap();
-    txn.put(&cf, b"k2", b"v2").unwrap();
-    txn.put(&cf, b"k3", b"v3").unwrap();
-    txn.commit().unwrap();
+    assert!(txn.get(&cf, b"shared").unwrap().is_some());
+    assert!(txn.get(&cf, b"child").unwrap().is_none());
 
-    let txn = store.begin(true).unwrap();
+    let txn = fork.begin(true).unwrap();
     let cf = txn.cf(CF).unwrap();
-    let keys: Vec<&[u8]> = vec![b"k1", b"k2", b"missing", b"k3"];
-    let results = txn.multi_get(&cf, &keys).unwrap();
-    assert_eq!(results.len(), 4);
-    assert_eq!(&**results[0].as_ref().unwrap(), b"v1");
-    assert_eq!(&**results[1].as_ref().unwrap(), b"v2");
-    assert!(results[2].is_none());
-    assert_eq!(&**results[3].as_ref().unwrap(), b"v3");
+    assert!(txn.get(&cf, b"shared").unwrap().is_some());
+    assert!(txn.get(&cf, b"parent").unwrap().is_none());
+    assert!(txn.cf("parent_only").is_err());
 }
 
-// ── Backup ──────────────────────────────────────────────────
-
 #[test]
-fn backup_returns_error() {
-    let store = mem_store();
-    let dir = tempfile::tempdir().unwrap();
-    let result = store.backup(dir.path());
-    assert!(result.is_err());
+fn fork_keeps_memory_budgets() {
+    let store = MemoryStore::new().with_cf_budget(CF, 4);
+    store.create_cf(CF).unwrap();
+    put(&store, b"k1", b"123");
+    let fork = store.fork().unwrap();
+    assert_eq!(fork.budget_overruns(), vec![(CF.to_string(), 1)]);
 }
@@ -0,0 +1,152 @@
+#![cfg(feature = "memory")]
+
+use std::ops::Bound;
+use std::time::Duration;
+
+use slate_store::{InstrumentedStore, MemoryStore, MetricsStore, Store, StoreOp, Transaction};
+
+const CF: &str = "test";
+
+fn metrics_store() -> MetricsStore<MemoryStore> {
+    let store = MetricsStore::new(MemoryStore::new());
+    store.create_cf(CF).unwrap();
+    store
+}
+
+#[test]
+fn counts_point_reads_and_writes() {
+    let store = metrics_store();
+    let txn = store.begin(false).unwrap();
+    let cf = txn.cf(CF).unwrap();
+    txn.put(&cf, b"k1", b"value").unwrap();
+    txn.put_batch(&cf, &[(b"k2", b"ab"), (b"k3", b"cd")])
+        .unwrap();
+    assert_eq!(txn.get(&cf, b"k1").unwrap().unwrap(), b"value");
+    assert!(txn.get(&cf, b"missing").unwrap().is_none());
+    let keys: Vec<&[u8]> = vec![b"k2", b"k3", b"nope"];
+    txn.multi_get(&cf, &keys).unwrap();
+    txn.commit().unwrap();
+
+    let metrics = store.metrics();
+    let put = metrics.op(CF, StoreOp::Put).unwrap();
+    assert_eq!((put.calls, put.bytes_written), (1, 7));
+    let batch = metrics.op(CF, StoreOp::PutBatch).unwrap();
+    assert_eq!((batch.calls, batch.bytes_written), (1, 8));
+    let get = metrics.op(CF, StoreOp::Get).unwrap();
+    assert_eq!((get.calls, get.bytes_read, get.keys_scanned), (2, 7, 2));
+    let multi = metrics.op(CF, StoreOp::MultiGet).unwrap();
+    assert_eq!(
+        (multi.calls, multi.bytes_read, multi.keys_scanned),
+        (1, 8, 3)
+    );
+    assert_eq!(metrics.commit.calls, 1);
+    assert_eq!(metrics.commit.latency.count(), 1);
+    assert!(metrics.op(CF, StoreOp::Delete).is_none());
+}
+
+#[test]
+fn scans_record_entries_yielded() {
+    let store = metrics_store();
+    let txn = store.begin(false).unwrap();
+    let cf = txn.cf(CF).unwrap();
+    txn.put_batch(&cf, &[(b"p:1", b"a"), (b"p:2", b"b"), (b"q:1", b"c")])
+        .unwrap();
+    txn.commit().unwrap();
+
+    let txn = store.begin(true).unwrap();
+    let cf = txn.cf(CF).unwrap();
+    assert_eq!(txn.scan_prefix(&cf, b"p:").unwrap().count(), 2);
+    // A scan abandoned early only counts what it read.
+    let first = txn
+        .scan_range(&cf, Bound::Unbounded, Bound::Unbounded, false)
+        .unwrap()
+        .next();
+    assert!(first.is_some());
+    txn.rollback().unwrap();
+
+    let metrics = store.metrics();
+    let scan = metrics.op(CF, StoreOp::ScanPrefix).unwrap();
+    assert_eq!((scan.calls, scan.keys_scanned, scan.bytes_read), (1, 2, 8));
+    let range = metrics.op(CF, StoreOp::ScanRange).unwrap();
+    assert_eq!((range.calls, range.keys_scanned), (1, 1));
+    assert_eq!(metrics.rollback.calls, 1);
+}
+
+#[test]
+fn metrics_are_per_column_family() {
+    let store = metrics_store();
+    store.create_cf("other").unwrap();
+    let txn = store.begin(false).unwrap();
+    let cf = txn.cf(CF).unwrap();
+    let other = txn.cf("other").unwrap();
+    txn.put(&cf, b"a", b"1").unwrap();
+    txn.put(&other, b"b", b"2").unwrap();
+    txn.put(&other, b"c", b"3").unwrap();
+    txn.commit().unwrap();
+
+    let metrics = store.metrics();
+    assert_eq!(metrics.op(CF, StoreOp::Put).unwrap().calls, 1);
+    assert_eq!(metrics.op("other", StoreOp::Put).unwrap().calls, 2);
+}
+
+#[test]
+fn errors_are_counted_and_passed_through() {
+    let store = metrics_store();
+    let txn = store.begin(true).unwrap();
+    let cf = txn.cf(CF).unwrap();
+    assert!(txn.put(&cf, b"k", b"v").is_err());
+
+    let put = store.metrics().op(CF, StoreOp::Put).cloned().unwrap();
+    assert_eq!((put.calls, put.bytes_written), (1, 0));
+}
+
+#[test]
+fn reset_zeroes_counters_for_open_handles() {
+    let store = metrics_store();
+    let txn = store.begin(false).unwrap();
+    let cf = txn.cf(CF).unwrap();
+    txn.put(&cf, b"a", b"1").unwrap();
+
+    store.reset_metrics();
+    assert!(store.metrics().column_families.is_empty());
+
+    txn.put(&cf, b"b", b"2").unwrap();
+    txn.commit().unwrap();
+    let metrics = store.metrics();
+    assert_eq!(metrics.op(CF, StoreOp::Put).unwrap().calls, 1);
+    assert_eq!(metrics.commit.calls, 1);
+}
+
+#[test]
+fn clones_share_counters() {
+    let store = metrics_store();
+    let observer = store.clone();
+    let txn = store.begin(false).unwrap();
+    let cf = txn.cf(CF).unwrap();
+    txn.delete(&cf, b"a").unwrap();
+    txn.commit().unwrap();
+
+    assert_eq!(observer.metrics().op(CF, StoreOp::Delete).unwrap().calls, 1);
+}
+
+#[test]
+fn latency_histogram_percentiles() {
+    let store = metrics_store();
+    for _ in 0..100 {
+        let txn = store.begin(true).unwrap();
+        let cf = txn.cf(CF).unwrap();
+        txn.get(&cf, b"k").unwrap();
+    }
+
+    let latency = store
+        .metrics()
+        .op(CF, StoreOp::Get)
+        .unwrap()
+        .latency
+        .clone();
+    assert_eq!(latency.count(), 100);
+    assert_eq!(latency.buckets().map(|(_, n)| n).sum::<u64>(), 100);
+    assert!(latency.percentile(50.0) <= latency.percentile(99.0));
+    assert!(latency.percentile(100.0) >= latency.mean());
+    assert!(latency.total() > Duration::ZERO);
+}
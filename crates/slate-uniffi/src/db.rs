@@ -16,6 +16,9 @@ type StoreImpl = slate_store::RedbStore;
 #[cfg(feature = "rocksdb")]
 type StoreImpl = slate_store::RocksStore;
 
+#[cfg(feature = "sqlite")]
+type StoreImpl = slate_store::SqliteStore;
+
 type Db = Database<StoreImpl>;
 type Txn<'a> = DatabaseTransaction<'a, StoreImpl>;
 
@@ -68,7 +71,7 @@ impl SlateDatabase {
     }
 }
 
-#[cfg(any(feature = "redb", feature = "rocksdb"))]
+#[cfg(any(feature = "redb", feature = "rocksdb", feature = "sqlite"))]
 #[uniffi::export]
 impl SlateDatabase {
     #[uniffi::constructor]
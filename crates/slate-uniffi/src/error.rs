@@ -8,6 +8,7 @@ pub enum SlateError {
     DuplicateKey { message: String },
     InvalidQuery { message: String },
     Store { message: String },
+    Conflict { message: String },
     Serialization { message: String },
 }
 
@@ -18,6 +19,7 @@ impl fmt::Display for SlateError {
             SlateError::DuplicateKey { message } => write!(f, "duplicate key: {message}"),
             SlateError::InvalidQuery { message } => write!(f, "invalid query: {message}"),
             SlateError::Store { message } => write!(f, "store error: {message}"),
+            SlateError::Conflict { message } => write!(f, "write conflict: {message}"),
             SlateError::Serialization { message } => write!(f, "serialization error: {message}"),
         }
     }
@@ -36,6 +38,7 @@ impl From<DbError> for SlateError {
             DbError::Store(e) => SlateError::Store {
                 message: e.to_string(),
             },
+            DbError::Conflict(msg) => SlateError::Conflict { message: msg },
             DbError::InvalidDocument(msg) => SlateError::Serialization { message: msg },
             DbError::Serialization(msg) => SlateError::Serialization { message: msg },
             DbError::IndexExists(msg) => SlateError::DuplicateKey { message: msg },
@@ -1,5 +1,5 @@
 use bson::{Bson, rawdoc};
-use criterion::{BatchSize, Criterion, criterion_group, criterion_main};
+use criterion::{Criterion, criterion_group, criterion_main};
 use slate_vm::{LuaScriptRuntime, ScriptCapabilities, ScriptHandle, ScriptRuntime, ScopedMethod, VmError};
 
 // ── Helpers ─────────────────────────────────────────────────
@@ -18,6 +18,12 @@ pub struct LuaScriptRuntime {
     instruction_limit: u32,
 }
 
+impl Default for LuaScriptRuntime {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl LuaScriptRuntime {
     pub fn new() -> Self {
         Self {
@@ -195,6 +201,7 @@ fn is_instruction_limit_error(e: &mlua::Error) -> bool {
 }
 
 #[cfg(test)]
+#[allow(clippy::approx_constant, clippy::bool_assert_comparison)]
 mod tests {
     use super::*;
     use std::sync::{Arc, Mutex};
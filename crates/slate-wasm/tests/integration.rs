@@ -133,6 +133,7 @@ fn count_documents() {
 // ── Update ──────────────────────────────────────────────────
 
 #[wasm_bindgen_test]
+#[allow(clippy::useless_conversion)]
 fn update_one_returns_updated_doc() {
     let db = SlateDb::new().unwrap();
     db.create_collection("users").unwrap();